@@ -0,0 +1,78 @@
+#![allow(unused)]
+
+use actix::{Actor, Context, Handler};
+use statig::blocking::{Dispatch, GetState, StateMachineActor, Subscribe, TransitionTaken};
+use statig::prelude::*;
+
+#[derive(Debug, Default)]
+pub struct Order;
+
+// Messages of the other services, which are wrapped in the event.
+#[derive(Debug)]
+pub struct PaymentReceived;
+
+#[derive(Debug)]
+pub struct Shipped;
+
+#[derive(Debug)]
+pub enum Event {
+    Payment(PaymentReceived),
+    Shipping(Shipped),
+}
+
+#[state_machine(
+    initial = "State::awaiting_payment()",
+    event_from("Payment(PaymentReceived)", "Shipping(Shipped)"),
+    state(derive(Debug, Clone, PartialEq, Eq))
+)]
+impl Order {
+    #[state]
+    fn awaiting_payment(event: &Event) -> Response<State> {
+        match event {
+            Event::Payment(_) => Transition(State::awaiting_shipment()),
+            _ => Handled,
+        }
+    }
+
+    #[state]
+    fn awaiting_shipment(event: &Event) -> Response<State> {
+        match event {
+            Event::Shipping(_) => Transition(State::delivered()),
+            _ => Handled,
+        }
+    }
+
+    #[state]
+    fn delivered(event: &Event) -> Response<State> {
+        Handled
+    }
+}
+
+/// Actor that logs the transitions of the order.
+struct Audit;
+
+impl Actor for Audit {
+    type Context = Context<Self>;
+}
+
+impl Handler<TransitionTaken<State>> for Audit {
+    type Result = ();
+
+    fn handle(&mut self, transition: TransitionTaken<State>, _: &mut Self::Context) {
+        println!("{:?} -> {:?}", transition.source, transition.target);
+    }
+}
+
+#[actix::main]
+async fn main() {
+    let state_machine = Order.uninitialized_state_machine().init();
+    let order = StateMachineActor::new(state_machine).start();
+    let audit = Audit.start();
+
+    order.do_send(Subscribe(audit.recipient()));
+    order.do_send(Dispatch(PaymentReceived));
+    order.do_send(Dispatch(Shipped));
+
+    let state = order.send(GetState::default()).await.unwrap();
+    println!("final state: {state:?}");
+}
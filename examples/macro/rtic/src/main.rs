@@ -0,0 +1,125 @@
+#![no_std]
+#![no_main]
+
+use panic_semihosting as _;
+use statig::prelude::*;
+
+#[derive(Default)]
+pub struct Blinky {
+    toggles: u32,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    TimerElapsed,
+    ButtonPressed,
+}
+
+#[state_machine(initial = "State::led_on()", state(derive(Debug)))]
+impl Blinky {
+    #[state(superstate = "blinking")]
+    fn led_on(&mut self, event: &Event) -> Response<State> {
+        match event {
+            Event::TimerElapsed => {
+                self.toggles += 1;
+                Transition(State::led_off())
+            }
+            _ => Super,
+        }
+    }
+
+    #[state(superstate = "blinking")]
+    fn led_off(&mut self, event: &Event) -> Response<State> {
+        match event {
+            Event::TimerElapsed => {
+                self.toggles += 1;
+                Transition(State::led_on())
+            }
+            _ => Super,
+        }
+    }
+
+    #[superstate]
+    fn blinking(event: &Event) -> Response<State> {
+        match event {
+            Event::ButtonPressed => Transition(State::not_blinking()),
+            _ => Super,
+        }
+    }
+
+    #[state]
+    fn not_blinking(event: &Event) -> Response<State> {
+        match event {
+            Event::ButtonPressed => Transition(State::led_on()),
+            _ => Super,
+        }
+    }
+}
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+    use statig::blocking::{EventConsumer, EventProducer, InitializedStateMachine, SplitChannel};
+    use statig::prelude::*;
+
+    use super::{Blinky, Event};
+
+    /// The number of events that can wait for the state machine. The channel
+    /// is allocated statically, so this is part of the RAM budget.
+    const CAPACITY: usize = 8;
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {
+        producer: EventProducer<'static, Event, CAPACITY>,
+        consumer: EventConsumer<'static, Event, CAPACITY>,
+        state_machine: InitializedStateMachine<Blinky>,
+    }
+
+    #[init(local = [channel: SplitChannel<Event, CAPACITY> = SplitChannel::new()])]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        // The interrupt handler only pushes events and the task only handles
+        // them, so neither of them needs to lock the state machine.
+        let (producer, consumer) = cx.local.channel.split();
+        let state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        rtic::pend(Interrupt::UART0);
+
+        (
+            Shared {},
+            Local {
+                producer,
+                consumer,
+                state_machine,
+            },
+        )
+    }
+
+    /// Interrupt service routine that produces events.
+    #[task(binds = UART0, local = [producer])]
+    fn uart0(cx: uart0::Context) {
+        for event in [Event::TimerElapsed, Event::TimerElapsed, Event::ButtonPressed] {
+            if cx.local.producer.push(event).is_err() {
+                hprintln!("event dropped, the channel is full");
+            }
+        }
+        handle_events::spawn().ok();
+    }
+
+    /// Software task that owns the state machine and handles the events.
+    #[task(local = [consumer, state_machine], priority = 1)]
+    async fn handle_events(cx: handle_events::Context) {
+        let handled = cx.local.consumer.process(cx.local.state_machine);
+        hprintln!(
+            "handled {} events, now in {:?} after {} toggles",
+            handled,
+            cx.local.state_machine.state(),
+            cx.local.state_machine.inner().toggles
+        );
+
+        debug::exit(debug::EXIT_SUCCESS);
+    }
+}
@@ -1,13 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use proc_macro2::TokenStream;
 use proc_macro_error::abort;
+use quote::ToTokens;
 use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
 use syn::{
-    parse_quote, Attribute, AttributeArgs, ExprCall, Field, FnArg, Generics, Ident, ImplItem,
-    ImplItemMethod, ItemImpl, Lit, Meta, MetaList, NestedMeta, Pat, PatType, Path, Receiver, Type,
-    Visibility,
+    parse_quote, Arm, Attribute, AttributeArgs, Expr, Field, Fields, FnArg, GenericArgument,
+    GenericParam, Generics, Ident, ImplItem, ImplItemMethod, ItemImpl, Lit, LitStr, Meta, MetaList,
+    MetaNameValue, NestedMeta, Pat, PatType, Path, PathArguments, Receiver, ReturnType, Type,
+    Variant, Visibility, WherePredicate,
 };
 
+use crate::visitors::GenericParamVisitor;
+
 /// Model of the state machine.
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct Model {
@@ -27,7 +34,7 @@ pub struct Model {
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct StateMachine {
     /// The inital state of the state machine.
-    pub initial_state: ExprCall,
+    pub initial_state: Expr,
     /// The type on which the state machine is implemented.
     pub shared_storage_type: Type,
     /// The path of the shared storage.
@@ -38,24 +45,142 @@ pub struct StateMachine {
     pub state_ident: Ident,
     /// Derives that will be applied on the state type.
     pub state_derives: Vec<Path>,
+    /// Whether the state enum is marked `#[non_exhaustive]`.
+    pub state_non_exhaustive: bool,
     /// The name of the superstate type.
     pub superstate_ident: Ident,
     /// Derives that will be applied to the superstate type.
     pub superstate_derives: Vec<Path>,
+    /// Whether the superstate enum is marked `#[non_exhaustive]`.
+    pub superstate_non_exhaustive: bool,
     /// The identifier that is used for the event argument.
     pub event_ident: Ident,
     /// The identifier that is used for the context argument.
     pub context_ident: Ident,
     /// The visibility of the derived types.
     pub visibility: Visibility,
+    /// Optional module in which the derived types are generated.
+    pub module: Option<Ident>,
+    /// Optional upper bound on the size of the state type, in bytes.
+    pub max_state_size: Option<usize>,
+    /// How states that are never entered are reported.
+    pub unreachable_states: LintLevel,
+    /// Optional path to an `AtomicU8` that mirrors the id of the current state.
+    pub state_id_mirror: Option<Path>,
+    /// Optional `after` function that returns the timed transition of a state.
+    pub after: Option<Path>,
     /// Optional `before_transition` callback.
     pub before_transition: Option<Path>,
     /// Optional `after_transition` callback.
     pub after_transition: Option<Path>,
+    /// Optional `on_init` callback.
+    pub on_init: Option<Path>,
+    /// Optional `on_transition` callback, which also receives the event.
+    pub on_transition: Option<Path>,
     /// Optional `before_dispatch` callback.
     pub before_dispatch: Option<Path>,
     /// Optional `after_dispatch` callback.
     pub after_dispatch: Option<Path>,
+    /// Optional callback that receives the errors of fallible actions.
+    pub on_action_error: Option<Path>,
+    /// What happens to a transition when one of its actions fails.
+    pub action_failure: ActionFailure,
+    /// The semantics that decide which states are exited and entered during
+    /// a transition.
+    pub semantics: Semantics,
+    /// Variants of the event enum for which a `From` impl is generated, with
+    /// the type they wrap.
+    pub event_wrappers: Vec<(Ident, Type)>,
+    /// Optional event enum that is generated from the event types of the
+    /// state machine.
+    pub event_enum: Option<EventEnum>,
+    /// Whether the static table that routes every event in every state is
+    /// generated.
+    pub event_routing: bool,
+}
+
+/// An event enum that wraps several event types, each of which is routed to
+/// the handlers that accept it.
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct EventEnum {
+    /// The name of the event enum.
+    pub ident: Ident,
+    /// Derives that will be applied on the event enum.
+    pub derives: Vec<Path>,
+    /// The variants of the event enum, with the event type they wrap.
+    pub variants: Vec<(Ident, Type)>,
+    /// Whether the variants borrow the events instead of owning them, so the
+    /// event types are handled as they are without wrapping them.
+    pub borrowed: bool,
+}
+
+/// What happens to a transition when one of its actions fails.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub enum ActionFailure {
+    /// Skip the remaining actions and leave the state machine poisoned.
+    Abort,
+    /// Execute the remaining actions as if the action succeeded.
+    Proceed,
+}
+
+/// The semantics that decide which states are exited and entered during a
+/// transition.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub enum Semantics {
+    /// Exit up to the common ancestor of the current state and the target.
+    Pragmatic,
+    /// UML semantics with external transitions.
+    Uml,
+    /// UML semantics with local transitions.
+    UmlLocal,
+}
+
+/// How a diagnostic of the macro is reported.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// What a state or superstate handler returns.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub enum HandlerOutput {
+    /// A `Response`.
+    Response,
+    /// Nothing, in which case every event is handled.
+    Unit,
+    /// Anything else that converts into a `Response` (e.g. a bare state).
+    Into,
+}
+
+/// Kind of pseudo-state, which the state machine passes through during a
+/// transition but never rests in.
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum PseudoState {
+    /// A choice, of which the handler selects the target after the exit
+    /// actions of the transition.
+    Choice,
+    /// A junction, of which the handler selects the target when the
+    /// transition is taken, before any exit actions.
+    Junction,
+    /// A terminate, which stops the state machine as soon as it is reached.
+    Terminate,
+}
+
+impl PseudoState {
+    fn name(self) -> &'static str {
+        match self {
+            PseudoState::Choice => "choice",
+            PseudoState::Junction => "junction",
+            PseudoState::Terminate => "terminate pseudo-state",
+        }
+    }
 }
 
 /// Information regarding a state.
@@ -63,14 +188,45 @@ pub struct StateMachine {
 pub struct State {
     /// Name of the state.
     pub handler_name: Ident,
+    /// Optional name of the variant (e.g. `WaitingForAck`).
+    pub name: Option<Ident>,
     /// Optional superstate.
     pub superstate: Option<Ident>,
     /// Optional entry action.
-    pub entry_action: Option<Ident>,
+    pub entry_action: Option<Path>,
     /// Optional exit action.
-    pub exit_action: Option<Ident>,
+    pub exit_action: Option<Path>,
+    /// Optional action that is called on every tick while the state is active.
+    pub activity: Option<Path>,
     /// Local storage,
     pub local_storage: Vec<Field>,
+    /// Optional payload that is passed to the state by the transition that
+    /// enters it (e.g. `cause: Cause`).
+    pub payload: Option<Field>,
+    /// Whether the local storage is stored on the heap.
+    pub boxed: bool,
+    /// Derives that are only applied to the local storage of this state.
+    pub derives: Vec<Path>,
+    /// Whether the state is constructed with a builder.
+    pub builder: bool,
+    /// Constants of the state (e.g. `TIMEOUT_MS = 500`).
+    pub consts: Vec<(Ident, Lit)>,
+    /// Declared transitions (e.g. `Event::TimerElapsed => State::led_off()`).
+    pub transitions: Vec<Arm>,
+    /// Optional condition transition, which is taken after an event was
+    /// dispatched when the condition holds (e.g. `self.pressure > LIMIT` and
+    /// `venting`).
+    pub condition: Option<(Expr, Expr)>,
+    /// Position among the declared transitions at which the condition
+    /// transition is matched, before the event is dispatched, if it was given
+    /// a priority (e.g. `priority = 1`).
+    pub condition_position: Option<usize>,
+    /// Actions of the transitions from this state to other states, by the
+    /// name of the target state (e.g. `error` and `send_nack`).
+    pub transition_actions: Vec<(Ident, Ident)>,
+    /// Optional kind of pseudo-state, of which the handler selects the target
+    /// of the transitions that target it.
+    pub pseudo_state: Option<PseudoState>,
     /// Inputs required by the state handler.
     pub inputs: Vec<FnArg>,
     /// Optional receiver input for the state handler (e.g. `&mut self`).
@@ -79,10 +235,24 @@ pub struct State {
     pub state_inputs: Vec<PatType>,
     /// Event that is submitted to the state machine.
     pub event_arg: Option<PatType>,
+    /// Pattern the event is destructured with (e.g. `Event::Button { id }`).
+    pub event_pattern: Option<Pat>,
     /// Context that is submitted to the state machine.
     pub context_arg: Option<PatType>,
     /// Whether the function is async or not.
     pub is_async: bool,
+    /// What the function returns.
+    pub output: HandlerOutput,
+    /// Whether the function returns a `Result`, of which the error is passed
+    /// to the `on_action_error` callback.
+    pub is_fallible: bool,
+    /// Conditional compilation attributes (e.g. `#[cfg(feature = "factory")]`).
+    pub cfg_attrs: Vec<Attribute>,
+    /// Attributes that are forwarded to the generated variant (e.g. doc
+    /// comments, `#[deprecated]` and `#[allow]`).
+    pub forwarded_attrs: Vec<Attribute>,
+    /// Key-value metadata (e.g. `owner = "comms"`).
+    pub meta: Vec<(Ident, LitStr)>,
 }
 
 /// Information regarding a superstate.
@@ -90,14 +260,24 @@ pub struct State {
 pub struct Superstate {
     /// Name of the superstate.
     pub handler_name: Ident,
+    /// Optional name of the variant (e.g. `Playing`).
+    pub name: Option<Ident>,
     /// Optional superstate.
     pub superstate: Option<Ident>,
     /// Optional entry action.
-    pub entry_action: Option<Ident>,
+    pub entry_action: Option<Path>,
     /// Optional exit action.
-    pub exit_action: Option<Ident>,
+    pub exit_action: Option<Path>,
+    /// Optional handler for the events that don't match the event pattern of
+    /// the superstate handler.
+    pub default_handler: Option<Ident>,
+    /// Optional target of the transition that is taken when a substate
+    /// completes (e.g. `State::idle()`).
+    pub completion: Option<Expr>,
     /// Local storage,
     pub local_storage: Vec<Field>,
+    /// Declared transitions (e.g. `Event::TimerElapsed => State::led_off()`).
+    pub transitions: Vec<Arm>,
     /// Inputs required by the superstate handler.
     pub inputs: Vec<FnArg>,
     /// Optional receiver input for the state handler (e.g. `&mut self`).
@@ -106,10 +286,24 @@ pub struct Superstate {
     pub state_inputs: Vec<PatType>,
     /// Event that is submitted to the state machine.
     pub event_arg: Option<PatType>,
+    /// Pattern the event is destructured with (e.g. `Event::Button { id }`).
+    pub event_pattern: Option<Pat>,
     /// Context that is submitted to the state machine.
     pub context_arg: Option<PatType>,
     /// Whether the function is async or not.
     pub is_async: bool,
+    /// What the function returns.
+    pub output: HandlerOutput,
+    /// Whether the function returns a `Result`, of which the error is passed
+    /// to the `on_action_error` callback.
+    pub is_fallible: bool,
+    /// Conditional compilation attributes (e.g. `#[cfg(feature = "factory")]`).
+    pub cfg_attrs: Vec<Attribute>,
+    /// Attributes that are forwarded to the generated variant (e.g. doc
+    /// comments, `#[deprecated]` and `#[allow]`).
+    pub forwarded_attrs: Vec<Attribute>,
+    /// Key-value metadata (e.g. `owner = "comms"`).
+    pub meta: Vec<(Ident, LitStr)>,
 }
 
 /// Information regarding an action.
@@ -121,15 +315,20 @@ pub struct Action {
     pub inputs: Vec<FnArg>,
     /// Whether the function is async or not.
     pub is_async: bool,
+    /// Whether the function returns a `Result`.
+    pub is_fallible: bool,
+    /// Conditional compilation attributes (e.g. `#[cfg(feature = "factory")]`).
+    pub cfg_attrs: Vec<Attribute>,
 }
 
 /// Analyze the impl block and create a model.
 pub fn analyze(attribute_args: AttributeArgs, item_impl: ItemImpl) -> Model {
-    let state_machine = analyze_state_machine(&attribute_args, &item_impl);
+    let mut state_machine = analyze_state_machine(&attribute_args, &item_impl);
 
-    let mut states = HashMap::new();
-    let mut superstates = HashMap::new();
-    let mut actions = HashMap::new();
+    let mut states: HashMap<Ident, State> = HashMap::new();
+    let mut superstates: HashMap<Ident, Superstate> = HashMap::new();
+    let mut actions: HashMap<Ident, Action> = HashMap::new();
+    let mut handler_names: HashMap<Ident, &ImplItemMethod> = HashMap::new();
 
     // Create an iterator over only the method items.
     let methods = item_impl.items.iter().filter_map(|item| match item {
@@ -143,24 +342,114 @@ pub fn analyze(attribute_args: AttributeArgs, item_impl: ItemImpl) -> Model {
             match &attr.path {
                 path if path.is_ident("state") => {
                     let state = analyze_state(method, &state_machine);
-                    states.insert(state.handler_name.clone(), state);
+                    match check_duplicate_name(&mut handler_names, method) {
+                        Some(first) => merge_cfg_attrs(
+                            &mut states.get_mut(&first.sig.ident).unwrap().cfg_attrs,
+                            &state.cfg_attrs,
+                        ),
+                        None => {
+                            states.insert(state.handler_name.clone(), state);
+                        }
+                    }
                 }
 
                 path if path.is_ident("superstate") => {
                     let superstate = analyze_superstate(method, &state_machine);
-                    superstates.insert(superstate.handler_name.clone(), superstate);
+                    match check_duplicate_name(&mut handler_names, method) {
+                        Some(first) => merge_cfg_attrs(
+                            &mut superstates.get_mut(&first.sig.ident).unwrap().cfg_attrs,
+                            &superstate.cfg_attrs,
+                        ),
+                        None => {
+                            superstates.insert(superstate.handler_name.clone(), superstate);
+                        }
+                    }
                 }
 
                 path if path.is_ident("action") => {
                     let action = analyze_action(method);
-                    actions.insert(action.handler_name.clone(), action);
+                    match check_duplicate_name(&mut handler_names, method) {
+                        Some(first) => merge_cfg_attrs(
+                            &mut actions.get_mut(&first.sig.ident).unwrap().cfg_attrs,
+                            &action.cfg_attrs,
+                        ),
+                        None => {
+                            actions.insert(action.handler_name.clone(), action);
+                        }
+                    }
+                }
+
+                _ => continue,
+            }
+
+            // The bounds on the handler must also hold for the trait impls
+            // that call it.
+            for predicate in analyze_handler_bounds(method) {
+                let where_clause = state_machine.shared_storage_generics.make_where_clause();
+                if !where_clause.predicates.iter().any(|p| *p == predicate) {
+                    where_clause.predicates.push(predicate);
                 }
+            }
+        }
+    }
+
+    // Check the superstates that are referenced, iterating in declaration
+    // order so the first mistake is reported.
+    for method in item_impl.items.iter().filter_map(|item| match item {
+        ImplItem::Method(method) => Some(method),
+        _ => None,
+    }) {
+        let superstate = match (
+            states.get(&method.sig.ident),
+            superstates.get(&method.sig.ident),
+        ) {
+            (Some(state), _) => state.superstate.as_ref(),
+            (None, Some(superstate)) => superstate.superstate.as_ref(),
+            (None, None) => None,
+        };
+        if let Some(superstate) = superstate {
+            check_superstate_reference(superstate, &states, &superstates);
+        }
 
-                _ => (),
+        // Check the entry and exit actions against the local storage of the
+        // state or superstate they are called for.
+        let (entry_action, exit_action, activity, storage) = match (
+            states.get(&method.sig.ident),
+            superstates.get(&method.sig.ident),
+        ) {
+            (Some(state), _) => (
+                state.entry_action.as_ref(),
+                state.exit_action.as_ref(),
+                state.activity.as_ref(),
+                storage_idents(
+                    &state.state_inputs,
+                    state.local_storage.iter().chain(&state.payload),
+                ),
+            ),
+            (None, Some(superstate)) => (
+                superstate.entry_action.as_ref(),
+                superstate.exit_action.as_ref(),
+                None,
+                storage_idents(&superstate.state_inputs, &superstate.local_storage),
+            ),
+            (None, None) => continue,
+        };
+        // Free functions are checked by the compiler.
+        for action in entry_action.into_iter().chain(exit_action).chain(activity) {
+            if let Some(action) = action.get_ident() {
+                check_action_reference(
+                    action,
+                    &method.sig.ident,
+                    &storage,
+                    &actions,
+                    &state_machine,
+                );
             }
         }
     }
 
+    check_superstate_cycles(&item_impl, &superstates);
+
     Model {
         item_impl,
         state_machine,
@@ -170,25 +459,219 @@ pub fn analyze(attribute_args: AttributeArgs, item_impl: ItemImpl) -> Model {
     }
 }
 
+/// Abort when the superstate hierarchy contains a cycle (e.g. `a` → `b` → `a`),
+/// listing the superstates that are part of it.
+fn check_superstate_cycles(item_impl: &ItemImpl, superstates: &HashMap<Ident, Superstate>) {
+    for item in &item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let Some(superstate) = superstates.get(&method.sig.ident) else {
+            continue;
+        };
+        let mut path = vec![&superstate.handler_name];
+        let mut parent = superstate.superstate.as_ref();
+        while let Some(next) = parent {
+            if let Some(index) = path.iter().position(|ident| *ident == next) {
+                let cycle: Vec<String> = path[index..]
+                    .iter()
+                    .chain([&next])
+                    .map(|ident| format!("`{ident}`"))
+                    .collect();
+                abort!(
+                    next,
+                    "superstate hierarchy contains a cycle: {}",
+                    cycle.join(" → ")
+                );
+            }
+            path.push(next);
+            parent = superstates
+                .get(next)
+                .and_then(|superstate| superstate.superstate.as_ref());
+        }
+    }
+}
+
+/// Abort when a referenced superstate doesn't exist, suggesting a superstate
+/// with a similar name if there is one.
+fn check_superstate_reference(
+    superstate: &Ident,
+    states: &HashMap<Ident, State>,
+    superstates: &HashMap<Ident, Superstate>,
+) {
+    if superstates.contains_key(superstate) {
+        return;
+    }
+    if states.contains_key(superstate) {
+        abort!(
+            superstate,
+            "superstate `{}` not found", superstate;
+            help = "`{}` is a state, tag it with `#[superstate]` instead", superstate
+        );
+    }
+    match similar_name(superstate, superstates.keys()) {
+        Some(similar) => abort!(
+            superstate,
+            "superstate `{}` not found", superstate;
+            help = "a superstate with a similar name exists: `{}`", similar
+        ),
+        None => abort!(superstate, "superstate `{}` not found", superstate),
+    }
+}
+
+/// Abort when a referenced action doesn't exist, or when it takes an input that
+/// can't be provided when it's called for the given state or superstate.
+fn check_action_reference(
+    action: &Ident,
+    handler_name: &Ident,
+    storage: &[Ident],
+    actions: &HashMap<Ident, Action>,
+    state_machine: &StateMachine,
+) {
+    let Some(analyzed_action) = actions.get(action) else {
+        match similar_name(action, actions.keys()) {
+            Some(similar) => abort!(
+                action,
+                "action `{}` not found", action;
+                help = "an action with a similar name exists: `{}`", similar
+            ),
+            None => abort!(
+                action,
+                "action `{}` not found", action;
+                help = "actions are tagged with `#[action]`"
+            ),
+        }
+    };
+
+    for input in &analyzed_action.inputs {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else {
+            continue;
+        };
+        let ident = &pat_ident.ident;
+        if *ident == state_machine.context_ident || storage.contains(ident) {
+            continue;
+        }
+        let available: Vec<String> = storage.iter().map(|ident| format!("`{ident}`")).collect();
+        let storage_note = match available.is_empty() {
+            true => format!("`{handler_name}` has no local storage"),
+            false => format!(
+                "the local storage of `{handler_name}` consists of {}",
+                available.join(", ")
+            ),
+        };
+        abort!(
+            ident,
+            "`{}` is not available when `{}` is called for `{}`", ident, action, handler_name;
+            help = "actions can take the shared storage, the context and the local storage of the state they are called for";
+            note = "{}", storage_note
+        );
+    }
+}
+
+/// The names of the local storage of a state or superstate.
+fn storage_idents<'a>(
+    state_inputs: &[PatType],
+    local_storage: impl IntoIterator<Item = &'a Field>,
+) -> Vec<Ident> {
+    let mut idents: Vec<Ident> = state_inputs
+        .iter()
+        .filter_map(|pat_type| match pat_type.pat.as_ref() {
+            Pat::Ident(pat_ident) => Some(pat_ident.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    for field in local_storage {
+        if let Some(ident) = &field.ident {
+            if !idents.contains(ident) {
+                idents.push(ident.clone());
+            }
+        }
+    }
+    idents
+}
+
+/// Find the candidate that is most similar to the name, if any is close
+/// enough to be a likely typo.
+pub fn similar_name<'a>(
+    name: &Ident,
+    candidates: impl Iterator<Item = &'a Ident>,
+) -> Option<&'a Ident> {
+    let name = name.to_string();
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(&name, &candidate.to_string()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by(|(a, a_ident), (b, b_ident)| {
+            a.cmp(b)
+                .then_with(|| a_ident.to_string().cmp(&b_ident.to_string()))
+        })
+        .map(|(_, candidate)| candidate)
+}
+
+/// The edit distance between two strings, where swapping two adjacent
+/// characters counts as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
 /// Retrieve the top level settings of the state machine.
 pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImpl) -> StateMachine {
     let shared_storage_type = item_impl.self_ty.as_ref().clone();
     let shared_storage_generics = item_impl.generics.clone();
     let shared_storage_path = get_shared_storage_path(&shared_storage_type);
 
-    let mut initial_state: Option<ExprCall> = None;
+    let mut initial_state: Option<Expr> = None;
 
     let mut state_ident = parse_quote!(State);
     let mut state_derives = Vec::new();
+    let mut state_non_exhaustive = false;
     let mut superstate_ident = parse_quote!(Superstate);
     let mut superstate_derives = Vec::new();
+    let mut superstate_non_exhaustive = false;
 
     let mut after_transition = None;
+    let mut on_transition = None;
+    let mut on_init = None;
     let mut before_transition = None;
     let mut before_dispatch = None;
     let mut after_dispatch = None;
 
     let mut visibility = parse_quote!(pub);
+    let mut module = None;
+    let mut max_state_size = None;
+    let mut unreachable_states = LintLevel::Allow;
+    let mut on_action_error = None;
+    let mut action_failure = ActionFailure::Abort;
+    let mut semantics = Semantics::Pragmatic;
+    let mut state_id_mirror = None;
+    let mut after = None;
+    let mut event_wrappers = Vec::new();
+    let mut event_enum = None;
+    let mut event_routing = None;
     let mut event_ident = parse_quote!(event);
     let mut context_ident = parse_quote!(context);
 
@@ -206,8 +689,10 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            // `event` is an alias of `event_identifier`.
             NestedMeta::Meta(Meta::NameValue(name_value))
-                if name_value.path.is_ident("event_identifier") =>
+                if name_value.path.is_ident("event_identifier")
+                    || name_value.path.is_ident("event") =>
             {
                 event_ident = match &name_value.lit {
                     Lit::Str(event_ident) => event_ident.parse().unwrap(),
@@ -239,7 +724,25 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
             NestedMeta::Meta(Meta::NameValue(name_value))
-                if name_value.path.is_ident("before_dispatch") =>
+                if name_value.path.is_ident("on_init") =>
+            {
+                on_init = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("on_transition") =>
+            {
+                on_transition = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            // `on_dispatch` is an alias of `before_dispatch`.
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("before_dispatch")
+                    || name_value.path.is_ident("on_dispatch") =>
             {
                 before_dispatch = match &name_value.lit {
                     Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
@@ -262,6 +765,97 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                     _ => abort!(name_value, "must be a string literal"),
                 }
             }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("module") => {
+                module = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("max_state_size") =>
+            {
+                max_state_size = match &name_value.lit {
+                    Lit::Int(int_lit) => match int_lit.base10_parse() {
+                        Ok(max_state_size) => Some(max_state_size),
+                        Err(error) => abort!(error),
+                    },
+                    _ => abort!(name_value, "must be an integer literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("unreachable_states") =>
+            {
+                unreachable_states = match &name_value.lit {
+                    Lit::Str(level) if level.value() == "allow" => LintLevel::Allow,
+                    Lit::Str(level) if level.value() == "warn" => LintLevel::Warn,
+                    Lit::Str(level) if level.value() == "deny" => LintLevel::Deny,
+                    _ => abort!(
+                        name_value.lit,
+                        "expected `\"allow\"`, `\"warn\"` or `\"deny\"`"
+                    ),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("on_action_error") =>
+            {
+                on_action_error = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("action_failure") =>
+            {
+                action_failure = match &name_value.lit {
+                    Lit::Str(policy) if policy.value() == "abort" => ActionFailure::Abort,
+                    Lit::Str(policy) if policy.value() == "proceed" => ActionFailure::Proceed,
+                    _ => abort!(name_value.lit, "expected `\"abort\"` or `\"proceed\"`"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("semantics") =>
+            {
+                semantics = match &name_value.lit {
+                    Lit::Str(kind) if kind.value() == "pragmatic" => Semantics::Pragmatic,
+                    Lit::Str(kind) if kind.value() == "uml" => Semantics::Uml,
+                    Lit::Str(kind) if kind.value() == "uml_local" => Semantics::UmlLocal,
+                    _ => abort!(
+                        name_value.lit,
+                        "expected `\"pragmatic\"`, `\"uml\"` or `\"uml_local\"`"
+                    ),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("state_id_mirror") =>
+            {
+                state_id_mirror = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("after") => {
+                after = match &name_value.lit {
+                    Lit::Str(input_pat) => Some(input_pat.parse().unwrap()),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("event_from") => {
+                for nested_meta in &list.nested {
+                    event_wrappers.push(match nested_meta {
+                        NestedMeta::Lit(Lit::Str(variant)) => analyze_event_wrapper(variant),
+                        _ => abort!(nested_meta, "must be a string literal"),
+                    });
+                }
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("events") => {
+                event_enum = Some(analyze_event_enum(list, false));
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("event_types") => {
+                event_enum = Some(analyze_event_enum(list, true));
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("event_routing") => {
+                event_routing = Some(path);
+            }
             NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("state") => {
                 state_meta = list.clone();
             }
@@ -273,6 +867,16 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
         }
     }
 
+    // The event routing lists every variant of the event enum, so the variants
+    // must be known to the macro.
+    if let (Some(path), None) = (event_routing, &event_enum) {
+        abort!(
+            path,
+            "the event routing requires an event enum that is generated by the macro";
+            help = "declare the events with `events(...)` or `event_types(...)`"
+        );
+    }
+
     // Check if there is an initial state given.
     let Some(initial_state) = initial_state else {
         abort!(
@@ -312,6 +916,11 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
 
+            // Mark the state enum as `#[non_exhaustive]`.
+            Meta::Path(path) if path.is_ident("non_exhaustive") => {
+                state_non_exhaustive = true;
+            }
+
             // Other attributes are not recognized.
             _ => abort!(meta, "unknown attribute"),
         }
@@ -347,6 +956,11 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
                 }
             }
 
+            // Mark the superstate enum as `#[non_exhaustive]`.
+            Meta::Path(path) if path.is_ident("non_exhaustive") => {
+                superstate_non_exhaustive = true;
+            }
+
             // Other attributes are not recognized.
             _ => abort!(meta, "unknown attribute"),
         }
@@ -359,63 +973,226 @@ pub fn analyze_state_machine(attribute_args: &AttributeArgs, item_impl: &ItemImp
         shared_storage_generics,
         state_ident,
         state_derives,
+        state_non_exhaustive,
         superstate_ident,
         superstate_derives,
+        superstate_non_exhaustive,
         before_dispatch,
         after_dispatch,
         before_transition,
         after_transition,
+        on_init,
+        on_transition,
         event_ident,
         context_ident,
         visibility,
+        module,
+        max_state_size,
+        unreachable_states,
+        state_id_mirror,
+        after,
+        on_action_error,
+        action_failure,
+        semantics,
+        event_wrappers,
+        event_enum,
+        event_routing: event_routing.is_some(),
+    }
+}
+
+/// Retrieve the event enum from `events("Control(ControlEvent)", ...)`, or
+/// the borrowing event enum from `event_types("Ping", ...)`.
+fn analyze_event_enum(list: &MetaList, borrowed: bool) -> EventEnum {
+    let mut ident = parse_quote!(Event);
+    let mut derives = Vec::new();
+    let mut variants = Vec::new();
+    for nested_meta in &list.nested {
+        match nested_meta {
+            NestedMeta::Lit(Lit::Str(variant)) if borrowed => {
+                variants.push(analyze_event_type(variant))
+            }
+            NestedMeta::Lit(Lit::Str(variant)) => variants.push(analyze_event_wrapper(variant)),
+
+            // Get the custom name for the event enum.
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("name") => {
+                ident = match &name_value.lit {
+                    Lit::Str(str_lit) => str_lit.parse().unwrap(),
+                    _ => abort!(name_value, "expected string literal"),
+                }
+            }
+
+            // Get the derives for the event enum.
+            NestedMeta::Meta(Meta::List(meta_list)) if meta_list.path.is_ident("derive") => {
+                for nested_meta in &meta_list.nested {
+                    match nested_meta {
+                        NestedMeta::Meta(meta) => derives.push(meta.path().clone()),
+                        _ => abort!(nested_meta, "expected list of traits"),
+                    }
+                }
+            }
+
+            _ => abort!(nested_meta, "unknown attribute"),
+        }
+    }
+    if variants.is_empty() {
+        match borrowed {
+            true => abort!(
+                list,
+                "no event types given";
+                help = "add the event types, e.g. `event_types(\"Ping\")`"
+            ),
+            false => abort!(
+                list,
+                "no event types given";
+                help = "add the variants of the event enum, e.g. `events(\"Control(ControlEvent)\")`"
+            ),
+        }
+    }
+    EventEnum {
+        ident,
+        derives,
+        variants,
+        borrowed,
+    }
+}
+
+/// Parse an event type of the borrowing event enum, such as `"Ping"`. The
+/// variant that borrows it is named after the type.
+fn analyze_event_type(ty: &LitStr) -> (Ident, Type) {
+    let ty: Type = match ty.parse() {
+        Ok(ty) => ty,
+        Err(error) => abort!(ty, "expected a type: {}", error),
+    };
+    match &ty {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            let ident = type_path.path.segments.last().unwrap().ident.clone();
+            (ident, ty)
+        }
+        _ => abort!(
+            ty,
+            "expected the path of an event type";
+            help = "the variant that borrows the event is named after the type, e.g. `\"Ping\"` or `\"protocol::Ping\"`"
+        ),
+    }
+}
+
+/// Parse a variant of the event enum that wraps a single type, such as
+/// `"Button(ButtonEvent)"`.
+fn analyze_event_wrapper(variant: &LitStr) -> (Ident, Type) {
+    let variant: Variant = match variant.parse() {
+        Ok(variant) => variant,
+        Err(error) => abort!(variant, "expected an enum variant: {}", error),
+    };
+    match &variant.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            (variant.ident.clone(), fields.unnamed[0].ty.clone())
+        }
+        _ => abort!(
+            variant,
+            "expected a variant that wraps a single type";
+            help = "use the form `\"Variant(Type)\"`"
+        ),
     }
 }
 
 /// Retrieve information regarding the state.
 pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> State {
     let handler_name = method.sig.ident.clone();
-    let inputs = method.sig.inputs.iter().cloned().collect();
+    let mut inputs: Vec<FnArg> = method.sig.inputs.iter().cloned().collect();
 
+    let mut name = None;
     let mut superstate = None;
     let mut entry_action = None;
     let mut exit_action = None;
+    let mut activity = None;
     let mut local_storage = Vec::new();
+    let mut payload = None;
+    let mut boxed = false;
+    let mut derives = Vec::new();
+    let mut builder = false;
+    let mut consts = Vec::new();
+    let mut transitions = Vec::new();
+    let mut when = None;
+    let mut when_target = None;
+    let mut when_priority = None;
+    let mut transition_actions = Vec::new();
+    let mut pseudo_state = None;
+    let mut metadata = Vec::new();
     let mut shared_storage_input = None;
     let mut state_inputs = Vec::new();
     let mut event_arg = None;
+    let mut event_pattern = None;
     let mut context_arg = None;
 
+    // Handlers can declare their own lifetimes, but not their own types.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
-            "state handlers can not define their generics themselves";
-            help = "consider declaring the generics on the impl block"
+            param,
+            "state handlers can not define their generic types themselves";
+            help = "consider declaring the generics on the impl block and bounding them in the where-clause of the handler"
         )
     }
 
     let is_async = method.sig.asyncness.is_some();
+    let (output, is_fallible) = analyze_handler_output(&method.sig.output);
+    let cfg_attrs = get_cfg_attrs(&method.attrs);
+    let forwarded_attrs = get_forwarded_attrs(&method.attrs);
 
     // Iterate over the inputs of the state handler.
-    for input in &method.sig.inputs {
+    for (index, input) in method.sig.inputs.iter().enumerate() {
         match input {
             FnArg::Receiver(receiver) => shared_storage_input = Some(receiver.clone()),
             FnArg::Typed(pat_type) => match *pat_type.pat.clone() {
                 Pat::Ident(pat) if state_machine.event_ident.eq(&pat.ident) => {
+                    if event_arg.is_some() {
+                        abort!(pat_type, "event is already bound by a pattern")
+                    }
                     event_arg = Some(pat_type.clone());
                 }
+                Pat::Struct(_) | Pat::Tuple(_) | Pat::TupleStruct(_) | Pat::Path(_)
+                    if is_shared_reference(&pat_type.ty) =>
+                {
+                    if event_arg.is_some() {
+                        abort!(pat_type, "event is already bound")
+                    }
+                    let bound = bind_event_pattern(pat_type, &state_machine.event_ident);
+                    inputs[index] = FnArg::Typed(bound.clone());
+                    event_pattern = Some(*pat_type.pat.clone());
+                    event_arg = Some(bound);
+                }
                 Pat::Ident(pat) if state_machine.context_ident.eq(&pat.ident) => {
                     context_arg = Some(pat_type.clone());
                 }
+                pat if is_storage_pattern(&pat) && is_mutable_reference(&pat_type.ty) => {
+                    let bound = bind_storage_pattern(pat_type);
+                    inputs[index] = FnArg::Typed(bound.clone());
+                    state_inputs.push(bound);
+                }
                 Pat::Ident(_) => {
                     state_inputs.push(pat_type.clone());
                 }
                 Pat::Reference(_) => {
                     state_inputs.push(pat_type.clone());
                 }
-                Pat::Tuple(_) => abort!(pat_type, "tuple pattern is not supported"),
-                Pat::TupleStruct(_) => abort!(pat_type, "tuple struct pattern is not supported"),
-                Pat::Struct(_) => abort!(pat_type, "struct pattern is not supported"),
+                Pat::Tuple(_) => abort!(
+                    pat_type,
+                    "tuple pattern is not supported";
+                    help = "tuple patterns can only destructure the event, which is passed in as a shared reference"
+                ),
+                Pat::TupleStruct(_) => abort!(
+                    pat_type,
+                    "tuple struct pattern is not supported";
+                    help = "tuple struct patterns can only destructure the event, which is passed in as a shared reference"
+                ),
+                Pat::Struct(_) => abort!(
+                    pat_type,
+                    "struct pattern is not supported";
+                    help = "struct patterns can only destructure the event or state-local storage, which is passed in as a mutable reference"
+                ),
                 Pat::Wild(_) => abort!(
                     pat_type,
                     "wildcard pattern is not supported";
@@ -426,22 +1203,43 @@ pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> S
         }
     }
 
+    check_storage_names(&state_inputs);
+
     // Iterate over the meta attributes on the state handler.
     for meta in get_meta(&method.attrs, "state") {
         match meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("name") => {
+                if let Lit::Str(value) = name_value.lit {
+                    name = Some(parse_variant_ident(&value));
+                }
+            }
             Meta::NameValue(name_value) if name_value.path.is_ident("superstate") => {
                 if let Lit::Str(value) = name_value.lit {
-                    superstate = Some(Ident::new(&value.value(), value.span()));
+                    superstate = Some(parse_handler_ident(&value));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("entry_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    entry_action = Some(Ident::new(&value.value(), value.span()));
+                    entry_action = Some(parse_action_path(&value));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("exit_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    exit_action = Some(Ident::new(&value.value(), value.span()));
+                    exit_action = Some(parse_action_path(&value));
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("activity") => {
+                if let Lit::Str(value) = name_value.lit {
+                    activity = Some(parse_action_path(&value));
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("payload") => {
+                payload = match &name_value.lit {
+                    Lit::Str(value) => match Field::parse_named.parse_str(&value.value()) {
+                        Ok(field) => Some(field),
+                        Err(error) => abort!(value, "expected a field: {}", error),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
                 }
             }
             Meta::List(list) if list.path.is_ident("local_storage") => {
@@ -452,70 +1250,308 @@ pub fn analyze_state(method: &ImplItemMethod, state_machine: &StateMachine) -> S
                     }
                 }
             }
+            Meta::List(list) if list.path.is_ident("transitions") => {
+                transitions.extend(analyze_transitions(list));
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("when") => {
+                when = match name_value.lit {
+                    Lit::Str(value) => Some(value),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("transition") => {
+                when_target = match name_value.lit {
+                    Lit::Str(value) => Some(value),
+                    _ => abort!(name_value, "must be a string literal"),
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("priority") => {
+                when_priority = match &name_value.lit {
+                    Lit::Int(value) => match value.base10_parse::<u32>() {
+                        Ok(priority) => Some((priority, name_value.clone())),
+                        Err(error) => abort!(value, "invalid priority: {}", error),
+                    },
+                    _ => abort!(name_value, "must be an integer literal"),
+                }
+            }
+            Meta::List(list) if list.path.is_ident("transition_actions") => {
+                transition_actions.extend(analyze_transition_actions(list));
+            }
+            Meta::List(list) if list.path.is_ident("meta") => {
+                metadata.extend(analyze_meta(list));
+            }
+            Meta::Path(path) if path.is_ident("boxed") => {
+                boxed = true;
+            }
+            Meta::Path(path) if path.is_ident("builder") => {
+                builder = true;
+            }
+            Meta::Path(path) if path.is_ident("choice") => {
+                pseudo_state = Some(PseudoState::Choice);
+            }
+            Meta::Path(path) if path.is_ident("junction") => {
+                pseudo_state = Some(PseudoState::Junction);
+            }
+            Meta::Path(path) if path.is_ident("terminate") => {
+                pseudo_state = Some(PseudoState::Terminate);
+            }
+            // Fork and join enter and leave several orthogonal regions at once,
+            // which a state machine that is in exactly one state doesn't have.
+            Meta::Path(path) if path.is_ident("fork") || path.is_ident("join") => abort!(
+                path,
+                "`{}` pseudo-states are not supported, as a state machine has no orthogonal regions",
+                path.to_token_stream();
+                help = "model each region as a separate state machine, with a `Router` delivering the events between them"
+            ),
+            Meta::List(list) if list.path.is_ident("const") => {
+                for nested_meta in list.nested {
+                    match nested_meta {
+                        NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                            match name_value.path.get_ident() {
+                                Some(ident) => consts.push((ident.clone(), name_value.lit)),
+                                None => abort!(name_value.path, "expected the name of a constant"),
+                            }
+                        }
+                        _ => abort!(
+                            nested_meta,
+                            "expected a constant";
+                            help = "declare constants as `const(TIMEOUT_MS = 500)`"
+                        ),
+                    }
+                }
+            }
+            Meta::List(list) if list.path.is_ident("derive") => {
+                for nested_meta in &list.nested {
+                    match nested_meta {
+                        NestedMeta::Meta(meta) => derives.push(meta.path().clone()),
+                        _ => abort!(nested_meta, "expected list of traits"),
+                    }
+                }
+            }
             _ => abort!(meta, "unknown attribute"),
         }
     }
 
+    // A condition transition is declared by a condition and a target, which
+    // is either the name of a state without local storage or a state.
+    let condition = match (when, when_target) {
+        (Some(when), Some(target)) => match (when.parse(), target.parse()) {
+            (Ok(when), Ok(Expr::Path(path))) if path.path.get_ident().is_some() => {
+                let state_ident = &state_machine.state_ident;
+                Some((when, parse_quote!(#state_ident::#path())))
+            }
+            (Ok(when), Ok(target)) => Some((when, target)),
+            (Err(error), _) => abort!(when, "expected a condition: {}", error),
+            (_, Err(error)) => abort!(target, "expected a state: {}", error),
+        },
+        (Some(when), None) => abort!(
+            when,
+            "condition without a target";
+            help = "add `transition = \"state_name\"` to the `state` attribute"
+        ),
+        (None, Some(target)) => abort!(
+            target,
+            "target without a condition";
+            help = "add `when = \"condition\"` to the `state` attribute"
+        ),
+        (None, None) => None,
+    };
+
+    // A condition transition with a priority is matched among the declared
+    // transitions instead of after the event was dispatched.
+    let (transitions, condition_position) = match (&condition, when_priority) {
+        (Some((when, _)), Some((priority, _))) => {
+            order_transitions(transitions, Some((when, priority)))
+        }
+        (None, Some((_, priority))) => abort!(
+            priority,
+            "priority without a condition";
+            help = "add `when = \"condition\"` and `transition = \"state_name\"` to the `state` attribute"
+        ),
+        (_, None) => order_transitions(transitions, None),
+    };
+
+    // A pseudo-state never handles events, its handler selects the target of
+    // the transition that reaches it or, for a terminate, does nothing.
+    if let Some(pseudo_state) = pseudo_state {
+        let kind = pseudo_state.name();
+        if let Some(input) = event_arg
+            .iter()
+            .chain(&context_arg)
+            .chain(&state_inputs)
+            .next()
+        {
+            abort!(
+                input,
+                "the handler of a {} can not take inputs other than `self`", kind;
+                help = "the state machine never handles events in a {}", kind
+            )
+        }
+        if is_async {
+            abort!(
+                method.sig.asyncness,
+                "the handler of a {} can not be async",
+                kind
+            )
+        }
+        if !local_storage.is_empty() || payload.is_some() {
+            abort!(handler_name, "a {} can not have state-local storage", kind)
+        }
+        if pseudo_state == PseudoState::Terminate
+            && (entry_action.is_some() || exit_action.is_some() || activity.is_some())
+        {
+            abort!(
+                handler_name,
+                "a terminate pseudo-state can not have actions";
+                help = "the state machine stops without executing any more actions when it reaches a terminate pseudo-state"
+            )
+        }
+        if entry_action.is_some() || exit_action.is_some() || activity.is_some() {
+            abort!(
+                handler_name,
+                "a {} can not have actions", kind;
+                help = "a {} is left as soon as it is entered, consider calling the action from its handler", kind
+            )
+        }
+        if pseudo_state == PseudoState::Terminate {
+            if is_fallible || !matches!(output, HandlerOutput::Unit) {
+                abort!(
+                    method.sig.output,
+                    "the handler of a terminate pseudo-state can not return anything";
+                    help = "the state machine stops as soon as it reaches a terminate pseudo-state"
+                )
+            }
+        } else if is_fallible || !matches!(output, HandlerOutput::Into) {
+            abort!(
+                method.sig,
+                "the handler of a {} must return the state it selects",
+                kind
+            )
+        }
+    }
+
     State {
         handler_name,
+        name,
         superstate,
         entry_action,
         exit_action,
+        activity,
         local_storage,
+        payload,
+        boxed,
+        derives,
+        builder,
+        consts,
+        transitions,
+        condition,
+        condition_position,
+        transition_actions,
+        pseudo_state,
         inputs,
         shared_storage_input,
         state_inputs,
         event_arg,
+        event_pattern,
         context_arg,
         is_async,
+        output,
+        is_fallible,
+        cfg_attrs,
+        forwarded_attrs,
+        meta: metadata,
     }
 }
 
 /// Retrieve the information regarding the superstate.
 pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine) -> Superstate {
     let handler_name = method.sig.ident.clone();
-    let inputs = method.sig.inputs.iter().cloned().collect();
+    let mut inputs: Vec<FnArg> = method.sig.inputs.iter().cloned().collect();
 
+    let mut name = None;
     let mut superstate = None;
     let mut entry_action = None;
     let mut exit_action = None;
+    let mut default_handler = None;
+    let mut completion = None;
     let mut local_storage = Vec::new();
+    let mut transitions = Vec::new();
+    let mut metadata = Vec::new();
     let mut shared_storage_input = None;
     let mut state_inputs = Vec::new();
     let mut event_arg = None;
+    let mut event_pattern = None;
     let mut context_arg = None;
 
+    // Handlers can declare their own lifetimes, but not their own types.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
-            "superstate handlers can not define their generics themselves";
-            help = "consider declaring the generics on the impl block"
+            param,
+            "superstate handlers can not define their generic types themselves";
+            help = "consider declaring the generics on the impl block and bounding them in the where-clause of the handler"
         )
     }
 
     let is_async = method.sig.asyncness.is_some();
+    let (output, is_fallible) = analyze_handler_output(&method.sig.output);
+    let cfg_attrs = get_cfg_attrs(&method.attrs);
+    let forwarded_attrs = get_forwarded_attrs(&method.attrs);
 
     // Iterate over the inputs of the superstate handler.
-    for input in &method.sig.inputs {
+    for (index, input) in method.sig.inputs.iter().enumerate() {
         match input {
             FnArg::Receiver(receiver) => shared_storage_input = Some(receiver.clone()),
             FnArg::Typed(pat_type) => match *pat_type.pat.clone() {
                 Pat::Ident(pat) if state_machine.event_ident.eq(&pat.ident) => {
+                    if event_arg.is_some() {
+                        abort!(pat_type, "event is already bound by a pattern")
+                    }
                     event_arg = Some(pat_type.clone());
                 }
+                Pat::Struct(_) | Pat::Tuple(_) | Pat::TupleStruct(_) | Pat::Path(_)
+                    if is_shared_reference(&pat_type.ty) =>
+                {
+                    if event_arg.is_some() {
+                        abort!(pat_type, "event is already bound")
+                    }
+                    let bound = bind_event_pattern(pat_type, &state_machine.event_ident);
+                    inputs[index] = FnArg::Typed(bound.clone());
+                    event_pattern = Some(*pat_type.pat.clone());
+                    event_arg = Some(bound);
+                }
                 Pat::Ident(pat) if state_machine.context_ident.eq(&pat.ident) => {
                     context_arg = Some(pat_type.clone());
                 }
+                pat if is_storage_pattern(&pat) && is_mutable_reference(&pat_type.ty) => {
+                    let bound = bind_storage_pattern(pat_type);
+                    inputs[index] = FnArg::Typed(bound.clone());
+                    state_inputs.push(bound);
+                }
                 Pat::Ident(_) => {
                     state_inputs.push(pat_type.clone());
                 }
                 Pat::Reference(_) => {
                     state_inputs.push(pat_type.clone());
                 }
-                Pat::Tuple(_) => abort!(pat_type, "tuple pattern is not supported"),
-                Pat::TupleStruct(_) => abort!(pat_type, "tuple struct pattern is not supported"),
-                Pat::Struct(_) => abort!(pat_type, "struct pattern is not supported"),
+                Pat::Tuple(_) => abort!(
+                    pat_type,
+                    "tuple pattern is not supported";
+                    help = "tuple patterns can only destructure the event, which is passed in as a shared reference"
+                ),
+                Pat::TupleStruct(_) => abort!(
+                    pat_type,
+                    "tuple struct pattern is not supported";
+                    help = "tuple struct patterns can only destructure the event, which is passed in as a shared reference"
+                ),
+                Pat::Struct(_) => abort!(
+                    pat_type,
+                    "struct pattern is not supported";
+                    help = "struct patterns can only destructure the event or state-local storage, which is passed in as a mutable reference"
+                ),
                 Pat::Wild(_) => abort!(
                     pat_type,
                     "wildcard pattern is not supported";
@@ -526,22 +1562,43 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
         }
     }
 
+    check_storage_names(&state_inputs);
+
     // Iterate over the meta attributes on the superstate handler.
     for meta in get_meta(&method.attrs, "superstate") {
         match meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("name") => {
+                if let Lit::Str(value) = name_value.lit {
+                    name = Some(parse_variant_ident(&value));
+                }
+            }
             Meta::NameValue(name_value) if name_value.path.is_ident("superstate") => {
                 if let Lit::Str(value) = name_value.lit {
-                    superstate = Some(Ident::new(&value.value(), value.span()));
+                    superstate = Some(parse_handler_ident(&value));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("entry_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    entry_action = Some(Ident::new(&value.value(), value.span()));
+                    entry_action = Some(parse_action_path(&value));
                 }
             }
             Meta::NameValue(name_value) if name_value.path.is_ident("exit_action") => {
                 if let Lit::Str(value) = name_value.lit {
-                    exit_action = Some(Ident::new(&value.value(), value.span()));
+                    exit_action = Some(parse_action_path(&value));
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("default") => {
+                if let Lit::Str(value) = name_value.lit {
+                    default_handler = Some(parse_handler_ident(&value));
+                }
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("completion") => {
+                completion = match &name_value.lit {
+                    Lit::Str(value) => match value.parse() {
+                        Ok(target) => Some(target),
+                        Err(error) => abort!(value, "expected a state: {}", error),
+                    },
+                    _ => abort!(name_value, "must be a string literal"),
                 }
             }
             Meta::List(list) if list.path.is_ident("local_storage") => {
@@ -552,47 +1609,419 @@ pub fn analyze_superstate(method: &ImplItemMethod, state_machine: &StateMachine)
                     }
                 }
             }
+            Meta::List(list) if list.path.is_ident("transitions") => {
+                transitions.extend(analyze_transitions(list));
+            }
+            Meta::List(list) if list.path.is_ident("meta") => {
+                metadata.extend(analyze_meta(list));
+            }
             _ => abort!(meta, "unknown attribute"),
         }
     }
 
     Superstate {
         handler_name,
+        name,
         superstate,
         entry_action,
         exit_action,
+        default_handler,
+        completion,
         local_storage,
+        transitions: order_transitions(transitions, None).0,
         inputs,
         shared_storage_input,
         state_inputs,
         event_arg,
+        event_pattern,
         context_arg,
         is_async,
+        output,
+        is_fallible,
+        cfg_attrs,
+        forwarded_attrs,
+        meta: metadata,
+    }
+}
+
+/// Retrieve the predicates in the where-clause of a handler that only bound the
+/// generics of the impl block (e.g. `where P: OutputPin`).
+pub fn analyze_handler_bounds(method: &ImplItemMethod) -> Vec<WherePredicate> {
+    let generics = &method.sig.generics;
+    let lifetimes: Vec<_> = generics.lifetimes().map(|def| &def.lifetime).collect();
+    generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .filter(|predicate| {
+            let mut visitor = GenericParamVisitor::new(generics);
+            visitor.visit_where_predicate(predicate);
+            !visitor.finish().iter().any(|param| {
+                matches!(param, GenericParam::Lifetime(def) if lifetimes.contains(&&def.lifetime))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Retrieve what a handler returns and whether it is wrapped in a `Result`.
+/// Handlers that return nothing handle every event, while anything but a
+/// `Response` is converted into one.
+pub fn analyze_handler_output(output: &ReturnType) -> (HandlerOutput, bool) {
+    match output {
+        ReturnType::Default => (HandlerOutput::Unit, false),
+        ReturnType::Type(_, ty) => match result_ok_type(ty) {
+            Some(ok_type) => (analyze_output_type(ok_type), true),
+            None => (analyze_output_type(ty), false),
+        },
+    }
+}
+
+fn analyze_output_type(ty: &Type) -> HandlerOutput {
+    match ty {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => HandlerOutput::Unit,
+        Type::Path(type_path)
+            if type_path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "Response") =>
+        {
+            HandlerOutput::Response
+        }
+        _ => HandlerOutput::Into,
+    }
+}
+
+/// Retrieve `T` from a `Result<T, E>`.
+fn result_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
     }
 }
 
 /// Retrieve the information regarding the action.
 pub fn analyze_action(method: &ImplItemMethod) -> Action {
     let handler_name = method.sig.ident.clone();
-    let inputs = method.sig.inputs.clone().into_iter().collect();
+    let inputs: Vec<FnArg> = method.sig.inputs.clone().into_iter().collect();
     let is_async = method.sig.asyncness.is_some();
+    let is_fallible = match &method.sig.output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    };
 
+    // Handlers can declare their own lifetimes, but not their own types.
     let generic_params = &method.sig.generics.params;
-    if !generic_params.is_empty() {
+    if let Some(param) = generic_params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
         abort!(
-            generic_params,
-            "action handlers can not define their generics themselves";
-            help = "consider declaring the generics on the impl block"
+            param,
+            "action handlers can not define their generic types themselves";
+            help = "consider declaring the generics on the impl block and bounding them in the where-clause of the handler"
         )
     }
 
+    // Inputs are passed by name, so they can't be destructured.
+    for input in &inputs {
+        if let FnArg::Typed(pat_type) = input {
+            if !matches!(pat_type.pat.as_ref(), Pat::Ident(_)) {
+                abort!(pat_type.pat, "patterns are not supported on action inputs");
+            }
+        }
+    }
+
     Action {
         handler_name,
         inputs,
         is_async,
+        is_fallible,
+        cfg_attrs: get_cfg_attrs(&method.attrs),
     }
 }
 
+/// Retrieve the key-value metadata (e.g. `owner = "comms"`).
+pub fn analyze_meta(list: MetaList) -> Vec<(Ident, LitStr)> {
+    let mut metadata: Vec<(Ident, LitStr)> = Vec::new();
+    for nested_meta in list.nested {
+        match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                let key = match name_value.path.get_ident() {
+                    Some(key) => key.clone(),
+                    None => abort!(name_value.path, "expected the name of a key"),
+                };
+                if let Some((first, _)) = metadata.iter().find(|(first, _)| *first == key) {
+                    abort!(
+                        key,
+                        "key `{}` is declared more than once", key;
+                        note = first.span() => "`{}` is first declared here", first
+                    );
+                }
+                match name_value.lit {
+                    Lit::Str(value) => metadata.push((key, value)),
+                    lit => abort!(lit, "must be a string literal"),
+                }
+            }
+            _ => abort!(
+                nested_meta,
+                "expected a key-value pair";
+                help = "declare metadata as `meta(owner = \"comms\")`"
+            ),
+        }
+    }
+    metadata
+}
+
+/// Retrieve the declared transitions (e.g. `"Event::A => State::a()"`).
+pub fn analyze_transitions(list: MetaList) -> Vec<Arm> {
+    list.nested
+        .iter()
+        .map(|nested_meta| match nested_meta {
+            NestedMeta::Lit(Lit::Str(value)) => match value.parse::<Arm>() {
+                Ok(arm) => arm,
+                Err(error) => abort!(
+                    value,
+                    "invalid transition: {}", error;
+                    help = "transitions are written as `\"Event::A => State::a()\"`"
+                ),
+            },
+            _ => abort!(nested_meta, "expected string literal"),
+        })
+        .collect()
+}
+
+/// Order the declared transitions by their priority (e.g.
+/// `"#[priority = 1] Event::A => State::a()"`), keeping the order in which they
+/// are declared among transitions of the same priority. A transition is
+/// ambiguous when an unguarded one of the same priority before it already
+/// matches all of its events, or when both are guarded and their event
+/// patterns overlap. A condition transition with a
+/// priority is ordered as a guarded transition for every event, declared after
+/// the others, and its position is returned alongside the transitions.
+fn order_transitions(
+    transitions: Vec<Arm>,
+    condition: Option<(&Expr, u32)>,
+) -> (Vec<Arm>, Option<usize>) {
+    let mut prioritized: Vec<(u32, Arm, bool)> = transitions
+        .into_iter()
+        .map(|mut arm| {
+            let mut priority = 0;
+            arm.attrs.retain(|attr| {
+                if !attr.path.is_ident("priority") {
+                    return true;
+                }
+                priority = match attr.parse_meta() {
+                    Ok(Meta::NameValue(MetaNameValue {
+                        lit: Lit::Int(value),
+                        ..
+                    })) => match value.base10_parse() {
+                        Ok(priority) => priority,
+                        Err(error) => abort!(value, "invalid priority: {}", error),
+                    },
+                    _ => abort!(attr, "expected a priority, e.g. `#[priority = 1]`"),
+                };
+                false
+            });
+            (priority, arm, false)
+        })
+        .collect();
+
+    if let Some((when, priority)) = condition {
+        let underscore = syn::Token![_](when.span());
+        let arm: Arm = parse_quote!(#underscore if #when => ());
+        prioritized.push((priority, arm, true));
+    }
+
+    for (index, (priority, arm, _)) in prioritized.iter().enumerate() {
+        let conflict = prioritized[..index]
+            .iter()
+            .find(|(other_priority, other, _)| {
+                other_priority == priority
+                    && match (&other.guard, &arm.guard) {
+                        (None, _) => pattern_covers(&other.pat, &arm.pat),
+                        (Some(_), Some(_)) => patterns_overlap(&other.pat, &arm.pat),
+                        (Some(_), None) => false,
+                    }
+            });
+        if let Some((_, other, _)) = conflict {
+            abort!(
+                arm.pat,
+                "ambiguous transition, `{}` is already handled by `{}` at the same priority",
+                arm.pat.to_token_stream(),
+                other.pat.to_token_stream();
+                note = other.pat.span() => "the other transition is declared here";
+                help = "give one of the transitions a higher priority, e.g. `#[priority = 1]`"
+            );
+        }
+    }
+
+    // The transitions with the highest priority are matched first.
+    prioritized.sort_by_key(|(priority, _, _)| core::cmp::Reverse(*priority));
+    let position = prioritized.iter().position(|(_, _, condition)| *condition);
+    let transitions = prioritized
+        .into_iter()
+        .filter(|(_, _, condition)| !condition)
+        .map(|(_, arm, _)| arm)
+        .collect();
+    (transitions, position)
+}
+
+/// Whether two event patterns can match the same event. The alternatives of
+/// or-patterns are compared one by one, wildcards and bindings match every
+/// event, and patterns of different variants never overlap. Fields are
+/// compared the same way, any other pattern is compared as written.
+fn patterns_overlap(a: &Pat, b: &Pat) -> bool {
+    match (a, b) {
+        (Pat::Or(a), _) => a.cases.iter().any(|a| patterns_overlap(a, b)),
+        (_, Pat::Or(b)) => b.cases.iter().any(|b| patterns_overlap(a, b)),
+        (Pat::Wild(_), _) | (_, Pat::Wild(_)) | (Pat::Rest(_), _) | (_, Pat::Rest(_)) => true,
+        (Pat::Ident(a), _) => match &a.subpat {
+            Some((_, a)) => patterns_overlap(a, b),
+            None => true,
+        },
+        (_, Pat::Ident(b)) => match &b.subpat {
+            Some((_, b)) => patterns_overlap(a, b),
+            None => true,
+        },
+        (Pat::Reference(a), _) => patterns_overlap(&a.pat, b),
+        (_, Pat::Reference(b)) => patterns_overlap(a, &b.pat),
+        (Pat::Type(a), _) => patterns_overlap(&a.pat, b),
+        (_, Pat::Type(b)) => patterns_overlap(a, &b.pat),
+        _ if pattern_path(a).is_some() && pattern_path(a) != pattern_path(b) => false,
+        (Pat::Path(_), _) | (_, Pat::Path(_)) => pattern_path(a) == pattern_path(b),
+        (Pat::TupleStruct(a), Pat::TupleStruct(b)) => {
+            elems_match(&a.pat.elems, &b.pat.elems, patterns_overlap)
+        }
+        (Pat::Tuple(a), Pat::Tuple(b)) => elems_match(&a.elems, &b.elems, patterns_overlap),
+        (Pat::Struct(a), Pat::Struct(b)) => a.fields.iter().all(|a_field| {
+            b.fields
+                .iter()
+                .find(|b_field| b_field.member == a_field.member)
+                .map_or(true, |b_field| patterns_overlap(&a_field.pat, &b_field.pat))
+        }),
+        _ => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
+    }
+}
+
+/// Whether the first event pattern matches every event the second one
+/// matches, so a transition with the second pattern declared after an
+/// unguarded one with the first pattern is never taken. Patterns are compared
+/// the same way as in [`patterns_overlap`], where an or-pattern only covers
+/// another pattern when one of its alternatives does.
+fn pattern_covers(a: &Pat, b: &Pat) -> bool {
+    match (a, b) {
+        (_, Pat::Or(b)) => b.cases.iter().all(|b| pattern_covers(a, b)),
+        (Pat::Or(a), _) => a.cases.iter().any(|a| pattern_covers(a, b)),
+        (Pat::Wild(_), _) | (Pat::Rest(_), _) => true,
+        (Pat::Ident(a), _) => match &a.subpat {
+            Some((_, a)) => pattern_covers(a, b),
+            None => true,
+        },
+        (_, Pat::Ident(b)) => match &b.subpat {
+            Some((_, b)) => pattern_covers(a, b),
+            None => false,
+        },
+        (_, Pat::Wild(_)) | (_, Pat::Rest(_)) => false,
+        (Pat::Reference(a), _) => pattern_covers(&a.pat, b),
+        (_, Pat::Reference(b)) => pattern_covers(a, &b.pat),
+        (Pat::Type(a), _) => pattern_covers(&a.pat, b),
+        (_, Pat::Type(b)) => pattern_covers(a, &b.pat),
+        _ if pattern_path(a).is_some() && pattern_path(a) != pattern_path(b) => false,
+        (Pat::Path(_), _) => pattern_path(a) == pattern_path(b),
+        (Pat::TupleStruct(a), Pat::TupleStruct(b)) => {
+            elems_match(&a.pat.elems, &b.pat.elems, pattern_covers)
+        }
+        (Pat::Tuple(a), Pat::Tuple(b)) => elems_match(&a.elems, &b.elems, pattern_covers),
+        (Pat::Struct(a), Pat::Struct(b)) => a.fields.iter().all(|a_field| {
+            match b
+                .fields
+                .iter()
+                .find(|b_field| b_field.member == a_field.member)
+            {
+                Some(b_field) => pattern_covers(&a_field.pat, &b_field.pat),
+                None => pattern_covers(&a_field.pat, &parse_quote!(_)),
+            }
+        }),
+        _ => a.to_token_stream().to_string() == b.to_token_stream().to_string(),
+    }
+}
+
+/// The path of the variant an event pattern matches (e.g. `Event::A`).
+fn pattern_path(pat: &Pat) -> Option<String> {
+    let path = match pat {
+        Pat::Path(pat) => &pat.path,
+        Pat::TupleStruct(pat) => &pat.path,
+        Pat::Struct(pat) => &pat.path,
+        _ => return None,
+    };
+    Some(path.to_token_stream().to_string())
+}
+
+/// Compare the elements of two tuple patterns pairwise. With a rest pattern
+/// (`..`) the elements can't be paired, so the first pattern's elements are
+/// compared with a wildcard instead.
+fn elems_match<'a>(
+    a: impl IntoIterator<Item = &'a Pat>,
+    b: impl IntoIterator<Item = &'a Pat>,
+    compare: fn(&Pat, &Pat) -> bool,
+) -> bool {
+    let a: Vec<&Pat> = a.into_iter().collect();
+    let b: Vec<&Pat> = b.into_iter().collect();
+    let has_rest = |elems: &[&Pat]| elems.iter().any(|pat| matches!(pat, Pat::Rest(_)));
+    if has_rest(&a) || has_rest(&b) {
+        let wildcard: Pat = parse_quote!(_);
+        return a.iter().all(|a| compare(a, &wildcard));
+    }
+    a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| compare(a, b))
+}
+
+/// Retrieve the actions of transitions (e.g. `"error => send_nack"`).
+pub fn analyze_transition_actions(list: MetaList) -> Vec<(Ident, Ident)> {
+    list.nested
+        .iter()
+        .map(|nested_meta| match nested_meta {
+            NestedMeta::Lit(Lit::Str(value)) => {
+                let parser = |input: syn::parse::ParseStream| {
+                    let target: Ident = input.parse()?;
+                    input.parse::<syn::Token![=>]>()?;
+                    let action: Ident = input.parse()?;
+                    Ok((target, action))
+                };
+                match value.parse_with(parser) {
+                    Ok(transition_action) => transition_action,
+                    Err(error) => abort!(
+                        value,
+                        "invalid transition action: {}", error;
+                        help = "transition actions are written as `\"target_state => action\"`"
+                    ),
+                }
+            }
+            _ => abort!(nested_meta, "expected string literal"),
+        })
+        .collect()
+}
+
 /// Parse the attributes as a meta item.
 pub fn get_meta(attrs: &[Attribute], name: &str) -> Vec<Meta> {
     attrs
@@ -611,6 +2040,135 @@ pub fn get_meta(attrs: &[Attribute], name: &str) -> Vec<Meta> {
         .collect()
 }
 
+/// Abort when the name of a handler was already used, so it doesn't silently
+/// replace another handler with the same name. The map associates every name
+/// with its first occurrence.
+///
+/// Handlers that share a name but are compiled under different `#[cfg]`
+/// attributes are accepted when their signatures and other attributes are the
+/// same, as at most one of them is compiled. The first occurrence is returned
+/// so they can be analyzed as one handler.
+fn check_duplicate_name<'a>(
+    names: &mut HashMap<Ident, &'a ImplItemMethod>,
+    method: &'a ImplItemMethod,
+) -> Option<&'a ImplItemMethod> {
+    let name = &method.sig.ident;
+    let Some(first) = names.get(name).copied() else {
+        names.insert(name.clone(), method);
+        return None;
+    };
+    let other_attrs = |method: &'a ImplItemMethod| {
+        method
+            .attrs
+            .iter()
+            .filter(|attr| !attr.path.is_ident("cfg"))
+            .map(ToTokens::to_token_stream)
+            .map(|tokens| tokens.to_string())
+            .collect::<Vec<_>>()
+    };
+    if get_cfg_attrs(&first.attrs) == get_cfg_attrs(&method.attrs) {
+        abort!(
+            name,
+            "`{}` is defined more than once", name;
+            note = first.sig.ident.span() => "`{}` is first defined here", first.sig.ident
+        );
+    }
+    if first.sig.to_token_stream().to_string() != method.sig.to_token_stream().to_string()
+        || other_attrs(first) != other_attrs(method)
+    {
+        abort!(
+            name,
+            "`{}` is defined more than once with a different signature or attributes", name;
+            note = first.sig.ident.span() => "`{}` is first defined here", first.sig.ident;
+            help = "handlers that share a name under different `#[cfg]` attributes must only differ in their body"
+        );
+    }
+    Some(first)
+}
+
+/// Merge the conditional compilation attributes of a handler with those of
+/// another handler with the same name, so the result holds in every
+/// configuration in which either of them is compiled. A handler that is
+/// compiled under `#[cfg(x)]` and one under `#[cfg(not(x))]` together are
+/// always compiled.
+fn merge_cfg_attrs(cfg_attrs: &mut Vec<Attribute>, other: &[Attribute]) {
+    let predicates = |cfg_attrs: &[Attribute]| -> Vec<TokenStream> {
+        cfg_attrs
+            .iter()
+            .map(|attr| {
+                attr.parse_args()
+                    .unwrap_or_else(|error| abort!(attr, "{}", error))
+            })
+            .collect()
+    };
+    let (first, second) = (predicates(cfg_attrs), predicates(other));
+    let is_negation = |predicate: &TokenStream, negated: &TokenStream| {
+        quote::quote!(not(#predicate)).to_string() == negated.to_string()
+    };
+    *cfg_attrs = match (first.as_slice(), second.as_slice()) {
+        ([], _) | (_, []) => Vec::new(),
+        ([first], [second]) if is_negation(first, second) || is_negation(second, first) => {
+            Vec::new()
+        }
+        _ => vec![parse_quote!(#[cfg(any(all(#(#first),*), all(#(#second),*)))])],
+    };
+}
+
+/// Iterate over the methods of the `impl` block in declaration order, skipping
+/// the methods that share the name of an earlier one, as handlers that are
+/// compiled under different `#[cfg]` attributes are analyzed as one.
+pub fn unique_methods(item_impl: &ItemImpl) -> impl Iterator<Item = &ImplItemMethod> {
+    let mut names = HashSet::new();
+    item_impl.items.iter().filter_map(move |item| match item {
+        ImplItem::Method(method) if names.insert(&method.sig.ident) => Some(method),
+        _ => None,
+    })
+}
+
+/// Get the conditional compilation attributes (e.g. `#[cfg(feature = "factory")]`).
+pub fn get_cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+/// Get the attributes on an input of a state handler that are moved to the
+/// field of the variant (e.g. `#[serde(skip)]`). Only the attributes that the
+/// compiler accepts on a function parameter (e.g. `#[cfg]` or `#[allow]`) are
+/// left on the input.
+pub fn get_field_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    const PARAM_ATTRS: [&str; 7] = [
+        "cfg", "cfg_attr", "allow", "expect", "warn", "deny", "forbid",
+    ];
+    attrs
+        .iter()
+        .filter(|attr| !PARAM_ATTRS.iter().any(|name| attr.path.is_ident(name)))
+        .cloned()
+        .collect()
+}
+
+/// Get the attributes that are forwarded from a handler to the generated
+/// variant (e.g. doc comments, `#[deprecated]` and `#[allow]`). An `#[expect]`
+/// is forwarded as an `#[allow]`, as the lint might not be triggered by the
+/// generated code.
+pub fn get_forwarded_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.path {
+            path if path.is_ident("doc") || path.is_ident("deprecated") => Some(attr.clone()),
+            path if path.is_ident("allow") => Some(attr.clone()),
+            path if path.is_ident("expect") => {
+                let mut attr = attr.clone();
+                attr.path = parse_quote!(allow);
+                Some(attr)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Get the ident of the shared storage type.
 pub fn get_shared_storage_path(ty: &Type) -> Path {
     match ty {
@@ -622,6 +2180,133 @@ pub fn get_shared_storage_path(ty: &Type) -> Path {
     }
 }
 
+/// Whether the type is a shared reference (e.g. `&Event`).
+fn is_shared_reference(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(reference) if reference.mutability.is_none())
+}
+
+/// Whether the type is a mutable reference (e.g. `&mut OnData`).
+fn is_mutable_reference(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(reference) if reference.mutability.is_some())
+}
+
+/// Whether the pattern destructures state-local storage, either on its own
+/// (e.g. `OnData { counter, .. }`) or bound to a name (e.g.
+/// `data @ OnData { counter, .. }`).
+pub fn is_storage_pattern(pat: &Pat) -> bool {
+    match pat {
+        Pat::Struct(_) => true,
+        Pat::Ident(pat) => {
+            matches!(&pat.subpat, Some((_, subpat)) if matches!(subpat.as_ref(), Pat::Struct(_)))
+        }
+        _ => false,
+    }
+}
+
+/// Replace the pattern that destructures state-local storage with the name
+/// of the field in the state. The name is taken from the binding of the
+/// pattern (e.g. `data @ OnData { counter, .. }` becomes `data`), or derived
+/// from the name of the struct when there is none (e.g. `OnData { counter, .. }`
+/// becomes `on_data`).
+fn bind_storage_pattern(pat_type: &PatType) -> PatType {
+    let field_ident = match pat_type.pat.as_ref() {
+        Pat::Ident(pat) => pat.ident.clone(),
+        Pat::Struct(pat) => pascal_case_to_snake_case(&pat.path.segments.last().unwrap().ident),
+        _ => abort!(pat_type, "expected a struct pattern"),
+    };
+    let mut pat_type = pat_type.clone();
+    pat_type.pat = parse_quote!(#field_ident);
+    pat_type
+}
+
+/// Abort when two inputs of a handler are stored in fields with the same
+/// name, e.g. when two structs of the same type are destructured.
+fn check_storage_names(state_inputs: &[PatType]) {
+    for (index, input) in state_inputs.iter().enumerate() {
+        let Pat::Ident(pat) = input.pat.as_ref() else {
+            continue;
+        };
+        let duplicate = state_inputs[..index].iter().any(
+            |other| matches!(other.pat.as_ref(), Pat::Ident(other) if other.ident == pat.ident),
+        );
+        if duplicate {
+            abort!(
+                input,
+                "state-local storage `{}` is already declared", pat.ident;
+                help = "bind one of the patterns to a name of its own, e.g. `other @ OnData {{ .. }}`"
+            );
+        }
+    }
+}
+
+/// Convert a `PascalCase` identifier into a `snake_case` identifier. An
+/// acronym is kept together as a single word (e.g. `HTTPData` becomes
+/// `http_data`).
+fn pascal_case_to_snake_case(pascal: &Ident) -> Ident {
+    let chars: Vec<char> = pascal.to_string().chars().collect();
+    let mut snake = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        let starts_word = index > 0
+            && c.is_uppercase()
+            && (!chars[index - 1].is_uppercase()
+                || chars
+                    .get(index + 1)
+                    .map_or(false, |next| next.is_lowercase()));
+        if starts_word {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    // A name that collides with a keyword (e.g. `Loop`) becomes a raw
+    // identifier.
+    match syn::parse_str::<Ident>(&snake) {
+        Ok(_) => Ident::new(&snake, pascal.span()),
+        Err(_) => Ident::new_raw(&snake, pascal.span()),
+    }
+}
+
+/// Parse the name of an action in the impl block (e.g. `"enter_on"`) or the
+/// path to a free function (e.g. `"crate::actions::start_motor"`).
+fn parse_action_path(value: &LitStr) -> Path {
+    match value.parse::<Path>() {
+        Ok(path) => path,
+        Err(_) => abort!(
+            value,
+            "`{}` is not a valid action name or path", value.value();
+            help = "names that collide with a keyword must be written as raw identifiers, e.g. `r#{}`", value.value()
+        ),
+    }
+}
+
+/// Parse the name of a variant (e.g. `name = "WaitingForAck"`).
+fn parse_variant_ident(value: &LitStr) -> Ident {
+    match value.parse::<Ident>() {
+        Ok(ident) => ident,
+        Err(_) => abort!(value, "`{}` is not a valid variant name", value.value()),
+    }
+}
+
+/// Parse the name of a handler that is referenced in an attribute (e.g.
+/// `superstate = "r#loop"`).
+fn parse_handler_ident(value: &LitStr) -> Ident {
+    match value.parse::<Ident>() {
+        Ok(ident) => ident,
+        Err(_) => abort!(
+            value,
+            "`{}` is not a valid handler name", value.value();
+            help = "names that collide with a keyword must be written as raw identifiers, e.g. `r#{}`", value.value()
+        ),
+    }
+}
+
+/// Replace the pattern that destructures the event with the event identifier,
+/// so the handler can be called with the event like any other handler.
+fn bind_event_pattern(pat_type: &PatType, event_ident: &Ident) -> PatType {
+    let mut pat_type = pat_type.clone();
+    pat_type.pat = parse_quote!(#event_ident);
+    pat_type
+}
+
 #[test]
 fn valid_state_analyze() {
     use syn::parse_quote;
@@ -668,13 +2353,28 @@ fn valid_state_analyze() {
     let state_derives = vec![parse_quote!(Copy), parse_quote!(Clone)];
     let superstate_ident = parse_quote!(Superstate);
     let superstate_derives = vec![parse_quote!(Copy), parse_quote!(Clone)];
+    let state_non_exhaustive = false;
+    let superstate_non_exhaustive = false;
     let before_transition = None;
     let after_transition = None;
+    let on_init = None;
+    let on_transition = None;
     let before_dispatch = None;
     let after_dispatch = None;
     let event_ident = parse_quote!(event);
     let context_ident = parse_quote!(context);
     let visibility = parse_quote!(pub);
+    let module = None;
+    let max_state_size = None;
+    let unreachable_states = LintLevel::Allow;
+    let state_id_mirror = None;
+    let after = None;
+    let on_action_error = None;
+    let action_failure = ActionFailure::Abort;
+    let semantics = Semantics::Pragmatic;
+    let event_wrappers = Vec::new();
+    let event_enum = None;
+    let event_routing = false;
 
     let state_machine = StateMachine {
         initial_state,
@@ -683,23 +2383,50 @@ fn valid_state_analyze() {
         shared_storage_generics,
         state_ident,
         state_derives,
+        state_non_exhaustive,
         superstate_ident,
         superstate_derives,
+        superstate_non_exhaustive,
         before_transition,
         after_transition,
+        on_init,
+        on_transition,
         before_dispatch,
         after_dispatch,
         event_ident,
         context_ident,
         visibility,
+        module,
+        max_state_size,
+        unreachable_states,
+        state_id_mirror,
+        after,
+        on_action_error,
+        action_failure,
+        semantics,
+        event_wrappers,
+        event_enum,
+        event_routing,
     };
 
     let state = State {
         handler_name: parse_quote!(on),
+        name: None,
         superstate: parse_quote!(playing),
-        entry_action: parse_quote!(enter_on),
-        exit_action: parse_quote!(enter_off),
+        entry_action: Some(parse_quote!(enter_on)),
+        exit_action: Some(parse_quote!(enter_off)),
+        activity: None,
         local_storage: vec![],
+        payload: None,
+        boxed: false,
+        derives: vec![],
+        builder: false,
+        consts: vec![],
+        transitions: vec![],
+        condition: None,
+        condition_position: None,
+        transition_actions: vec![],
+        pseudo_state: None,
         inputs: vec![parse_quote!(&mut self), parse_quote!(event: &Event)],
         shared_storage_input: Some(parse_quote!(&mut self)),
         state_inputs: vec![],
@@ -708,16 +2435,26 @@ fn valid_state_analyze() {
         } else {
             return;
         }),
+        event_pattern: None,
         context_arg: None,
         is_async: false,
+        output: HandlerOutput::Response,
+        is_fallible: false,
+        cfg_attrs: vec![],
+        forwarded_attrs: vec![],
+        meta: vec![],
     };
 
     let superstate = Superstate {
         handler_name: parse_quote!(playing),
+        name: None,
         superstate: None,
         entry_action: None,
         exit_action: None,
+        default_handler: None,
+        completion: None,
         local_storage: vec![],
+        transitions: vec![],
         inputs: vec![parse_quote!(&mut self), parse_quote!(event: &Event)],
         shared_storage_input: Some(parse_quote!(&mut self)),
         state_inputs: vec![],
@@ -726,20 +2463,30 @@ fn valid_state_analyze() {
         } else {
             return;
         }),
+        event_pattern: None,
         context_arg: None,
         is_async: false,
+        output: HandlerOutput::Response,
+        is_fallible: false,
+        cfg_attrs: vec![],
+        forwarded_attrs: vec![],
+        meta: vec![],
     };
 
     let entry_action = Action {
         handler_name: parse_quote!(enter_on),
         inputs: vec![parse_quote!(&mut self)],
         is_async: false,
+        is_fallible: false,
+        cfg_attrs: vec![],
     };
 
     let exit_action = Action {
         handler_name: parse_quote!(enter_off),
         inputs: vec![parse_quote!(&mut self)],
         is_async: false,
+        is_fallible: false,
+        cfg_attrs: vec![],
     };
 
     let mut states = HashMap::new();
@@ -761,3 +2508,341 @@ fn valid_state_analyze() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn duplicate_name_with_different_cfg_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Device {
+            #[cfg(test)]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                Transition(State::running())
+            }
+
+            #[cfg(not(test))]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[cfg(feature = "a")]
+            #[action]
+            fn log(&mut self) {}
+
+            #[cfg(feature = "b")]
+            #[action]
+            fn log(&mut self) {}
+        }
+    );
+
+    let model = analyze(vec![parse_quote!(initial = "State::idle()")], item_impl);
+
+    assert_eq!(model.states.len(), 1);
+    assert_eq!(model.states[&parse_quote!(idle)].cfg_attrs, vec![]);
+    let expected: Attribute = parse_quote!(#[cfg(any(all(feature = "a"), all(feature = "b")))]);
+    assert_eq!(model.actions[&parse_quote!(log)].cfg_attrs, vec![expected]);
+}
+
+#[test]
+#[should_panic]
+fn duplicate_name_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Device {
+            #[cfg(test)]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[cfg(test)]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::idle()")], item_impl);
+}
+
+#[test]
+#[should_panic]
+fn duplicate_name_with_different_signature_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Device {
+            #[cfg(test)]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[cfg(not(test))]
+            #[state]
+            fn idle(counter: &mut u32, event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::idle()")], item_impl);
+}
+
+#[test]
+fn action_with_local_storage_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_action = "enter_on")]
+            fn on(counter: &mut u32, event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[action]
+            fn enter_on(&mut self, counter: &mut u32) {}
+        }
+    );
+
+    let model = analyze(vec![parse_quote!(initial = "State::on(0)")], item_impl);
+
+    assert_eq!(
+        model.states[&parse_quote!(on)].entry_action,
+        Some(parse_quote!(enter_on))
+    );
+}
+
+#[test]
+#[should_panic]
+fn missing_action_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_action = "enter_on")]
+            fn on(counter: &mut u32, event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[action]
+            fn enter_off(&mut self, counter: &mut u32) {}
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::on(0)")], item_impl);
+}
+
+#[test]
+#[should_panic]
+fn unavailable_action_input_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(entry_action = "enter_on")]
+            fn on(counter: &mut u32, event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[action]
+            fn enter_on(&mut self, led: &mut bool) {}
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::on(0)")], item_impl);
+}
+
+#[test]
+#[should_panic]
+fn superstate_cycle_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state(superstate = "a")]
+            fn on(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[superstate(superstate = "b")]
+            fn a(event: &Event) -> Response<State> {
+                Super
+            }
+
+            #[superstate(superstate = "a")]
+            fn b(event: &Event) -> Response<State> {
+                Super
+            }
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+}
+
+#[test]
+#[should_panic]
+fn fork_analyze() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Sequencer {
+            #[state(fork)]
+            fn start() {}
+        }
+    );
+
+    analyze(vec![parse_quote!(initial = "State::start()")], item_impl);
+}
+
+#[test]
+fn handler_bounds_analyze() {
+    let method: ImplItemMethod = parse_quote!(
+        #[action]
+        fn enter_on<'a>(&mut self, led: &'a mut P)
+        where
+            P: OutputPin,
+            P: 'a,
+        {
+        }
+    );
+
+    let actual = analyze_handler_bounds(&method);
+    let expected: Vec<WherePredicate> = vec![parse_quote!(P: OutputPin)];
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn pascal_case_to_snake_case_analyze() {
+    let pascal: Ident = parse_quote!(OnData);
+    assert_eq!(pascal_case_to_snake_case(&pascal), "on_data");
+
+    let keyword: Ident = parse_quote!(Loop);
+    assert_eq!(pascal_case_to_snake_case(&keyword), "r#loop");
+
+    let acronym: Ident = parse_quote!(HTTPData);
+    assert_eq!(pascal_case_to_snake_case(&acronym), "http_data");
+
+    let trailing_acronym: Ident = parse_quote!(RawIO);
+    assert_eq!(pascal_case_to_snake_case(&trailing_acronym), "raw_io");
+}
+
+#[test]
+fn similar_name_analyze() {
+    let candidates: Vec<Ident> = vec![parse_quote!(playing), parse_quote!(paused)];
+
+    let typo: Ident = parse_quote!(playingg);
+    assert_eq!(similar_name(&typo, candidates.iter()), Some(&candidates[0]));
+
+    let swapped: Ident = parse_quote!(palying);
+    assert_eq!(
+        similar_name(&swapped, candidates.iter()),
+        Some(&candidates[0])
+    );
+
+    let unrelated: Ident = parse_quote!(stopped);
+    assert_eq!(similar_name(&unrelated, candidates.iter()), None);
+}
+
+#[test]
+fn order_transitions_analyze() {
+    let transitions: Vec<Arm> = vec![
+        parse_quote!(Event::A if ready => State::a()),
+        parse_quote!(#[priority = 1] Event::A if urgent => State::b()),
+        parse_quote!(Event::A => State::c()),
+    ];
+
+    let expected: Vec<Arm> = vec![
+        parse_quote!(Event::A if urgent => State::b()),
+        parse_quote!(Event::A if ready => State::a()),
+        parse_quote!(Event::A => State::c()),
+    ];
+
+    assert_eq!(order_transitions(transitions, None), (expected, None));
+}
+
+#[test]
+fn order_transitions_with_condition_analyze() {
+    let transitions: Vec<Arm> = vec![
+        parse_quote!(Event::A => State::a()),
+        parse_quote!(#[priority = 2] Event::B => State::b()),
+    ];
+    let condition: Expr = parse_quote!(self.pressure > LIMIT);
+
+    let expected: Vec<Arm> = vec![
+        parse_quote!(Event::B => State::b()),
+        parse_quote!(Event::A => State::a()),
+    ];
+
+    assert_eq!(
+        order_transitions(transitions, Some((&condition, 1))),
+        (expected, Some(1))
+    );
+}
+
+#[test]
+fn patterns_overlap_analyze() {
+    // Or-patterns are only parsed as the pattern of a match arm.
+    let pat = |arm: Arm| arm.pat;
+    let a = pat(parse_quote!(Event::A => ()));
+    let b = pat(parse_quote!(Event::B => ()));
+    let a_or_b = pat(parse_quote!(Event::A | Event::B => ()));
+    let c_or_d = pat(parse_quote!(Event::C | Event::D => ()));
+    let wildcard = pat(parse_quote!(_ => ()));
+    let low = pat(parse_quote!(Event::Level(Level::Low) => ()));
+    let high = pat(parse_quote!(Event::Level(Level::High) => ()));
+    let any_level = pat(parse_quote!(Event::Level(_) => ()));
+    let named = pat(parse_quote!(Event::Data { kind: Kind::Text, .. } => ()));
+    let other_named = pat(parse_quote!(Event::Data { len: 0, .. } => ()));
+
+    assert!(patterns_overlap(&a, &a));
+    assert!(!patterns_overlap(&a, &b));
+    assert!(patterns_overlap(&a_or_b, &a));
+    assert!(!patterns_overlap(&a_or_b, &c_or_d));
+    assert!(patterns_overlap(&wildcard, &a));
+    assert!(patterns_overlap(&a, &wildcard));
+    assert!(!patterns_overlap(&low, &high));
+    assert!(patterns_overlap(&any_level, &high));
+    assert!(patterns_overlap(&named, &other_named));
+
+    assert!(pattern_covers(&a_or_b, &a));
+    assert!(!pattern_covers(&a, &a_or_b));
+    assert!(pattern_covers(&wildcard, &a));
+    assert!(!pattern_covers(&a, &wildcard));
+    assert!(pattern_covers(&any_level, &high));
+    assert!(!pattern_covers(&high, &any_level));
+    assert!(!pattern_covers(&named, &other_named));
+}
+
+#[test]
+fn fallback_wildcard_analyze() {
+    let transitions: Vec<Arm> = vec![
+        parse_quote!(Event::A => State::a()),
+        parse_quote!(_ => State::b()),
+    ];
+
+    assert_eq!(
+        order_transitions(transitions.clone(), None),
+        (transitions, None)
+    );
+}
+
+#[test]
+#[should_panic]
+fn overlapping_or_pattern_analyze() {
+    let transitions: Vec<Arm> = vec![
+        parse_quote!(Event::A | Event::B => State::a()),
+        parse_quote!(Event::A => State::b()),
+    ];
+
+    order_transitions(transitions, None);
+}
+
+#[test]
+#[should_panic]
+fn overlapping_wildcard_analyze() {
+    let transitions: Vec<Arm> = vec![
+        parse_quote!(_ => State::a()),
+        parse_quote!(Event::A => State::b()),
+    ];
+
+    order_transitions(transitions, None);
+}
+
+#[test]
+#[should_panic]
+fn overlapping_condition_analyze() {
+    let transitions: Vec<Arm> = vec![parse_quote!(Event::A if ready => State::a())];
+    let condition: Expr = parse_quote!(self.pressure > LIMIT);
+
+    order_transitions(transitions, Some((&condition, 0)));
+}
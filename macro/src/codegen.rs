@@ -1,10 +1,16 @@
-use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use std::collections::HashMap;
+
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::visit::Visit;
 use syn::{
-    parse_quote, Arm, GenericParam, ItemEnum, ItemFn, ItemImpl, Lifetime, LifetimeDef, Variant,
+    parse_quote, Arm, Block, GenericParam, ImplItem, ItemConst, ItemEnum, ItemFn, ItemImpl,
+    Lifetime, LifetimeDef, Variant,
 };
 
-use crate::lower::{Ir, Mode};
+use crate::analyze;
+use crate::lower::{get_generic_argument_ident, Ir, Mode, State};
+use crate::visitors::StateReferenceVisitor;
 use crate::{CONTEXT_LIFETIME, EVENT_LIFETIME, SUPERSTATE_LIFETIME};
 
 pub fn codegen(ir: Ir) -> TokenStream {
@@ -13,29 +19,64 @@ pub fn codegen(ir: Ir) -> TokenStream {
     let state_machine_impl = codegen_state_machine_impl(&ir);
 
     let state_enum = codegen_state(&ir);
+    let state_items = states_in_declaration_order(&ir).flat_map(|state| &state.items);
+    let state_size_assertion = codegen_state_size_assertion(&ir);
+    let unreachable_state_warnings = codegen_unreachable_state_warnings(&ir);
     let state_impl = codegen_state_impl(&ir);
     let state_impl_state = codegen_state_impl_state(&ir);
+    let event_enum = &ir.state_machine.event_enum;
+    let event_from_impls = &ir.state_machine.event_from_impls;
     let superstate_enum = codegen_superstate(&ir);
     let superstate_impl = codegen_superstate_impl_superstate(&ir);
 
-    quote!(
-        // Import the proc_macro attributes so they can be used to tag functions.
-        use statig::{state, superstate, action};
-
-        #item_impl
-
+    let derived_items = quote!(
         #state_machine_impl
 
         #state_enum
 
+        #(#state_items)*
+
+        #state_size_assertion
+
+        #(#unreachable_state_warnings)*
+
         #state_impl
 
         #state_impl_state
 
+        #event_enum
+
+        #(#event_from_impls)*
+
         #superstate_enum
 
         #superstate_impl
-    )
+    );
+
+    match &ir.state_machine.module {
+        // The derived types are generated in a module of their own, which can
+        // see everything in the scope of the `impl` block.
+        Some(module) => {
+            let visibility = &ir.state_machine.visibility;
+            quote!(
+                #item_impl
+
+                #visibility mod #module {
+                    use super::*;
+
+                    #derived_items
+                }
+            )
+        }
+        None => quote!(
+            // Import the proc_macro attributes so they can be used to tag functions.
+            use statig::{state, superstate, action};
+
+            #item_impl
+
+            #derived_items
+        ),
+    }
 }
 
 fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
@@ -73,6 +114,20 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
         ),
     };
 
+    let on_init = match &ir.state_machine.on_init {
+        None => quote!(),
+        Some(on_init) => quote!(
+            const ON_INIT: fn(&mut Self, &Self::State) = #on_init;
+        ),
+    };
+
+    let on_transition = match &ir.state_machine.on_transition {
+        None => quote!(),
+        Some(on_transition) => quote!(
+            const ON_TRANSITION: fn(&mut Self, &Self::State, &Self::State, &Self::Event<'_>) = #on_transition;
+        ),
+    };
+
     let before_dispatch = match &ir.state_machine.before_dispatch {
         None => quote!(),
         Some(before_dispatch) => quote!(
@@ -86,6 +141,99 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
         ),
     };
 
+    let state_id_mirror = match &ir.state_machine.state_id_mirror {
+        None => quote!(),
+        Some(state_id_mirror) => quote!(
+            const STATE_ID_MIRROR: Option<&'static core::sync::atomic::AtomicU8> = Some(&#state_id_mirror);
+        ),
+    };
+
+    let after = match &ir.state_machine.after {
+        None => quote!(),
+        Some(after) => quote!(
+            const AFTER: fn(&Self::State) -> Option<(core::time::Duration, Self::State)> = #after;
+        ),
+    };
+
+    let when = match &ir.state_machine.when {
+        None => quote!(),
+        Some(when) => quote!(
+            #[allow(unused_variables)]
+            const WHEN: fn(&Self, &Self::State) -> Option<Self::State> = #when;
+        ),
+    };
+
+    let choice = match &ir.state_machine.choice {
+        None => quote!(),
+        Some(choice) => quote!(
+            #[allow(unused_variables)]
+            const CHOICE: fn(&mut Self, &Self::State) -> Option<Self::State> = #choice;
+        ),
+    };
+
+    let junction = match &ir.state_machine.junction {
+        None => quote!(),
+        Some(junction) => quote!(
+            #[allow(unused_variables)]
+            const JUNCTION: fn(&mut Self, &Self::State) -> Option<Self::State> = #junction;
+        ),
+    };
+
+    let terminate = match &ir.state_machine.terminate {
+        None => quote!(),
+        Some(terminate) => quote!(
+            const TERMINATE: fn(&Self::State) -> bool = #terminate;
+        ),
+    };
+
+    let transition_action = match &ir.state_machine.transition_action {
+        None => quote!(),
+        Some(transition_action) => quote!(
+            const TRANSITION_ACTION: fn(&mut Self, &Self::State, &Self::State) = #transition_action;
+        ),
+    };
+
+    let semantics = match &ir.state_machine.semantics {
+        None => quote!(),
+        Some(semantics) => quote!(
+            const SEMANTICS: statig::Semantics = #semantics;
+        ),
+    };
+
+    let hierarchy = &ir.state_machine.hierarchy;
+    let transitions = &ir.state_machine.transitions;
+    let event_routing = match &ir.state_machine.event_routing {
+        None => quote!(),
+        Some(routes) => quote!(
+            const EVENT_ROUTING: &'static [statig::EventRoute] = &[#(#routes),*];
+        ),
+    };
+
+    // The longest path is known at compile time, so the worst-case execution
+    // time of a transition can be bounded analytically.
+    // With UML semantics a transition can exit and enter every level when it
+    // is taken by a top-level superstate, and so can a transition that passes
+    // through a top-level choice.
+    let ancestor_indices = ancestor_indices(ir);
+    let exits_every_level =
+        ir.state_machine.semantics.is_some() || ir.state_machine.choice.is_some();
+    let max_transition_depth = match exits_every_level {
+        false => transition_paths(&ancestor_indices)
+            .flatten()
+            .map(|(exit_levels, enter_levels)| exit_levels + enter_levels)
+            .max()
+            .unwrap_or(0),
+        true => {
+            ancestor_indices
+                .iter()
+                .map(|ancestors| ancestors.len() + 1)
+                .max()
+                .unwrap_or(0)
+                * 2
+        }
+    };
+    let max_transition_depth = Literal::usize_unsuffixed(max_transition_depth);
+
     parse_quote!(
         impl #impl_generics statig::#mode::IntoStateMachine for #shared_storage_type #where_clause
         {
@@ -95,11 +243,33 @@ fn codegen_state_machine_impl(ir: &Ir) -> ItemImpl {
             type Superstate<#superstate_lifetime> = #superstate_ident #superstate_generics ;
             const INITIAL: #state_ident #state_generics = #initial_state;
 
+            #on_init
+
             #before_transition
             #after_transition
+            #on_transition
 
             #before_dispatch
             #after_dispatch
+
+            #state_id_mirror
+            #after
+            #when
+            #choice
+            #junction
+            #terminate
+
+            #transition_action
+
+            #semantics
+
+            const MAX_TRANSITION_DEPTH: usize = #max_transition_depth;
+
+            const HIERARCHY: &'static [statig::StateInfo] = &[#(#hierarchy),*];
+
+            const TRANSITIONS: &'static [statig::TransitionInfo] = &[#(#transitions),*];
+
+            #event_routing
         }
     )
 }
@@ -108,22 +278,203 @@ fn codegen_state(ir: &Ir) -> ItemEnum {
     let state_ident = &ir.state_machine.state_ident;
     let (state_generics, _, _) = &ir.state_machine.state_generics.split_for_impl();
     let state_derives = &ir.state_machine.state_derives;
+    let non_exhaustive = match ir.state_machine.state_non_exhaustive {
+        true => quote!(#[non_exhaustive]),
+        false => quote!(),
+    };
 
-    let variants: Vec<Variant> = ir
-        .states
-        .values()
+    let variants: Vec<Variant> = states_in_declaration_order(ir)
         .map(|state| state.variant.clone())
         .collect();
     let visibility = &ir.state_machine.visibility;
+    let doc = format!(
+        "State of the `{}` state machine.",
+        get_generic_argument_ident(&ir.state_machine.shared_storage_type)
+    );
 
+    // Variants and fields are only documented when the user documented the
+    // handlers, so `missing_docs` is silenced for them.
     parse_quote!(
+        #[doc = #doc]
+        #[allow(missing_docs)]
         #[derive(#(#state_derives),*)]
+        #non_exhaustive
         # visibility enum #state_ident #state_generics {
             #(#variants),*
         }
     )
 }
 
+/// Iterate over the states in the order in which they are declared in the
+/// `impl` block, so the state enum and the state ids are stable across builds.
+fn states_in_declaration_order(ir: &Ir) -> impl Iterator<Item = &State> {
+    analyze::unique_methods(&ir.item_impl).filter_map(|method| ir.states.get(&method.sig.ident))
+}
+
+/// Get the indices of the superstates of every state from the top down, with
+/// the states in declaration order.
+fn ancestor_indices(ir: &Ir) -> Vec<Vec<usize>> {
+    let superstate_indices: HashMap<&Ident, usize> = ir
+        .superstates
+        .keys()
+        .enumerate()
+        .map(|(index, key)| (key, index))
+        .collect();
+
+    states_in_declaration_order(ir)
+        .map(|state| {
+            state
+                .ancestors
+                .iter()
+                .rev()
+                .map(|ancestor| superstate_indices[ancestor])
+                .collect()
+        })
+        .collect()
+}
+
+/// Compute the `(exit_levels, enter_levels)` pair for every transition between
+/// two states, given the superstate indices of every state from the top down.
+fn transition_paths(
+    ancestor_indices: &[Vec<usize>],
+) -> impl Iterator<Item = impl Iterator<Item = (usize, usize)> + '_> + '_ {
+    ancestor_indices
+        .iter()
+        .enumerate()
+        .map(move |(source_index, source)| {
+            ancestor_indices
+                .iter()
+                .enumerate()
+                .map(move |(target_index, target)| {
+                    transition_path(source, target, source_index == target_index)
+                })
+        })
+}
+
+/// Compute the `(exit_levels, enter_levels)` pair for a transition between two
+/// states, given the superstate indices of both states from the top down.
+fn transition_path(source: &[usize], target: &[usize], same_state: bool) -> (usize, usize) {
+    if same_state {
+        return (1, 1);
+    }
+
+    let common = source
+        .iter()
+        .zip(target)
+        .take_while(|(source, target)| source == target)
+        .count();
+
+    (source.len() + 1 - common, target.len() + 1 - common)
+}
+
+/// Compute the `(exit_levels, enter_levels)` pair for the transitions from
+/// every state to the states that are referenced by its handler, the handlers
+/// of its superstates and its declared transitions, as
+/// `(source, target, exit_levels, enter_levels)` sorted by source and target.
+/// Returns `None` when the indices or the levels don't fit in a `u8`.
+fn declared_transition_paths(
+    ir: &Ir,
+    ancestor_indices: &[Vec<usize>],
+) -> Option<Vec<(u8, u8, u8, u8)>> {
+    // Handlers that share a name under different `#[cfg]` attributes each
+    // contribute the transitions of their own body.
+    let mut blocks: HashMap<&Ident, Vec<&Block>> = HashMap::new();
+    for item in &ir.item_impl.items {
+        if let ImplItem::Method(method) = item {
+            blocks
+                .entry(&method.sig.ident)
+                .or_default()
+                .push(&method.block);
+        }
+    }
+    let state_keys: Vec<&Ident> = analyze::unique_methods(&ir.item_impl)
+        .map(|method| &method.sig.ident)
+        .filter(|ident| ir.states.contains_key(*ident))
+        .collect();
+    let state_indices: HashMap<&Ident, usize> = state_keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| (*key, index))
+        .collect();
+
+    let mut paths = Vec::new();
+    for (source_index, key) in state_keys.iter().enumerate() {
+        let state = &ir.states[*key];
+        let mut visitor = StateReferenceVisitor::new(
+            &ir.state_machine.state_ident,
+            ir.state_machine.module.as_ref(),
+            &[],
+        );
+        for handler in std::iter::once(*key).chain(&state.ancestors) {
+            for block in blocks.get(handler).into_iter().flatten() {
+                visitor.visit_block(block);
+            }
+        }
+        for arm in &state.transition_arms {
+            visitor.visit_arm(arm);
+        }
+        for arm in &state.ancestor_transition_arms {
+            visitor.visit_arm(arm);
+        }
+        let mut targets: Vec<usize> = visitor
+            .finish()
+            .iter()
+            .filter_map(|(target, _)| state_indices.get(target).copied())
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        for target_index in targets {
+            let (exit_levels, enter_levels) = transition_path(
+                &ancestor_indices[source_index],
+                &ancestor_indices[target_index],
+                source_index == target_index,
+            );
+            paths.push((
+                u8::try_from(source_index).ok()?,
+                u8::try_from(target_index).ok()?,
+                u8::try_from(exit_levels).ok()?,
+                u8::try_from(enter_levels).ok()?,
+            ));
+        }
+    }
+    Some(paths)
+}
+
+fn codegen_state_size_assertion(ir: &Ir) -> Option<ItemConst> {
+    let state_ident = &ir.state_machine.state_ident;
+    let max_state_size = ir.state_machine.max_state_size?;
+    let message = format!("`{state_ident}` exceeds the maximum size of {max_state_size} bytes");
+
+    Some(parse_quote!(
+        const _: () = assert!(core::mem::size_of::<#state_ident>() <= #max_state_size, #message);
+    ))
+}
+
+/// Proc macros can't emit warnings on stable, so unreachable states are
+/// reported through the use of a deprecated item instead.
+fn codegen_unreachable_state_warnings(ir: &Ir) -> Vec<ItemConst> {
+    ir.state_machine
+        .unreachable_states
+        .iter()
+        .map(|state| {
+            let cfg_attrs = &ir.states[state].cfg_attrs;
+            let note = format!(
+                "state `{state}` is unreachable, as it is neither the initial state nor the target of a transition"
+            );
+            let unreachable = quote_spanned!(state.span()=> UnreachableState);
+            parse_quote!(
+                #(#cfg_attrs)*
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    struct UnreachableState;
+                    let _ = #unreachable;
+                };
+            )
+        })
+        .collect()
+}
+
 fn codegen_state_impl(ir: &Ir) -> ItemImpl {
     let state_ident = &ir.state_machine.state_ident;
     let (impl_generics, state_generics, _) = &ir.state_machine.state_generics.split_for_impl();
@@ -131,13 +482,17 @@ fn codegen_state_impl(ir: &Ir) -> ItemImpl {
     let constructors: Vec<ItemFn> = ir
         .states
         .values()
-        .map(|state| &state.constructor)
+        .flat_map(|state| std::iter::once(&state.constructor).chain(&state.payload_constructor))
         .cloned()
         .collect();
+    let const_lookups = &ir.state_machine.const_lookups;
 
     parse_quote!(
+        #[allow(deprecated, clippy::too_many_arguments)]
         impl #impl_generics #state_ident #state_generics {
             #(#constructors)*
+
+            #(#const_lookups)*
         }
     )
 }
@@ -151,67 +506,376 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
     let event_ident = &ir.state_machine.event_ident;
     let context_ident = &ir.state_machine.context_ident;
 
+    let mode = match ir.state_machine.mode {
+        Mode::Blocking => quote!(blocking),
+        Mode::Awaitable => quote!(awaitable),
+    };
+    let await_call = match ir.state_machine.mode {
+        Mode::Blocking => quote!(),
+        Mode::Awaitable => quote!(.await),
+    };
+
     let mut constructors: Vec<ItemFn> = Vec::new();
     let mut call_handler_arms: Vec<Arm> = Vec::new();
     let mut call_entry_action_arms: Vec<Arm> = Vec::new();
     let mut call_exit_action_arms: Vec<Arm> = Vec::new();
+    let mut call_activity_arms: Vec<Arm> = Vec::new();
+    let mut call_ancestor_entry_action_arms: Vec<Arm> = Vec::new();
+    let mut call_ancestor_exit_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_entry_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_exit_action_arms: Vec<Arm> = Vec::new();
+    let mut transition_arms: Vec<Arm> = Vec::new();
+    let mut ancestor_transition_arms: Vec<Arm> = Vec::new();
     let mut superstate_arms: Vec<Arm> = Vec::new();
     let mut same_state_arms: Vec<Arm> = Vec::new();
+    let mut index_arms: Vec<Arm> = Vec::new();
+    let mut from_state_id_arms: Vec<Arm> = Vec::new();
+    let mut depths: Vec<Literal> = Vec::new();
+    let mut recreatable = true;
 
-    for state in ir.states.values() {
+    for (index, state) in states_in_declaration_order(ir).enumerate() {
         let pat = &state.pat;
         let handler_call = &state.handler_call;
         let entry_action_call = &state.entry_action_call;
         let exit_action_call = &state.exit_action_call;
+        let activity_call = &state.activity_call;
+        let try_entry_action_call = &state.try_entry_action_call;
+        let try_exit_action_call = &state.try_exit_action_call;
         let superstate_pat = &state.superstate_pat;
+        let cfg_attrs = &state.cfg_attrs;
 
         constructors.push(state.constructor.clone());
-        call_handler_arms.push(parse_quote!(#pat => #handler_call));
-        call_entry_action_arms.push(parse_quote!(#pat => #entry_action_call));
-        call_exit_action_arms.push(parse_quote!(#pat => #exit_action_call));
-        superstate_arms.push(parse_quote!(#pat => #superstate_pat));
+        try_call_entry_action_arms
+            .push(parse_quote!(#(#cfg_attrs)* (#pat, 0) => #try_entry_action_call));
+        try_call_exit_action_arms
+            .push(parse_quote!(#(#cfg_attrs)* (#pat, 0) => #try_exit_action_call));
+        call_handler_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #handler_call));
+        call_entry_action_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #entry_action_call));
+        call_exit_action_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #exit_action_call));
+        call_activity_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #activity_call));
+        superstate_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #superstate_pat));
+        transition_arms.extend(state.transition_arms.iter().cloned());
+        ancestor_transition_arms.extend(state.ancestor_transition_arms.iter().cloned());
+
+        // The depth of every state and the transition path between every pair
+        // of states are laid out in const tables indexed by the state, so
+        // computing a transition path doesn't require walking up the hierarchy.
+        let variant_ident = &state.variant.ident;
+        let index = Literal::usize_unsuffixed(index);
+        index_arms.push(parse_quote!(#(#cfg_attrs)* #state_ident::#variant_ident { .. } => #index));
+        // Only states without state-local storage can be recreated from their id.
+        if state.variant.fields.is_empty() {
+            from_state_id_arms
+                .push(parse_quote!(#(#cfg_attrs)* #index => Some(#state_ident::#variant_ident {})));
+        } else {
+            recreatable = false;
+        }
+        depths.push(Literal::usize_unsuffixed(state.ancestors.len() + 1));
+
+        // Every ancestor is constructed directly from the state, so entering or
+        // exiting a level is a single lookup instead of a walk up the hierarchy.
+        for (height, ancestor) in state.ancestors.iter().enumerate() {
+            let ancestor_pat = &ir.superstates[ancestor].pat;
+            let height = Literal::usize_unsuffixed(height + 1);
+            call_ancestor_entry_action_arms.push(parse_quote!(
+                #(#cfg_attrs)* (#pat, #height) => statig::#mode::Superstate::<#shared_storage_type>::call_entry_action(
+                    &mut #ancestor_pat,
+                    shared_storage,
+                    #context_ident
+                )#await_call
+            ));
+            call_ancestor_exit_action_arms.push(parse_quote!(
+                #(#cfg_attrs)* (#pat, #height) => statig::#mode::Superstate::<#shared_storage_type>::call_exit_action(
+                    &mut #ancestor_pat,
+                    shared_storage,
+                    #context_ident
+                )#await_call
+            ));
+            try_call_entry_action_arms.push(parse_quote!(
+                #(#cfg_attrs)* (#pat, #height) => statig::#mode::Superstate::<#shared_storage_type>::try_call_entry_action(
+                    &mut #ancestor_pat,
+                    shared_storage,
+                    #context_ident
+                )#await_call
+            ));
+            try_call_exit_action_arms.push(parse_quote!(
+                #(#cfg_attrs)* (#pat, #height) => statig::#mode::Superstate::<#shared_storage_type>::try_call_exit_action(
+                    &mut #ancestor_pat,
+                    shared_storage,
+                    #context_ident
+                )#await_call
+            ));
+        }
     }
 
     call_handler_arms.push(parse_quote!(_ => statig::Response::Super));
     call_entry_action_arms.push(parse_quote!(_ => {}));
     call_exit_action_arms.push(parse_quote!(_ => {}));
+    call_activity_arms.push(parse_quote!(_ => {}));
+    call_ancestor_entry_action_arms.push(parse_quote!(_ => {}));
+    call_ancestor_exit_action_arms.push(parse_quote!(_ => {}));
+    try_call_entry_action_arms.push(parse_quote!(_ => true));
+    try_call_exit_action_arms.push(parse_quote!(_ => true));
     superstate_arms.push(parse_quote!(_ => None));
+    from_state_id_arms.push(parse_quote!(_ => None));
     same_state_arms.push(parse_quote!(_ => false));
 
+    // Only the transition paths between a state and the states that it
+    // references are laid out in a table, so its size grows with the number of
+    // transitions instead of the square of the number of states. Other
+    // transitions walk up the hierarchy.
+    let ancestor_indices = ancestor_indices(ir);
+    let lookup_transition_path = declared_transition_paths(ir, &ancestor_indices).map(|paths| {
+        let path_count = Literal::usize_unsuffixed(paths.len());
+        let paths = paths
+            .into_iter()
+            .map(|(source, target, exit_levels, enter_levels)| {
+                quote!((#source, #target, #exit_levels, #enter_levels))
+            });
+        quote!(
+            fn lookup_transition_path(&self, target: &Self) -> Option<(usize, usize)> {
+                const PATHS: [(u8, u8, u8, u8); #path_count] = [#(#paths),*];
+
+                let source = match self { #(#index_arms),* };
+                let target = match target { #(#index_arms),* };
+
+                PATHS
+                    .binary_search_by_key(&(source, target), |&(source, target, _, _)| (source, target))
+                    .ok()
+                    .map(|index| (PATHS[index].2 as usize, PATHS[index].3 as usize))
+            }
+        )
+    });
+
+    let state_count = Literal::usize_unsuffixed(ir.states.len());
+    let lookups = quote!(
+        const RECREATABLE: bool = #recreatable;
+
+        fn state_id(&self) -> Option<usize> {
+            Some(match self { #(#index_arms),* })
+        }
+
+        fn from_state_id(state_id: usize) -> Option<Self> {
+            match state_id { #(#from_state_id_arms),* }
+        }
+
+        fn lookup_depth(&self) -> Option<usize> {
+            const DEPTHS: [usize; #state_count] = [#(#depths),*];
+
+            Some(DEPTHS[match self { #(#index_arms),* }])
+        }
+
+        #lookup_transition_path
+    );
+
+    // When a failed action aborts the transition, the actions are called
+    // through the fallible variants, so the transition can stop at the
+    // action that failed.
+    let try_call_actions = match (
+        ir.state_machine.aborts_on_action_failure,
+        ir.state_machine.mode,
+    ) {
+        (false, _) => quote!(),
+        (true, Mode::Blocking) => quote!(
+            fn try_call_entry_action(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                height: usize
+            ) -> bool {
+                match (self, height) {
+                    #(#try_call_entry_action_arms),*
+                }
+            }
+
+            fn try_call_exit_action(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                height: usize
+            ) -> bool {
+                match (self, height) {
+                    #(#try_call_exit_action_arms),*
+                }
+            }
+        ),
+        (true, Mode::Awaitable) => quote!(
+            fn try_call_entry_action<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                height: usize
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = bool> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    match (self, height) {
+                        #(#try_call_entry_action_arms),*
+                    }
+                })
+            }
+
+            fn try_call_exit_action<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                height: usize
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = bool> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    match (self, height) {
+                        #(#try_call_exit_action_arms),*
+                    }
+                })
+            }
+        ),
+    };
+
+    // The declared transitions of a state are matched before its handler is called.
+    let declared_transitions = match transition_arms.is_empty() {
+        true => quote!(),
+        false => quote!(
+            match (&*self, #event_ident) {
+                #(#transition_arms,)*
+                _ => {}
+            }
+        ),
+    };
+
+    // The declared transitions of the ancestors of a state are matched once its
+    // handler defers the event, before the superstates are dispatched to.
+    let ancestor_transitions = match ancestor_transition_arms.is_empty() {
+        true => quote!(),
+        false => quote!(
+            if let statig::Response::Super = response {
+                match (&*self, #event_ident) {
+                    #(#ancestor_transition_arms,)*
+                    _ => {}
+                }
+            }
+        ),
+    };
+
+    // With UML semantics the handler also returns the source of the
+    // transition, which the declared transitions return themselves.
+    let call_handler = match (ir.state_machine.mode, ir.state_machine.semantics.is_some()) {
+        (Mode::Blocking, false) => quote!(
+            fn call_handler(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #event_ident: &<#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> statig::Response<Self> where Self: Sized {
+                #declared_transitions
+                let response = match self {
+                    #(#call_handler_arms),*
+                };
+                #ancestor_transitions
+                response
+            }
+        ),
+        (Mode::Blocking, true) => quote!(
+            fn call_handler(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #event_ident: &<#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> statig::Response<Self> where Self: Sized {
+                self.call_handler_with_source(shared_storage, #event_ident, #context_ident).0
+            }
+
+            fn call_handler_with_source(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #event_ident: &<#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> (statig::Response<Self>, usize) {
+                #declared_transitions
+                let response = match self {
+                    #(#call_handler_arms),*
+                };
+                #ancestor_transitions
+                (response, 0)
+            }
+        ),
+        (Mode::Awaitable, false) => quote!(
+            fn call_handler<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = statig::Response<Self>> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    #declared_transitions
+                    let response = match self {
+                        #(#call_handler_arms),*
+                    };
+                    #ancestor_transitions
+                    response
+                })
+            }
+        ),
+        (Mode::Awaitable, true) => quote!(
+            fn call_handler<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = statig::Response<Self>> + 'fut + Send>> {
+                let future = self.call_handler_with_source(shared_storage, #event_ident, #context_ident);
+                statig::alloc::boxed::Box::pin(async move { future.await.0 })
+            }
+
+            fn call_handler_with_source<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = (statig::Response<Self>, usize)> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    #declared_transitions
+                    let response = match self {
+                        #(#call_handler_arms),*
+                    };
+                    #ancestor_transitions
+                    (response, 0)
+                })
+            }
+        ),
+    };
+
     match ir.state_machine.mode {
         Mode::Blocking => {
             parse_quote!(
-                #[allow(unused)]
+                #[allow(unused, deprecated)]
                 impl #impl_generics statig::blocking::State<#shared_storage_type> for #state_ident #state_generics #where_clause
                 {
-                    fn call_handler(
+                    #call_handler
+
+                    fn call_entry_action(
                         &mut self,
                         shared_storage: &mut #shared_storage_type,
-                        #event_ident: &<#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
                         #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                    ) -> statig::Response<Self> where Self: Sized {
+                    ) {
                         match self {
-                            #(#call_handler_arms),*
+                            #(#call_entry_action_arms),*
                         }
                     }
 
-                    fn call_entry_action(
+                    fn call_exit_action(
                         &mut self,
                         shared_storage: &mut #shared_storage_type,
                         #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
                     ) {
                         match self {
-                            #(#call_entry_action_arms),*
+                            #(#call_exit_action_arms),*
                         }
                     }
 
-                    fn call_exit_action(
+                    fn call_activity(
                         &mut self,
                         shared_storage: &mut #shared_storage_type,
                         #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
                     ) {
                         match self {
-                            #(#call_exit_action_arms),*
+                            #(#call_activity_arms),*
                         }
                     }
 
@@ -220,46 +884,73 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                             #(#superstate_arms),*
                         }
                     }
+
+                    #lookups
+
+                    fn call_ancestor_entry_action(
+                        &mut self,
+                        shared_storage: &mut #shared_storage_type,
+                        #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                        height: usize
+                    ) {
+                        match (self, height) {
+                            #(#call_ancestor_entry_action_arms),*
+                        }
+                    }
+
+                    fn call_ancestor_exit_action(
+                        &mut self,
+                        shared_storage: &mut #shared_storage_type,
+                        #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                        height: usize
+                    ) {
+                        match (self, height) {
+                            #(#call_ancestor_exit_action_arms),*
+                        }
+                    }
+
+                    #try_call_actions
                 }
             )
         }
         Mode::Awaitable => parse_quote!(
-            #[allow(unused)]
+            #[allow(unused, deprecated)]
             impl #impl_generics statig::awaitable::State<#shared_storage_type> for #state_ident #state_generics #where_clause
             {
-                fn call_handler<'fut>(
+                #call_handler
+
+                fn call_entry_action<'fut>(
                     &'fut mut self,
                     shared_storage: &'fut mut #shared_storage_type,
-                    #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
                     #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = statig::Response<Self>> + 'fut + Send>> {
-                    Box::pin(async move {
+                ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                    statig::alloc::boxed::Box::pin(async move {
                         match self {
-                            #(#call_handler_arms),*
+                            #(#call_entry_action_arms),*
                         }
                     })
                 }
 
-                fn call_entry_action<'fut>(
+                fn call_exit_action<'fut>(
                     &'fut mut self,
                     shared_storage: &'fut mut #shared_storage_type,
                     #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
-                    Box::pin(async move {
+                ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                    statig::alloc::boxed::Box::pin(async move {
                         match self {
-                            #(#call_entry_action_arms),*
+                            #(#call_exit_action_arms),*
                         }
                     })
                 }
 
-                fn call_exit_action<'fut>(
+                fn call_activity<'fut>(
                     &'fut mut self,
                     shared_storage: &'fut mut #shared_storage_type,
                     #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
-                    Box::pin(async move {
+                ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                    statig::alloc::boxed::Box::pin(async move {
                         match self {
-                            #(#call_exit_action_arms),*
+                            #(#call_activity_arms),*
                         }
                     })
                 }
@@ -269,6 +960,36 @@ fn codegen_state_impl_state(ir: &Ir) -> ItemImpl {
                         #(#superstate_arms),*
                     }
                 }
+
+                #lookups
+
+                fn call_ancestor_entry_action<'fut>(
+                    &'fut mut self,
+                    shared_storage: &'fut mut #shared_storage_type,
+                    #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                    height: usize
+                ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                    statig::alloc::boxed::Box::pin(async move {
+                        match (self, height) {
+                            #(#call_ancestor_entry_action_arms),*
+                        }
+                    })
+                }
+
+                fn call_ancestor_exit_action<'fut>(
+                    &'fut mut self,
+                    shared_storage: &'fut mut #shared_storage_type,
+                    #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>,
+                    height: usize
+                ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                    statig::alloc::boxed::Box::pin(async move {
+                        match (self, height) {
+                            #(#call_ancestor_exit_action_arms),*
+                        }
+                    })
+                }
+
+                #try_call_actions
             }
         ),
     }
@@ -278,6 +999,10 @@ fn codegen_superstate(ir: &Ir) -> ItemEnum {
     let superstate_ident = &ir.state_machine.superstate_ident;
     let (superstate_generics, _, _) = &ir.state_machine.superstate_generics.split_for_impl();
     let superstate_derives = &ir.state_machine.superstate_derives;
+    let non_exhaustive = match ir.state_machine.superstate_non_exhaustive {
+        true => quote!(#[non_exhaustive]),
+        false => quote!(),
+    };
 
     let variants: Vec<Variant> = ir
         .superstates
@@ -285,9 +1010,16 @@ fn codegen_superstate(ir: &Ir) -> ItemEnum {
         .map(|superstate| superstate.variant.clone())
         .collect();
     let visibility = &ir.state_machine.visibility;
+    let doc = format!(
+        "Superstate of the `{}` state machine.",
+        get_generic_argument_ident(&ir.state_machine.shared_storage_type)
+    );
 
     parse_quote!(
+        #[doc = #doc]
+        #[allow(missing_docs)]
         #[derive(#(#superstate_derives),*)]
+        #non_exhaustive
         #visibility enum #superstate_ident #superstate_generics {
             #(#variants),*
         }
@@ -316,7 +1048,10 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
     let mut call_handler_arms: Vec<Arm> = Vec::new();
     let mut call_entry_action_arms: Vec<Arm> = Vec::new();
     let mut call_exit_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_entry_action_arms: Vec<Arm> = Vec::new();
+    let mut try_call_exit_action_arms: Vec<Arm> = Vec::new();
     let mut superstate_arms: Vec<Arm> = Vec::new();
+    let mut completion_arms: Vec<Arm> = Vec::new();
     let mut same_state_arms: Vec<Arm> = Vec::new();
 
     for state in ir.superstates.values() {
@@ -324,24 +1059,88 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
         let handler_call = &state.handler_call;
         let entry_action_call = &state.entry_action_call;
         let exit_action_call = &state.exit_action_call;
+        let try_entry_action_call = &state.try_entry_action_call;
+        let try_exit_action_call = &state.try_exit_action_call;
         let superstate_pat = &state.superstate_pat;
+        let completion = &state.completion;
+        let cfg_attrs = &state.cfg_attrs;
 
-        call_handler_arms.push(parse_quote!(#pat => #handler_call));
-        call_entry_action_arms.push(parse_quote!(#pat => #entry_action_call));
-        call_exit_action_arms.push(parse_quote!(#pat => #exit_action_call));
-        superstate_arms.push(parse_quote!(#pat => #superstate_pat));
+        call_handler_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #handler_call));
+        call_entry_action_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #entry_action_call));
+        call_exit_action_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #exit_action_call));
+        try_call_entry_action_arms
+            .push(parse_quote!(#(#cfg_attrs)* #pat => #try_entry_action_call));
+        try_call_exit_action_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #try_exit_action_call));
+        superstate_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #superstate_pat));
+        completion_arms.push(parse_quote!(#(#cfg_attrs)* #pat => #completion));
     }
 
     call_handler_arms.push(parse_quote!(_ => statig::Response::Super));
     call_entry_action_arms.push(parse_quote!(_ => {}));
     call_exit_action_arms.push(parse_quote!(_ => {}));
+    try_call_entry_action_arms.push(parse_quote!(_ => true));
+    try_call_exit_action_arms.push(parse_quote!(_ => true));
     superstate_arms.push(parse_quote!(_ => None));
+    completion_arms.push(parse_quote!(_ => None));
     same_state_arms.push(parse_quote!(_ => false));
 
+    let try_call_actions = match (
+        ir.state_machine.aborts_on_action_failure,
+        ir.state_machine.mode,
+    ) {
+        (false, _) => quote!(),
+        (true, Mode::Blocking) => quote!(
+            fn try_call_entry_action(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> bool {
+                match self {
+                    #(#try_call_entry_action_arms),*
+                }
+            }
+
+            fn try_call_exit_action(
+                &mut self,
+                shared_storage: &mut #shared_storage_type,
+                #context_ident: &mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> bool {
+                match self {
+                    #(#try_call_exit_action_arms),*
+                }
+            }
+        ),
+        (true, Mode::Awaitable) => quote!(
+            fn try_call_entry_action<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = bool> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    match self {
+                        #(#try_call_entry_action_arms),*
+                    }
+                })
+            }
+
+            fn try_call_exit_action<'fut>(
+                &'fut mut self,
+                shared_storage: &'fut mut #shared_storage_type,
+                #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
+            ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = bool> + 'fut + Send>> {
+                statig::alloc::boxed::Box::pin(async move {
+                    match self {
+                        #(#try_call_exit_action_arms),*
+                    }
+                })
+            }
+        ),
+    };
+
     match ir.state_machine.mode {
         Mode::Blocking => {
             parse_quote!(
-                #[allow(unused)]
+                #[allow(unused, deprecated)]
                 impl #impl_generics statig::blocking::Superstate<#shared_storage_type> for #superstate_ident #superstate_generics #where_clause
                 {
                     fn call_handler(
@@ -380,12 +1179,20 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                             #(#superstate_arms),*
                         }
                     }
+
+                    fn completion(&mut self) -> Option<<#shared_storage_type as statig::IntoStateMachine>::State> {
+                        match self {
+                            #(#completion_arms),*
+                        }
+                    }
+
+                    #try_call_actions
                 }
             )
         }
         Mode::Awaitable => {
             parse_quote!(
-                #[allow(unused)]
+                #[allow(unused, deprecated)]
                 impl #impl_generics statig::awaitable::Superstate<#shared_storage_type> for #superstate_ident #superstate_generics #where_clause
                 {
                     fn call_handler<'fut>(
@@ -393,8 +1200,8 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         shared_storage: &'fut mut #shared_storage_type,
                         #event_ident: &'fut <#shared_storage_type as statig::IntoStateMachine>::Event<'_>,
                         #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                    ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = statig::Response<<#shared_storage_type as statig::IntoStateMachine>::State>> + 'fut + Send>> {
-                        Box::pin(async move {
+                    ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = statig::Response<<#shared_storage_type as statig::IntoStateMachine>::State>> + 'fut + Send>> {
+                        statig::alloc::boxed::Box::pin(async move {
                             match self {
                                 #(#call_handler_arms),*
                             }
@@ -405,8 +1212,8 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         &'fut mut self,
                         shared_storage: &'fut mut #shared_storage_type,
                         #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                    ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
-                        Box::pin(async move {
+                    ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                        statig::alloc::boxed::Box::pin(async move {
                             match self {
                                 #(#call_entry_action_arms),*
                             }
@@ -417,8 +1224,8 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                         &'fut mut self,
                         shared_storage: &'fut mut #shared_storage_type,
                         #context_ident: &'fut mut <#shared_storage_type as statig::IntoStateMachine>::Context<'_>
-                    ) -> core::pin::Pin<std::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
-                        Box::pin(async move {
+                    ) -> core::pin::Pin<statig::alloc::boxed::Box<dyn core::future::Future<Output = ()> + 'fut + Send>> {
+                        statig::alloc::boxed::Box::pin(async move {
                             match self {
                                 #(#call_exit_action_arms),*
                             }
@@ -430,6 +1237,14 @@ fn codegen_superstate_impl_superstate(ir: &Ir) -> ItemImpl {
                             #(#superstate_arms),*
                         }
                     }
+
+                    fn completion(&mut self) -> Option<<#shared_storage_type as statig::IntoStateMachine>::State> {
+                        match self {
+                            #(#completion_arms),*
+                        }
+                    }
+
+                    #try_call_actions
                 }
             )
         }
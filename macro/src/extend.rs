@@ -0,0 +1,312 @@
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use proc_macro_error::abort;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Attribute, AttributeArgs, Ident, ImplItem, ItemImpl, Lit, LitStr, Meta,
+    NestedMeta, Path,
+};
+
+use crate::parse::{parse_args, parse_input};
+
+/// Argument that makes a state machine available as the base of other state
+/// machines, under the name of a generated `macro_rules!` template.
+const EXTENDABLE: &str = "extendable";
+/// Argument that derives a state machine from the template of a base.
+const EXTENDS: &str = "extends";
+/// Argument through which a template passes the base to the derived machine
+/// (e.g. `__base { [initial = "State::off()"] impl Heater { .. } }`).
+const BASE: &str = "__base";
+
+/// The arguments and the `impl` block of a base, as attached by its template.
+pub struct Base {
+    attribute_args: AttributeArgs,
+    item_impl: ItemImpl,
+}
+
+/// Get the string value of the argument with the given name.
+fn find_arg(attribute_args: &AttributeArgs, name: &str) -> Option<LitStr> {
+    attribute_args.iter().find_map(|arg| match arg {
+        NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident(name) => {
+            match &name_value.lit {
+                Lit::Str(value) => Some(value.clone()),
+                _ => abort!(name_value, "must be a string literal"),
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Remove the arguments with the given names.
+fn without_args(attribute_args: &AttributeArgs, names: &[&str]) -> AttributeArgs {
+    attribute_args
+        .iter()
+        .filter(|arg| match arg {
+            NestedMeta::Meta(meta) => !names.iter().any(|name| meta.path().is_ident(name)),
+            NestedMeta::Lit(_) => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// When the state machine extends a base, hand it to the template of the base,
+/// which calls the `state_machine` macro again with the base attached.
+pub fn forward_to_base(
+    attribute_args: &AttributeArgs,
+    item_impl: &ItemImpl,
+) -> Option<TokenStream> {
+    let base = find_arg(attribute_args, EXTENDS)?;
+    let template: Path = match base.parse() {
+        Ok(template) => template,
+        Err(error) => abort!(base, "expected the path of a base: {}", error),
+    };
+    let attribute_args = without_args(attribute_args, &[EXTENDS]);
+    Some(quote!(
+        #template! { [#(#attribute_args),*] #item_impl }
+    ))
+}
+
+/// Split the base that was attached by its template off the arguments of the
+/// state machine. The base is passed as tokens rather than as a string, so
+/// diagnostics in the base still point at its source.
+pub fn take_base(args: TokenStream) -> (Option<Base>, TokenStream) {
+    let tokens: Vec<TokenTree> = args.clone().into_iter().collect();
+    let base = match tokens.as_slice() {
+        [TokenTree::Ident(ident), TokenTree::Group(group), ..]
+            if ident == BASE && group.delimiter() == Delimiter::Brace =>
+        {
+            group.clone()
+        }
+        _ => return (None, args),
+    };
+    // Skip the base and the comma that separates it from the other arguments.
+    let args = tokens.into_iter().skip(3).collect();
+
+    let mut tokens = base.stream().into_iter();
+    let attribute_args = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            parse_args(group.stream())
+        }
+        _ => abort!(base, "expected the arguments of the base"),
+    };
+    let item_impl = parse_input(tokens.collect());
+    let base = Base {
+        attribute_args,
+        item_impl,
+    };
+    (Some(base), args)
+}
+
+/// Merge the base that was attached by its template into the state machine.
+/// The arguments of the derived state machine take precedence, and its
+/// methods replace the methods of the base with the same name or the name
+/// given in `overrides`.
+pub fn merge_base(
+    base: Option<Base>,
+    attribute_args: AttributeArgs,
+    item_impl: ItemImpl,
+) -> (AttributeArgs, ItemImpl) {
+    let Some(base) = base else {
+        return (attribute_args, item_impl);
+    };
+    let mut merged_args = base.attribute_args;
+    merged_args.extend(attribute_args);
+
+    // The template calls the macro from within its own expansion, so the
+    // items are resolved at the call site to resolve the inputs of the
+    // handlers in the same context as the code that is generated for them.
+    // They keep their location, so diagnostics still point at their source.
+    let base_impl = resolve_at_call_site(&base.item_impl);
+    let item_impl = resolve_at_call_site(&item_impl);
+    (merged_args, merge_items(base_impl, item_impl))
+}
+
+fn resolve_at_call_site(item_impl: &ItemImpl) -> ItemImpl {
+    match syn::parse2(resolved_at(quote!(#item_impl), Span::call_site())) {
+        Ok(item_impl) => item_impl,
+        Err(error) => abort!(error),
+    }
+}
+
+/// Resolve the names in the tokens at the given span, keeping their location.
+fn resolved_at(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut resolved = Group::new(group.delimiter(), resolved_at(group.stream(), span));
+                resolved.set_span(group.span().resolved_at(span));
+                token = TokenTree::Group(resolved);
+            } else {
+                token.set_span(token.span().resolved_at(span));
+            }
+            token
+        })
+        .collect()
+}
+
+fn merge_items(base_impl: ItemImpl, mut item_impl: ItemImpl) -> ItemImpl {
+    let is_base_method = |name: &Ident| {
+        base_impl
+            .items
+            .iter()
+            .any(|item| matches!(item, ImplItem::Method(method) if method.sig.ident == *name))
+    };
+
+    let mut overridden: Vec<Ident> = Vec::new();
+    for item in &mut item_impl.items {
+        if let ImplItem::Method(method) = item {
+            if let Some(name) = take_overrides(&mut method.attrs) {
+                if !is_base_method(&name) {
+                    abort!(name, "the base has no method named `{}`", name);
+                }
+                method.sig.ident = name;
+            }
+            overridden.push(method.sig.ident.clone());
+        }
+    }
+
+    let base_items = base_impl.items.into_iter().filter(|item| match item {
+        ImplItem::Method(method) => !overridden.contains(&method.sig.ident),
+        _ => true,
+    });
+    item_impl.items = base_items.chain(item_impl.items).collect();
+    item_impl
+}
+
+/// Remove `overrides = "name"` from the `state`, `superstate` or `action`
+/// attribute of a method and return the name of the method it overrides.
+fn take_overrides(attrs: &mut [Attribute]) -> Option<Ident> {
+    let mut overrides = None;
+    for attr in attrs.iter_mut() {
+        let Some(kind) = ["state", "superstate", "action"]
+            .iter()
+            .find(|kind| attr.path.is_ident(kind))
+        else {
+            continue;
+        };
+        let Ok(Meta::List(list)) = attr.parse_meta() else {
+            continue;
+        };
+        let mut nested = Vec::new();
+        for meta in list.nested {
+            match meta {
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("overrides") =>
+                {
+                    overrides = match &name_value.lit {
+                        Lit::Str(value) => Some(Ident::new(&value.value(), value.span())),
+                        _ => abort!(name_value, "must be a string literal"),
+                    }
+                }
+                meta => nested.push(meta),
+            }
+        }
+        let kind = Ident::new(kind, attr.path.span());
+        *attr = match nested.is_empty() {
+            true => parse_quote!(#[#kind]),
+            false => parse_quote!(#[#kind(#(#nested),*)]),
+        };
+    }
+    overrides
+}
+
+/// Strip the arguments that only apply to this invocation of the macro, so
+/// they aren't passed on to the analysis.
+pub fn strip_args(attribute_args: &AttributeArgs) -> AttributeArgs {
+    without_args(attribute_args, &[EXTENDABLE])
+}
+
+/// When the state machine is extendable, generate the template through which
+/// other state machines can extend it.
+pub fn template(attribute_args: &AttributeArgs, item_impl: &ItemImpl) -> TokenStream {
+    let Some(name) = find_arg(attribute_args, EXTENDABLE) else {
+        return TokenStream::new();
+    };
+    let name: Ident = match name.parse() {
+        Ok(name) => name,
+        Err(error) => abort!(name, "expected an identifier: {}", error),
+    };
+    let base = Ident::new(BASE, Span::call_site());
+    let base_args = without_args(attribute_args, &[EXTENDABLE]);
+    quote!(
+        #[allow(unused_macros)]
+        macro_rules! #name {
+            ([$($args:tt)*] $($item:tt)*) => {
+                #[statig::state_machine(#base { [#(#base_args),*] #item_impl }, $($args)*)]
+                $($item)*
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use #name;
+    )
+}
+
+#[test]
+fn merge_items_overrides_base_methods() {
+    let base_impl: ItemImpl = parse_quote!(
+        impl Heater {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[state(entry_action = "enter_on")]
+            fn on(event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let item_impl: ItemImpl = parse_quote!(
+        impl Heater {
+            #[state(overrides = "on", entry_action = "enter_on")]
+            fn on_with_boost(event: &Event) -> Response<State> {
+                Transition(State::boosted())
+            }
+
+            #[state]
+            fn boosted(event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    let expected: ItemImpl = parse_quote!(
+        impl Heater {
+            #[state]
+            fn off(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[state(entry_action = "enter_on")]
+            fn on(event: &Event) -> Response<State> {
+                Transition(State::boosted())
+            }
+
+            #[state]
+            fn boosted(event: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+
+    assert_eq!(merge_items(base_impl, item_impl), expected);
+}
+
+#[test]
+fn take_base_splits_off_the_base() {
+    let args = quote!(
+        __base { [initial = "State::off()"] impl Heater {} },
+        state(derive(Debug))
+    );
+
+    let (base, args) = take_base(args);
+
+    let base = base.unwrap();
+    let expected_args: AttributeArgs = vec![parse_quote!(initial = "State::off()")];
+    let expected_impl: ItemImpl = parse_quote!(impl Heater {});
+    assert_eq!(base.attribute_args, expected_args);
+    assert_eq!(base.item_impl, expected_impl);
+    assert_eq!(args.to_string(), quote!(state(derive(Debug))).to_string());
+}
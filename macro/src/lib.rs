@@ -7,6 +7,7 @@ use proc_macro_error::proc_macro_error;
 
 mod analyze;
 mod codegen;
+mod extend;
 mod lower;
 mod parse;
 mod visitors;
@@ -23,11 +24,18 @@ const CONTEXT_LIFETIME: &str = "'context";
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn state_machine(args: TokenStream, input: TokenStream) -> TokenStream {
-    let attribute_args = parse_args(args.into());
+    let (base, args) = extend::take_base(args.into());
+    let attribute_args = parse_args(args);
     let item_impl = parse_input(input.into());
-    let model = analyze(attribute_args, item_impl);
+    if let Some(rust) = extend::forward_to_base(&attribute_args, &item_impl) {
+        return rust.into();
+    }
+    let (attribute_args, item_impl) = extend::merge_base(base, attribute_args, item_impl);
+    let template = extend::template(&attribute_args, &item_impl);
+    let model = analyze(extend::strip_args(&attribute_args), item_impl);
     let ir = lower(&model);
-    let rust = codegen(ir);
+    let mut rust = codegen(ir);
+    rust.extend(template);
     rust.into()
 }
 
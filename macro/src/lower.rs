@@ -4,18 +4,23 @@ use std::ops::Deref;
 use proc_macro2::Span;
 use proc_macro_error::abort;
 
+use syn::ext::IdentExt;
 use syn::parse::Parser;
 use syn::parse_quote;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
 use syn::{
-    Expr, ExprCall, Field, FnArg, GenericParam, Generics, Ident, ItemFn, ItemImpl, Lifetime, Pat,
-    PatType, Path, Type, Variant, Visibility, WhereClause, WherePredicate,
+    Arm, Attribute, Expr, ExprPath, Field, FieldValue, FnArg, GenericParam, Generics, Ident,
+    ImplItem, ImplItemMethod, Item, ItemEnum, ItemFn, ItemImpl, Lifetime, Lit, Pat, PatType, Path,
+    Stmt, Type, Variant, Visibility, WhereClause, WherePredicate,
 };
 
-use quote::format_ident;
+use quote::{format_ident, ToTokens};
 
 use crate::analyze;
 use crate::analyze::Model;
-use crate::visitors::{GenericParamVisitor, LifetimeVisitor};
+use crate::visitors::{GenericParamVisitor, LifetimeVisitor, StateReferenceVisitor};
 use crate::SUPERSTATE_LIFETIME;
 
 /// Intermediate representation of the state machine.
@@ -35,7 +40,7 @@ pub struct Ir {
 /// General information regarding the state machine.
 pub struct StateMachine {
     /// Initial state.
-    pub initial_state: ExprCall,
+    pub initial_state: Expr,
     /// The type on which the state machine is implemented.
     pub shared_storage_type: Type,
     /// The generics associated with the shared storage type.
@@ -48,30 +53,86 @@ pub struct StateMachine {
     pub state_ident: Ident,
     /// Derives that will be applied on the state type.
     pub state_derives: Vec<Path>,
+    /// Whether the state enum is marked `#[non_exhaustive]`.
+    pub state_non_exhaustive: bool,
     /// The generics associated with the state type.
     pub state_generics: Generics,
     /// The type of the superstate enum (ex. `Superstate<'sub>`)
     pub superstate_ident: Ident,
     /// Derives that will be applied to the superstate type.
     pub superstate_derives: Vec<Path>,
+    /// Whether the superstate enum is marked `#[non_exhaustive]`.
+    pub superstate_non_exhaustive: bool,
     /// The generics associated with the superstate type.
     pub superstate_generics: Generics,
     /// The path of the `before_transition` callback.
     pub before_transition: Option<Path>,
     /// The path of the `after_transition` callback.
     pub after_transition: Option<Path>,
+    /// The path of the `on_init` callback.
+    pub on_init: Option<Path>,
+    /// The path of the `on_transition` callback.
+    pub on_transition: Option<Path>,
     /// The path of the `before_dispatch` callback.
     pub before_dispatch: Option<Path>,
     /// The path of the `after_dispatch` callback.
     pub after_dispatch: Option<Path>,
     /// The visibility for the derived types,
     pub visibility: Visibility,
+    /// Optional module in which the derived types are generated.
+    pub module: Option<Ident>,
+    /// Optional upper bound on the size of the state type, in bytes.
+    pub max_state_size: Option<usize>,
+    /// States that are never entered, which are reported with a warning.
+    pub unreachable_states: Vec<Ident>,
+    /// Functions on the state enum that look up the constants declared on the
+    /// states (e.g. `const fn timeout_ms(&self) -> Option<i32>`).
+    pub const_lookups: Vec<ItemFn>,
+    /// The entries of the static table of states and superstates, in
+    /// declaration order (e.g. `statig::StateInfo { name: "on", .. }`).
+    pub hierarchy: Vec<Expr>,
+    /// The entries of the static table of declared transitions, starting with
+    /// the initial transition (e.g. `statig::TransitionInfo { target: "on", .. }`).
+    pub transitions: Vec<Expr>,
+    /// Optional path to an `AtomicU8` that mirrors the id of the current state.
+    pub state_id_mirror: Option<Path>,
+    /// Optional path of the function that returns the timed transition of a state.
+    pub after: Option<Path>,
+    /// Optional function that returns the target of the condition transition
+    /// of a state, if its condition holds.
+    pub when: Option<Expr>,
+    /// Optional function that returns the state that is selected by a choice,
+    /// if the given state is a choice.
+    pub choice: Option<Expr>,
+    /// Optional function that returns the state that is selected by a
+    /// junction, if the given state is a junction.
+    pub junction: Option<Expr>,
+    /// Optional function that returns whether the given state is a terminate
+    /// pseudo-state.
+    pub terminate: Option<Expr>,
+    /// Optional function that calls the actions of the transitions from a
+    /// source to a target state.
+    pub transition_action: Option<Expr>,
     /// The external input pattern.
     pub event_ident: Ident,
     /// The external input pattern.
     pub context_ident: Ident,
     /// Whether the state machine is sync (blocking) or async (awaitable).
     pub mode: Mode,
+    /// Whether a failed action aborts the transition, in which case the
+    /// actions are called through `try_call_entry_action` and
+    /// `try_call_exit_action`.
+    pub aborts_on_action_failure: bool,
+    /// The path of the semantics of the state machine, unless they are the
+    /// default pragmatic semantics (e.g. `statig::Semantics::Uml`).
+    pub semantics: Option<Path>,
+    /// The `From` impls that wrap other types in the event enum.
+    pub event_from_impls: Vec<ItemImpl>,
+    /// Optional event enum that wraps the event types of the state machine.
+    pub event_enum: Option<ItemEnum>,
+    /// The entries of the static table that routes every event in every state,
+    /// if it is generated (e.g. `statig::EventRoute { state: "on", .. }`).
+    pub event_routing: Option<Vec<Expr>>,
 }
 
 /// Information regarding a state.
@@ -92,12 +153,45 @@ pub struct State {
     /// The call to the exit action of the state, if defined
     /// (e.g. `Blinky::exit_on(shared_storage, led)`, `{}`, ..).
     pub exit_action_call: Expr,
+    /// The call to the activity of the state, if defined
+    /// (e.g. `Blinky::poll_sensor(shared_storage, led)`, `{}`, ..).
+    pub activity_call: Expr,
+    /// The constructor that passes a payload to the state, if it takes one
+    /// (e.g. `const fn error_with(cause: Cause) -> Self { Self::Error { cause: Some(cause) }}`).
+    pub payload_constructor: Option<ItemFn>,
+    /// The call to the entry action of the state that evaluates to whether
+    /// the transition can proceed (e.g. `{ Blinky::enter_on(shared_storage, led); true }`, `true`, ..).
+    pub try_entry_action_call: Expr,
+    /// The call to the exit action of the state that evaluates to whether
+    /// the transition can proceed (e.g. `{ Blinky::exit_on(shared_storage, led); true }`, `true`, ..).
+    pub try_exit_action_call: Expr,
     /// The pattern to create the superstate variant.
     /// (e.g. `Some(Superstate::Playing { led })`, `None`, ..).
     pub superstate_pat: Pat,
+    /// The ancestor superstates of the state, starting from the direct
+    /// superstate and going up to the top-most superstate.
+    /// (e.g. `[playing, active]`).
+    pub ancestors: Vec<Ident>,
+    /// The declared transitions of the state, as match arms on the state and
+    /// the event that are matched before the handler is called.
+    /// (e.g. `(State::On { .. }, Event::TimerElapsed) => return Response::Transition(State::off())`).
+    pub transition_arms: Vec<Arm>,
+    /// The declared transitions of the ancestors of the state, flattened into
+    /// match arms on the state and the event that are matched once the handler
+    /// of the state returns `Super`, starting from the direct superstate
+    /// (e.g. `(State::On { .. }, Event::Stop) => return Response::Transition(State::idle())`).
+    pub ancestor_transition_arms: Vec<Arm>,
     /// The constructor to create the state
     /// (e.g. `const fn on(led: bool) -> Self { Self::On { led }}`).
     pub constructor: ItemFn,
+    /// Conditional compilation attributes that are applied to everything that
+    /// is generated for the state (e.g. `#[cfg(feature = "factory")]`).
+    pub cfg_attrs: Vec<Attribute>,
+    /// Items that are generated alongside the state enum, such as the struct
+    /// that holds the local storage of a state with derives of its own
+    /// (e.g. `#[derive(Serialize)] struct OnStorage { led: bool }`) or the
+    /// builder of a state.
+    pub items: Vec<Item>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -117,9 +211,21 @@ pub struct Superstate {
     /// The call to the exit action of the superstate, if defined
     /// (e.g. `Blinky::exit_playing(shared_storage, led)`).
     pub exit_action_call: Expr,
+    /// The call to the entry action of the superstate that evaluates to
+    /// whether the transition can proceed.
+    pub try_entry_action_call: Expr,
+    /// The call to the exit action of the superstate that evaluates to
+    /// whether the transition can proceed.
+    pub try_exit_action_call: Expr,
     /// The pattern to create the superstate variant.
     /// (e.g. `Some(Superstate::Playing { led })`, `None`, ..).
     pub superstate_pat: Expr,
+    /// The target of the completion transition of the superstate
+    /// (e.g. `Some(State::idle())`, `None`).
+    pub completion: Expr,
+    /// Conditional compilation attributes that are applied to everything that
+    /// is generated for the superstate (e.g. `#[cfg(feature = "factory")]`).
+    pub cfg_attrs: Vec<Attribute>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -127,6 +233,10 @@ pub struct Action {
     /// The call to the action.
     /// (e.g. `Blinky::exit_off(shared_storage, led)`)
     pub handler_call: Expr,
+    /// The call to the action that evaluates to whether the transition can
+    /// proceed.
+    /// (e.g. `{ Blinky::exit_off(shared_storage, led); true }`)
+    pub try_handler_call: Expr,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -136,12 +246,23 @@ pub enum Mode {
 }
 
 pub fn lower(model: &Model) -> Ir {
-    let item_impl = model.item_impl.clone();
+    let mut item_impl = model.item_impl.clone();
+    lower_event_patterns(&mut item_impl, model);
+    lower_storage_bindings(&mut item_impl, model);
+    strip_field_attrs(&mut item_impl, model);
+    allow_dead_terminates(&mut item_impl, model);
+    if model.state_machine.module.is_some() {
+        strip_marker_attrs(&mut item_impl);
+    }
+    check_variant_names(model);
+    check_state_references(model);
     let initial_state = model.state_machine.initial_state.clone();
     let state_ident = model.state_machine.state_ident.clone();
     let superstate_ident = model.state_machine.superstate_ident.clone();
     let before_transition = model.state_machine.before_transition.clone();
     let after_transition = model.state_machine.after_transition.clone();
+    let on_init = model.state_machine.on_init.clone();
+    let on_transition = model.state_machine.on_transition.clone();
     let before_dispatch = model.state_machine.before_dispatch.clone();
     let after_dispatch = model.state_machine.after_dispatch.clone();
     let event_ident = model.state_machine.event_ident.clone();
@@ -150,7 +271,34 @@ pub fn lower(model: &Model) -> Ir {
     let shared_storage_generics = model.state_machine.shared_storage_generics.clone();
     let state_derives = model.state_machine.state_derives.clone();
     let superstate_derives = model.state_machine.superstate_derives.clone();
+    let state_non_exhaustive = model.state_machine.state_non_exhaustive;
+    let superstate_non_exhaustive = model.state_machine.superstate_non_exhaustive;
     let visibility = model.state_machine.visibility.clone();
+    let module = model.state_machine.module.clone();
+    let max_state_size = model.state_machine.max_state_size;
+    let unreachable_states = match model.state_machine.unreachable_states {
+        analyze::LintLevel::Allow => Vec::new(),
+        analyze::LintLevel::Warn => find_unreachable_states(model),
+        analyze::LintLevel::Deny => match find_unreachable_states(model).first() {
+            Some(state) => abort!(
+                state,
+                "state `{}` is unreachable", state;
+                help = "it is neither the initial state nor the target of a transition"
+            ),
+            None => Vec::new(),
+        },
+    };
+    let const_lookups = lower_state_consts(model);
+    let hierarchy = lower_hierarchy(model);
+    let transitions = lower_transition_table(model);
+    let event_routing = lower_event_routing(model);
+    let state_id_mirror = model.state_machine.state_id_mirror.clone();
+    let after = model.state_machine.after.clone();
+    let when = lower_condition_transitions(model);
+    let choice = lower_pseudo_states(model, analyze::PseudoState::Choice);
+    let junction = lower_pseudo_states(model, analyze::PseudoState::Junction);
+    let terminate = lower_terminates(model);
+    let transition_action = lower_transition_actions(model);
 
     let mut superstate_lifetime: Option<Lifetime> = None;
 
@@ -192,17 +340,79 @@ pub fn lower(model: &Model) -> Ir {
                 }
                 None => abort!(superstate, "superstate not found"),
             }
+            check_cfg_attrs(
+                superstate,
+                &superstates[superstate].cfg_attrs,
+                &state.cfg_attrs,
+            );
+
+            // Walk up the hierarchy so the ancestors can be constructed directly
+            // from the state, without going through the intermediate superstates.
+            let mut ancestor = Some(superstate);
+            while let Some(key) = ancestor {
+                if state.ancestors.len() > superstates.len() {
+                    abort!(superstate, "superstate hierarchy contains a cycle");
+                }
+                match superstates.contains_key(key) {
+                    true => state.ancestors.push(key.clone()),
+                    false => abort!(key, "superstate not found"),
+                }
+                ancestor = model
+                    .superstates
+                    .get(key)
+                    .and_then(|superstate| superstate.superstate.as_ref());
+            }
         }
 
+        // The declared transitions of the state are matched before its handler
+        // is called. Those of its ancestors are flattened into a single match
+        // on the state that is only tried when the handler returns `Super`, so
+        // they don't require walking up the hierarchy.
+        let variant_name = variant_name(key, &model.states[key].name);
+        let state_ident = &model.state_machine.state_ident;
+        let state_pat: Pat = parse_quote!(#state_ident::#variant_name { .. });
+        // With UML semantics the handler of a state also returns the source of
+        // the transition, as the height of the state or ancestor that declares it.
+        let with_source = !matches!(model.state_machine.semantics, analyze::Semantics::Pragmatic);
+        // A condition transition with a priority is matched among the declared
+        // transitions, for every event.
+        let analyze_state = &model.states[key];
+        let mut transitions = analyze_state.transitions.clone();
+        if let (Some((condition, target)), Some(position)) =
+            (&analyze_state.condition, analyze_state.condition_position)
+        {
+            let mut condition = condition.clone();
+            ReplaceSelf.visit_expr_mut(&mut condition);
+            transitions.insert(position, parse_quote!(_ if #condition => #target));
+        }
+        state.transition_arms = lower_transition_arms(
+            &state_pat,
+            &transitions,
+            &state.cfg_attrs,
+            with_source.then_some(0),
+        );
+        state.ancestor_transition_arms = state
+            .ancestors
+            .iter()
+            .enumerate()
+            .flat_map(|(height, ancestor)| {
+                lower_transition_arms(
+                    &state_pat,
+                    &model.superstates[ancestor].transitions,
+                    &state.cfg_attrs,
+                    with_source.then_some(height + 1),
+                )
+            })
+            .collect();
+
         if let Some(entry_action) = model
             .states
             .get(key)
             .and_then(|state| state.entry_action.as_ref())
         {
-            match actions.get(entry_action) {
-                Some(action) => state.entry_action_call = action.handler_call.clone(),
-                None => abort!(entry_action, "entry action not found"),
-            }
+            state.entry_action_call = action_call(entry_action, &actions, &model.state_machine);
+            state.try_entry_action_call =
+                try_action_call(entry_action, &actions, &model.state_machine);
         }
 
         if let Some(exit_action) = model
@@ -210,10 +420,17 @@ pub fn lower(model: &Model) -> Ir {
             .get(key)
             .and_then(|state| state.exit_action.as_ref())
         {
-            match actions.get(exit_action) {
-                Some(action) => state.exit_action_call = action.handler_call.clone(),
-                None => abort!(exit_action, "exit action not found"),
-            }
+            state.exit_action_call = action_call(exit_action, &actions, &model.state_machine);
+            state.try_exit_action_call =
+                try_action_call(exit_action, &actions, &model.state_machine);
+        }
+
+        if let Some(activity) = model
+            .states
+            .get(key)
+            .and_then(|state| state.activity.as_ref())
+        {
+            state.activity_call = action_call(activity, &actions, &model.state_machine);
         }
     }
 
@@ -232,6 +449,11 @@ pub fn lower(model: &Model) -> Ir {
                 }
                 None => abort!(superstate_superstate, "superstate not found"),
             }
+            check_cfg_attrs(
+                superstate_superstate,
+                &superstates_clone[superstate_superstate].cfg_attrs,
+                &superstate.cfg_attrs,
+            );
         }
 
         if let Some(entry_action) = model
@@ -239,10 +461,10 @@ pub fn lower(model: &Model) -> Ir {
             .get(key)
             .and_then(|state| state.entry_action.as_ref())
         {
-            match actions.get(entry_action) {
-                Some(action) => superstate.entry_action_call = action.handler_call.clone(),
-                None => abort!(entry_action, "action not found"),
-            }
+            superstate.entry_action_call =
+                action_call(entry_action, &actions, &model.state_machine);
+            superstate.try_entry_action_call =
+                try_action_call(entry_action, &actions, &model.state_machine);
         }
 
         if let Some(exit_action) = model
@@ -250,88 +472,122 @@ pub fn lower(model: &Model) -> Ir {
             .get(key)
             .and_then(|state| state.exit_action.as_ref())
         {
-            match actions.get(exit_action) {
-                Some(action) => superstate.exit_action_call = action.handler_call.clone(),
-                None => abort!(exit_action, "action not found"),
+            superstate.exit_action_call = action_call(exit_action, &actions, &model.state_machine);
+            superstate.try_exit_action_call =
+                try_action_call(exit_action, &actions, &model.state_machine);
+        }
+
+        if let Some(analyzed) = model.superstates.get(key) {
+            if let Some(default_handler) = &analyzed.default_handler {
+                superstate.handler_call = lower_default_handler(
+                    superstate.handler_call.clone(),
+                    analyzed,
+                    default_handler,
+                    model,
+                );
             }
         }
     }
 
     // Find event and/or context types and check whether there are any async functions.
     let mut mode = Mode::Blocking;
-    let mut event_type = None;
-    let mut context_type = None;
 
-    for state in model.states.values() {
-        if let Some(pat_type) = &state.event_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .event_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "event must be passed in as a reference"),
+    // The external inputs of all handlers must have the same types, so they
+    // are checked in declaration order against the first one that is found.
+    let mut event_arg: Option<&PatType> = None;
+    let mut context_arg: Option<&PatType> = None;
+    let mut local_storage_inputs: Vec<&PatType> = Vec::new();
+    for item in &model.item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let (handler_event_arg, handler_context_arg, state_inputs, is_async) = match (
+            model.states.get(&method.sig.ident),
+            model.superstates.get(&method.sig.ident),
+        ) {
+            (Some(state), _) => (
+                &state.event_arg,
+                &state.context_arg,
+                &state.state_inputs,
+                state.is_async,
+            ),
+            (None, Some(superstate)) => (
+                &superstate.event_arg,
+                &superstate.context_arg,
+                &superstate.state_inputs,
+                superstate.is_async,
+            ),
+            (None, None) => continue,
+        };
+        if let Some(pat_type) = handler_event_arg {
+            // A handler that accepts one of the types of the event enum is
+            // only called for the events of that type.
+            match event_variant(model, pat_type) {
+                Some(variant) => {
+                    let handler_call = match states.get_mut(&method.sig.ident) {
+                        Some(state) => &mut state.handler_call,
+                        None => &mut superstates.get_mut(&method.sig.ident).unwrap().handler_call,
                     };
-                    event_type = Some(ty);
+                    *handler_call = route_event(handler_call, variant, model);
                 }
+                None => check_external_input(&mut event_arg, pat_type, "event"),
             }
         }
-        if let Some(pat_type) = &state.context_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .context_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "context must be passed in as a reference"),
-                    };
-                    context_type = Some(ty);
-                }
-            }
+        if let Some(pat_type) = handler_context_arg {
+            check_external_input(&mut context_arg, pat_type, "context");
         }
-        if state.is_async {
+        local_storage_inputs.extend(state_inputs);
+        if is_async {
             mode = Mode::Awaitable;
         }
     }
-
-    for superstate in model.superstates.values() {
-        if let Some(pat_type) = &superstate.event_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .event_ident
-                    .eq(&external_input_ident.ident)
-                {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "event must be passed in as a reference"),
-                    };
-                    event_type = Some(ty);
+    let event_type = match &model.state_machine.event_enum {
+        None => event_arg.map(external_input_type),
+        Some(event_enum) => {
+            let event_enum_ident = &event_enum.ident;
+            let event_enum_type: Type = match event_enum.borrowed {
+                true => parse_quote!(#event_enum_ident<'_>),
+                false => parse_quote!(#event_enum_ident),
+            };
+            if let Some(pat_type) = event_arg {
+                // The lifetime of a borrowing event enum can be elided.
+                let handler_event_type = erase_lifetimes(&external_input_type(pat_type));
+                let is_elided_event_enum = match &handler_event_type {
+                    Type::Path(type_path) => {
+                        type_path.qself.is_none() && type_path.path.is_ident(event_enum_ident)
+                    }
+                    _ => false,
+                };
+                if handler_event_type != event_enum_type && !is_elided_event_enum {
+                    abort!(
+                        pat_type.ty,
+                        "the event type `{}` is not one of the types of the event enum",
+                        external_input_type(pat_type).to_token_stream()
+                    );
                 }
             }
+            Some(event_enum_type)
         }
-        if let Some(pat_type) = &superstate.context_arg {
-            if let Pat::Ident(external_input_ident) = &*pat_type.pat {
-                if model
-                    .state_machine
-                    .context_ident
-                    .eq(&external_input_ident.ident)
+    };
+    let context_type = context_arg.map(external_input_type);
+
+    // An input with the type of the event that isn't named after the event is
+    // taken as local storage, which is most likely a mistake.
+    if let Some(event_type) = &event_type {
+        let event_ident = &model.state_machine.event_ident;
+        for pat_type in local_storage_inputs {
+            if let Type::Reference(reference) = pat_type.ty.as_ref() {
+                if reference.mutability.is_none()
+                    && erase_lifetimes(&reference.elem) == erase_lifetimes(event_type)
                 {
-                    let ty = match &*pat_type.ty {
-                        Type::Reference(reference) => reference.elem.deref().clone(),
-                        _ => abort!(pat_type.ty, "context must be passed in as a reference"),
-                    };
-                    context_type = Some(ty);
+                    abort!(
+                        pat_type.pat,
+                        "input has the type of the event, but is not named `{}`", event_ident;
+                        help = "rename the input to `{}`, or set `event_identifier` on the `state_machine` attribute", event_ident
+                    );
                 }
             }
         }
-        if superstate.is_async {
-            mode = Mode::Awaitable;
-        }
     }
 
     for action in model.actions.values() {
@@ -339,6 +595,26 @@ pub fn lower(model: &Model) -> Ir {
             mode = Mode::Awaitable;
         }
     }
+    for superstate in model.superstates.values() {
+        let default_method = superstate
+            .default_handler
+            .as_ref()
+            .and_then(|default_handler| find_method(model, default_handler));
+        if default_method.map_or(false, |method| method.sig.asyncness.is_some()) {
+            mode = Mode::Awaitable;
+        }
+    }
+
+    let aborts_on_action_failure = matches!(
+        model.state_machine.action_failure,
+        analyze::ActionFailure::Abort
+    ) && model.actions.values().any(|action| action.is_fallible);
+
+    let semantics = match model.state_machine.semantics {
+        analyze::Semantics::Pragmatic => None,
+        analyze::Semantics::Uml => Some(parse_quote!(statig::Semantics::Uml)),
+        analyze::Semantics::UmlLocal => Some(parse_quote!(statig::Semantics::UmlLocal)),
+    };
 
     // Set the event type if it was found, otherwise set it to `()`.
     let mut event_type = match event_type {
@@ -350,6 +626,10 @@ pub fn lower(model: &Model) -> Ir {
     let mut lifetime_visitor = LifetimeVisitor::new("'event");
     lifetime_visitor.rename_type(&mut event_type);
 
+    let mut event_from_impls = lower_event_from_impls(model, &event_type);
+    event_from_impls.extend(lower_event_type_impls(model));
+    let event_enum = lower_event_enum(model);
+
     // Set the context type if it was found, otherwise set it to `()`.
     let mut context_type = match context_type {
         Some(context_type) => context_type,
@@ -369,6 +649,28 @@ pub fn lower(model: &Model) -> Ir {
     }
     let state_generic_params = visitor.finish();
 
+    // The state must outlive the superstates that borrow from it, for any
+    // lifetime of those superstates. So state-local storage can only borrow
+    // from the shared storage when it lives for `'static`.
+    let shared_storage_lifetimes: Vec<_> = shared_storage_generics
+        .lifetimes()
+        .map(|def| &def.lifetime)
+        .collect();
+    for pat_type in model.states.values().flat_map(|state| &state.state_inputs) {
+        let mut visitor = GenericParamVisitor::new(&model.state_machine.shared_storage_generics);
+        visitor.search([pat_type]);
+        let borrows_shared_storage = visitor.finish().iter().any(|param| {
+            matches!(param, GenericParam::Lifetime(def) if shared_storage_lifetimes.contains(&&def.lifetime))
+        });
+        if borrows_shared_storage {
+            abort!(
+                pat_type,
+                "state-local storage can not borrow with a lifetime of the shared storage";
+                help = "consider keeping the borrowed data in the shared storage and storing an index or a range in the state instead"
+            )
+        }
+    }
+
     let mut visitor = GenericParamVisitor::new(&model.state_machine.shared_storage_generics);
     for superstate in model.superstates.values() {
         visitor.search(&superstate.state_inputs);
@@ -407,6 +709,14 @@ pub fn lower(model: &Model) -> Ir {
         }
     }
 
+    // The size of a generic state type can not be checked in a const context.
+    if max_state_size.is_some() && !state_generics.params.is_empty() {
+        abort!(
+            state_generics.params,
+            "`max_state_size` is not supported when the state type is generic"
+        );
+    }
+
     // If a lifetime is required it must be part of the superstate generics.
     if let Some(lifetime) = superstate_lifetime {
         superstate_generics
@@ -422,18 +732,40 @@ pub fn lower(model: &Model) -> Ir {
         context_type,
         state_ident,
         state_derives,
+        state_non_exhaustive,
         state_generics,
         superstate_ident,
         superstate_derives,
+        superstate_non_exhaustive,
         superstate_generics,
         before_transition,
         after_transition,
+        on_init,
+        on_transition,
         before_dispatch,
         after_dispatch,
         visibility,
+        module,
+        max_state_size,
+        unreachable_states,
+        const_lookups,
+        hierarchy,
+        transitions,
+        state_id_mirror,
+        after,
+        when,
+        choice,
+        junction,
+        terminate,
+        transition_action,
         event_ident,
         context_ident,
         mode,
+        aborts_on_action_failure,
+        semantics,
+        event_from_impls,
+        event_enum,
+        event_routing,
     };
 
     Ir {
@@ -444,8 +776,145 @@ pub fn lower(model: &Model) -> Ir {
     }
 }
 
+/// Rewrite the handlers that destructure the event in their signature, so they
+/// bind the event instead and defer to their superstate when the event does
+/// not match the pattern.
+fn lower_event_patterns(item_impl: &mut ItemImpl, model: &Model) {
+    let event_ident = &model.state_machine.event_ident;
+    for item in &mut item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let event_pattern = match (
+            model.states.get(&method.sig.ident),
+            model.superstates.get(&method.sig.ident),
+        ) {
+            (Some(state), _) => state.event_pattern.as_ref(),
+            (None, Some(superstate)) => superstate.event_pattern.as_ref(),
+            (None, None) => None,
+        };
+        let Some(event_pattern) = event_pattern else {
+            continue;
+        };
+        for input in &mut method.sig.inputs {
+            if let FnArg::Typed(pat_type) = input {
+                if pat_type.pat.as_ref() == event_pattern {
+                    pat_type.pat = parse_quote!(#event_ident);
+                }
+            }
+        }
+        let block = &method.block;
+        method.block = parse_quote!({
+            #[allow(irrefutable_let_patterns)]
+            let #event_pattern = #event_ident else {
+                return statig::Response::Super;
+            };
+            #block
+        });
+    }
+}
+
+/// Rewrite the handlers that bind a name to the pattern that destructures
+/// state-local storage (e.g. `data @ OnData { counter, .. }`), so they take the
+/// storage by that name and destructure it at the start of their body, as a
+/// binding can not be combined with a pattern that borrows from the same value.
+fn lower_storage_bindings(item_impl: &mut ItemImpl, model: &Model) {
+    for item in &mut item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        if !model.states.contains_key(&method.sig.ident)
+            && !model.superstates.contains_key(&method.sig.ident)
+        {
+            continue;
+        }
+        let mut destructures: Vec<Stmt> = Vec::new();
+        for input in &mut method.sig.inputs {
+            let FnArg::Typed(pat_type) = input else {
+                continue;
+            };
+            let is_mutable_reference = matches!(
+                pat_type.ty.as_ref(),
+                Type::Reference(reference) if reference.mutability.is_some()
+            );
+            if !is_mutable_reference || !analyze::is_storage_pattern(&pat_type.pat) {
+                continue;
+            }
+            let Pat::Ident(pat) = pat_type.pat.as_mut() else {
+                continue;
+            };
+            let Some((_, subpat)) = pat.subpat.take() else {
+                continue;
+            };
+            let ident = &pat.ident;
+            destructures.push(parse_quote!(let #subpat = &mut *#ident;));
+        }
+        if destructures.is_empty() {
+            continue;
+        }
+        let block = &method.block;
+        method.block = parse_quote!({
+            #(#destructures)*
+            #block
+        });
+    }
+}
+
+/// Remove the attributes that are moved to the fields of the variants (e.g.
+/// `#[serde(skip)]`) from the inputs of the state and superstate handlers, as
+/// the compiler doesn't accept them on function parameters.
+fn strip_field_attrs(item_impl: &mut ItemImpl, model: &Model) {
+    for item in &mut item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        if !model.states.contains_key(&method.sig.ident)
+            && !model.superstates.contains_key(&method.sig.ident)
+        {
+            continue;
+        }
+        for input in &mut method.sig.inputs {
+            if let FnArg::Typed(pat_type) = input {
+                let field_attrs = analyze::get_field_attrs(&pat_type.attrs);
+                pat_type.attrs.retain(|attr| !field_attrs.contains(attr));
+            }
+        }
+    }
+}
+
+/// Allow the handlers of terminate pseudo-states to be dead code, as they are
+/// never called: the state machine stops as soon as it reaches one.
+fn allow_dead_terminates(item_impl: &mut ItemImpl, model: &Model) {
+    for item in &mut item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let is_terminate = model.states.get(&method.sig.ident).map_or(false, |state| {
+            state.pseudo_state == Some(analyze::PseudoState::Terminate)
+        });
+        if is_terminate {
+            method.attrs.push(parse_quote!(#[allow(dead_code)]));
+        }
+    }
+}
+
+/// Remove the `state`, `superstate` and `action` attributes, which only tag the
+/// handlers, so they don't have to be imported next to the `impl` block when
+/// the derived types are generated in a module.
+fn strip_marker_attrs(item_impl: &mut ItemImpl) {
+    for item in &mut item_impl.items {
+        if let ImplItem::Method(method) = item {
+            method.attrs.retain(|attr| {
+                !["state", "superstate", "action"]
+                    .iter()
+                    .any(|marker| attr.path.is_ident(marker))
+            });
+        }
+    }
+}
+
 pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine) -> State {
-    let variant_name = snake_case_to_pascal_case(&state.handler_name);
+    let variant_name = variant_name(&state.handler_name, &state.name);
     let state_handler_name = &state.handler_name;
     let shared_storage_path = &state_machine.shared_storage_path;
     let (_, shared_storage_type_generics, _) =
@@ -468,15 +937,232 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         }
     }
 
+    // The payload is passed to the state by the transition that enters it, so
+    // it is not an input of the constructor.
+    let payload = state.payload.as_ref().map(|payload| {
+        variant_fields.retain(|field| field.ident != payload.ident);
+        (payload.ident.clone().unwrap(), payload.ty.clone())
+    });
+
+    // Default values of the fields of a builder (e.g. `#[default(3)]`).
+    let defaults: Vec<Option<Expr>> = variant_fields
+        .iter_mut()
+        .map(|field| {
+            let index = field
+                .attrs
+                .iter()
+                .position(|attr| attr.path.is_ident("default"))?;
+            let attr = field.attrs.remove(index);
+            if !state.builder {
+                abort!(
+                    attr,
+                    "default values are only supported for states with a builder";
+                    help = "add `builder` to the `state` attribute"
+                );
+            }
+            match attr.parse_args() {
+                Ok(default) => Some(default),
+                Err(error) => abort!(error.span(), "{}", error),
+            }
+        })
+        .collect();
+
     let pat_fields: Vec<Ident> = variant_fields
         .iter()
         .map(|field| field.ident.as_ref().unwrap().clone())
         .collect();
-    let handler_inputs: Vec<Ident> = state.inputs.iter().map(fn_arg_to_ident).collect();
+    let handler_inputs: Vec<Expr> = state.inputs.iter().map(fn_arg_to_handler_input).collect();
+
+    let cfg_attrs = &state.cfg_attrs;
+    let forwarded_attrs = &state.forwarded_attrs;
+
+    // The attributes of the fields (e.g. `#[serde(skip)]`) are not allowed on
+    // the inputs of the constructor.
+    let constructor_inputs: Vec<Field> = variant_fields
+        .iter()
+        .cloned()
+        .map(|mut field| {
+            field.attrs.clear();
+            field
+        })
+        .collect();
+
+    // Boxed states keep their local storage on the heap. The handlers still
+    // receive plain references to the fields through deref coercion.
+    let (mut storage_fields, mut field_values): (Vec<Field>, Vec<FieldValue>) = match state.boxed {
+        true => variant_fields
+            .iter()
+            .cloned()
+            .map(|mut field| {
+                let ty = &field.ty;
+                let ident = &field.ident;
+                let value = parse_quote!(#ident: statig::alloc::boxed::Box::new(#ident));
+                field.ty = parse_quote!(statig::alloc::boxed::Box<#ty>);
+                (field, value)
+            })
+            .unzip(),
+        false => variant_fields
+            .iter()
+            .cloned()
+            .map(|field| {
+                let ident = &field.ident;
+                let value = parse_quote!(#ident);
+                (field, value)
+            })
+            .unzip(),
+    };
+    let mut storage_pat_fields = pat_fields.clone();
+    // The payload is only set by the constructor that takes it, so passing a
+    // payload to a state that doesn't take one (or one of another type) fails
+    // to compile.
+    let mut payload_field_values = field_values.clone();
+    if let Some((ident, ty)) = &payload {
+        let field = Field::parse_named.parse2(quote::quote!(#ident: Option<#ty>));
+        storage_fields.push(field.unwrap());
+        field_values.push(parse_quote!(#ident: None));
+        payload_field_values.push(parse_quote!(#ident: Some(#ident)));
+        storage_pat_fields.push(ident.clone());
+    }
+    let constness = match state.boxed {
+        true => None,
+        false => Some(quote::quote!(const)),
+    };
 
-    let variant = parse_quote!(#variant_name { #(#variant_fields),* });
-    let pat = parse_quote!(#state_name::#variant_name { #(#pat_fields),*});
-    let constructor = parse_quote!(const fn #state_handler_name ( #(#variant_fields),* ) -> Self { Self::#variant_name { #(#pat_fields),*} });
+    // A state with derives of its own keeps its local storage in a separate
+    // struct, as derives can not be applied to a single variant.
+    let mut items: Vec<Item> = Vec::new();
+    let storage_ident = match state.derives.is_empty() {
+        true => None,
+        false => {
+            let storage_ident = format_ident!("{}Storage", variant_name);
+            let visibility = &state_machine.visibility;
+            let mut derives = state_machine.state_derives.clone();
+            for derive in &state.derives {
+                if !derives.contains(derive) {
+                    derives.push(derive.clone());
+                }
+            }
+
+            check_storage_generics(state, &storage_fields, state_machine, "derives");
+
+            let storage_fields = storage_fields.iter().cloned().map(|mut field| {
+                field.vis = visibility.clone();
+                field
+            });
+            items.push(parse_quote!(
+                #(#cfg_attrs)*
+                #[allow(missing_docs)]
+                #[derive(#(#derives),*)]
+                #visibility struct #storage_ident { #(#storage_fields),* }
+            ));
+            Some(storage_ident)
+        }
+    };
+
+    let (pat, variant) = match &storage_ident {
+        None => (
+            parse_quote!(#state_name::#variant_name { #(#storage_pat_fields),*}),
+            parse_quote!(#(#cfg_attrs)* #(#forwarded_attrs)* #variant_name { #(#storage_fields),* }),
+        ),
+        Some(storage_ident) => (
+            parse_quote!(#state_name::#variant_name(#storage_ident { #(#storage_pat_fields),* })),
+            parse_quote!(#(#cfg_attrs)* #(#forwarded_attrs)* #variant_name(#storage_ident)),
+        ),
+    };
+    let construct_with = |path: &dyn ToTokens, field_values: &[FieldValue]| -> Expr {
+        match &storage_ident {
+            None => parse_quote!(#path::#variant_name { #(#field_values),*}),
+            Some(storage_ident) => {
+                parse_quote!(#path::#variant_name(#storage_ident { #(#field_values),*}))
+            }
+        }
+    };
+    let construct = |path: &dyn ToTokens| construct_with(path, &field_values);
+
+    // When the state enum is generated in a module, the constructors remain
+    // visible to the `impl` block that transitions to them.
+    let constructor_visibility = state_machine
+        .module
+        .as_ref()
+        .map(|_| quote::quote!(pub(super)));
+
+    // A state with a builder is constructed by setting the fields that don't
+    // keep their default value, so adding a field doesn't break every
+    // transition to the state.
+    let mut payload_constructor = None;
+    let constructor = match state.builder {
+        false => {
+            if let Some((ident, ty)) = &payload {
+                let value = construct_with(&quote::quote!(Self), &payload_field_values);
+                let payload_constructor_name = format_ident!("{}_with", state_handler_name);
+                payload_constructor = Some(parse_quote!(
+                    #(#cfg_attrs)* #(#forwarded_attrs)* #constructor_visibility #constness fn #payload_constructor_name ( #(#constructor_inputs,)* #ident: #ty ) -> Self { #value }
+                ));
+            }
+            let value = construct(&quote::quote!(Self));
+            parse_quote!(#(#cfg_attrs)* #(#forwarded_attrs)* #constructor_visibility #constness fn #state_handler_name ( #(#constructor_inputs),* ) -> Self { #value })
+        }
+        true => {
+            if !state_machine.shared_storage_generics.params.is_empty() {
+                abort!(
+                    state.handler_name,
+                    "builders are not supported for state machines with generics"
+                );
+            }
+            check_storage_generics(state, &constructor_inputs, state_machine, "a builder");
+
+            // The state can only be built in a const context when every field
+            // has a default value that doesn't rely on `Default`.
+            let builder_constness = match !state.boxed && defaults.iter().all(Option::is_some) {
+                true => Some(quote::quote!(const)),
+                false => None,
+            };
+            let builder_ident = format_ident!("{}Builder", variant_name);
+            let visibility = &state_machine.visibility;
+            let doc = format!("Builder for the `{variant_name}` state.");
+            let field_types = constructor_inputs.iter().map(|field| &field.ty);
+            let value = construct(state_name);
+            let build_with = payload.as_ref().map(|(ident, ty)| {
+                let value = construct_with(state_name, &payload_field_values);
+                quote::quote!(
+                    #constructor_visibility #builder_constness fn build_with(self, #ident: #ty) -> #state_name {
+                        let Self { #(#pat_fields),* } = self;
+                        #value
+                    }
+                )
+            });
+            items.push(parse_quote!(
+                #(#cfg_attrs)*
+                #[doc = #doc]
+                #visibility struct #builder_ident { #(#constructor_inputs),* }
+            ));
+            items.push(parse_quote!(
+                #(#cfg_attrs)*
+                #[allow(missing_docs)]
+                impl #builder_ident {
+                    #(
+                        #constructor_visibility fn #pat_fields(mut self, #pat_fields: #field_types) -> Self {
+                            self.#pat_fields = #pat_fields;
+                            self
+                        }
+                    )*
+
+                    #constructor_visibility #builder_constness fn build(self) -> #state_name {
+                        let Self { #(#pat_fields),* } = self;
+                        #value
+                    }
+
+                    #build_with
+                }
+            ));
+
+            let defaults = defaults.iter().map(|default| match default {
+                Some(default) => default.clone(),
+                None => parse_quote!(Default::default()),
+            });
+            parse_quote!(#(#cfg_attrs)* #(#forwarded_attrs)* #constructor_visibility #builder_constness fn #state_handler_name() -> #builder_ident { #builder_ident { #(#pat_fields: #defaults),* } })
+        }
+    };
 
     let handler_call = match &state.is_async {
         true => {
@@ -487,9 +1173,28 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         }
     };
 
+    // The handler of a pseudo-state is called during the transitions that
+    // target it, and the state machine never stays in one to handle events.
+    let handler_call = match state.is_fallible {
+        _ if state.pseudo_state.is_some() => parse_quote!(statig::Response::Super),
+        true => lower_fallible_handler_output(
+            handler_call,
+            state.output,
+            state_handler_name,
+            state_machine,
+        ),
+        false => lower_handler_output(handler_call, state.output),
+    };
+
     let entry_action_call = parse_quote!({});
     let exit_action_call = parse_quote!({});
+    let activity_call = parse_quote!({});
+    let try_entry_action_call = parse_quote!(true);
+    let try_exit_action_call = parse_quote!(true);
     let superstate_pat = parse_quote!(None);
+    let ancestors = Vec::new();
+    let transition_arms = Vec::new();
+    let ancestor_transition_arms = Vec::new();
 
     State {
         variant,
@@ -498,15 +1203,178 @@ pub fn lower_state(state: &analyze::State, state_machine: &analyze::StateMachine
         handler_call,
         entry_action_call,
         exit_action_call,
+        activity_call,
+        payload_constructor,
+        try_entry_action_call,
+        try_exit_action_call,
         superstate_pat,
+        ancestors,
+        transition_arms,
+        ancestor_transition_arms,
+        cfg_attrs: cfg_attrs.clone(),
+        items,
     }
 }
 
+/// Lower the declared transitions of a state or one of its ancestors into match
+/// arms on the state and the event, which return the transition from the
+/// handler (e.g. `(State::On { .. }, Event::TimerElapsed) => return
+/// statig::Response::Transition(State::off())`). With a `source`, the response
+/// is paired with the height of the state or ancestor that declares it.
+fn lower_transition_arms(
+    pat: &Pat,
+    transitions: &[Arm],
+    cfg_attrs: &[Attribute],
+    source: Option<usize>,
+) -> Vec<Arm> {
+    transitions
+        .iter()
+        .map(|arm| {
+            let event_pat = &arm.pat;
+            let guard = arm.guard.as_ref().map(|(if_token, condition)| {
+                let mut condition = condition.as_ref().clone();
+                compose_guards(&mut condition);
+                quote::quote!(#if_token #condition)
+            });
+            let target = &arm.body;
+            let cfg_attrs = cfg_attrs.iter().chain(&arm.attrs);
+            let response = match source {
+                None => quote::quote!(statig::Response::Transition(#target)),
+                Some(source) => quote::quote!((statig::Response::Transition(#target), #source)),
+            };
+            parse_quote!(
+                #(#cfg_attrs)* (#pat, #event_pat) #guard => return #response
+            )
+        })
+        .collect()
+}
+
+/// Turn the output of a handler into a `Response`. A handler without a return
+/// type handles every event.
+fn lower_handler_output(handler_call: Expr, output: analyze::HandlerOutput) -> Expr {
+    match output {
+        analyze::HandlerOutput::Response => handler_call,
+        analyze::HandlerOutput::Unit => parse_quote!({
+            #handler_call;
+            statig::Response::Handled
+        }),
+        analyze::HandlerOutput::Into => {
+            parse_quote!(core::convert::Into::<statig::Response<_>>::into(#handler_call))
+        }
+    }
+}
+
+/// Call the default handler of a superstate for the events that don't match
+/// the event pattern of the superstate handler, instead of deferring them to
+/// the next superstate.
+fn lower_default_handler(
+    handler_call: Expr,
+    superstate: &analyze::Superstate,
+    default_handler: &Ident,
+    model: &Model,
+) -> Expr {
+    let state_machine = &model.state_machine;
+    let Some(event_pattern) = &superstate.event_pattern else {
+        abort!(
+            default_handler,
+            "a default handler requires an event pattern on the superstate handler";
+            help = "destructure the event in the inputs of `{}` (e.g. `Event::Reset: &Event`)", superstate.handler_name
+        );
+    };
+    let Some(method) = find_method(model, default_handler) else {
+        abort!(default_handler, "default handler not found");
+    };
+    for input in &method.sig.inputs {
+        if let FnArg::Typed(pat_type) = input {
+            if !matches!(pat_type.pat.as_ref(), Pat::Ident(_)) {
+                abort!(
+                    pat_type.pat,
+                    "inputs of a default handler must be identifiers"
+                );
+            }
+        }
+    }
+
+    let shared_storage_path = &state_machine.shared_storage_path;
+    let (_, shared_storage_type_generics, _) =
+        &state_machine.shared_storage_generics.split_for_impl();
+    let shared_storage_turbofish = shared_storage_type_generics.as_turbofish();
+    let inputs: Vec<Expr> = method
+        .sig
+        .inputs
+        .iter()
+        .map(fn_arg_to_handler_input)
+        .collect();
+    let default_call: Expr = match method.sig.asyncness.is_some() {
+        true => {
+            parse_quote!(#shared_storage_path #shared_storage_turbofish ::#default_handler(#(#inputs),*).await)
+        }
+        false => {
+            parse_quote!(#shared_storage_path #shared_storage_turbofish ::#default_handler(#(#inputs),*))
+        }
+    };
+    let (output, is_fallible) = analyze::analyze_handler_output(&method.sig.output);
+    let default_call = match is_fallible {
+        true => lower_fallible_handler_output(default_call, output, default_handler, state_machine),
+        false => lower_handler_output(default_call, output),
+    };
+
+    let event_ident = &state_machine.event_ident;
+    parse_quote!({
+        #[allow(unused_variables)]
+        let matched = matches!(#event_ident, #event_pattern);
+        match matched {
+            true => #handler_call,
+            false => #default_call,
+        }
+    })
+}
+
+/// Find a method in the `impl` block by its name.
+fn find_method<'a>(model: &'a Model, ident: &Ident) -> Option<&'a ImplItemMethod> {
+    model.item_impl.items.iter().find_map(|item| match item {
+        ImplItem::Method(method) if method.sig.ident == *ident => Some(method),
+        _ => None,
+    })
+}
+
+/// Unwrap the `Result` of a fallible handler. The error is passed to the
+/// `on_action_error` callback, after which the event is considered handled.
+fn lower_fallible_handler_output(
+    handler_call: Expr,
+    output: analyze::HandlerOutput,
+    handler_name: &Ident,
+    state_machine: &analyze::StateMachine,
+) -> Expr {
+    let on_action_error = match &state_machine.on_action_error {
+        Some(on_action_error) => resolve_self(on_action_error, state_machine),
+        None => abort!(
+            handler_name,
+            "fallible handler requires an `on_action_error` callback";
+            help = "add `on_action_error = \"Self::on_action_error\"` to the `state_machine` attribute"
+        ),
+    };
+    let ok_arm: Arm = match output {
+        analyze::HandlerOutput::Unit => parse_quote!(Ok(()) => statig::Response::Handled),
+        _ => {
+            let response = lower_handler_output(parse_quote!(output), output);
+            parse_quote!(Ok(output) => #response)
+        }
+    };
+    parse_quote!(match #handler_call {
+        #ok_arm,
+        Err(error) => {
+            #on_action_error(shared_storage, error);
+            statig::Response::Handled
+        }
+    })
+}
+
 pub fn lower_superstate(
     superstate: &analyze::Superstate,
     state_machine: &analyze::StateMachine,
 ) -> Superstate {
-    let superstate_name = snake_case_to_pascal_case(&superstate.handler_name);
+    let superstate_name = variant_name(&superstate.handler_name, &superstate.name);
     let superstate_handler_name = &superstate.handler_name;
     let shared_storage_path = &state_machine.shared_storage_path;
     let (_, shared_storage_type_generics, _) =
@@ -533,9 +1401,16 @@ pub fn lower_superstate(
         .iter()
         .map(|field| field.ident.as_ref().unwrap().clone())
         .collect();
-    let handler_inputs: Vec<Ident> = superstate.inputs.iter().map(fn_arg_to_ident).collect();
+    let handler_inputs: Vec<Expr> = superstate
+        .inputs
+        .iter()
+        .map(fn_arg_to_handler_input)
+        .collect();
 
-    let variant = parse_quote!(#superstate_name { #(#variant_fields),* });
+    let cfg_attrs = &superstate.cfg_attrs;
+    let forwarded_attrs = &superstate.forwarded_attrs;
+    let variant =
+        parse_quote!(#(#cfg_attrs)* #(#forwarded_attrs)* #superstate_name { #(#variant_fields),* });
     let pat = parse_quote!(#superstate_type::#superstate_name { #(#pat_fields),*});
 
     let handler_call = match &superstate.is_async {
@@ -547,9 +1422,25 @@ pub fn lower_superstate(
         }
     };
 
+    let handler_call = match superstate.is_fallible {
+        true => lower_fallible_handler_output(
+            handler_call,
+            superstate.output,
+            superstate_handler_name,
+            state_machine,
+        ),
+        false => lower_handler_output(handler_call, superstate.output),
+    };
+
     let entry_action_call = parse_quote!({});
     let exit_action_call = parse_quote!({});
+    let try_entry_action_call = parse_quote!(true);
+    let try_exit_action_call = parse_quote!(true);
     let superstate_pat = parse_quote!(None);
+    let completion = match &superstate.completion {
+        Some(target) => parse_quote!(Some(#target)),
+        None => parse_quote!(None),
+    };
 
     Superstate {
         variant,
@@ -557,7 +1448,11 @@ pub fn lower_superstate(
         handler_call,
         entry_action_call,
         exit_action_call,
+        try_entry_action_call,
+        try_exit_action_call,
         superstate_pat,
+        completion,
+        cfg_attrs: cfg_attrs.clone(),
     }
 }
 
@@ -589,7 +1484,7 @@ pub fn lower_action(action: &analyze::Action, state_machine: &analyze::StateMach
 
     let handler_inputs: Vec<Ident> = action.inputs.iter().map(fn_arg_to_ident).collect();
 
-    let handler_call = match &action.is_async {
+    let handler_call: Expr = match &action.is_async {
         true => {
             parse_quote!(#shared_storage_path #shared_storage_turbofish ::#action_handler_name(#(#handler_inputs),*).await)
         }
@@ -598,7 +1493,171 @@ pub fn lower_action(action: &analyze::Action, state_machine: &analyze::StateMach
         }
     };
 
-    Action { handler_call }
+    // The error of a fallible action is passed to the `on_action_error`
+    // callback, after which the transition proceeds or is aborted.
+    let (handler_call, try_handler_call): (Expr, Expr) = match action.is_fallible {
+        true => {
+            let on_action_error = match &state_machine.on_action_error {
+                Some(on_action_error) => resolve_self(on_action_error, state_machine),
+                None => abort!(
+                    action_handler_name,
+                    "fallible action requires an `on_action_error` callback";
+                    help = "add `on_action_error = \"Self::on_action_error\"` to the `state_machine` attribute"
+                ),
+            };
+            (
+                parse_quote!(if let Err(error) = #handler_call {
+                    #on_action_error(shared_storage, error);
+                }),
+                parse_quote!(match #handler_call {
+                    Ok(()) => true,
+                    Err(error) => {
+                        #on_action_error(shared_storage, error);
+                        false
+                    }
+                }),
+            )
+        }
+        false => (
+            handler_call.clone(),
+            parse_quote!({
+                #handler_call;
+                true
+            }),
+        ),
+    };
+
+    // An action that is compiled out is simply not called.
+    let cfg_attrs = &action.cfg_attrs;
+    let (handler_call, try_handler_call) = match cfg_attrs.is_empty() {
+        true => (handler_call, try_handler_call),
+        false => (
+            parse_quote!({
+                #(#cfg_attrs)*
+                {
+                    #handler_call;
+                }
+            }),
+            parse_quote!({
+                let mut proceed = true;
+                #(#cfg_attrs)*
+                {
+                    proceed = #try_handler_call;
+                }
+                proceed
+            }),
+        ),
+    };
+
+    Action {
+        handler_call,
+        try_handler_call,
+    }
+}
+
+/// Resolve a path that starts with `Self` to the shared storage type, so it
+/// can be called from the implementations of the state and superstate enums.
+fn resolve_self(path: &Path, state_machine: &analyze::StateMachine) -> Expr {
+    let shared_storage_path = &state_machine.shared_storage_path;
+    let (_, shared_storage_type_generics, _) =
+        &state_machine.shared_storage_generics.split_for_impl();
+    let shared_storage_turbofish = shared_storage_type_generics.as_turbofish();
+    match path.segments.first() {
+        Some(segment) if segment.ident == "Self" => {
+            let rest = path.segments.iter().skip(1);
+            parse_quote!(#shared_storage_path #shared_storage_turbofish #(::#rest)*)
+        }
+        _ => parse_quote!(#path),
+    }
+}
+
+/// Check that the item referenced by `reference` is compiled in every
+/// configuration in which the item that references it is compiled, i.e. that
+/// all of its `cfg` attributes are also present on the referencing item.
+fn check_cfg_attrs(reference: &Ident, referenced_cfg_attrs: &[Attribute], cfg_attrs: &[Attribute]) {
+    if let Some(missing) = referenced_cfg_attrs
+        .iter()
+        .find(|attr| !cfg_attrs.contains(attr))
+    {
+        let condition = &missing.tokens;
+        abort!(
+            reference,
+            "`{}` is compiled out in configurations in which it is referenced here", reference;
+            help = "consider adding `#[cfg{}]` to the handler, statement or match arm that references it", condition
+        )
+    }
+}
+
+/// Check that the states that are referenced in the handlers, the declared
+/// transitions and the initial state are compiled in every configuration in
+/// which they are referenced.
+fn check_state_references(model: &Model) {
+    let state_ident = &model.state_machine.state_ident;
+    let module = model.state_machine.module.as_ref();
+    let mut references = Vec::new();
+
+    let mut visitor = StateReferenceVisitor::new(state_ident, module, &[]);
+    visitor.visit_expr(&model.state_machine.initial_state);
+    references.extend(visitor.finish());
+
+    for item in &model.item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let name = &method.sig.ident;
+        let transitions = match (
+            model.states.get(name),
+            model.superstates.get(name),
+            model.actions.get(name),
+        ) {
+            (Some(state), _, _) => state.transitions.as_slice(),
+            (_, Some(superstate), _) => superstate.transitions.as_slice(),
+            (_, _, Some(_)) => [].as_slice(),
+            _ => continue,
+        };
+        // Handlers that share a name are checked against the configurations in
+        // which their own body is compiled.
+        let cfg_attrs = analyze::get_cfg_attrs(&method.attrs);
+        let mut visitor = StateReferenceVisitor::new(state_ident, module, &cfg_attrs);
+        visitor.visit_block(&method.block);
+        for arm in transitions {
+            visitor.visit_arm(arm);
+        }
+        if let Some(completion) = model
+            .superstates
+            .get(name)
+            .and_then(|superstate| superstate.completion.as_ref())
+        {
+            visitor.visit_expr(completion);
+        }
+        if let Some((_, target)) = model
+            .states
+            .get(name)
+            .and_then(|state| state.condition.as_ref())
+        {
+            visitor.visit_expr(target);
+        }
+        references.extend(visitor.finish());
+    }
+
+    for (reference, cfg_attrs) in references {
+        if let Some(state) = model.states.get(&reference) {
+            check_cfg_attrs(&reference, &state.cfg_attrs, &cfg_attrs);
+        }
+    }
+}
+
+/// The argument that is passed to a handler for the given input. An input
+/// that is received by value (i.e. a `Copy` event) is copied out of the
+/// reference it is dispatched with.
+fn fn_arg_to_handler_input(fn_arg: &FnArg) -> Expr {
+    let ident = fn_arg_to_ident(fn_arg);
+    match fn_arg {
+        FnArg::Typed(pat_type) if !matches!(pat_type.ty.as_ref(), Type::Reference(_)) => {
+            parse_quote!(*#ident)
+        }
+        _ => parse_quote!(#ident),
+    }
 }
 
 fn fn_arg_to_ident(fn_arg: &FnArg) -> Ident {
@@ -620,8 +1679,9 @@ fn fn_arg_to_state_field(pat_type: &PatType) -> Field {
     match pat_type.pat.as_ref() {
         Pat::Ident(pat_ident) => {
             let field_ident = &pat_ident.ident;
+            let field_attrs = analyze::get_field_attrs(&pat_type.attrs);
             Field::parse_named
-                .parse2(quote::quote!(#field_ident: #field_type))
+                .parse2(quote::quote!(#(#field_attrs)* #field_ident: #field_type))
                 .unwrap()
         }
         _ => panic!("all patterns should be verified to be idents"),
@@ -693,9 +1753,929 @@ fn map_generics(generics: &Generics) -> Vec<(GenericParam, Vec<WherePredicate>)>
     map
 }
 
+/// Abort when the local storage of a state uses the generics of the shared
+/// storage, as the items that are generated for it (`reason`) don't carry them.
+fn check_storage_generics(
+    state: &analyze::State,
+    fields: &[Field],
+    state_machine: &analyze::StateMachine,
+    reason: &str,
+) {
+    let mut visitor = GenericParamVisitor::new(&state_machine.shared_storage_generics);
+    for field in fields {
+        visitor.visit_type(&field.ty);
+    }
+    if !visitor.finish().is_empty() {
+        abort!(
+            state.handler_name,
+            "local storage of a state with {} can not use generics",
+            reason
+        );
+    }
+}
+
+/// The call to an entry or exit action. An action in the impl block is
+/// referenced by its name, while a free function is referenced by its path
+/// (e.g. `crate::actions::start_motor`) and is called with the shared storage
+/// and the context.
+fn action_call(
+    action: &Path,
+    actions: &HashMap<Ident, Action>,
+    state_machine: &analyze::StateMachine,
+) -> Expr {
+    match action.get_ident() {
+        Some(ident) => match actions.get(ident) {
+            Some(action) => action.handler_call.clone(),
+            None => abort!(ident, "action not found"),
+        },
+        None => {
+            let context_ident = &state_machine.context_ident;
+            parse_quote!(#action(shared_storage, #context_ident))
+        }
+    }
+}
+
+/// The call to an entry or exit action that evaluates to whether the
+/// transition can proceed. Free functions can't fail.
+fn try_action_call(
+    action: &Path,
+    actions: &HashMap<Ident, Action>,
+    state_machine: &analyze::StateMachine,
+) -> Expr {
+    match action.get_ident() {
+        Some(ident) => match actions.get(ident) {
+            Some(action) => action.try_handler_call.clone(),
+            None => abort!(ident, "action not found"),
+        },
+        None => {
+            let action_call = action_call(action, actions, state_machine);
+            parse_quote!({
+                #action_call;
+                true
+            })
+        }
+    }
+}
+
+/// Abort when the type of an external input (the event or the context) differs
+/// from the type of the first handler that takes it.
+fn check_external_input<'a>(first: &mut Option<&'a PatType>, pat_type: &'a PatType, name: &str) {
+    match pat_type.ty.as_ref() {
+        // Events are shared with the superstates and the dispatch hooks, so
+        // they can only be borrowed immutably.
+        Type::Reference(reference) if name == "event" && reference.mutability.is_some() => abort!(
+            pat_type.ty,
+            "event can not be passed in as a mutable reference";
+            help = "take the event by reference, or by value when it is `Copy`, and use interior mutability (e.g. `Cell<Option<T>>`) to take data out of it"
+        ),
+        Type::Reference(_) => {}
+        // A `Copy` event can also be received by value.
+        _ if name == "event" => {}
+        _ => abort!(pat_type.ty, "{} must be passed in as a reference", name),
+    }
+    let Some(first) = first else {
+        *first = Some(pat_type);
+        return;
+    };
+    let ty = external_input_type(pat_type);
+    let first_ty = external_input_type(first);
+    if erase_lifetimes(&ty) != erase_lifetimes(&first_ty) {
+        abort!(
+            pat_type.ty,
+            "the {} type `{}` differs from the {} type of the other handlers",
+            name, ty.to_token_stream(), name;
+            note = first.ty.span() => "the {} type is first declared as `{}` here", name, first_ty.to_token_stream()
+        );
+    }
+}
+
+/// The type of an external input, without the reference it is passed in with.
+fn external_input_type(pat_type: &PatType) -> Type {
+    match pat_type.ty.as_ref() {
+        Type::Reference(reference) => reference.elem.deref().clone(),
+        ty => ty.clone(),
+    }
+}
+
+/// Find the variant of the event enum that wraps the event type of a handler.
+fn event_variant<'a>(model: &'a Model, pat_type: &PatType) -> Option<&'a Ident> {
+    let event_type = erase_lifetimes(&external_input_type(pat_type));
+    let event_enum = model.state_machine.event_enum.as_ref()?;
+    event_enum
+        .variants
+        .iter()
+        .find(|(_, ty)| erase_lifetimes(ty) == event_type)
+        .map(|(variant, _)| variant)
+}
+
+/// Only call a handler for the events that are wrapped by the given variant,
+/// and defer the other events to its superstate.
+fn route_event(handler_call: &Expr, variant: &Ident, model: &Model) -> Expr {
+    let event_ident = &model.state_machine.event_ident;
+    let event_enum = model.state_machine.event_enum.as_ref().unwrap();
+    let event_enum_ident = &event_enum.ident;
+    // A variant of a borrowing event enum holds a reference to the event.
+    let handler_call: Expr = match event_enum.borrowed {
+        true => parse_quote!({
+            let #event_ident = *#event_ident;
+            #handler_call
+        }),
+        false => handler_call.clone(),
+    };
+    parse_quote!(match #event_ident {
+        #event_enum_ident::#variant(#event_ident) => #handler_call,
+        #[allow(unreachable_patterns)]
+        _ => statig::Response::Super,
+    })
+}
+
+/// Generate the event enum that wraps the event types of the state machine.
+fn lower_event_enum(model: &Model) -> Option<ItemEnum> {
+    let event_enum = model.state_machine.event_enum.as_ref()?;
+    let visibility = &model.state_machine.visibility;
+    let ident = &event_enum.ident;
+    let derives = &event_enum.derives;
+    let variants = event_enum
+        .variants
+        .iter()
+        .map(|(variant, ty)| match event_enum.borrowed {
+            true => quote::quote!(#variant(&'event #ty)),
+            false => quote::quote!(#variant(#ty)),
+        });
+    let generics = match event_enum.borrowed {
+        true => quote::quote!(<'event>),
+        false => quote::quote!(),
+    };
+    Some(parse_quote!(
+        #[derive(#(#derives),*)]
+        #visibility enum #ident #generics {
+            #(#variants),*
+        }
+    ))
+}
+
+/// Generate a `From` impl and a `statig::Event` impl for every type of the
+/// borrowing event enum, so a reference to an event of one of the types is
+/// turned into the event of the state machine without wrapping it.
+fn lower_event_type_impls(model: &Model) -> Vec<ItemImpl> {
+    let Some(event_enum) = model
+        .state_machine
+        .event_enum
+        .as_ref()
+        .filter(|event_enum| event_enum.borrowed)
+    else {
+        return Vec::new();
+    };
+    let ident = &event_enum.ident;
+    let shared_storage_type = &model.state_machine.shared_storage_type;
+    let (impl_generics, _, where_clause) =
+        model.state_machine.shared_storage_generics.split_for_impl();
+    event_enum
+        .variants
+        .iter()
+        .flat_map(|(variant, ty)| -> [ItemImpl; 2] {
+            [
+                parse_quote!(
+                    impl<'event> From<&'event #ty> for #ident<'event> {
+                        fn from(event: &'event #ty) -> Self {
+                            Self::#variant(event)
+                        }
+                    }
+                ),
+                parse_quote!(
+                    impl #impl_generics statig::Event<#shared_storage_type> for #ty #where_clause {
+                        fn as_event(&self) -> #ident<'_> {
+                            #ident::#variant(self)
+                        }
+                    }
+                ),
+            ]
+        })
+        .collect()
+}
+
+/// Generate a `From` impl for every type that is wrapped by a variant of the
+/// event enum, so events can be handled without wrapping them first.
+fn lower_event_from_impls(model: &Model, event_type: &Type) -> Vec<ItemImpl> {
+    let event_wrappers: Vec<_> = model
+        .state_machine
+        .event_wrappers
+        .iter()
+        .chain(
+            model
+                .state_machine
+                .event_enum
+                .iter()
+                .filter(|event_enum| !event_enum.borrowed)
+                .flat_map(|event_enum| &event_enum.variants),
+        )
+        .collect();
+    if let (Some((variant, _)), Type::Tuple(tuple)) = (event_wrappers.first(), event_type) {
+        if tuple.elems.is_empty() {
+            abort!(
+                variant,
+                "`event_from` requires a state machine that handles events";
+                help = "add an `{}` input to the state handlers", model.state_machine.event_ident
+            );
+        }
+    }
+    let event_type = erase_lifetimes(event_type);
+    event_wrappers
+        .iter()
+        .map(|(variant, ty)| {
+            parse_quote!(
+                impl From<#ty> for #event_type {
+                    fn from(event: #ty) -> Self {
+                        Self::#variant(event)
+                    }
+                }
+            )
+        })
+        .collect()
+}
+
+/// Replace all the lifetimes in a type with `'_`, so types can be compared
+/// regardless of the lifetimes they are declared with.
+fn erase_lifetimes(ty: &Type) -> Type {
+    struct EraseLifetimes;
+
+    impl VisitMut for EraseLifetimes {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            *lifetime = Lifetime::new("'_", lifetime.span());
+        }
+    }
+
+    let mut ty = ty.clone();
+    EraseLifetimes.visit_type_mut(&mut ty);
+    ty
+}
+
+/// Refer to the shared storage as `shared_storage` instead of `self`, in the
+/// conditions of condition transitions.
+struct ReplaceSelf;
+
+impl VisitMut for ReplaceSelf {
+    fn visit_expr_path_mut(&mut self, expr_path: &mut ExprPath) {
+        if expr_path.path.is_ident("self") {
+            let span = expr_path.path.span();
+            expr_path.path = Ident::new("shared_storage", span).into();
+        }
+    }
+}
+
+/// Generate the function that returns the target of the condition transition
+/// of the current state, if its condition holds. The conditions refer to the
+/// shared storage as `self`, which is passed to the function instead.
+fn lower_condition_transitions(model: &Model) -> Option<Expr> {
+    let state_ident = &model.state_machine.state_ident;
+    let arms: Vec<Arm> = analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| model.states.get(&method.sig.ident))
+        .filter_map(|state| {
+            if state.condition_position.is_some() {
+                return None;
+            }
+            let (condition, target) = state.condition.as_ref()?;
+            let mut condition = condition.clone();
+            ReplaceSelf.visit_expr_mut(&mut condition);
+            compose_guards(&mut condition);
+            let variant_name = variant_name(&state.handler_name, &state.name);
+            let cfg_attrs = &state.cfg_attrs;
+            Some(parse_quote!(
+                #(#cfg_attrs)* #state_ident::#variant_name { .. } if #condition => Some(#target)
+            ))
+        })
+        .collect();
+
+    match arms.is_empty() {
+        true => None,
+        false => Some(parse_quote!(|shared_storage, state| match state {
+            #(#arms,)*
+            _ => None,
+        })),
+    }
+}
+
+/// Generate the function that calls the handler of the pseudo-state of the
+/// given kind the given state is, if it is one, to select the target of the
+/// transition.
+fn lower_pseudo_states(model: &Model, kind: analyze::PseudoState) -> Option<Expr> {
+    let state_ident = &model.state_machine.state_ident;
+    let shared_storage_path = &model.state_machine.shared_storage_path;
+    let (_, shared_storage_type_generics, _) =
+        model.state_machine.shared_storage_generics.split_for_impl();
+    let shared_storage_turbofish = shared_storage_type_generics.as_turbofish();
+    let arms: Vec<Arm> = analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| model.states.get(&method.sig.ident))
+        .filter(|state| state.pseudo_state == Some(kind))
+        .map(|state| {
+            let handler_name = &state.handler_name;
+            let handler_inputs = state.inputs.iter().map(fn_arg_to_handler_input);
+            let variant_name = variant_name(&state.handler_name, &state.name);
+            let cfg_attrs = &state.cfg_attrs;
+            parse_quote!(
+                #(#cfg_attrs)* #state_ident::#variant_name { .. } => Some(#shared_storage_path #shared_storage_turbofish ::#handler_name(#(#handler_inputs),*))
+            )
+        })
+        .collect();
+
+    match arms.is_empty() {
+        true => None,
+        false => Some(parse_quote!(|shared_storage, state| match state {
+            #(#arms,)*
+            _ => None,
+        })),
+    }
+}
+
+/// Generate the function that returns whether the given state is a terminate
+/// pseudo-state.
+fn lower_terminates(model: &Model) -> Option<Expr> {
+    let state_ident = &model.state_machine.state_ident;
+    let arms: Vec<Arm> = analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| model.states.get(&method.sig.ident))
+        .filter(|state| state.pseudo_state == Some(analyze::PseudoState::Terminate))
+        .map(|state| {
+            let variant_name = variant_name(&state.handler_name, &state.name);
+            let cfg_attrs = &state.cfg_attrs;
+            parse_quote!(#(#cfg_attrs)* #state_ident::#variant_name { .. } => true)
+        })
+        .collect();
+
+    match arms.is_empty() {
+        true => None,
+        false => Some(parse_quote!(|state| match state {
+            #(#arms,)*
+            _ => false,
+        })),
+    }
+}
+
+/// Generate the function that calls the actions of the transitions from a
+/// source to a target state. The actions are methods on the shared storage.
+fn lower_transition_actions(model: &Model) -> Option<Expr> {
+    let state_ident = &model.state_machine.state_ident;
+    let arms: Vec<Arm> = analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| model.states.get(&method.sig.ident))
+        .flat_map(|source| {
+            source.transition_actions.iter().map(move |(target, action)| {
+                let Some(target) = model.states.get(target) else {
+                    abort!(target, "state not found");
+                };
+                let source_name = variant_name(&source.handler_name, &source.name);
+                let target_name = variant_name(&target.handler_name, &target.name);
+                let cfg_attrs = source.cfg_attrs.iter().chain(&target.cfg_attrs);
+                parse_quote!(
+                    #(#cfg_attrs)* (#state_ident::#source_name { .. }, #state_ident::#target_name { .. }) => shared_storage.#action()
+                )
+            })
+        })
+        .collect();
+
+    match arms.is_empty() {
+        true => None,
+        false => Some(
+            parse_quote!(|shared_storage, source, target| match (source, target) {
+                #(#arms,)*
+                _ => {}
+            }),
+        ),
+    }
+}
+
+/// Generate the boolean logic of the guards that are combined with `all(...)`,
+/// `any(...)` and `not(...)`. Within them, the name of a guard is a call to the
+/// method of that name on the shared storage, e.g. `fn is_locked(&self) -> bool`,
+/// and any other expression is used as is.
+fn compose_guards(condition: &mut Expr) {
+    struct ComposeGuards;
+
+    impl ComposeGuards {
+        fn compose(&mut self, expr: &mut Expr) {
+            match expr {
+                Expr::Path(expr_path) if expr_path.path.get_ident().is_some() => {
+                    let guard = expr_path.path.get_ident().unwrap();
+                    *expr = parse_quote!(shared_storage.#guard());
+                }
+                expr => {
+                    self.visit_expr_mut(expr);
+                    // Keep the precedence of an operation within the guards.
+                    if matches!(expr, Expr::Binary(_) | Expr::Cast(_) | Expr::Range(_)) {
+                        *expr = parse_quote!((#expr));
+                    }
+                }
+            }
+        }
+    }
+
+    impl VisitMut for ComposeGuards {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            let Expr::Call(call) = expr else {
+                return syn::visit_mut::visit_expr_mut(self, expr);
+            };
+            let Expr::Path(func) = call.func.as_ref() else {
+                return syn::visit_mut::visit_expr_mut(self, expr);
+            };
+            let Some(combinator) = func.path.get_ident().map(Ident::to_string) else {
+                return syn::visit_mut::visit_expr_mut(self, expr);
+            };
+            let mut guards: Vec<Expr> = call.args.iter().cloned().collect();
+            for guard in &mut guards {
+                self.compose(guard);
+            }
+            *expr = match combinator.as_str() {
+                "all" if guards.is_empty() => parse_quote!(true),
+                "all" => parse_quote!((#(#guards)&&*)),
+                "any" if guards.is_empty() => parse_quote!(false),
+                "any" => parse_quote!((#(#guards)||*)),
+                "not" if guards.len() == 1 => {
+                    let guard = &guards[0];
+                    parse_quote!(!#guard)
+                }
+                "not" => abort!(call, "`not` takes a single guard"),
+                _ => return syn::visit_mut::visit_expr_mut(self, expr),
+            };
+        }
+    }
+
+    ComposeGuards.visit_expr_mut(condition);
+}
+
+/// Generate a function on the state enum for every constant that is declared
+/// on the states, which returns the value of the current state, if it declares
+/// the constant. The type of the constant follows from the suffix of any of
+/// its values, and otherwise defaults to the type Rust would infer.
+fn lower_state_consts(model: &Model) -> Vec<ItemFn> {
+    let state_ident = &model.state_machine.state_ident;
+    let visibility = &model.state_machine.visibility;
+
+    /// A constant that is declared on one or more states.
+    struct ConstLookup<'a> {
+        name: &'a Ident,
+        explicit_type: Option<Type>,
+        default_type: Type,
+        first_value: &'a Lit,
+        arms: Vec<Arm>,
+    }
+
+    // The constants in the order in which they are first declared.
+    let mut lookups: Vec<ConstLookup> = Vec::new();
+    for method in analyze::unique_methods(&model.item_impl) {
+        let Some(state) = model.states.get(&method.sig.ident) else {
+            continue;
+        };
+        let variant_name = variant_name(&state.handler_name, &state.name);
+        let cfg_attrs = &state.cfg_attrs;
+        for (index, (name, value)) in state.consts.iter().enumerate() {
+            if let Some((first, _)) = state.consts[..index]
+                .iter()
+                .find(|(first, _)| first == name)
+            {
+                abort!(
+                    name,
+                    "constant `{}` is declared more than once", name;
+                    note = first.span() => "`{}` is first declared here", first
+                );
+            }
+            let (explicit_type, default_type) = const_type(value);
+            let arm: Arm =
+                parse_quote!(#(#cfg_attrs)* #state_ident::#variant_name { .. } => Some(#value));
+            match lookups.iter_mut().find(|lookup| lookup.name == name) {
+                Some(lookup) => {
+                    match (&lookup.explicit_type, explicit_type) {
+                        (Some(lookup_type), Some(explicit_type))
+                            if *lookup_type != explicit_type =>
+                        {
+                            abort!(
+                                value,
+                                "the type of `{}` differs from the type of its other values", name;
+                                note = lookup.first_value.span() => "the type is first declared as `{}` here", lookup_type.to_token_stream()
+                            )
+                        }
+                        (None, Some(explicit_type)) => lookup.explicit_type = Some(explicit_type),
+                        _ => {}
+                    }
+                    lookup.arms.push(arm);
+                }
+                None => lookups.push(ConstLookup {
+                    name,
+                    explicit_type,
+                    default_type,
+                    first_value: value,
+                    arms: vec![arm],
+                }),
+            }
+        }
+    }
+
+    lookups
+        .into_iter()
+        .map(|lookup| {
+            let name = lookup.name;
+            let mut arms = lookup.arms;
+            arms.push(parse_quote!(_ => None));
+            let const_type = lookup.explicit_type.unwrap_or(lookup.default_type);
+            let lookup_name = name.to_string().to_lowercase();
+            // A name that collides with a keyword (e.g. `TYPE`) becomes a raw
+            // identifier.
+            let lookup_ident = match syn::parse_str::<Ident>(&lookup_name) {
+                Ok(_) => Ident::new(&lookup_name, name.span()),
+                Err(_) => Ident::new_raw(&lookup_name, name.span()),
+            };
+            let doc = format!("The `{}` of the current state, if it declares one.", name);
+            parse_quote!(
+                #[doc = #doc]
+                #[allow(unreachable_patterns)]
+                #visibility const fn #lookup_ident(&self) -> Option<#const_type> {
+                    match self {
+                        #(#arms),*
+                    }
+                }
+            )
+        })
+        .collect()
+}
+
+/// Describe every state and superstate, in declaration order, with its
+/// superstate and its metadata.
+fn lower_hierarchy(model: &Model) -> Vec<Expr> {
+    analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| {
+            let name = &method.sig.ident;
+            match (model.states.get(name), model.superstates.get(name)) {
+                (Some(state), _) => Some((
+                    name,
+                    &state.superstate,
+                    false,
+                    &state.meta,
+                    &state.cfg_attrs,
+                )),
+                (_, Some(superstate)) => Some((
+                    name,
+                    &superstate.superstate,
+                    true,
+                    &superstate.meta,
+                    &superstate.cfg_attrs,
+                )),
+                _ => None,
+            }
+        })
+        .map(|(name, superstate, is_superstate, meta, cfg_attrs)| {
+            let name = name.unraw().to_string();
+            let superstate = match superstate {
+                Some(superstate) => {
+                    let superstate = superstate.unraw().to_string();
+                    quote::quote!(Some(#superstate))
+                }
+                None => quote::quote!(None),
+            };
+            let meta = meta.iter().map(|(key, value)| {
+                let key = key.unraw().to_string();
+                quote::quote!((#key, #value))
+            });
+            parse_quote!(
+                #(#cfg_attrs)*
+                statig::StateInfo {
+                    name: #name,
+                    superstate: #superstate,
+                    is_superstate: #is_superstate,
+                    meta: &[#(#meta),*],
+                }
+            )
+        })
+        .collect()
+}
+
+/// Describe the initial transition and the declared transitions of every
+/// state and superstate, in declaration order.
+fn lower_transition_table(model: &Model) -> Vec<Expr> {
+    let initial = state_name(&model.state_machine.initial_state);
+    let initial: Expr = parse_quote!(
+        statig::TransitionInfo {
+            source: None,
+            event: None,
+            guard: None,
+            target: #initial,
+        }
+    );
+    let declared = analyze::unique_methods(&model.item_impl).filter_map(|method| {
+        let name = &method.sig.ident;
+        match (model.states.get(name), model.superstates.get(name)) {
+            (Some(state), _) => Some((name, &state.transitions, &state.cfg_attrs)),
+            (_, Some(superstate)) => Some((name, &superstate.transitions, &superstate.cfg_attrs)),
+            _ => None,
+        }
+    });
+    let declared = declared.flat_map(|(name, transitions, cfg_attrs)| {
+        let source = name.unraw().to_string();
+        transitions.iter().map(move |arm| {
+            let event = event_name(&arm.pat);
+            let guard = match &arm.guard {
+                Some((_, condition)) => {
+                    let condition = condition.to_token_stream().to_string();
+                    quote::quote!(Some(#condition))
+                }
+                None => quote::quote!(None),
+            };
+            let target = state_name(&arm.body);
+            let cfg_attrs = cfg_attrs.iter().chain(&arm.attrs);
+            parse_quote!(
+                #(#cfg_attrs)*
+                statig::TransitionInfo {
+                    source: Some(#source),
+                    event: Some(#event),
+                    guard: #guard,
+                    target: #target,
+                }
+            )
+        })
+    });
+    std::iter::once(initial).chain(declared).collect()
+}
+
+/// Describe, for every state and every variant of the event enum, whether the
+/// event is handled by a declared transition of the state, by one of its
+/// superstates or not at all. Only transitions without a guard or `cfg`
+/// attributes whose pattern matches every value of the variant are counted,
+/// so an event that is listed as handled is handled in every case.
+fn lower_event_routing(model: &Model) -> Option<Vec<Expr>> {
+    if !model.state_machine.event_routing {
+        return None;
+    }
+    let event_enum = model.state_machine.event_enum.as_ref()?;
+
+    // Choices and junctions are never the current state, so they don't handle
+    // events.
+    let states = analyze::unique_methods(&model.item_impl)
+        .filter_map(|method| model.states.get(&method.sig.ident))
+        .filter(|state| {
+            !matches!(
+                state.pseudo_state,
+                Some(analyze::PseudoState::Choice | analyze::PseudoState::Junction)
+            )
+        });
+
+    let routes = states.flat_map(|state| {
+        // The declared transitions of the state and its ancestors, from the
+        // bottom up, as they are resolved.
+        let mut levels = vec![(None, &state.transitions)];
+        let mut superstate = state.superstate.as_ref();
+        while let Some(superstate_ident) = superstate {
+            let Some(ancestor) = model.superstates.get(superstate_ident) else {
+                break;
+            };
+            levels.push((Some(superstate_ident), &ancestor.transitions));
+            superstate = ancestor.superstate.as_ref();
+        }
+
+        let name = state.handler_name.unraw().to_string();
+        let cfg_attrs = &state.cfg_attrs;
+        event_enum
+            .variants
+            .iter()
+            .map(move |(variant, _)| {
+                let handling = levels
+                    .iter()
+                    .find(|(_, transitions)| {
+                        transitions.iter().any(|arm| {
+                            arm.guard.is_none()
+                                && arm.attrs.is_empty()
+                                && matches_variant(&arm.pat, variant)
+                        })
+                    })
+                    .map(|(superstate, _)| superstate);
+                let handling = match handling {
+                    None => quote::quote!(statig::Handling::Unhandled),
+                    Some(None) => quote::quote!(statig::Handling::State),
+                    Some(Some(superstate)) => {
+                        let superstate = superstate.unraw().to_string();
+                        quote::quote!(statig::Handling::Superstate(#superstate))
+                    }
+                };
+                let event = variant.unraw().to_string();
+                parse_quote!(
+                    #(#cfg_attrs)*
+                    statig::EventRoute {
+                        state: #name,
+                        event: #event,
+                        handling: #handling,
+                    }
+                )
+            })
+            .collect::<Vec<Expr>>()
+    });
+    Some(routes.collect())
+}
+
+/// Whether a pattern matches every value of a variant of the event enum (e.g.
+/// `Event::Reset { .. }` for `Reset`, but not `Event::Reset { hard: true }`).
+fn matches_variant(pat: &Pat, variant: &Ident) -> bool {
+    let is_variant = |path: &Path| {
+        path.segments
+            .last()
+            .map_or(false, |segment| segment.ident == *variant)
+    };
+    match pat {
+        Pat::Wild(_) => true,
+        Pat::Ident(pat) => match &pat.subpat {
+            Some((_, subpat)) => matches_variant(subpat, variant),
+            None => true,
+        },
+        Pat::Or(pat) => pat.cases.iter().any(|case| matches_variant(case, variant)),
+        Pat::Reference(pat) => matches_variant(&pat.pat, variant),
+        Pat::Path(pat) => is_variant(&pat.path),
+        Pat::TupleStruct(pat) => is_variant(&pat.path) && pat.pat.elems.iter().all(is_irrefutable),
+        Pat::Struct(pat) => {
+            is_variant(&pat.path) && pat.fields.iter().all(|field| is_irrefutable(&field.pat))
+        }
+        _ => false,
+    }
+}
+
+/// Whether a pattern matches every value (e.g. `_`, `id` or `(_, ..)`).
+fn is_irrefutable(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wild(_) | Pat::Rest(_) => true,
+        Pat::Ident(pat) => match &pat.subpat {
+            Some((_, subpat)) => is_irrefutable(subpat),
+            None => true,
+        },
+        Pat::Reference(pat) => is_irrefutable(&pat.pat),
+        Pat::Tuple(pat) => pat.elems.iter().all(is_irrefutable),
+        _ => false,
+    }
+}
+
+/// The name of the state that is constructed by an expression (e.g. `"on"` for
+/// `State::on()`), or the expression itself if it isn't a constructor call.
+fn state_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Call(call) => match call.func.as_ref() {
+            Expr::Path(path) => match path.path.segments.last() {
+                Some(segment) => segment.ident.unraw().to_string(),
+                None => expr.to_token_stream().to_string(),
+            },
+            _ => expr.to_token_stream().to_string(),
+        },
+        _ => expr.to_token_stream().to_string(),
+    }
+}
+
+/// The name of the event that is matched by a pattern (e.g. `"TimerElapsed"`
+/// for `Event::TimerElapsed`), or the pattern itself if it doesn't match a
+/// single variant.
+fn event_name(pat: &Pat) -> String {
+    let path = match pat {
+        Pat::Path(pat) => &pat.path,
+        Pat::TupleStruct(pat) => &pat.path,
+        Pat::Struct(pat) => &pat.path,
+        Pat::Or(pat) => {
+            let cases: Vec<String> = pat.cases.iter().map(event_name).collect();
+            return cases.join(" | ");
+        }
+        _ => return pat.to_token_stream().to_string(),
+    };
+    match path.segments.last() {
+        Some(segment) => segment.ident.unraw().to_string(),
+        None => pat.to_token_stream().to_string(),
+    }
+}
+
+/// The type of the value of a constant, if it is explicit (e.g. `500u32`),
+/// and the type Rust would infer for it otherwise.
+fn const_type(value: &Lit) -> (Option<Type>, Type) {
+    match value {
+        Lit::Str(_) => (None, parse_quote!(&'static str)),
+        Lit::ByteStr(_) => (None, parse_quote!(&'static [u8])),
+        Lit::Byte(_) => (None, parse_quote!(u8)),
+        Lit::Char(_) => (None, parse_quote!(char)),
+        Lit::Bool(_) => (None, parse_quote!(bool)),
+        Lit::Int(int) => match int.suffix() {
+            "" => (None, parse_quote!(i32)),
+            suffix => {
+                let suffix = Ident::new(suffix, int.span());
+                (Some(parse_quote!(#suffix)), parse_quote!(#suffix))
+            }
+        },
+        Lit::Float(float) => match float.suffix() {
+            "" => (None, parse_quote!(f64)),
+            suffix => {
+                let suffix = Ident::new(suffix, float.span());
+                (Some(parse_quote!(#suffix)), parse_quote!(#suffix))
+            }
+        },
+        Lit::Verbatim(_) => abort!(value, "unsupported literal"),
+    }
+}
+
+/// Find the states, in declaration order, that are neither the initial state
+/// nor referenced by any method other than their own handler, so they can
+/// never be entered.
+fn find_unreachable_states(model: &Model) -> Vec<Ident> {
+    let state_ident = &model.state_machine.state_ident;
+    let module = model.state_machine.module.as_ref();
+    let mut visitor = StateReferenceVisitor::new(state_ident, module, &[]);
+    visitor.visit_expr(&model.state_machine.initial_state);
+    let mut referenced: Vec<Ident> = visitor
+        .finish()
+        .into_iter()
+        .map(|(ident, _)| ident)
+        .collect();
+
+    for item in &model.item_impl.items {
+        let ImplItem::Method(method) = item else {
+            continue;
+        };
+        let mut visitor = StateReferenceVisitor::new(state_ident, module, &[]);
+        visitor.visit_block(&method.block);
+        let transitions = model
+            .states
+            .get(&method.sig.ident)
+            .map(|state| &state.transitions)
+            .or_else(|| {
+                model
+                    .superstates
+                    .get(&method.sig.ident)
+                    .map(|superstate| &superstate.transitions)
+            });
+        for arm in transitions.into_iter().flatten() {
+            visitor.visit_arm(arm);
+        }
+        if let Some(completion) = model
+            .superstates
+            .get(&method.sig.ident)
+            .and_then(|superstate| superstate.completion.as_ref())
+        {
+            visitor.visit_expr(completion);
+        }
+        if let Some((_, target)) = model
+            .states
+            .get(&method.sig.ident)
+            .and_then(|state| state.condition.as_ref())
+        {
+            visitor.visit_expr(target);
+        }
+        referenced.extend(
+            visitor
+                .finish()
+                .into_iter()
+                .map(|(ident, _)| ident)
+                .filter(|ident| *ident != method.sig.ident),
+        );
+    }
+
+    analyze::unique_methods(&model.item_impl)
+        .map(|method| &method.sig.ident)
+        .filter(|ident| model.states.contains_key(*ident) && !referenced.contains(*ident))
+        .cloned()
+        .collect()
+}
+
+/// Abort when two states or two superstates end up with the same variant name
+/// (e.g. `#[state(name = "Idle")]` next to a state named `idle`).
+fn check_variant_names(model: &Model) {
+    let mut state_variants = HashMap::new();
+    let mut superstate_variants = HashMap::new();
+    for method in analyze::unique_methods(&model.item_impl) {
+        if let Some(state) = model.states.get(&method.sig.ident) {
+            let variant = variant_name(&state.handler_name, &state.name);
+            let origin = state.name.as_ref().unwrap_or(&state.handler_name);
+            check_variant_name(&mut state_variants, variant, origin);
+        }
+        if let Some(superstate) = model.superstates.get(&method.sig.ident) {
+            let variant = variant_name(&superstate.handler_name, &superstate.name);
+            let origin = superstate.name.as_ref().unwrap_or(&superstate.handler_name);
+            check_variant_name(&mut superstate_variants, variant, origin);
+        }
+    }
+}
+
+/// Abort when the variant was already generated. The map associates every
+/// variant with the handler or name it was first generated for.
+fn check_variant_name(variants: &mut HashMap<Ident, Ident>, variant: Ident, origin: &Ident) {
+    match variants.get(&variant) {
+        Some(first) => abort!(
+            origin,
+            "the variant `{}` is generated more than once", variant;
+            note = first.span() => "`{}` is first generated for `{}`", variant, first
+        ),
+        None => {
+            variants.insert(variant, origin.clone());
+        }
+    }
+}
+
+/// Get the name of the variant of a state or superstate, which is either set
+/// explicitly or derived from the name of the handler.
+fn variant_name(handler_name: &Ident, name: &Option<Ident>) -> Ident {
+    match name {
+        Some(name) => name.clone(),
+        None => snake_case_to_pascal_case(handler_name),
+    }
+}
+
 fn snake_case_to_pascal_case(snake: &Ident) -> Ident {
     let mut pascal = String::new();
-    for part in snake.to_string().split('_') {
+    for part in snake.unraw().to_string().split('_') {
         let mut characters = part.chars();
         pascal.push_str(&characters.next().map_or_else(String::new, |c| {
             c.to_uppercase().chain(characters).collect()
@@ -713,13 +2693,28 @@ fn create_analyze_state_machine() -> analyze::StateMachine {
         shared_storage_generics: parse_quote!(),
         state_ident: parse_quote!(State),
         state_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        state_non_exhaustive: false,
         superstate_ident: parse_quote!(Superstate),
         superstate_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        superstate_non_exhaustive: false,
         before_transition: None,
         after_transition: None,
+        on_init: None,
+        on_transition: None,
         before_dispatch: None,
         after_dispatch: None,
         visibility: parse_quote!(pub),
+        module: None,
+        max_state_size: None,
+        unreachable_states: analyze::LintLevel::Allow,
+        state_id_mirror: None,
+        after: None,
+        on_action_error: None,
+        action_failure: analyze::ActionFailure::Abort,
+        semantics: analyze::Semantics::Pragmatic,
+        event_wrappers: vec![],
+        event_enum: None,
+        event_routing: false,
         event_ident: parse_quote!(input),
         context_ident: parse_quote!(context),
     }
@@ -738,18 +2733,45 @@ fn create_lower_state_machine() -> StateMachine {
         #[rustfmt::skip]
         state_ident: parse_quote!(State),
         state_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        state_non_exhaustive: false,
         state_generics: Generics::default(),
         superstate_ident: parse_quote!(Superstate),
         superstate_derives: vec![parse_quote!(Copy), parse_quote!(Clone)],
+        superstate_non_exhaustive: false,
         superstate_generics,
         before_transition: None,
         after_transition: None,
+        on_init: None,
+        on_transition: None,
         before_dispatch: None,
         after_dispatch: None,
         visibility: parse_quote!(pub),
+        module: None,
+        max_state_size: None,
+        unreachable_states: vec![],
+        const_lookups: vec![],
+        hierarchy: vec![],
+        transitions: vec![parse_quote!(statig::TransitionInfo {
+            source: None,
+            event: None,
+            guard: None,
+            target: "on",
+        })],
+        state_id_mirror: None,
+        after: None,
+        when: None,
+        choice: None,
+        junction: None,
+        terminate: None,
+        transition_action: None,
         event_ident: parse_quote!(input),
         context_ident: parse_quote!(context),
         mode: Mode::Blocking,
+        aborts_on_action_failure: false,
+        semantics: None,
+        event_from_impls: vec![],
+        event_enum: None,
+        event_routing: None,
     }
 }
 
@@ -757,10 +2779,22 @@ fn create_lower_state_machine() -> StateMachine {
 fn create_analyze_state() -> analyze::State {
     analyze::State {
         handler_name: parse_quote!(on),
+        name: None,
         superstate: parse_quote!(playing),
-        entry_action: parse_quote!(enter_on),
+        entry_action: Some(parse_quote!(enter_on)),
         exit_action: None,
+        activity: None,
         local_storage: vec![],
+        payload: None,
+        boxed: false,
+        derives: vec![],
+        builder: false,
+        consts: vec![],
+        transitions: vec![],
+        condition: None,
+        condition_position: None,
+        transition_actions: vec![],
+        pseudo_state: None,
         inputs: vec![
             parse_quote!(&mut self),
             parse_quote!(input: &Event),
@@ -775,6 +2809,7 @@ fn create_analyze_state() -> analyze::State {
                 panic!();
             },
         ),
+        event_pattern: None,
         context_arg: None,
         state_inputs: vec![
             if let FnArg::Typed(pat_type) = parse_quote!(led: &mut bool) {
@@ -789,6 +2824,11 @@ fn create_analyze_state() -> analyze::State {
             },
         ],
         is_async: false,
+        output: analyze::HandlerOutput::Response,
+        is_fallible: false,
+        cfg_attrs: vec![],
+        forwarded_attrs: vec![],
+        meta: vec![],
     }
 }
 
@@ -803,12 +2843,21 @@ fn create_lower_state() -> State {
         handler_call: parse_quote!(Blinky::on(shared_storage, input, led, counter)),
         entry_action_call: parse_quote!({}),
         exit_action_call: parse_quote!({}),
+        activity_call: parse_quote!({}),
+        payload_constructor: None,
+        try_entry_action_call: parse_quote!(true),
+        try_exit_action_call: parse_quote!(true),
         superstate_pat: parse_quote!(None),
+        ancestors: vec![],
+        transition_arms: vec![],
+        ancestor_transition_arms: vec![],
         constructor: parse_quote!(
             const fn on(led: bool, counter: usize) -> Self {
                 Self::On { led, counter }
             }
         ),
+        cfg_attrs: vec![],
+        items: vec![],
     }
 }
 
@@ -816,7 +2865,12 @@ fn create_lower_state() -> State {
 fn create_linked_lower_state() -> State {
     let mut state = create_lower_state();
     state.superstate_pat = parse_quote!(Some(Superstate::Playing { led, counter }));
+    state.ancestors = vec![parse_quote!(playing)];
     state.entry_action_call = parse_quote!(Blinky::enter_on(shared_storage, led));
+    state.try_entry_action_call = parse_quote!({
+        Blinky::enter_on(shared_storage, led);
+        true
+    });
     state
 }
 
@@ -824,10 +2878,14 @@ fn create_linked_lower_state() -> State {
 fn create_analyze_superstate() -> analyze::Superstate {
     analyze::Superstate {
         handler_name: parse_quote!(playing),
+        name: None,
         superstate: None,
         entry_action: None,
         exit_action: None,
+        default_handler: None,
+        completion: None,
         local_storage: vec![],
+        transitions: vec![],
         inputs: vec![
             parse_quote!(&mut self),
             parse_quote!(input: &Event),
@@ -842,6 +2900,7 @@ fn create_analyze_superstate() -> analyze::Superstate {
                 panic!();
             },
         ),
+        event_pattern: None,
         context_arg: None,
         state_inputs: vec![
             if let FnArg::Typed(pat_type) = parse_quote!(led: &mut bool) {
@@ -856,6 +2915,11 @@ fn create_analyze_superstate() -> analyze::Superstate {
             },
         ],
         is_async: false,
+        output: analyze::HandlerOutput::Response,
+        is_fallible: false,
+        cfg_attrs: vec![],
+        forwarded_attrs: vec![],
+        meta: vec![],
     }
 }
 
@@ -870,7 +2934,11 @@ fn create_lower_superstate() -> Superstate {
         handler_call: parse_quote!(Blinky::playing(shared_storage, input, led, counter)),
         entry_action_call: parse_quote!({}),
         exit_action_call: parse_quote!({}),
+        try_entry_action_call: parse_quote!(true),
+        try_exit_action_call: parse_quote!(true),
         superstate_pat: parse_quote!(None),
+        completion: parse_quote!(None),
+        cfg_attrs: vec![],
     }
 }
 
@@ -880,6 +2948,8 @@ fn create_analyze_action() -> analyze::Action {
         handler_name: parse_quote!(enter_on),
         inputs: vec![parse_quote!(&mut self), parse_quote!(led: &mut bool)],
         is_async: false,
+        is_fallible: false,
+        cfg_attrs: vec![],
     }
 }
 
@@ -887,6 +2957,10 @@ fn create_analyze_action() -> analyze::Action {
 fn create_lower_action() -> Action {
     Action {
         handler_call: parse_quote!(Blinky::enter_on(shared_storage, led)),
+        try_handler_call: parse_quote!({
+            Blinky::enter_on(shared_storage, led);
+            true
+        }),
     }
 }
 
@@ -968,3 +3042,157 @@ fn test_lower() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_snake_case_to_pascal_case() {
+    let snake: Ident = parse_quote!(led_on);
+    assert_eq!(snake_case_to_pascal_case(&snake), "LedOn");
+
+    let raw: Ident = parse_quote!(r#loop);
+    assert_eq!(snake_case_to_pascal_case(&raw), "Loop");
+}
+
+#[test]
+fn test_erase_lifetimes() {
+    let named: Type = parse_quote!(Event<'evt>);
+    let anonymous: Type = parse_quote!(Event<'_>);
+    assert_eq!(erase_lifetimes(&named), erase_lifetimes(&anonymous));
+
+    let other: Type = parse_quote!(OtherEvent<'evt>);
+    assert_ne!(erase_lifetimes(&named), erase_lifetimes(&other));
+}
+
+#[test]
+fn test_compose_guards() {
+    let mut condition: Expr = parse_quote!(all(is_armed, any(is_open, count > 3), not(is_locked)));
+    compose_guards(&mut condition);
+    let expected: Expr = parse_quote!(
+        (shared_storage.is_armed()
+            && (shared_storage.is_open() || (count > 3))
+            && !shared_storage.is_locked())
+    );
+    assert_eq!(condition, expected);
+}
+
+#[test]
+fn test_check_external_input() {
+    let typed = |fn_arg: FnArg| match fn_arg {
+        FnArg::Typed(pat_type) => pat_type,
+        FnArg::Receiver(_) => unreachable!(),
+    };
+    let by_reference = typed(parse_quote!(event: &Event));
+    let by_value = typed(parse_quote!(event: Event));
+    let mut first = None;
+    check_external_input(&mut first, &by_reference, "event");
+    check_external_input(&mut first, &by_value, "event");
+    assert_eq!(first, Some(&by_reference));
+}
+
+#[test]
+#[should_panic]
+fn test_check_external_input_mutable_event() {
+    let by_mutable_reference = match parse_quote!(event: &mut Event) {
+        FnArg::Typed(pat_type) => pat_type,
+        FnArg::Receiver(_) => unreachable!(),
+    };
+    check_external_input(&mut None, &by_mutable_reference, "event");
+}
+
+#[test]
+fn test_matching_external_inputs() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event<'_>, context: &mut Context) -> Response<State> {
+                Handled
+            }
+
+            #[state]
+            fn off<'a>(event: &Event<'a>, context: &mut Context) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let model = analyze::analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_mismatched_event_type() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[state]
+            fn off(event: &OtherEvent) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let model = analyze::analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_mismatched_context_type() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event, context: &mut Context) -> Response<State> {
+                Handled
+            }
+
+            #[state]
+            fn off(event: &Event, context: &mut OtherContext) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let model = analyze::analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_context_by_value() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event, context: Context) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let model = analyze::analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+
+    lower(&model);
+}
+
+#[test]
+#[should_panic]
+fn test_misnamed_event_input() {
+    let item_impl: ItemImpl = parse_quote!(
+        impl Blinky {
+            #[state]
+            fn on(event: &Event) -> Response<State> {
+                Handled
+            }
+
+            #[state]
+            fn off(evt: &Event) -> Response<State> {
+                Handled
+            }
+        }
+    );
+    let model = analyze::analyze(vec![parse_quote!(initial = "State::on()")], item_impl);
+
+    lower(&model);
+}
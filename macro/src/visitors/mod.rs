@@ -1,5 +1,7 @@
 mod generic_param_visitor;
 mod lifetime_visitor;
+mod state_reference_visitor;
 
 pub use generic_param_visitor::*;
 pub use lifetime_visitor::*;
+pub use state_reference_visitor::*;
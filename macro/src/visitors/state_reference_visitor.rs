@@ -0,0 +1,145 @@
+use syn::visit::{self, Visit};
+use syn::{Arm, Attribute, Expr, Ident, Stmt};
+
+use crate::analyze::get_cfg_attrs;
+
+/// Visitor to find all the references to states (e.g. `State::calibration()`),
+/// together with the `cfg` attributes of the statements and match arms that
+/// enclose them. When the state enum is generated in a module, references
+/// through that module (e.g. `fsm::State::calibration()`) are found as well.
+#[derive(Debug)]
+pub struct StateReferenceVisitor<'a> {
+    state_ident: &'a Ident,
+    module: Option<&'a Ident>,
+    cfg_attrs: Vec<Attribute>,
+    found: Vec<(Ident, Vec<Attribute>)>,
+}
+
+impl<'a> StateReferenceVisitor<'a> {
+    pub fn new(state_ident: &'a Ident, module: Option<&'a Ident>, cfg_attrs: &[Attribute]) -> Self {
+        Self {
+            state_ident,
+            module,
+            cfg_attrs: cfg_attrs.to_vec(),
+            found: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> Vec<(Ident, Vec<Attribute>)> {
+        self.found
+    }
+
+    fn visit_with_cfg_attrs(&mut self, attrs: &[Attribute], visit: impl FnOnce(&mut Self)) {
+        let len = self.cfg_attrs.len();
+        self.cfg_attrs.extend(get_cfg_attrs(attrs));
+        visit(self);
+        self.cfg_attrs.truncate(len);
+    }
+}
+
+impl<'ast> Visit<'ast> for StateReferenceVisitor<'_> {
+    fn visit_arm(&mut self, arm: &'ast Arm) {
+        self.visit_with_cfg_attrs(&arm.attrs, |visitor| visit::visit_arm(visitor, arm));
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        let attrs = match stmt {
+            Stmt::Local(local) => &local.attrs,
+            Stmt::Expr(expr) | Stmt::Semi(expr, _) => expr_attrs(expr),
+            Stmt::Item(_) => return,
+        };
+        self.visit_with_cfg_attrs(attrs, |visitor| visit::visit_stmt(visitor, stmt));
+    }
+
+    fn visit_expr_path(&mut self, expr_path: &'ast syn::ExprPath) {
+        let segments: Vec<&Ident> = expr_path
+            .path
+            .segments
+            .iter()
+            .map(|segment| &segment.ident)
+            .collect();
+        let state = match segments.as_slice() {
+            [state_ident, state] if *state_ident == self.state_ident => Some(state),
+            [module, state_ident, state]
+                if Some(*module) == self.module && *state_ident == self.state_ident =>
+            {
+                Some(state)
+            }
+            _ => None,
+        };
+        if let Some(state) = state {
+            self.found.push(((*state).clone(), self.cfg_attrs.clone()));
+        }
+        visit::visit_expr_path(self, expr_path);
+    }
+}
+
+/// Get the attributes of the expressions that can be conditionally compiled
+/// as a statement.
+fn expr_attrs(expr: &Expr) -> &[Attribute] {
+    match expr {
+        Expr::Assign(expr) => &expr.attrs,
+        Expr::Block(expr) => &expr.attrs,
+        Expr::Call(expr) => &expr.attrs,
+        Expr::ForLoop(expr) => &expr.attrs,
+        Expr::If(expr) => &expr.attrs,
+        Expr::Loop(expr) => &expr.attrs,
+        Expr::Macro(expr) => &expr.attrs,
+        Expr::Match(expr) => &expr.attrs,
+        Expr::MethodCall(expr) => &expr.attrs,
+        Expr::Return(expr) => &expr.attrs,
+        Expr::Unsafe(expr) => &expr.attrs,
+        Expr::While(expr) => &expr.attrs,
+        _ => &[],
+    }
+}
+
+#[test]
+fn visit_block() {
+    use syn::{parse_quote, Block};
+
+    let block: Block = parse_quote!({
+        match event {
+            #[cfg(feature = "factory")]
+            Event::Calibrate => Transition(State::calibration()),
+            Event::Stop => Transition(State::idle()),
+        }
+    });
+    let state_ident = parse_quote!(State);
+    let cfg_attrs: Vec<Attribute> = vec![parse_quote!(#[cfg(test)])];
+    let mut visitor = StateReferenceVisitor::new(&state_ident, None, &cfg_attrs);
+    visitor.visit_block(&block);
+
+    let expected: Vec<(Ident, Vec<Attribute>)> = vec![
+        (
+            parse_quote!(calibration),
+            vec![
+                parse_quote!(#[cfg(test)]),
+                parse_quote!(#[cfg(feature = "factory")]),
+            ],
+        ),
+        (parse_quote!(idle), vec![parse_quote!(#[cfg(test)])]),
+    ];
+
+    assert_eq!(visitor.finish(), expected);
+}
+
+#[test]
+fn visit_block_with_module() {
+    use syn::{parse_quote, Block};
+
+    let block: Block = parse_quote!({
+        match event {
+            Event::Start => Transition(fsm::State::running()),
+            Event::Stop => Transition(other::State::idle()),
+        }
+    });
+    let state_ident = parse_quote!(State);
+    let module = parse_quote!(fsm);
+    let mut visitor = StateReferenceVisitor::new(&state_ident, Some(&module), &[]);
+    visitor.visit_block(&block);
+
+    let expected: Vec<(Ident, Vec<Attribute>)> = vec![(parse_quote!(running), vec![])];
+
+    assert_eq!(visitor.finish(), expected);
+}
@@ -0,0 +1,104 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use statig::blocking::InitializedStateMachine;
+use statig::prelude::*;
+
+pub enum Event {
+    TimerElapsed,
+    ButtonPressed,
+}
+
+#[derive(Default)]
+pub struct Blinky {
+    toggles: u32,
+}
+
+#[state_machine(initial = "State::led_on()")]
+impl Blinky {
+    #[state(superstate = "blinking")]
+    fn led_on(&mut self, event: &Event) -> Response<State> {
+        match event {
+            Event::TimerElapsed => {
+                self.toggles += 1;
+                Transition(State::led_off())
+            }
+            _ => Super,
+        }
+    }
+
+    #[state(superstate = "blinking")]
+    fn led_off(&mut self, event: &Event) -> Response<State> {
+        match event {
+            Event::TimerElapsed => {
+                self.toggles += 1;
+                Transition(State::led_on())
+            }
+            _ => Super,
+        }
+    }
+
+    #[superstate]
+    fn blinking(event: &Event) -> Response<State> {
+        match event {
+            Event::ButtonPressed => Transition(State::not_blinking()),
+            _ => Super,
+        }
+    }
+
+    #[state]
+    fn not_blinking(event: &Event) -> Response<State> {
+        match event {
+            Event::ButtonPressed => Transition(State::led_on()),
+            _ => Super,
+        }
+    }
+}
+
+fn instances(count: usize) -> Vec<InitializedStateMachine<Blinky>> {
+    (0..count)
+        .map(|_| Blinky::default().uninitialized_state_machine().init())
+        .collect()
+}
+
+/// Dispatch one event kind across many instances, either in a batch over a
+/// contiguous slice or one instance at a time through boxed instances that are
+/// scattered over the heap.
+fn batch_dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_dispatch");
+
+    for count in [1_000, 10_000] {
+        group.bench_with_input(BenchmarkId::new("slice", count), &count, |b, &count| {
+            b.iter_batched_ref(
+                || instances(count),
+                |state_machines| {
+                    InitializedStateMachine::handle_all(
+                        state_machines,
+                        black_box(&Event::TimerElapsed),
+                    )
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_with_input(BenchmarkId::new("boxed", count), &count, |b, &count| {
+            b.iter_batched_ref(
+                || {
+                    instances(count)
+                        .into_iter()
+                        .map(Box::new)
+                        .collect::<Vec<_>>()
+                },
+                |state_machines| {
+                    for state_machine in state_machines.iter_mut() {
+                        state_machine.handle(black_box(&Event::TimerElapsed));
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, batch_dispatch);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use statig::blocking::InitializedStateMachine;
+use statig::prelude::*;
+
+pub enum Event {
+    Ping,
+}
+
+#[derive(Default)]
+pub struct Nested;
+
+/// A state at the top of the hierarchy and a state at every depth from one to
+/// four, so transitioning between `top` and `depth_n` exits one level and
+/// enters `n + 1` levels.
+#[state_machine(initial = "State::top()", state(derive(Clone)))]
+impl Nested {
+    #[state]
+    fn top(event: &Event) -> Response<State> {
+        match event {
+            Event::Ping => Super,
+        }
+    }
+
+    #[state(superstate = "level_1")]
+    fn depth_1() -> Response<State> {
+        Super
+    }
+
+    #[state(superstate = "level_2")]
+    fn depth_2() -> Response<State> {
+        Super
+    }
+
+    #[state(superstate = "level_3")]
+    fn depth_3() -> Response<State> {
+        Super
+    }
+
+    #[state(superstate = "level_4")]
+    fn depth_4() -> Response<State> {
+        Super
+    }
+
+    #[superstate]
+    fn level_1() -> Response<State> {
+        Super
+    }
+
+    #[superstate(superstate = "level_1")]
+    fn level_2() -> Response<State> {
+        Super
+    }
+
+    #[superstate(superstate = "level_2")]
+    fn level_3() -> Response<State> {
+        Super
+    }
+
+    #[superstate(superstate = "level_3")]
+    fn level_4() -> Response<State> {
+        Super
+    }
+}
+
+fn state_at_depth(depth: usize) -> State {
+    match depth {
+        0 => State::top(),
+        1 => State::depth_1(),
+        2 => State::depth_2(),
+        3 => State::depth_3(),
+        _ => State::depth_4(),
+    }
+}
+
+fn state_machine() -> InitializedStateMachine<Nested> {
+    Nested.uninitialized_state_machine().init()
+}
+
+/// Handle an event in a state at increasing depths, where the event bubbles up
+/// the entire hierarchy before it is dropped.
+fn dispatch_latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dispatch_latency");
+
+    for depth in 0..=4 {
+        let mut state_machine = state_machine();
+        statig::bench::transition_cost(&mut state_machine, state_at_depth(depth), State::top(), 0);
+
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, _| {
+            b.iter(|| state_machine.handle(black_box(&Event::Ping)))
+        });
+    }
+
+    group.finish();
+}
+
+/// Transition back and forth between the top of the hierarchy and a state at
+/// increasing depths.
+fn transition_cost(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transition_cost");
+
+    for depth in 1..=4 {
+        let mut state_machine = state_machine();
+
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &depth, |b, &depth| {
+            b.iter_custom(|iterations| {
+                statig::bench::transition_cost(
+                    &mut state_machine,
+                    State::top(),
+                    state_at_depth(depth),
+                    iterations as u32,
+                )
+                .elapsed
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, dispatch_latency, transition_cost);
+criterion_main!(benches);
@@ -1,12 +1,16 @@
 //! Module for awaitable (async) mode.
 
+mod notified_dispatcher;
 mod state;
 mod state_machine;
 mod superstate;
+mod timed_state_machine;
 
 pub use crate::Response::{self, *};
 pub use crate::*;
 
+pub use notified_dispatcher::*;
 pub use state::*;
 pub use state_machine::*;
 pub use superstate::*;
+pub use timed_state_machine::*;
@@ -0,0 +1,117 @@
+use crate::awaitable::{self, InitializedStateMachine};
+use crate::blocking::EventConsumer;
+use crate::{IntoStateMachine, Notifier};
+
+/// Owns an async state machine and handles the events that arrive over a
+/// [`SplitChannel`](crate::blocking::SplitChannel), waiting on a [`Notifier`]
+/// whenever the channel is empty. Producers push an event and then notify the
+/// notifier, so the same dispatcher runs on tokio as well as on bare metal.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::awaitable::NotifiedDispatcher;
+/// # use statig::blocking::SplitChannel;
+/// # use statig::{Notifier, SpinNotifier};
+/// # #[derive(Default)]
+/// # pub struct Counter {
+/// #     count: usize,
+/// # }
+/// #
+/// # pub struct Increment;
+/// #
+/// # #[state_machine(initial = "State::counting()")]
+/// # impl Counter {
+/// #     #[state]
+/// #     async fn counting(&mut self, event: &Increment) -> Response<State> {
+/// #         self.count += 1;
+/// #         Handled
+/// #     }
+/// # }
+/// #
+/// # futures::executor::block_on(async {
+/// let mut channel: SplitChannel<Increment, 8> = SplitChannel::new();
+/// let (mut producer, consumer) = channel.split();
+/// let notifier = SpinNotifier::new();
+///
+/// let state_machine = Counter::default().uninitialized_state_machine().init().await;
+/// let mut dispatcher = NotifiedDispatcher::new(state_machine, consumer, &notifier);
+///
+/// // In the producer, e.g. an interrupt service routine.
+/// let _ = producer.push(Increment);
+/// notifier.notify();
+///
+/// dispatcher.run_until(|state_machine| state_machine.count == 1).await;
+/// # });
+/// ```
+pub struct NotifiedDispatcher<'a, M, W, const N: usize>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    consumer: EventConsumer<'a, M::Event<'static>, N>,
+    notifier: &'a W,
+}
+
+impl<'a, M, W, const N: usize> NotifiedDispatcher<'a, M, W, N>
+where
+    M: IntoStateMachine + Send,
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    W: Notifier,
+{
+    /// Create a dispatcher that handles the events of the given consumer.
+    pub fn new(
+        state_machine: InitializedStateMachine<M>,
+        consumer: EventConsumer<'a, M::Event<'static>, N>,
+        notifier: &'a W,
+    ) -> Self {
+        Self {
+            state_machine,
+            consumer,
+            notifier,
+        }
+    }
+
+    /// Handle all the events that are waiting in the channel. Returns the
+    /// number of events that were handled.
+    pub async fn dispatch_pending(&mut self) -> usize {
+        let mut handled = 0;
+        while let Some(event) = self.consumer.pop() {
+            self.state_machine.handle(&event).await;
+            handled += 1;
+        }
+        handled
+    }
+
+    /// Handle events forever, waiting on the notifier whenever the channel is
+    /// empty.
+    pub async fn run(mut self) -> ! {
+        loop {
+            if self.dispatch_pending().await == 0 {
+                self.notifier.notified().await;
+            }
+        }
+    }
+
+    /// Handle events, waiting on the notifier whenever the channel is empty,
+    /// until `done` returns `true` for the state machine.
+    pub async fn run_until(&mut self, mut done: impl FnMut(&InitializedStateMachine<M>) -> bool) {
+        while !done(&self.state_machine) {
+            if self.dispatch_pending().await == 0 {
+                self.notifier.notified().await;
+            }
+        }
+    }
+
+    /// Get a reference to the state machine.
+    pub fn state_machine(&self) -> &InitializedStateMachine<M> {
+        &self.state_machine
+    }
+
+    /// Take the state machine out of the dispatcher.
+    pub fn into_state_machine(self) -> InitializedStateMachine<M> {
+        self.state_machine
+    }
+}
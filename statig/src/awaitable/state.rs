@@ -1,6 +1,8 @@
 use core::future::Future;
 use core::pin::Pin;
 
+use alloc::boxed::Box;
+
 use crate::awaitable::{Superstate, SuperstateExt};
 use crate::IntoStateMachine;
 use crate::Response;
@@ -21,6 +23,23 @@ where
         context: &'fut mut M::Context<'_>,
     ) -> Pin<Box<dyn Future<Output = Response<Self>> + 'fut + Send>>;
 
+    /// Call the handler for the current state and let it handle the given
+    /// event. Also returns how many levels above the current state the
+    /// transition that was returned is declared, which is the source of the
+    /// transition with UML [`Semantics`](crate::Semantics).
+    ///
+    /// The default implementation returns `0`. The `state_machine` macro
+    /// overrides it when the state machine has UML semantics.
+    fn call_handler_with_source<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        event: &'fut M::Event<'_>,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = (Response<Self>, usize)> + 'fut + Send>> {
+        let future = self.call_handler(shared_storage, event, context);
+        Box::pin(async move { (future.await, 0) })
+    }
+
     #[allow(unused)]
     /// Call the entry action for the current state.
     fn call_entry_action<'fut>(
@@ -41,10 +60,187 @@ where
         Box::pin(core::future::ready(()))
     }
 
+    #[allow(unused)]
+    /// Call the activity for the current state, which is called on every tick
+    /// while the state is active.
+    fn call_activity<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+        Box::pin(core::future::ready(()))
+    }
+
     /// Return the superstate of the current state, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>> {
         None
     }
+
+    /// Whether every state can be recreated from its id with
+    /// [`from_state_id`](Self::from_state_id), because none of them has
+    /// state-local storage.
+    ///
+    /// The `state_machine` macro sets it when no state has state-local storage.
+    const RECREATABLE: bool = false;
+
+    /// Return the id of the current state, if the states are numbered.
+    ///
+    /// The `state_machine` macro numbers the states in the order in which they
+    /// are declared, starting from zero.
+    fn state_id(&self) -> Option<usize> {
+        None
+    }
+
+    /// Create the state with the given id, if it has no state-local storage.
+    ///
+    /// The `state_machine` macro implements it for every state without
+    /// state-local storage.
+    fn from_state_id(_state_id: usize) -> Option<Self> {
+        None
+    }
+
+    /// Return the depth of the current state if it is known up front, so it
+    /// doesn't have to be computed by walking up the hierarchy.
+    ///
+    /// The `state_machine` macro overrides it with a lookup in a const table.
+    fn lookup_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return the transition path from the current state to the target state
+    /// if it is known up front, so it doesn't have to be computed by walking up
+    /// the hierarchy.
+    ///
+    /// The `state_machine` macro overrides it with a lookup in a const table
+    /// of the transitions to the states that the handlers of the state and its
+    /// superstates reference.
+    fn lookup_transition_path(&self, _target: &Self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Call the entry action of the superstate that is `height` levels above
+    /// the current state.
+    ///
+    /// The default implementation walks up the hierarchy recursively. The
+    /// `state_machine` macro overrides it with a flat lookup, so no recursion
+    /// happens when entering or exiting states.
+    fn call_ancestor_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>
+    where
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => (),
+                1 => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.call_entry_action(shared_storage, context).await;
+                    }
+                }
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_ancestor_entry_action(shared_storage, context, height - 1)
+                            .await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Call the exit action of the superstate that is `height` levels above
+    /// the current state.
+    ///
+    /// The default implementation walks up the hierarchy recursively. The
+    /// `state_machine` macro overrides it with a flat lookup, so no recursion
+    /// happens when entering or exiting states.
+    fn call_ancestor_exit_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>
+    where
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => (),
+                1 => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.call_exit_action(shared_storage, context).await;
+                    }
+                }
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_ancestor_exit_action(shared_storage, context, height - 1)
+                            .await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Call the entry action of the current state, or of the superstate that
+    /// is `height` levels above it, and return whether the transition can
+    /// proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>>
+    where
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => self.call_entry_action(shared_storage, context).await,
+                _ => {
+                    self.call_ancestor_entry_action(shared_storage, context, height)
+                        .await
+                }
+            }
+            true
+        })
+    }
+
+    /// Call the exit action of the current state, or of the superstate that
+    /// is `height` levels above it, and return whether the transition can
+    /// proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_exit_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>>
+    where
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => self.call_exit_action(shared_storage, context).await,
+                _ => {
+                    self.call_ancestor_exit_action(shared_storage, context, height)
+                        .await
+                }
+            }
+            true
+        })
+    }
 }
 
 /// Extensions for `State` trait.
@@ -63,6 +259,10 @@ where
 
     /// Get the depth of the current state.
     fn depth(&mut self) -> usize {
+        if let Some(depth) = self.lookup_depth() {
+            return depth;
+        }
+
         match self.superstate() {
             Some(mut superstate) => superstate.depth() + 1,
             None => 1,
@@ -85,6 +285,10 @@ where
     /// the target state. The returned tuple respectively represents the levels that need
     /// to be exited, and the levels that need to be entered.
     fn transition_path(&mut self, target: &mut Self) -> (usize, usize) {
+        if let Some(path) = self.lookup_transition_path(target) {
+            return path;
+        }
+
         if Self::same_state(self, target) {
             return (1, 1);
         }
@@ -110,15 +314,30 @@ where
         event: &'fut M::Event<'_>,
         context: &'fut mut M::Context<'_>,
     ) -> Pin<Box<dyn Future<Output = Response<Self>> + 'fut + Send>> {
+        let future = self.handle_with_source(shared_storage, event, context);
+        Box::pin(async move { future.await.0 })
+    }
+
+    /// Handle the given event in the current state. Also returns how many
+    /// levels above the current state the state or superstate is that took
+    /// the transition, if one was taken.
+    fn handle_with_source<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        event: &'fut M::Event<'_>,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = (Response<Self>, usize)> + 'fut + Send>> {
         let future = async move {
             M::BEFORE_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
-            let response = self.call_handler(shared_storage, event, context).await;
+            let (response, source) = self
+                .call_handler_with_source(shared_storage, event, context)
+                .await;
 
             M::AFTER_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
             match response {
-                Response::Handled => Response::Handled,
+                Response::Handled => (Response::Handled, 0),
                 Response::Super => match self.superstate() {
                     Some(mut superstate) => {
                         M::BEFORE_DISPATCH(
@@ -127,7 +346,9 @@ where
                             event,
                         );
 
-                        let response = superstate.handle(shared_storage, event, context).await;
+                        let (response, source) = superstate
+                            .handle_with_source(shared_storage, event, context)
+                            .await;
 
                         M::AFTER_DISPATCH(
                             shared_storage,
@@ -135,58 +356,65 @@ where
                             event,
                         );
 
-                        response
+                        (response, source + 1)
                     }
-                    None => Response::Super,
+                    None => (Response::Super, 0),
+                },
+                Response::Transition(state) => (Response::Transition(state), source),
+                Response::Completed => match self.superstate() {
+                    Some(mut superstate) => {
+                        let (response, source) = superstate.complete_with_source();
+                        (response, source + 1)
+                    }
+                    None => (Response::Completed, 0),
                 },
-                Response::Transition(state) => Response::Transition(state),
             }
         };
         Box::pin(future)
     }
 
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current state.
+    /// entry actions while going back down to the current state. Resolves to
+    /// `false` when an action failed and the remaining actions were skipped.
     fn enter<'fut>(
         &'fut mut self,
         shared_storage: &'fut mut M,
         context: &'fut mut M::Context<'_>,
         levels: usize,
-    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
         let future = async move {
-            match levels {
-                0 => (),
-                1 => self.call_entry_action(shared_storage, context).await,
-                _ => {
-                    if let Some(mut superstate) = self.superstate() {
-                        superstate.enter(shared_storage, context, levels - 1).await;
-                    }
-                    self.call_entry_action(shared_storage, context).await;
+            for height in (0..levels).rev() {
+                if !self
+                    .try_call_entry_action(shared_storage, context, height)
+                    .await
+                {
+                    return false;
                 }
             }
+            true
         };
         Box::pin(future)
     }
 
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// the exit actions while going up to a certain superstate.
+    /// the exit actions while going up to a certain superstate. Resolves to
+    /// `false` when an action failed and the remaining actions were skipped.
     fn exit<'fut>(
         &'fut mut self,
         shared_storage: &'fut mut M,
         context: &'fut mut M::Context<'_>,
         levels: usize,
-    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>> {
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>> {
         let future = async move {
-            match levels {
-                0 => (),
-                1 => self.call_exit_action(shared_storage, context).await,
-                _ => {
-                    self.call_exit_action(shared_storage, context).await;
-                    if let Some(mut superstate) = self.superstate() {
-                        superstate.exit(shared_storage, context, levels - 1).await;
-                    }
+            for height in 0..levels {
+                if !self
+                    .try_call_exit_action(shared_storage, context, height)
+                    .await
+                {
+                    return false;
                 }
             }
+            true
         };
         Box::pin(future)
     }
@@ -15,24 +15,13 @@ where
     where
         Self: Sized,
     {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
-        StateMachine {
-            inner,
-            initialized: false,
-        }
+        StateMachine::new(self)
     }
 
     /// Create an uninitialized state machine that must be explicitly initialized with
     /// [`init`](UninitializedStateMachine::init).
     fn uninitialized_state_machine(self) -> UninitializedStateMachine<Self> {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
-        UninitializedStateMachine { inner }
+        UninitializedStateMachine::new(self)
     }
 }
 
@@ -53,6 +42,23 @@ where
     initialized: bool,
 }
 
+impl<M> StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create a state machine that will be lazily initialized.
+    ///
+    /// Unlike [`state_machine`](IntoStateMachineExt::state_machine) this is a
+    /// `const fn`, so the state machine can be stored in a `static`.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self {
+            inner,
+            initialized: false,
+        }
+    }
+}
+
 impl<M> StateMachine<M>
 where
     M: IntoStateMachine + Send,
@@ -85,6 +91,12 @@ where
 
     /// Handle an event. If the state machine is still uninitialized, it will be initialized
     /// before handling the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
     pub async fn handle(&mut self, event: &M::Event<'_>)
     where
         for<'evt> M::Event<'evt>: Send + Sync,
@@ -95,6 +107,12 @@ where
 
     /// Handle an event. If the state machine is still uninitialized, it will be initialized
     /// before handling the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
     pub async fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>)
     where
         for<'ctx> M::Context<'ctx>: Send + Sync,
@@ -107,13 +125,36 @@ where
         self.inner.async_handle_with_context(event, context).await;
     }
 
+    /// Convert a value into an event and handle it. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub async fn handle_from<'evt>(&mut self, event: impl Into<M::Event<'evt>>)
+    where
+        for<'e> M::Event<'e>: Send + Sync,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(&event.into(), &mut ()).await;
+    }
+
+    /// Convert a value into an event and handle it. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub async fn handle_from_with_context<'evt>(
+        &mut self,
+        event: impl Into<M::Event<'evt>>,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+        for<'e> M::Event<'e>: Send + Sync,
+    {
+        self.handle_with_context(&event.into(), context).await;
+    }
+
     pub async fn step(&mut self)
     where
         for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
         for<'evt> M::Event<'evt>: Send + Sync,
         for<'ctx> M::Context<'ctx>: Send + Sync,
     {
-        self.handle_with_context(&(), &mut ()).await;
+        self.step_with_context(&mut ()).await;
     }
 
     pub async fn step_with_context(&mut self, context: &mut M::Context<'_>)
@@ -123,6 +164,44 @@ where
         for<'ctx> M::Context<'ctx>: Send + Sync,
     {
         self.handle_with_context(&(), context).await;
+        self.inner.async_tick_with_context(context).await;
+    }
+
+    /// Call the activity of the current state. If the state machine is still
+    /// uninitialized, it will be initialized first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn tick(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.tick_with_context(&mut ()).await;
+    }
+
+    /// Call the activity of the current state. If the state machine is still
+    /// uninitialized, it will be initialized first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn tick_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        if !self.initialized {
+            self.inner.async_init_with_context(context).await;
+            self.initialized = true;
+        }
+        self.inner.async_tick_with_context(context).await;
     }
 
     /// Get the current state.
@@ -130,6 +209,85 @@ where
         &self.inner.state
     }
 
+    /// Check if the state machine is poisoned. This happens when a transition
+    /// was interrupted by a panic, or by an action that failed with
+    /// `action_failure = "abort"`, leaving the state machine in between the
+    /// source and the target state. A poisoned state machine panics when it
+    /// handles an event, ticks or shuts down, until it is brought back into a
+    /// well-defined state with [`recover`](Self::recover).
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.poisoned
+    }
+
+    /// Check if the state machine reached a terminate pseudo-state. A
+    /// terminated state machine ignores the events it handles, and shutting
+    /// it down executes no exit actions.
+    pub fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub async fn recover(&mut self, state: M::State)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.recover_with_context(state, &mut ()).await;
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub async fn recover_with_context(&mut self, state: M::State, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_recover_with_context(state, context).await;
+        self.initialized = true;
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage. If the
+    /// state machine was never initialized, no exit actions are executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn shutdown(self) -> M
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.shutdown_with_context(&mut ()).await
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage. If the
+    /// state machine was never initialized, no exit actions are executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn shutdown_with_context(mut self, context: &mut M::Context<'_>) -> M
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        if self.initialized {
+            self.inner.async_shutdown_with_context(context).await;
+        }
+        self.inner.shared_storage
+    }
+
     /// Get a reference to the [StateMachine]'s underlying type.
     ///
     /// ```
@@ -256,10 +414,7 @@ where
     M: IntoStateMachine + Default,
 {
     fn default() -> Self {
-        let inner = Inner {
-            shared_storage: M::default(),
-            state: M::INITIAL,
-        };
+        let inner = Inner::new(M::default(), M::INITIAL);
         Self {
             inner,
             initialized: false,
@@ -326,56 +481,30 @@ pub struct InitializedStateMachine<M>
 where
     M: IntoStateMachine,
 {
-    inner: Inner<M>,
+    pub(crate) inner: Inner<M>,
 }
 
 impl<M> InitializedStateMachine<M>
 where
-    M: IntoStateMachine + Send,
-    M::State: awaitable::State<M> + 'static + Send,
-    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    M: IntoStateMachine,
 {
-    /// Handle the given event.
-    pub async fn handle(&mut self, event: &M::Event<'_>)
-    where
-        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
-        for<'evt> M::Event<'evt>: Send + Sync,
-        for<'ctx> M::Context<'ctx>: Send + Sync,
-    {
-        self.handle_with_context(event, &mut ()).await;
+    /// Get a reference to the shared storage of the state machine, the same
+    /// as [`inner`](Self::inner). Unlike a method call through `Deref`, it
+    /// can't be shadowed by a method of the state machine, which keeps generic
+    /// code unambiguous.
+    pub fn shared(&self) -> &M {
+        self.inner()
     }
 
-    /// Handle the given event.
-    pub async fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>)
-    where
-        M: IntoStateMachine,
-        for<'evt> M::Event<'evt>: Send + Sync,
-        for<'ctx> M::Context<'ctx>: Send + Sync,
-    {
-        self.inner.async_handle_with_context(event, context).await;
-    }
-
-    /// This is the same as `handle(())` in the case `Event` is of type `()`.
-    pub async fn step(&mut self)
-    where
-        for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
-    {
-        self.handle(&()).await;
-    }
-
-    /// This is the same as `handle(())` in the case `Event` is of type `()`.
-    pub async fn step_with_context(&mut self, context: &mut M::Context<'_>)
-    where
-        for<'evt> M: IntoStateMachine<Event<'evt> = ()>,
-        for<'evt> M::Event<'evt>: Send + Sync,
-        for<'ctx> M::Context<'ctx>: Send + Sync,
-    {
-        self.handle_with_context(&(), context).await;
-    }
-
-    /// Get an immutable reference to the current state of the state machine.
-    pub fn state(&self) -> &M::State {
-        &self.inner.state
+    /// Get a mutable reference to the shared storage of the state machine, the
+    /// same as [`inner_mut`](Self::inner_mut).
+    ///
+    /// # Safety
+    ///
+    /// - The user is responsible for validating that mutating a
+    ///   [InitializedStateMachine] does not break any invariants.
+    pub unsafe fn shared_mut(&mut self) -> &mut M {
+        self.inner_mut()
     }
 
     /// Get a reference to the [InitializedStateMachine]'s underlying type.
@@ -437,6 +566,200 @@ where
     pub unsafe fn inner_mut(&mut self) -> &mut M {
         &mut self.inner.shared_storage
     }
+}
+
+impl<M> InitializedStateMachine<M>
+where
+    M: IntoStateMachine + Send,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+{
+    /// Handle the given event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_context(event, &mut ()).await;
+    }
+
+    /// Handle the given event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>)
+    where
+        M: IntoStateMachine,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_handle_with_context(event, context).await;
+    }
+
+    /// Convert a value into an event and handle it.
+    pub async fn handle_from<'evt>(&mut self, event: impl Into<M::Event<'evt>>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'e> M::Event<'e>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_context(&event.into(), &mut ()).await;
+    }
+
+    /// Convert a value into an event and handle it.
+    pub async fn handle_from_with_context<'evt>(
+        &mut self,
+        event: impl Into<M::Event<'evt>>,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'e> M::Event<'e>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_context(&event.into(), context).await;
+    }
+
+    /// This is the same as `handle(())` followed by `tick()` in the case
+    /// `Event` is of type `()`.
+    pub async fn step(&mut self)
+    where
+        for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
+    {
+        self.step_with_context(&mut ()).await;
+    }
+
+    /// This is the same as `handle(())` followed by `tick()` in the case
+    /// `Event` is of type `()`.
+    pub async fn step_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M: IntoStateMachine<Event<'evt> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.handle_with_context(&(), context).await;
+        self.tick_with_context(context).await;
+    }
+
+    /// Call the activity of the current state, which is called on every tick
+    /// while the state is active (between its entry and exit actions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn tick(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.tick_with_context(&mut ()).await;
+    }
+
+    /// Call the activity of the current state, which is called on every tick
+    /// while the state is active (between its entry and exit actions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn tick_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_tick_with_context(context).await;
+    }
+
+    /// Get an immutable reference to the current state of the state machine.
+    pub fn state(&self) -> &M::State {
+        &self.inner.state
+    }
+
+    /// Check if the state machine is poisoned. This happens when a transition
+    /// was interrupted by a panic, or by an action that failed with
+    /// `action_failure = "abort"`, leaving the state machine in between the
+    /// source and the target state. A poisoned state machine panics when it
+    /// handles an event, ticks or shuts down, until it is brought back into a
+    /// well-defined state with [`recover`](Self::recover).
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.poisoned
+    }
+
+    /// Check if the state machine reached a terminate pseudo-state. A
+    /// terminated state machine ignores the events it handles, and shutting
+    /// it down executes no exit actions.
+    pub fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub async fn recover(&mut self, state: M::State)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.recover_with_context(state, &mut ()).await;
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub async fn recover_with_context(&mut self, state: M::State, context: &mut M::Context<'_>)
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_recover_with_context(state, context).await;
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn shutdown(self) -> M
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.shutdown_with_context(&mut ()).await
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub async fn shutdown_with_context(mut self, context: &mut M::Context<'_>) -> M
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        self.inner.async_shutdown_with_context(context).await;
+        self.inner.shared_storage
+    }
 
     /// Get a mutable reference to the [InitializedStateMachine]'s current state.
     ///
@@ -570,12 +893,34 @@ where
 
 impl<M> UninitializedStateMachine<M>
 where
-    M: IntoStateMachine + Send,
-    M::State: awaitable::State<M> + 'static + Send,
-    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    M: IntoStateMachine,
 {
-    /// Initialize the state machine by executing all entry actions towards
-    /// the initial state.
+    /// Create an uninitialized state machine that must be explicitly initialized
+    /// with [`init`](Self::init).
+    ///
+    /// Unlike [`uninitialized_state_machine`](IntoStateMachineExt::uninitialized_state_machine)
+    /// this is a `const fn`, so the state machine can be stored in a `static`.
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self { inner }
+    }
+
+    /// Get a reference to the shared storage of the state machine, the same
+    /// as [`inner`](Self::inner). Unlike a method call through `Deref`, it
+    /// can't be shadowed by a method of the state machine, which keeps generic
+    /// code unambiguous.
+    pub fn shared(&self) -> &M {
+        self.inner()
+    }
+
+    /// Get a mutable reference to the shared storage of the state machine, the
+    /// same as [`inner_mut`](Self::inner_mut), e.g. to configure it before the
+    /// state machine is initialized.
+    pub fn shared_mut(&mut self) -> &mut M {
+        self.inner_mut()
+    }
+
+    /// Get a reference to the [UninitializedStateMachine]'s underlying type.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -586,7 +931,10 @@ where
     /// #
     /// # pub struct Event;
     /// #
-    /// # #[state_machine(initial = "State::on()")]
+    /// # #[state_machine(
+    /// #     initial = "State::on()",
+    /// #     state(derive(Debug, PartialEq, Eq))
+    /// # )]
     /// # impl Blinky {
     /// #     #[state]
     /// #     fn on(event: &Event) -> Response<State> { Handled }
@@ -594,23 +942,13 @@ where
     /// #
     /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// // The uninitialized state machine is consumed to create the initialized
-    /// // state machine.
-    /// let initialized_state_machine = uninitialized_state_machine.init();
+    /// assert_eq!(uninitialized_state_machine.inner().led, false);
     /// ```
-    pub async fn init(self) -> InitializedStateMachine<M>
-    where
-        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
-        for<'evt> M::Event<'evt>: Send + Sync,
-        for<'ctx> M::Context<'ctx>: Send + Sync,
-    {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
-        state_machine.inner.async_init_with_context(&mut ()).await;
-        state_machine
+    pub fn inner(&self) -> &M {
+        &self.inner.shared_storage
     }
 
-    /// Initialize the state machine by executing all entry actions towards
-    /// the initial state.
+    /// Get a mutable reference to the [UninitializedStateMachine]'s underlying type.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -627,23 +965,23 @@ where
     /// #     fn on(event: &Event) -> Response<State> { Handled }
     /// # }
     /// #
-    /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
+    /// let mut uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// // The uninitialized state machine is consumed to create the initialized
-    /// // state machine.
-    /// let initialized_state_machine = uninitialized_state_machine.init();
+    /// uninitialized_state_machine.inner_mut().led = true;
     /// ```
-    pub async fn init_with_context(self, context: &mut M::Context<'_>) -> InitializedStateMachine<M>
-    where
-        for<'evt> M::Event<'evt>: Send + Sync,
-        for<'ctx> M::Context<'ctx>: Send + Sync,
-    {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
-        state_machine.inner.async_init_with_context(context).await;
-        state_machine
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner.shared_storage
     }
+}
 
-    /// Get a reference to the [UninitializedStateMachine]'s underlying type.
+impl<M> UninitializedStateMachine<M>
+where
+    M: IntoStateMachine + Send,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+{
+    /// Initialize the state machine by executing all entry actions towards
+    /// the initial state.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -654,10 +992,7 @@ where
     /// #
     /// # pub struct Event;
     /// #
-    /// # #[state_machine(
-    /// #     initial = "State::on()",
-    /// #     state(derive(Debug, PartialEq, Eq))
-    /// # )]
+    /// # #[state_machine(initial = "State::on()")]
     /// # impl Blinky {
     /// #     #[state]
     /// #     fn on(event: &Event) -> Response<State> { Handled }
@@ -665,13 +1000,23 @@ where
     /// #
     /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// assert_eq!(uninitialized_state_machine.inner().led, false);
+    /// // The uninitialized state machine is consumed to create the initialized
+    /// // state machine.
+    /// let initialized_state_machine = uninitialized_state_machine.init();
     /// ```
-    pub fn inner(&self) -> &M {
-        &self.inner.shared_storage
+    pub async fn init(self) -> InitializedStateMachine<M>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        state_machine.inner.async_init_with_context(&mut ()).await;
+        state_machine
     }
 
-    /// Get a mutable reference to the [UninitializedStateMachine]'s underlying type.
+    /// Initialize the state machine by executing all entry actions towards
+    /// the initial state.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -688,12 +1033,20 @@ where
     /// #     fn on(event: &Event) -> Response<State> { Handled }
     /// # }
     /// #
-    /// let mut uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
+    /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// uninitialized_state_machine.inner_mut().led = true;
+    /// // The uninitialized state machine is consumed to create the initialized
+    /// // state machine.
+    /// let initialized_state_machine = uninitialized_state_machine.init();
     /// ```
-    pub fn inner_mut(&mut self) -> &mut M {
-        &mut self.inner.shared_storage
+    pub async fn init_with_context(self, context: &mut M::Context<'_>) -> InitializedStateMachine<M>
+    where
+        for<'evt> M::Event<'evt>: Send + Sync,
+        for<'ctx> M::Context<'ctx>: Send + Sync,
+    {
+        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        state_machine.inner.async_init_with_context(context).await;
+        state_machine
     }
 
     /// Get a mutable reference to the [StateMachine]'s current state.
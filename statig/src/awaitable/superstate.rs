@@ -2,6 +2,8 @@ use core::cmp::Ordering;
 use core::future::Future;
 use core::pin::Pin;
 
+use alloc::boxed::Box;
+
 use crate::IntoStateMachine;
 use crate::Response;
 use crate::StateOrSuperstate;
@@ -39,6 +41,50 @@ where
         Box::pin(core::future::ready(()))
     }
 
+    /// Call the entry action for the current superstate and return whether the
+    /// transition can proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>>
+    where
+        Self: Send,
+        M: Send,
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            self.call_entry_action(shared_storage, context).await;
+            true
+        })
+    }
+
+    /// Call the exit action for the current superstate and return whether the
+    /// transition can proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_exit_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'fut + Send>>
+    where
+        Self: Send,
+        M: Send,
+        for<'ctx> M::Context<'ctx>: Send,
+    {
+        Box::pin(async move {
+            self.call_exit_action(shared_storage, context).await;
+            true
+        })
+    }
+
     /// Return the superstate of the current superstate, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>>
     where
@@ -46,6 +92,78 @@ where
     {
         None
     }
+
+    /// Return the target of the completion transition of the current
+    /// superstate, if it declares one.
+    fn completion(&mut self) -> Option<M::State> {
+        None
+    }
+
+    /// Call the entry action of the superstate that is `height` levels above
+    /// the current superstate.
+    fn call_ancestor_entry_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>
+    where
+        Self: Sized + Send,
+        M: Send,
+        for<'ctx> M::Context<'ctx>: Send,
+        for<'sub> M::Superstate<'sub>: Superstate<M> + Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => (),
+                1 => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.call_entry_action(shared_storage, context).await;
+                    }
+                }
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_ancestor_entry_action(shared_storage, context, height - 1)
+                            .await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Call the exit action of the superstate that is `height` levels above
+    /// the current superstate.
+    fn call_ancestor_exit_action<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        context: &'fut mut M::Context<'_>,
+        height: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + 'fut + Send>>
+    where
+        Self: Sized + Send,
+        M: Send,
+        for<'ctx> M::Context<'ctx>: Send,
+        for<'sub> M::Superstate<'sub>: Superstate<M> + Send,
+    {
+        Box::pin(async move {
+            match height {
+                0 => (),
+                1 => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate.call_exit_action(shared_storage, context).await;
+                    }
+                }
+                _ => {
+                    if let Some(mut superstate) = self.superstate() {
+                        superstate
+                            .call_ancestor_exit_action(shared_storage, context, height - 1)
+                            .await;
+                    }
+                }
+            }
+        })
+    }
 }
 
 /// Extensions for `Superstate` trait.
@@ -103,6 +221,28 @@ where
         }
     }
 
+    /// Take the completion transition of the nearest superstate that declares
+    /// one, starting from the current superstate.
+    fn complete(&mut self) -> Response<M::State> {
+        self.complete_with_source().0
+    }
+
+    /// Take the completion transition of the nearest superstate that declares
+    /// one, starting from the current superstate. Also returns how many levels
+    /// above the current superstate that superstate is.
+    fn complete_with_source(&mut self) -> (Response<M::State>, usize) {
+        match self.completion() {
+            Some(state) => (Response::Transition(state), 0),
+            None => match self.superstate() {
+                Some(mut superstate) => {
+                    let (response, source) = superstate.complete_with_source();
+                    (response, source + 1)
+                }
+                None => (Response::Completed, 0),
+            },
+        }
+    }
+
     /// Handle the given event in the current superstate.
     fn handle<'fut>(
         &'fut mut self,
@@ -110,11 +250,25 @@ where
         event: &'fut M::Event<'_>,
         context: &'fut mut M::Context<'_>,
     ) -> Pin<Box<dyn Future<Output = Response<M::State>> + 'fut + Send>> {
+        let future = self.handle_with_source(shared_storage, event, context);
+        Box::pin(async move { future.await.0 })
+    }
+
+    /// Handle the given event in the current superstate. Also returns how many
+    /// levels above the current superstate the superstate is that took the
+    /// transition, if one was taken.
+    #[allow(clippy::type_complexity)]
+    fn handle_with_source<'fut>(
+        &'fut mut self,
+        shared_storage: &'fut mut M,
+        event: &'fut M::Event<'_>,
+        context: &'fut mut M::Context<'_>,
+    ) -> Pin<Box<dyn Future<Output = (Response<M::State>, usize)> + 'fut + Send>> {
         Box::pin(async move {
             let response = self.call_handler(shared_storage, event, context).await;
 
             match response {
-                Response::Handled => Response::Handled,
+                Response::Handled => (Response::Handled, 0),
                 Response::Super => match self.superstate() {
                     Some(mut superstate) => {
                         M::BEFORE_DISPATCH(
@@ -123,7 +277,9 @@ where
                             event,
                         );
 
-                        let response = superstate.handle(shared_storage, event, context).await;
+                        let (response, source) = superstate
+                            .handle_with_source(shared_storage, event, context)
+                            .await;
 
                         M::AFTER_DISPATCH(
                             shared_storage,
@@ -131,11 +287,18 @@ where
                             event,
                         );
 
-                        response
+                        (response, source + 1)
+                    }
+                    None => (Response::Super, 0),
+                },
+                Response::Transition(state) => (Response::Transition(state), 0),
+                Response::Completed => match self.superstate() {
+                    Some(mut superstate) => {
+                        let (response, source) = superstate.complete_with_source();
+                        (response, source + 1)
                     }
-                    None => Response::Super,
+                    None => (Response::Completed, 0),
                 },
-                Response::Transition(state) => Response::Transition(state),
             }
         })
     }
@@ -0,0 +1,181 @@
+use core::future;
+use core::pin::Pin;
+
+use alloc::boxed::Box;
+
+use crate::awaitable::{self, InitializedStateMachine};
+use crate::{AsyncTimer, IntoStateMachine};
+
+/// An async state machine that takes the timed transitions returned by
+/// [`AFTER`](IntoStateMachine::AFTER), driven by an [`AsyncTimer`].
+///
+/// Whenever a state is entered, a sleep is started with the duration of the
+/// timed transition of that state. Await [`timeout`](Self::timeout) next to
+/// the events that are received in the actor loop, and the timed transition
+/// is taken once the sleep completes.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use statig::prelude::*;
+/// # use statig::awaitable::{InitializedStateMachine, TimedStateMachine};
+/// # use statig::TokioTimer;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub struct Event;
+/// #
+/// # #[state_machine(initial = "State::on()", after = "Self::after", state(derive(Debug, PartialEq)))]
+/// # impl Blinky {
+/// #     fn after(state: &State) -> Option<(Duration, State)> {
+/// #         match state {
+/// #             State::On {} => Some((Duration::from_secs(5), State::off())),
+/// #             State::Off {} => None,
+/// #         }
+/// #     }
+/// #
+/// #     #[state]
+/// #     async fn on(event: &Event) -> Response<State> { Handled }
+/// #
+/// #     #[state]
+/// #     async fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// # #[tokio::main(flavor = "current_thread", start_paused = true)]
+/// # async fn main() {
+/// let state_machine = Blinky::default().uninitialized_state_machine().init().await;
+/// let mut state_machine = TimedStateMachine::new(state_machine, TokioTimer);
+/// let (sender, mut receiver) = tokio::sync::mpsc::channel::<Event>(8);
+/// # drop(sender);
+///
+/// loop {
+///     tokio::select! {
+///         Some(event) = receiver.recv() => state_machine.handle(&event).await,
+///         _ = state_machine.timeout() => break,
+///     }
+/// }
+///
+/// assert_eq!(state_machine.state(), &State::off());
+/// # }
+/// ```
+pub struct TimedStateMachine<M, T>
+where
+    M: IntoStateMachine,
+    T: AsyncTimer,
+{
+    state_machine: InitializedStateMachine<M>,
+    timer: T,
+    sleep: Option<Pin<Box<T::Sleep>>>,
+}
+
+impl<M, T> TimedStateMachine<M, T>
+where
+    M: IntoStateMachine + Send,
+    M::State: awaitable::State<M> + 'static + Send,
+    for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
+    for<'evt> M::Event<'evt>: Send + Sync,
+    for<'ctx> M::Context<'ctx>: Send + Sync,
+    T: AsyncTimer,
+{
+    /// Create a timed state machine and start the sleep for the timed
+    /// transition of the current state.
+    pub fn new(state_machine: InitializedStateMachine<M>, timer: T) -> Self {
+        let mut timed = Self {
+            state_machine,
+            timer,
+            sleep: None,
+        };
+        timed.start_sleep();
+        timed
+    }
+
+    /// Handle the given event. If a transition is taken, the sleep is started
+    /// for the timed transition of the new state.
+    pub async fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ()).await;
+    }
+
+    /// Handle the given event. If a transition is taken, the sleep is started
+    /// for the timed transition of the new state.
+    pub async fn handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) {
+        if self
+            .state_machine
+            .inner
+            .async_handle_with_context(event, context)
+            .await
+        {
+            self.start_sleep();
+        }
+    }
+
+    /// Wait until the sleep of the current state completes and take its timed
+    /// transition. If the current state has no timed transition this never
+    /// completes.
+    ///
+    /// The sleep is kept when this future is dropped while it waits for the
+    /// sleep (e.g. by `select!`), so awaiting it again doesn't restart the
+    /// sleep. Once the sleep completed it is dropped and the timed transition
+    /// is taken, so dropping this future during the transition leaves the
+    /// state machine poisoned, as with any transition that doesn't complete.
+    pub async fn timeout(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.timeout_with_context(&mut ()).await;
+    }
+
+    /// Wait until the sleep of the current state completes and take its timed
+    /// transition. If the current state has no timed transition this never
+    /// completes.
+    ///
+    /// The sleep is kept when this future is dropped while it waits for the
+    /// sleep (e.g. by `select!`), so awaiting it again doesn't restart the
+    /// sleep. Once the sleep completed it is dropped and the timed transition
+    /// is taken, so dropping this future during the transition leaves the
+    /// state machine poisoned, as with any transition that doesn't complete.
+    pub async fn timeout_with_context(&mut self, context: &mut M::Context<'_>) {
+        match self.sleep.as_mut() {
+            Some(sleep) => sleep.as_mut().await,
+            None => future::pending().await,
+        }
+        self.sleep = None;
+
+        if let Some((_, target)) = M::AFTER(self.state_machine.state()) {
+            self.state_machine
+                .inner
+                .async_transition(target, context)
+                .await;
+        }
+        self.start_sleep();
+    }
+
+    /// Start the sleep for the timed transition of the current state, or drop
+    /// it if the current state has none.
+    fn start_sleep(&mut self) {
+        self.sleep = M::AFTER(self.state_machine.state())
+            .map(|(duration, _)| Box::pin(self.timer.sleep(duration)));
+    }
+
+    /// Get the state machine and the timer back.
+    pub fn into_parts(self) -> (InitializedStateMachine<M>, T) {
+        (self.state_machine, self.timer)
+    }
+}
+
+impl<M, T> core::ops::Deref for TimedStateMachine<M, T>
+where
+    M: IntoStateMachine,
+    T: AsyncTimer,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
@@ -0,0 +1,145 @@
+//! Helpers to measure the performance of a state machine on your own target.
+//!
+//! Configurations such as boxed states or a deep hierarchy can behave very
+//! differently depending on the target, so these helpers let you compare them
+//! where it matters without pulling in a benchmarking framework.
+//!
+//! ```
+//! # use statig::prelude::*;
+//! # #[derive(Default)]
+//! # pub struct Blinky;
+//! #
+//! # pub enum Event {
+//! #     TimerElapsed,
+//! # }
+//! #
+//! # #[state_machine(initial = "State::on()", state(derive(Clone)))]
+//! # impl Blinky {
+//! #     #[state]
+//! #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+//! #
+//! #     #[state]
+//! #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+//! # }
+//! #
+//! use statig::bench;
+//!
+//! let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+//!
+//! let dispatch = bench::dispatch_latency(&mut state_machine, &[Event::TimerElapsed], 1_000);
+//! let transition = bench::transition_cost(&mut state_machine, State::on(), State::off(), 1_000);
+//! let sizes = bench::sizes::<Blinky>();
+//!
+//! println!("dispatch: {:?} per event", dispatch.per_iteration());
+//! println!("transition: {:?} per transition", transition.per_iteration());
+//! println!("state: {} bytes", sizes.state);
+//! ```
+
+use core::hint::black_box;
+use core::mem::size_of;
+use std::time::{Duration, Instant};
+
+use crate::blocking::{self, InitializedStateMachine};
+use crate::IntoStateMachine;
+
+/// The time it took to run a number of iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    /// The number of iterations that were run.
+    pub iterations: u32,
+    /// The total time it took to run all the iterations.
+    pub elapsed: Duration,
+}
+
+impl Measurement {
+    /// Get the average time it took to run a single iteration.
+    pub fn per_iteration(&self) -> Duration {
+        match self.iterations {
+            0 => Duration::ZERO,
+            iterations => self.elapsed / iterations,
+        }
+    }
+}
+
+/// The size in bytes of the types that make up a state machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sizes {
+    /// The size of the state enum.
+    pub state: usize,
+    /// The size of the superstate enum.
+    pub superstate: usize,
+    /// The size of an initialized state machine, including its shared storage.
+    pub state_machine: usize,
+}
+
+/// Get the size of the types that make up the state machine `M`.
+pub fn sizes<M>() -> Sizes
+where
+    M: IntoStateMachine,
+{
+    Sizes {
+        state: size_of::<M::State>(),
+        superstate: size_of::<M::Superstate<'_>>(),
+        state_machine: size_of::<InitializedStateMachine<M>>(),
+    }
+}
+
+/// Measure the time it takes to handle an event. The events are handled in
+/// order, over and over again, until `iterations` events have been handled.
+pub fn dispatch_latency<M>(
+    state_machine: &mut InitializedStateMachine<M>,
+    events: &[M::Event<'_>],
+    iterations: u32,
+) -> Measurement
+where
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    assert!(!events.is_empty(), "at least one event is required");
+
+    let start = Instant::now();
+    for event in events.iter().cycle().take(iterations as usize) {
+        state_machine.handle(black_box(event));
+    }
+    let elapsed = start.elapsed();
+
+    Measurement {
+        iterations,
+        elapsed,
+    }
+}
+
+/// Measure the time it takes to transition between two states, including the
+/// exit and entry actions along the way. The state machine transitions back and
+/// forth between `source` and `target` until `iterations` transitions have been
+/// made, and is left in `source` or `target` afterwards.
+pub fn transition_cost<M>(
+    state_machine: &mut InitializedStateMachine<M>,
+    source: M::State,
+    target: M::State,
+    iterations: u32,
+) -> Measurement
+where
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M> + Clone,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    let inner = &mut state_machine.inner;
+    inner.transition(source.clone(), &mut ());
+
+    let start = Instant::now();
+    for iteration in 0..iterations {
+        let state = match iteration % 2 {
+            0 => target.clone(),
+            _ => source.clone(),
+        };
+        inner.transition(black_box(state), &mut ());
+    }
+    let elapsed = start.elapsed();
+
+    Measurement {
+        iterations,
+        elapsed,
+    }
+}
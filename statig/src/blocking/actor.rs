@@ -0,0 +1,184 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use actix::{Actor, Context, Handler, Message, MessageResult, Recipient};
+
+use crate::blocking::{self, InitializedStateMachine};
+use crate::IntoStateMachine;
+
+/// An actix actor that owns a state machine and handles every [`Dispatch`]
+/// message as an event. Other actors can [`Subscribe`] to the transitions it
+/// takes and ask for its state with [`GetState`].
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::{Dispatch, GetState, StateMachineActor};
+/// # use actix::Actor;
+/// # #[derive(Default)]
+/// # pub struct Order;
+/// #
+/// # pub struct PaymentReceived;
+/// #
+/// # #[state_machine(
+/// #     initial = "State::awaiting_payment()",
+/// #     state(derive(Clone, Debug, PartialEq))
+/// # )]
+/// # impl Order {
+/// #     #[state]
+/// #     fn awaiting_payment(event: &PaymentReceived) -> Response<State> {
+/// #         Transition(State::paid())
+/// #     }
+/// #
+/// #     #[state]
+/// #     fn paid(event: &PaymentReceived) -> Response<State> { Handled }
+/// # }
+/// #
+/// # #[actix::main]
+/// # async fn main() {
+/// let state_machine = Order.uninitialized_state_machine().init();
+/// let order = StateMachineActor::new(state_machine).start();
+///
+/// order.do_send(Dispatch(PaymentReceived));
+///
+/// let state = order.send(GetState::default()).await.unwrap();
+/// assert_eq!(state, State::paid());
+/// # }
+/// ```
+pub struct StateMachineActor<M>
+where
+    M: IntoStateMachine,
+    M::State: Send + 'static,
+{
+    state_machine: InitializedStateMachine<M>,
+    subscribers: Vec<Recipient<TransitionTaken<M::State>>>,
+}
+
+impl<M> StateMachineActor<M>
+where
+    M: IntoStateMachine,
+    M::State: Send + 'static,
+{
+    /// Create an actor that owns the given state machine.
+    pub fn new(state_machine: InitializedStateMachine<M>) -> Self {
+        Self {
+            state_machine,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Get the state machine back.
+    pub fn into_inner(self) -> InitializedStateMachine<M> {
+        self.state_machine
+    }
+}
+
+impl<M> Actor for StateMachineActor<M>
+where
+    M: IntoStateMachine + Unpin + 'static,
+    M::State: Send + Unpin + 'static,
+{
+    type Context = Context<Self>;
+}
+
+/// Message that is handled as an event by the state machine. Any message that
+/// converts into the event can be dispatched, so producers don't need to wrap
+/// their messages in the event.
+pub struct Dispatch<T>(pub T);
+
+impl<T> Message for Dispatch<T> {
+    type Result = ();
+}
+
+/// Message that is sent to the subscribers after every transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionTaken<S> {
+    /// The state before the event was handled.
+    pub source: S,
+    /// The state after the event was handled.
+    pub target: S,
+}
+
+impl<S> Message for TransitionTaken<S> {
+    type Result = ();
+}
+
+/// Message that subscribes an actor to the transitions of the state machine.
+pub struct Subscribe<S>(pub Recipient<TransitionTaken<S>>)
+where
+    S: Send + 'static;
+
+impl<S> Message for Subscribe<S>
+where
+    S: Send + 'static,
+{
+    type Result = ();
+}
+
+/// Message that returns the current state of the state machine.
+pub struct GetState<S>(PhantomData<S>);
+
+impl<S> Default for GetState<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S> Message for GetState<S>
+where
+    S: 'static,
+{
+    type Result = S;
+}
+
+impl<M, T> Handler<Dispatch<T>> for StateMachineActor<M>
+where
+    M: IntoStateMachine + Unpin + 'static,
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M> + Clone + Send + Unpin + 'static,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    T: Into<M::Event<'static>>,
+{
+    type Result = ();
+
+    fn handle(&mut self, Dispatch(message): Dispatch<T>, _: &mut Self::Context) {
+        let source = self.state_machine.state().clone();
+        let event = message.into();
+        if self
+            .state_machine
+            .inner
+            .handle_with_context(&event, &mut ())
+        {
+            let target = self.state_machine.state();
+            for subscriber in &self.subscribers {
+                subscriber.do_send(TransitionTaken {
+                    source: source.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+    }
+}
+
+impl<M> Handler<Subscribe<M::State>> for StateMachineActor<M>
+where
+    M: IntoStateMachine + Unpin + 'static,
+    M::State: Send + Unpin + 'static,
+{
+    type Result = ();
+
+    fn handle(&mut self, Subscribe(subscriber): Subscribe<M::State>, _: &mut Self::Context) {
+        self.subscribers.push(subscriber);
+    }
+}
+
+impl<M> Handler<GetState<M::State>> for StateMachineActor<M>
+where
+    M: IntoStateMachine + Unpin + 'static,
+    M::State: blocking::State<M> + Clone + Send + Unpin + 'static,
+{
+    type Result = MessageResult<GetState<M::State>>;
+
+    fn handle(&mut self, _: GetState<M::State>, _: &mut Self::Context) -> Self::Result {
+        MessageResult(self.state_machine.state().clone())
+    }
+}
@@ -0,0 +1,325 @@
+use core::marker::PhantomData;
+
+use crate::blocking::{self, StateExt as _};
+use crate::{Inner, IntoStateMachine, Response};
+
+/// Integer type that holds the id of the current state of a
+/// [`CompactStateMachine`].
+pub trait CompactStateId: Copy + Eq {
+    /// Value that marks a state machine that is not yet initialized.
+    const UNINITIALIZED: Self;
+
+    /// Convert a state id, if it fits.
+    fn from_usize(state_id: usize) -> Option<Self>;
+
+    /// Convert back to a state id.
+    fn into_usize(self) -> usize;
+}
+
+impl CompactStateId for u8 {
+    const UNINITIALIZED: Self = u8::MAX;
+
+    fn from_usize(state_id: usize) -> Option<Self> {
+        u8::try_from(state_id)
+            .ok()
+            .filter(|state_id| *state_id != Self::UNINITIALIZED)
+    }
+
+    fn into_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl CompactStateId for u16 {
+    const UNINITIALIZED: Self = u16::MAX;
+
+    fn from_usize(state_id: usize) -> Option<Self> {
+        u16::try_from(state_id)
+            .ok()
+            .filter(|state_id| *state_id != Self::UNINITIALIZED)
+    }
+
+    fn into_usize(self) -> usize {
+        self as usize
+    }
+}
+
+/// A state machine that only stores the [id](blocking::State::state_id) of the
+/// current state next to the shared storage, so it takes up as little memory
+/// as possible. This is useful on deeply embedded targets that run dozens of
+/// instances of the same state machine.
+///
+/// The state is recreated from its id whenever an event is handled, so all the
+/// states must be free of state-local storage, which fails the build otherwise.
+/// Data that would otherwise live in a state can be kept in a buffer that is
+/// owned by the caller and passed in as the context.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::CompactStateMachine;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event {
+/// #     TimerElapsed,
+/// # }
+/// #
+/// # #[state_machine(initial = "State::on()", state(derive(Debug, PartialEq)))]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// let mut state_machine = CompactStateMachine::<Blinky>::new(Blinky);
+///
+/// state_machine.handle(&Event::TimerElapsed);
+///
+/// assert_eq!(state_machine.state(), Some(State::off()));
+/// assert_eq!(core::mem::size_of_val(&state_machine), 1);
+/// ```
+///
+/// A state machine of which a state has state-local storage can't be compact:
+///
+/// ```compile_fail
+/// # use statig::prelude::*;
+/// # use statig::blocking::CompactStateMachine;
+/// # #[derive(Default)]
+/// # pub struct Counter;
+/// #
+/// # pub struct Event;
+/// #
+/// # #[state_machine(initial = "State::counting(0)")]
+/// # impl Counter {
+/// #     #[state]
+/// #     fn counting(count: &mut u32, event: &Event) -> Response<State> {
+/// #         *count += 1;
+/// #         Handled
+/// #     }
+/// # }
+/// #
+/// let mut state_machine = CompactStateMachine::<Counter>::new(Counter);
+///
+/// state_machine.handle(&Event);
+/// ```
+pub struct CompactStateMachine<M, I = u8>
+where
+    M: IntoStateMachine,
+    I: CompactStateId,
+{
+    shared_storage: M,
+    state_id: I,
+}
+
+impl<M, I> CompactStateMachine<M, I>
+where
+    M: IntoStateMachine,
+    I: CompactStateId,
+{
+    /// Create a compact state machine that will be lazily initialized.
+    pub const fn new(shared_storage: M) -> Self {
+        Self {
+            shared_storage,
+            state_id: I::UNINITIALIZED,
+        }
+    }
+
+    /// Get the id of the current state, or `None` if the state machine is not
+    /// yet initialized.
+    pub fn state_id(&self) -> Option<usize> {
+        match self.state_id == I::UNINITIALIZED {
+            true => None,
+            false => Some(self.state_id.into_usize()),
+        }
+    }
+}
+
+impl<M, I> CompactStateMachine<M, I>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    I: CompactStateId,
+{
+    /// Get the current state, or `None` if the state machine is not yet
+    /// initialized.
+    pub fn state(&self) -> Option<M::State> {
+        self.state_id().map(|state_id| Self::recreate(state_id))
+    }
+
+    /// Check if the state machine reached a terminate pseudo-state, after
+    /// which it ignores events.
+    pub fn is_terminated(&self) -> bool {
+        matches!(self.state(), Some(state) if M::TERMINATE(&state))
+    }
+
+    /// Explicitly initialize the state machine. If the state machine is already initialized
+    /// this is a no-op.
+    pub fn init(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.init_with_context(&mut ());
+    }
+
+    /// Explicitly initialize the state machine. If the state machine is already initialized
+    /// this is a no-op.
+    pub fn init_with_context(&mut self, context: &mut M::Context<'_>) {
+        if self.state_id != I::UNINITIALIZED {
+            return;
+        }
+
+        let mut state = M::INITIAL;
+        while let Some(selected) = Inner::<M>::select(&mut self.shared_storage, &state) {
+            state = selected;
+        }
+        let enter_levels = match M::TERMINATE(&state) {
+            true => 0,
+            false => state.depth(),
+        };
+        state.enter(&mut self.shared_storage, context, enter_levels);
+        self.store(&state);
+
+        M::ON_INIT(&mut self.shared_storage, &state);
+    }
+
+    /// Handle an event. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ());
+    }
+
+    /// Handle an event. If the state machine is still uninitialized, it will be initialized
+    /// before handling the event.
+    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        self.init_with_context(context);
+
+        let mut state = Self::recreate(self.state_id.into_usize());
+        if M::TERMINATE(&state) {
+            return;
+        }
+        let (response, source) = state.handle_with_source(&mut self.shared_storage, event, context);
+        match response {
+            Response::Super => {}
+            Response::Handled => {}
+            Response::Completed => {}
+            Response::Transition(target) => self.transition(state, target, event, source, context),
+        }
+
+        // Take the condition transition of the current state if its condition
+        // holds now that the event was dispatched.
+        let state = Self::recreate(self.state_id.into_usize());
+        if let Some(target) = M::WHEN(&self.shared_storage, &state) {
+            self.transition(state, target, event, 0, context);
+        }
+    }
+
+    /// Transition from the current state to the given target state. The
+    /// transition was taken by the state or superstate `height` levels above
+    /// the current state.
+    fn transition(
+        &mut self,
+        mut source: M::State,
+        mut target: M::State,
+        event: &M::Event<'_>,
+        height: usize,
+        context: &mut M::Context<'_>,
+    ) {
+        // A junction selects the target before any state is exited.
+        Inner::<M>::resolve_junctions(&mut self.shared_storage, &mut target);
+        M::BEFORE_TRANSITION(&mut self.shared_storage, &target, &source);
+
+        // A terminate stops the state machine without exiting or entering any
+        // state.
+        let path = source.transition_path(&mut target);
+        let (mut exit_levels, mut enter_levels) = match M::TERMINATE(&target) {
+            true => (0, 0),
+            false => M::SEMANTICS.transition_path(path, height),
+        };
+        source.exit(&mut self.shared_storage, context, exit_levels);
+
+        // A choice selects the target now that the states are exited.
+        while let Some(mut selected) = Inner::<M>::select(&mut self.shared_storage, &target) {
+            if M::TERMINATE(&selected) {
+                enter_levels = 0;
+                target = selected;
+                break;
+            }
+            let (choice_exit_levels, choice_enter_levels) = target.transition_path(&mut selected);
+            let more_exit_levels = choice_exit_levels.saturating_sub(enter_levels);
+            for height in exit_levels..exit_levels + more_exit_levels {
+                blocking::State::try_call_exit_action(
+                    &mut source,
+                    &mut self.shared_storage,
+                    context,
+                    height,
+                );
+            }
+            exit_levels += more_exit_levels;
+            enter_levels = choice_enter_levels + enter_levels.saturating_sub(choice_exit_levels);
+            target = selected;
+        }
+        M::TRANSITION_ACTION(&mut self.shared_storage, &source, &target);
+        target.enter(&mut self.shared_storage, context, enter_levels);
+        self.store(&target);
+
+        M::AFTER_TRANSITION(&mut self.shared_storage, &source, &target);
+        M::ON_TRANSITION(&mut self.shared_storage, &source, &target, event);
+    }
+
+    /// Store the id of the given state as the current state.
+    fn store(&mut self, state: &M::State) {
+        let () = Recreatable::<M>::ASSERT;
+        let state_id = blocking::State::state_id(state).expect("states must be numbered");
+        self.state_id = I::from_usize(state_id).expect("state id does not fit in the id type");
+        Inner::<M>::mirror_state_id(Some(state_id));
+    }
+
+    /// Recreate the state with the given id.
+    fn recreate(state_id: usize) -> M::State {
+        let () = Recreatable::<M>::ASSERT;
+        <M::State as blocking::State<M>>::from_state_id(state_id).expect("invalid state id")
+    }
+}
+
+/// Fails the build when the states of `M` can't be recreated from their id,
+/// as soon as a compact state machine stores or recreates one of them.
+struct Recreatable<M>(PhantomData<M>);
+
+impl<M> Recreatable<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+{
+    const ASSERT: () = assert!(
+        <M::State as blocking::State<M>>::RECREATABLE,
+        "states of a compact state machine can not have state-local storage"
+    );
+}
+
+impl<M, I> Default for CompactStateMachine<M, I>
+where
+    M: IntoStateMachine + Default,
+    I: CompactStateId,
+{
+    fn default() -> Self {
+        Self::new(M::default())
+    }
+}
+
+impl<M, I> core::ops::Deref for CompactStateMachine<M, I>
+where
+    M: IntoStateMachine,
+    I: CompactStateId,
+{
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.shared_storage
+    }
+}
@@ -0,0 +1,165 @@
+use core::cell::RefCell;
+
+use ::critical_section::Mutex;
+
+use crate::blocking::{self, StateMachine};
+use crate::ArrayQueue;
+use crate::IntoStateMachine;
+
+/// A state machine that can be shared between thread mode and interrupt
+/// service routines on bare-metal targets, using the
+/// [`critical-section`](https://docs.rs/critical-section) crate.
+///
+/// Interrupt service routines [`push`](Self::push) events to a queue with a
+/// capacity of `N` events, while thread mode calls [`handle`](Self::handle) or
+/// [`process`](Self::process) to dispatch them to the state machine.
+///
+/// # Memory ordering
+///
+/// Every access to the queue and the state machine happens inside a critical
+/// section, and a critical section acts as a barrier: all writes made before
+/// an event is pushed are visible to the handler that processes the event,
+/// and all writes made by a handler are visible once `handle` or `process`
+/// returns.
+///
+/// Each event is dispatched in its own critical section, so interrupts are
+/// delayed for at most the time it takes to handle a single event. Interrupts
+/// that fire between two events can push new events, which will be processed
+/// by the same call to `process`.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::CriticalSectionStateMachine;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event {
+/// #     TimerElapsed,
+/// # }
+/// #
+/// # #[state_machine(initial = "State::on()")]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// static STATE_MACHINE: CriticalSectionStateMachine<Blinky, 8> =
+///     CriticalSectionStateMachine::new(Blinky);
+///
+/// // In an interrupt service routine.
+/// fn timer_interrupt() {
+///     let _ = STATE_MACHINE.push(Event::TimerElapsed);
+/// }
+///
+/// // In thread mode.
+/// timer_interrupt();
+/// STATE_MACHINE.process();
+/// ```
+pub struct CriticalSectionStateMachine<M, const N: usize>
+where
+    M: IntoStateMachine,
+{
+    state_machine: Mutex<RefCell<StateMachine<M>>>,
+    queue: Mutex<RefCell<ArrayQueue<M::Event<'static>, N>>>,
+}
+
+impl<M, const N: usize> CriticalSectionStateMachine<M, N>
+where
+    M: IntoStateMachine,
+{
+    /// Create a state machine that will be lazily initialized when the first
+    /// event is handled.
+    pub const fn new(shared_storage: M) -> Self {
+        Self {
+            state_machine: Mutex::new(RefCell::new(StateMachine::new(shared_storage))),
+            queue: Mutex::new(RefCell::new(ArrayQueue::new())),
+        }
+    }
+
+    /// Push an event to the queue, so it will be handled by the next call to
+    /// [`process`](Self::process). This can be called from an interrupt
+    /// service routine. If the queue is full the event is handed back.
+    pub fn push(&self, event: M::Event<'static>) -> Result<(), M::Event<'static>> {
+        ::critical_section::with(|cs| self.queue.borrow_ref_mut(cs).push(event))
+    }
+
+    /// Get the number of events that are waiting in the queue.
+    pub fn queued(&self) -> usize {
+        ::critical_section::with(|cs| self.queue.borrow_ref(cs).len())
+    }
+}
+
+impl<M, const N: usize> CriticalSectionStateMachine<M, N>
+where
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Handle an event immediately, and then process the events in the queue.
+    ///
+    /// When this is called from within a handler or action of the state
+    /// machine itself (e.g. through a callback in the shared storage), the
+    /// event is pushed to the queue instead, and it will be handled once the
+    /// current event has run to completion. If the queue is full the event is
+    /// handed back.
+    ///
+    /// This must only be called from thread mode. Interrupts are disabled
+    /// while an event is being handled.
+    pub fn handle(&self, event: M::Event<'static>) -> Result<(), M::Event<'static>> {
+        let dispatched = ::critical_section::with(|cs| {
+            match self.state_machine.borrow(cs).try_borrow_mut() {
+                Ok(mut state_machine) => {
+                    state_machine.handle(&event);
+                    Ok(true)
+                }
+                // The state machine is already handling an event, so this is
+                // a reentrant call.
+                Err(_) => self.queue.borrow_ref_mut(cs).push(event).map(|_| false),
+            }
+        })?;
+        if dispatched {
+            self.process();
+        }
+        Ok(())
+    }
+
+    /// Handle all the events in the queue, including the ones that are pushed
+    /// while processing. Returns the number of events that were handled.
+    ///
+    /// This must only be called from thread mode. When it is called from within
+    /// a handler or action of the state machine itself, it returns immediately.
+    pub fn process(&self) -> usize {
+        let mut handled = 0;
+        while ::critical_section::with(|cs| {
+            // When called from within a handler, the queue will be processed by
+            // the outer call once the current event has run to completion.
+            let Ok(mut state_machine) = self.state_machine.borrow(cs).try_borrow_mut() else {
+                return false;
+            };
+            let event = self.queue.borrow_ref_mut(cs).pop();
+            match event {
+                Some(event) => {
+                    state_machine.handle(&event);
+                    true
+                }
+                None => false,
+            }
+        }) {
+            handled += 1;
+        }
+        handled
+    }
+
+    /// Get access to the state machine inside a critical section.
+    ///
+    /// # Panics
+    ///
+    /// Panics when it is called from within a handler or action of the state
+    /// machine itself.
+    pub fn with<R>(&self, f: impl FnOnce(&mut StateMachine<M>) -> R) -> R {
+        ::critical_section::with(|cs| f(&mut self.state_machine.borrow_ref_mut(cs)))
+    }
+}
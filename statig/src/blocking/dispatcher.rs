@@ -0,0 +1,223 @@
+#[cfg(feature = "critical-section")]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{self, Receiver, SyncSender};
+#[cfg(feature = "std")]
+use std::thread::{self, JoinHandle};
+
+#[cfg(feature = "critical-section")]
+use ::critical_section::Mutex;
+
+use crate::blocking::{self, StateMachine};
+#[cfg(feature = "critical-section")]
+use crate::ArrayQueue;
+use crate::IntoStateMachine;
+
+/// Owns a state machine and handles the events it receives over a bounded
+/// channel, strictly in the order in which they were sent.
+///
+/// This is the canonical way to feed a state machine from many producers: each
+/// producer gets a clone of the sender, and the dispatcher runs on a thread of
+/// its own.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::Dispatcher;
+/// # #[derive(Default)]
+/// # pub struct Counter {
+/// #     count: usize,
+/// # }
+/// #
+/// # pub struct Increment;
+/// #
+/// # #[state_machine(initial = "State::counting()")]
+/// # impl Counter {
+/// #     #[state]
+/// #     fn counting(&mut self, event: &Increment) -> Response<State> {
+/// #         self.count += 1;
+/// #         Handled
+/// #     }
+/// # }
+/// #
+/// let (dispatcher, sender) = Dispatcher::new(Counter::default().state_machine(), 16);
+/// let dispatcher = dispatcher.spawn();
+///
+/// for _ in 0..10 {
+///     let sender = sender.clone();
+///     std::thread::spawn(move || sender.send(Increment).unwrap());
+/// }
+///
+/// // Once all the senders are dropped, the dispatcher hands back the state machine.
+/// drop(sender);
+/// let state_machine = dispatcher.join().unwrap();
+///
+/// assert_eq!(state_machine.count, 10);
+/// ```
+#[cfg(feature = "std")]
+pub struct Dispatcher<M>
+where
+    M: IntoStateMachine,
+{
+    state_machine: StateMachine<M>,
+    receiver: Receiver<M::Event<'static>>,
+}
+
+#[cfg(feature = "std")]
+impl<M> Dispatcher<M>
+where
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Create a dispatcher for the given state machine and a sender that can
+    /// hold up to `capacity` events before sending blocks.
+    pub fn new(
+        state_machine: StateMachine<M>,
+        capacity: usize,
+    ) -> (Self, SyncSender<M::Event<'static>>) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let dispatcher = Self {
+            state_machine,
+            receiver,
+        };
+        (dispatcher, sender)
+    }
+
+    /// Handle events until all the senders are dropped, and then return the
+    /// state machine.
+    pub fn run(mut self) -> StateMachine<M> {
+        for event in self.receiver.iter() {
+            self.state_machine.handle(&event);
+        }
+        self.state_machine
+    }
+
+    /// Run the dispatcher on a new thread.
+    pub fn spawn(self) -> JoinHandle<StateMachine<M>>
+    where
+        M: Send + 'static,
+        M::State: Send,
+        M::Event<'static>: Send,
+    {
+        thread::spawn(move || self.run())
+    }
+}
+
+/// A bounded channel of `N` events, protected by a critical section, that can
+/// be stored in a `static` and sent to from interrupt service routines.
+///
+/// This is the embedded counterpart of the channel used by [`Dispatcher`].
+#[cfg(feature = "critical-section")]
+pub struct EventChannel<E, const N: usize> {
+    queue: Mutex<RefCell<ArrayQueue<E, N>>>,
+}
+
+#[cfg(feature = "critical-section")]
+impl<E, const N: usize> EventChannel<E, N> {
+    /// Create an empty channel.
+    pub const fn new() -> Self {
+        Self {
+            queue: Mutex::new(RefCell::new(ArrayQueue::new())),
+        }
+    }
+
+    /// Get a sender for this channel.
+    pub fn sender(&self) -> EventSender<'_, E, N> {
+        EventSender { channel: self }
+    }
+
+    fn receive(&self) -> Option<E> {
+        ::critical_section::with(|cs| self.queue.borrow_ref_mut(cs).pop())
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<E, const N: usize> Default for EventChannel<E, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sending half of an [`EventChannel`].
+#[cfg(feature = "critical-section")]
+pub struct EventSender<'a, E, const N: usize> {
+    channel: &'a EventChannel<E, N>,
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, E, const N: usize> EventSender<'a, E, N> {
+    /// Send an event. If the channel is full the event is handed back.
+    pub fn send(&self, event: E) -> Result<(), E> {
+        ::critical_section::with(|cs| self.channel.queue.borrow_ref_mut(cs).push(event))
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, E, const N: usize> Clone for EventSender<'a, E, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, E, const N: usize> Copy for EventSender<'a, E, N> {}
+
+/// Owns a state machine and handles the events it receives over an
+/// [`EventChannel`], strictly in the order in which they were sent.
+///
+/// The state machine is only accessed by the dispatcher, so unlike with
+/// [`CriticalSectionStateMachine`](crate::blocking::CriticalSectionStateMachine)
+/// interrupts are never disabled while an event is handled.
+#[cfg(feature = "critical-section")]
+pub struct ChannelDispatcher<'a, M, const N: usize>
+where
+    M: IntoStateMachine,
+{
+    state_machine: StateMachine<M>,
+    channel: &'a EventChannel<M::Event<'static>, N>,
+}
+
+#[cfg(feature = "critical-section")]
+impl<'a, M, const N: usize> ChannelDispatcher<'a, M, N>
+where
+    for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Create a dispatcher that handles the events sent over the given channel.
+    pub fn new(
+        state_machine: StateMachine<M>,
+        channel: &'a EventChannel<M::Event<'static>, N>,
+    ) -> Self {
+        Self {
+            state_machine,
+            channel,
+        }
+    }
+
+    /// Handle all the events that are waiting in the channel. Returns the
+    /// number of events that were handled.
+    pub fn dispatch_pending(&mut self) -> usize {
+        let mut handled = 0;
+        while let Some(event) = self.channel.receive() {
+            self.state_machine.handle(&event);
+            handled += 1;
+        }
+        handled
+    }
+
+    /// Handle events forever, calling `idle` whenever the channel is empty
+    /// (e.g. to wait for an interrupt).
+    pub fn run(mut self, mut idle: impl FnMut()) -> ! {
+        loop {
+            if self.dispatch_pending() == 0 {
+                idle();
+            }
+        }
+    }
+
+    /// Get a reference to the state machine.
+    pub fn state_machine(&self) -> &StateMachine<M> {
+        &self.state_machine
+    }
+}
@@ -0,0 +1,185 @@
+use crate::blocking::{self, InitializedStateMachine, Middleware, Next};
+use crate::IntoStateMachine;
+
+/// What an event filter does with an event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterVerdict<E> {
+    /// Pass the event on unchanged.
+    Pass,
+    /// Drop the event, so it is never handled.
+    Drop,
+    /// Replace the event with another one.
+    Rewrite(E),
+}
+
+/// Predicate that decides what happens with an event in the given state.
+pub type EventFilter<M> = fn(
+    &<M as IntoStateMachine>::State,
+    &<M as IntoStateMachine>::Event<'_>,
+) -> FilterVerdict<<M as IntoStateMachine>::Event<'static>>;
+
+/// Handle to a filter that was registered with [`EventFilters::register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FilterId(usize);
+
+/// [`Middleware`] with up to `N` event filters that are registered and removed
+/// at runtime, so an operator can suppress or rewrite events without changing
+/// the state machine (e.g. ignore button events while it is locked).
+///
+/// The filters are applied in the order in which they were registered, each
+/// one seeing the event as it was rewritten by the filters before it. Once a
+/// filter drops an event, the remaining filters are skipped.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::{EventFilters, FilterVerdict, MiddlewareStateMachine};
+/// # #[derive(Default)]
+/// # pub struct Panel { presses: usize }
+/// #
+/// # pub enum Event { Button, Lock }
+/// #
+/// # #[state_machine(initial = "State::unlocked()")]
+/// # impl Panel {
+/// #     #[state]
+/// #     fn unlocked(&mut self, event: &Event) -> Response<State> {
+/// #         match event {
+/// #             Event::Button => { self.presses += 1; Handled }
+/// #             Event::Lock => Transition(State::locked()),
+/// #         }
+/// #     }
+/// #
+/// #     #[state]
+/// #     fn locked(&mut self, event: &Event) -> Response<State> {
+/// #         self.presses += 1;
+/// #         Handled
+/// #     }
+/// # }
+/// #
+/// let state_machine = Panel::default().uninitialized_state_machine().init();
+/// let mut state_machine = MiddlewareStateMachine::new(state_machine, EventFilters::<Panel, 4>::new());
+///
+/// let ignore_buttons_while_locked = state_machine
+///     .middleware_mut()
+///     .register(|state, event| match (state, event) {
+///         (State::Locked {}, Event::Button) => FilterVerdict::Drop,
+///         _ => FilterVerdict::Pass,
+///     })
+///     .map_err(|_| "no free filter slot")
+///     .unwrap();
+///
+/// state_machine.handle(&Event::Lock);
+/// state_machine.handle(&Event::Button);
+/// assert_eq!(state_machine.presses, 0);
+///
+/// state_machine.middleware_mut().remove(ignore_buttons_while_locked);
+/// state_machine.handle(&Event::Button);
+/// assert_eq!(state_machine.presses, 1);
+/// ```
+pub struct EventFilters<M, const N: usize>
+where
+    M: IntoStateMachine,
+{
+    filters: [Option<(FilterId, EventFilter<M>)>; N],
+    next_id: usize,
+}
+
+impl<M, const N: usize> EventFilters<M, N>
+where
+    M: IntoStateMachine,
+{
+    const EMPTY: Option<(FilterId, EventFilter<M>)> = None;
+
+    /// Create a middleware without any filters.
+    pub const fn new() -> Self {
+        Self {
+            filters: [Self::EMPTY; N],
+            next_id: 0,
+        }
+    }
+
+    /// Register a filter after the filters that are already registered. If
+    /// all `N` slots are taken the filter is handed back.
+    pub fn register(&mut self, filter: EventFilter<M>) -> Result<FilterId, EventFilter<M>> {
+        let len = self.len();
+        if len == N {
+            return Err(filter);
+        }
+        let id = FilterId(self.next_id);
+        self.next_id += 1;
+        self.filters[len] = Some((id, filter));
+        Ok(id)
+    }
+
+    /// Remove a filter. Returns whether the filter was registered.
+    pub fn remove(&mut self, id: FilterId) -> bool {
+        let Some(index) = self
+            .filters
+            .iter()
+            .position(|filter| matches!(filter, Some((filter_id, _)) if *filter_id == id))
+        else {
+            return false;
+        };
+        // Shift the remaining filters down to keep them in order.
+        self.filters[index] = None;
+        self.filters[index..].rotate_left(1);
+        true
+    }
+
+    /// Remove all the filters.
+    pub fn clear(&mut self) {
+        self.filters = [Self::EMPTY; N];
+    }
+
+    /// Get the number of filters that are registered.
+    pub fn len(&self) -> usize {
+        self.filters
+            .iter()
+            .take_while(|filter| filter.is_some())
+            .count()
+    }
+
+    /// Check whether no filters are registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<M, const N: usize> Default for EventFilters<M, N>
+where
+    M: IntoStateMachine,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, const N: usize> Middleware<M> for EventFilters<M, N>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    fn around_dispatch(
+        &mut self,
+        state_machine: &mut InitializedStateMachine<M>,
+        event: &M::Event<'_>,
+        next: Next<'_, M>,
+    ) {
+        let mut rewritten: Option<M::Event<'static>> = None;
+        for (_, filter) in self.filters.iter().map_while(Option::as_ref) {
+            let verdict = match &rewritten {
+                Some(rewritten) => filter(state_machine.state(), rewritten),
+                None => filter(state_machine.state(), event),
+            };
+            match verdict {
+                FilterVerdict::Pass => {}
+                FilterVerdict::Drop => return,
+                FilterVerdict::Rewrite(event) => rewritten = Some(event),
+            }
+        }
+        match &rewritten {
+            Some(rewritten) => next.run(state_machine, rewritten),
+            None => next.run(state_machine, event),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::IntoStateMachine;
+
+/// Code that runs around the dispatch of every event, for cross-cutting
+/// concerns such as logging, authorization, filtering and metrics that don't
+/// belong in the handlers.
+///
+/// A middleware decides whether and how the event continues by calling
+/// [`next`](Next::run): it can inspect the state machine before and after,
+/// drop the event by not calling `next`, or call it with a different event.
+/// Middlewares are stacked with [`MiddlewareStateMachine::layer`], or as a
+/// tuple `(outer, inner)`.
+pub trait Middleware<M>
+where
+    M: IntoStateMachine,
+{
+    /// Dispatch the event by calling `next`, with any code of the middleware
+    /// around it.
+    fn around_dispatch(
+        &mut self,
+        state_machine: &mut InitializedStateMachine<M>,
+        event: &M::Event<'_>,
+        next: Next<'_, M>,
+    );
+}
+
+/// The rest of the middleware chain, ending with the state machine handling
+/// the event.
+pub struct Next<'a, M>
+where
+    M: IntoStateMachine,
+{
+    dispatch: &'a mut dyn FnMut(&mut InitializedStateMachine<M>, &M::Event<'_>),
+}
+
+impl<'a, M> Next<'a, M>
+where
+    M: IntoStateMachine,
+{
+    /// Pass the event on to the rest of the chain.
+    pub fn run(self, state_machine: &mut InitializedStateMachine<M>, event: &M::Event<'_>) {
+        (self.dispatch)(state_machine, event)
+    }
+
+    /// Borrow the rest of the chain, so it can be run more than once.
+    pub fn by_ref(&mut self) -> Next<'_, M> {
+        Next {
+            dispatch: &mut *self.dispatch,
+        }
+    }
+}
+
+impl<M> Middleware<M> for ()
+where
+    M: IntoStateMachine,
+{
+    fn around_dispatch(
+        &mut self,
+        state_machine: &mut InitializedStateMachine<M>,
+        event: &M::Event<'_>,
+        next: Next<'_, M>,
+    ) {
+        next.run(state_machine, event);
+    }
+}
+
+impl<M, A, B> Middleware<M> for (A, B)
+where
+    M: IntoStateMachine,
+    A: Middleware<M>,
+    B: Middleware<M>,
+{
+    fn around_dispatch(
+        &mut self,
+        state_machine: &mut InitializedStateMachine<M>,
+        event: &M::Event<'_>,
+        mut next: Next<'_, M>,
+    ) {
+        let (outer, inner) = self;
+        let mut dispatch = |state_machine: &mut InitializedStateMachine<M>,
+                            event: &M::Event<'_>| {
+            inner.around_dispatch(state_machine, event, next.by_ref())
+        };
+        outer.around_dispatch(
+            state_machine,
+            event,
+            Next {
+                dispatch: &mut dispatch,
+            },
+        );
+    }
+}
+
+/// A state machine that passes every event through a chain of [`Middleware`]
+/// before it is handled.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::{InitializedStateMachine, Middleware, MiddlewareStateMachine, Next};
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub struct Event;
+/// #
+/// # #[state_machine(initial = "State::on()", state(derive(Debug)))]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// struct Logger;
+///
+/// impl Middleware<Blinky> for Logger {
+///     fn around_dispatch(
+///         &mut self,
+///         state_machine: &mut InitializedStateMachine<Blinky>,
+///         event: &Event,
+///         next: Next<'_, Blinky>,
+///     ) {
+///         let source = format!("{:?}", state_machine.state());
+///         next.run(state_machine, event);
+///         println!("{source} -> {:?}", state_machine.state());
+///     }
+/// }
+///
+/// let state_machine = Blinky::default().uninitialized_state_machine().init();
+/// let mut state_machine = MiddlewareStateMachine::new(state_machine, Logger);
+///
+/// state_machine.handle(&Event);
+/// ```
+pub struct MiddlewareStateMachine<M, W>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    middleware: W,
+}
+
+impl<M, W> MiddlewareStateMachine<M, W>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    W: Middleware<M>,
+{
+    /// Create a state machine that passes its events through the given
+    /// middleware.
+    pub fn new(state_machine: InitializedStateMachine<M>, middleware: W) -> Self {
+        Self {
+            state_machine,
+            middleware,
+        }
+    }
+
+    /// Add a middleware on top of the chain, so it sees every event before
+    /// the middlewares that were added earlier.
+    pub fn layer<O>(self, middleware: O) -> MiddlewareStateMachine<M, (O, W)>
+    where
+        O: Middleware<M>,
+    {
+        MiddlewareStateMachine {
+            state_machine: self.state_machine,
+            middleware: (middleware, self.middleware),
+        }
+    }
+
+    /// Pass the given event through the middleware chain and handle it.
+    pub fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ());
+    }
+
+    /// Pass the given event through the middleware chain and handle it.
+    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        let mut dispatch = |state_machine: &mut InitializedStateMachine<M>,
+                            event: &M::Event<'_>| {
+            state_machine.inner.handle_with_context(event, context);
+        };
+        self.middleware.around_dispatch(
+            &mut self.state_machine,
+            event,
+            Next {
+                dispatch: &mut dispatch,
+            },
+        );
+    }
+
+    /// Get a reference to the middleware.
+    pub fn middleware(&self) -> &W {
+        &self.middleware
+    }
+
+    /// Get a mutable reference to the middleware.
+    pub fn middleware_mut(&mut self) -> &mut W {
+        &mut self.middleware
+    }
+
+    /// Get the state machine and the middleware back.
+    pub fn into_parts(self) -> (InitializedStateMachine<M>, W) {
+        (self.state_machine, self.middleware)
+    }
+}
+
+impl<M, W> core::ops::Deref for MiddlewareStateMachine<M, W>
+where
+    M: IntoStateMachine,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
@@ -1,12 +1,52 @@
 //! Module for blocking (sync) mode.
 
+#[cfg(feature = "actix")]
+mod actor;
+mod compact_state_machine;
+#[cfg(feature = "critical-section")]
+mod critical_section;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+mod dispatcher;
+mod event_filters;
+mod middleware;
+mod persistent_state_machine;
+mod queued_state_machine;
+mod router;
+#[cfg(feature = "std")]
+mod shared_state_machine;
+#[cfg(feature = "alloc")]
+mod simulation;
+mod split_channel;
 mod state;
 mod state_machine;
 mod superstate;
+mod supervisor;
+mod timed_state_machine;
+mod watchdog;
 
 pub use crate::Response::{self, *};
 pub use crate::*;
 
+#[cfg(feature = "critical-section")]
+pub use self::critical_section::*;
+#[cfg(feature = "actix")]
+pub use actor::*;
+pub use compact_state_machine::*;
+#[cfg(any(feature = "std", feature = "critical-section"))]
+pub use dispatcher::*;
+pub use event_filters::*;
+pub use middleware::*;
+pub use persistent_state_machine::*;
+pub use queued_state_machine::*;
+pub use router::*;
+#[cfg(feature = "std")]
+pub use shared_state_machine::*;
+#[cfg(feature = "alloc")]
+pub use simulation::*;
+pub use split_channel::*;
 pub use state::*;
 pub use state_machine::*;
 pub use superstate::*;
+pub use supervisor::*;
+pub use timed_state_machine::*;
+pub use watchdog::*;
@@ -0,0 +1,190 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::{Inner, IntoStateMachine, PersistError, StateCodec, StateStore};
+
+/// A state machine that writes its state to a [`StateStore`] after every
+/// transition, so it can be restored after a restart.
+///
+/// The state is encoded with a [`StateCodec`] into a buffer of `N` bytes.
+/// Changes to the shared storage or to state-local storage that are not
+/// accompanied by a transition are not persisted, unless
+/// [`persist`](Self::persist) is called explicitly.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::PersistentStateMachine;
+/// # use statig::{MemoryStore, StateIdCodec};
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event {
+/// #     TimerElapsed,
+/// # }
+/// #
+/// # #[state_machine(initial = "State::on()", state(derive(Debug, PartialEq)))]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// let store = MemoryStore::<2>::new();
+/// let mut state_machine = PersistentStateMachine::<_, _, _>::restore(Blinky, store, StateIdCodec).unwrap();
+///
+/// state_machine.handle(&Event::TimerElapsed).unwrap();
+///
+/// // Restoring from the same store picks up where the state machine left off.
+/// let store = state_machine.into_store();
+/// let state_machine = PersistentStateMachine::<_, _, _>::restore(Blinky, store, StateIdCodec).unwrap();
+/// assert_eq!(state_machine.state(), &State::off());
+/// ```
+pub struct PersistentStateMachine<M, S, C, const N: usize = 64>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    store: S,
+    codec: C,
+}
+
+impl<M, S, C, const N: usize> PersistentStateMachine<M, S, C, N>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    S: StateStore,
+    C: StateCodec<M>,
+{
+    /// Restore the state machine from the state in the store, or start from
+    /// the initial state if no state has been stored yet. The state machine is
+    /// initialized by executing all entry actions towards the restored state.
+    pub fn restore(
+        shared_storage: M,
+        store: S,
+        codec: C,
+    ) -> Result<Self, PersistError<S::Error, C::Error>>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        Self::restore_with_context(shared_storage, store, codec, &mut ())
+    }
+
+    /// Restore the state machine from the state in the store, or start from
+    /// the initial state if no state has been stored yet. The state machine is
+    /// initialized by executing all entry actions towards the restored state.
+    pub fn restore_with_context(
+        shared_storage: M,
+        mut store: S,
+        codec: C,
+        context: &mut M::Context<'_>,
+    ) -> Result<Self, PersistError<S::Error, C::Error>> {
+        let mut buffer = [0; N];
+        let state = match store.read(&mut buffer).map_err(PersistError::Store)? {
+            Some(bytes) => codec.decode(bytes).map_err(PersistError::Codec)?,
+            None => M::INITIAL,
+        };
+
+        Ok(Self::init(shared_storage, state, store, codec, context))
+    }
+
+    /// Restore the state machine from the state in the store, or start from
+    /// the initial state if no state has been stored yet or if the stored state
+    /// can not be decoded (e.g. because it was corrupted).
+    pub fn restore_or_initial(shared_storage: M, store: S, codec: C) -> Result<Self, S::Error>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        Self::restore_or_initial_with_context(shared_storage, store, codec, &mut ())
+    }
+
+    /// Restore the state machine from the state in the store, or start from
+    /// the initial state if no state has been stored yet or if the stored state
+    /// can not be decoded (e.g. because it was corrupted).
+    pub fn restore_or_initial_with_context(
+        shared_storage: M,
+        mut store: S,
+        codec: C,
+        context: &mut M::Context<'_>,
+    ) -> Result<Self, S::Error> {
+        let mut buffer = [0; N];
+        let state = store
+            .read(&mut buffer)?
+            .and_then(|bytes| codec.decode(bytes).ok())
+            .unwrap_or(M::INITIAL);
+
+        Ok(Self::init(shared_storage, state, store, codec, context))
+    }
+
+    /// Initialize the state machine by executing all entry actions towards the
+    /// given state.
+    fn init(
+        shared_storage: M,
+        state: M::State,
+        store: S,
+        codec: C,
+        context: &mut M::Context<'_>,
+    ) -> Self {
+        let mut inner = Inner::new(shared_storage, state);
+        inner.init_with_context(context);
+
+        Self {
+            state_machine: InitializedStateMachine { inner },
+            store,
+            codec,
+        }
+    }
+
+    /// Handle the given event and persist the state if a transition was
+    /// taken.
+    pub fn handle(&mut self, event: &M::Event<'_>) -> Result<(), PersistError<S::Error, C::Error>>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ())
+    }
+
+    /// Handle the given event and persist the state if a transition was
+    /// taken.
+    pub fn handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), PersistError<S::Error, C::Error>> {
+        match self.state_machine.inner.handle_with_context(event, context) {
+            true => self.persist(),
+            false => Ok(()),
+        }
+    }
+
+    /// Write the current state to the store.
+    pub fn persist(&mut self) -> Result<(), PersistError<S::Error, C::Error>> {
+        let mut buffer = [0; N];
+        let bytes = self
+            .codec
+            .encode(self.state_machine.state(), &mut buffer)
+            .map_err(PersistError::Codec)?;
+        self.store.write(bytes).map_err(PersistError::Store)
+    }
+
+    /// Get a reference to the store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the store back, e.g. to restore the state machine from it later.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+impl<M, S, C, const N: usize> core::ops::Deref for PersistentStateMachine<M, S, C, N>
+where
+    M: IntoStateMachine,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
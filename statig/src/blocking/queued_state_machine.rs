@@ -0,0 +1,236 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::{ArrayQueue, EventQueue, IntoStateMachine, OverflowPolicy, OverflowQueue};
+
+/// A state machine with a queue of pending events and a queue of deferred
+/// events.
+///
+/// Events that are posted to the state machine wait in the pending queue until
+/// [`dispatch_pending`](Self::dispatch_pending) handles them, each one running
+/// to completion before the next one is taken. When the current state defers
+/// an event, it is moved to the deferred queue instead, and it is recalled
+/// once a transition is taken to a state that no longer defers it.
+///
+/// Both queues can be any [`EventQueue`], so the storage is chosen to fit the
+/// target: an [`ArrayQueue`](crate::ArrayQueue) on a microcontroller without
+/// an allocator, a `VecDeque` on a server (with the `alloc` feature), or
+/// a [`SliceQueue`](crate::SliceQueue) on top of a buffer of your own. Wrap
+/// a queue in an [`OverflowQueue`](crate::OverflowQueue) to drop the oldest or
+/// the newest event when it is full, instead of handing the event back.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::QueuedStateMachine;
+/// # use statig::ArrayQueue;
+/// #[derive(Default)]
+/// pub struct Printer {
+///     printed: usize,
+/// }
+///
+/// pub enum Event {
+///     Print,
+///     Ready,
+/// }
+///
+/// #[state_machine(initial = "State::warming_up()", state(derive(Debug, PartialEq)))]
+/// impl Printer {
+///     #[state]
+///     fn warming_up(event: &Event) -> Response<State> {
+///         match event {
+///             Event::Ready => Transition(State::idle()),
+///             Event::Print => Handled,
+///         }
+///     }
+///
+///     #[state]
+///     fn idle(&mut self, event: &Event) -> Response<State> {
+///         match event {
+///             Event::Print => {
+///                 self.printed += 1;
+///                 Handled
+///             }
+///             Event::Ready => Handled,
+///         }
+///     }
+/// }
+///
+/// let state_machine = Printer::default().uninitialized_state_machine().init();
+/// let mut state_machine = QueuedStateMachine::new(
+///     state_machine,
+///     ArrayQueue::<Event, 4>::new(),
+///     ArrayQueue::<Event, 4>::new(),
+/// )
+/// .with_deferral(|state, event| matches!((state, event), (State::WarmingUp {}, Event::Print)));
+///
+/// state_machine.post(Event::Print).ok();
+/// state_machine.post(Event::Ready).ok();
+/// state_machine.dispatch_pending().ok();
+///
+/// // The print job was deferred until the printer was ready.
+/// assert_eq!(state_machine.printed, 1);
+/// ```
+pub struct QueuedStateMachine<M, P, D = P>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    pending: P,
+    deferred: D,
+    defers: fn(&M::State, &M::Event<'static>) -> bool,
+}
+
+impl<M, P, D> QueuedStateMachine<M, P, D>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    P: EventQueue<M::Event<'static>>,
+    D: EventQueue<M::Event<'static>>,
+{
+    /// Create a state machine that queues its events in the given queues. No
+    /// events are deferred until a deferral is set with
+    /// [`with_deferral`](Self::with_deferral).
+    pub fn new(state_machine: InitializedStateMachine<M>, pending: P, deferred: D) -> Self {
+        Self {
+            state_machine,
+            pending,
+            deferred,
+            defers: |_, _| false,
+        }
+    }
+
+    /// Set the function that decides whether an event is deferred in the
+    /// given state.
+    pub fn with_deferral(mut self, defers: fn(&M::State, &M::Event<'static>) -> bool) -> Self {
+        self.defers = defers;
+        self
+    }
+
+    /// Post an event, so it is handled by the next call to
+    /// [`dispatch_pending`](Self::dispatch_pending). If the pending queue is
+    /// full the event is handed back.
+    pub fn post(&mut self, event: M::Event<'static>) -> Result<(), M::Event<'static>> {
+        self.pending.push(event)
+    }
+
+    /// Handle all the pending events, recalling the deferred events whenever a
+    /// transition is taken. Returns the number of events that were handled.
+    ///
+    /// If an event must be deferred while the deferred queue is full,
+    /// dispatching stops and the event is handed back.
+    pub fn dispatch_pending(&mut self) -> Result<usize, M::Event<'static>>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.dispatch_pending_with_context(&mut ())
+    }
+
+    /// Handle all the pending events, recalling the deferred events whenever a
+    /// transition is taken. Returns the number of events that were handled.
+    ///
+    /// If an event must be deferred while the deferred queue is full,
+    /// dispatching stops and the event is handed back.
+    pub fn dispatch_pending_with_context(
+        &mut self,
+        context: &mut M::Context<'_>,
+    ) -> Result<usize, M::Event<'static>> {
+        let mut handled = 0;
+        while let Some(event) = self.pending.pop() {
+            if (self.defers)(self.state_machine.state(), &event) {
+                self.deferred.push(event)?;
+                continue;
+            }
+            handled += 1;
+            if self
+                .state_machine
+                .inner
+                .handle_with_context(&event, context)
+            {
+                handled += self.recall_deferred(context);
+            }
+        }
+        Ok(handled)
+    }
+
+    /// Handle the deferred events that are no longer deferred in the current
+    /// state, in the order in which they were deferred. Returns the number of
+    /// events that were handled.
+    fn recall_deferred(&mut self, context: &mut M::Context<'_>) -> usize {
+        let mut handled = 0;
+        for _ in 0..self.deferred.len() {
+            let Some(event) = self.deferred.pop() else {
+                break;
+            };
+            if (self.defers)(self.state_machine.state(), &event) {
+                // The event was just popped, so there is room to put it back.
+                let _ = self.deferred.push(event);
+            } else {
+                self.state_machine
+                    .inner
+                    .handle_with_context(&event, context);
+                handled += 1;
+            }
+        }
+        handled
+    }
+
+    /// Get the number of events that are waiting in the pending queue.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Get the number of events that are waiting in the deferred queue.
+    pub fn deferred(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Get the state machine and the queues back.
+    pub fn into_parts(self) -> (InitializedStateMachine<M>, P, D) {
+        (self.state_machine, self.pending, self.deferred)
+    }
+}
+
+/// A [`QueuedStateMachine`] that holds up to `P` pending and `D` deferred
+/// events in arrays, so its memory budget is fixed at compile time.
+pub type BoundedStateMachine<M, const P: usize, const D: usize> = QueuedStateMachine<
+    M,
+    OverflowQueue<ArrayQueue<<M as IntoStateMachine>::Event<'static>, P>>,
+    OverflowQueue<ArrayQueue<<M as IntoStateMachine>::Event<'static>, D>>,
+>;
+
+impl<M, const P: usize, const D: usize> BoundedStateMachine<M, P, D>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Create a state machine with queues of `P` pending and `D` deferred
+    /// events, that apply the given policies when they are full.
+    pub fn bounded(
+        state_machine: InitializedStateMachine<M>,
+        pending: OverflowPolicy,
+        deferred: OverflowPolicy,
+    ) -> Self {
+        Self::new(
+            state_machine,
+            OverflowQueue::new(ArrayQueue::new(), pending),
+            OverflowQueue::new(ArrayQueue::new(), deferred),
+        )
+    }
+
+    /// Get the number of events that were dropped because the pending queue
+    /// or the deferred queue was full.
+    pub fn dropped(&self) -> usize {
+        self.pending.dropped() + self.deferred.dropped()
+    }
+}
+
+impl<M, P, D> core::ops::Deref for QueuedStateMachine<M, P, D>
+where
+    M: IntoStateMachine,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
@@ -0,0 +1,187 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::{EventQueue, IntoStateMachine};
+
+/// Address of a state machine that is registered with a [`Router`]. Machines
+/// are numbered in the order in which they are registered, starting at `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MachineId(pub usize);
+
+/// Events that a state machine emits to other state machines while it handles
+/// an event. This is the context of the state machines that are registered with
+/// a [`Router`], so handlers take it as their `context` argument.
+pub struct Outbox<'a, E> {
+    queue: &'a mut dyn EventQueue<(MachineId, E)>,
+}
+
+impl<'a, E> Outbox<'a, E> {
+    /// Create an outbox that emits events to the given queue, e.g. to
+    /// initialize a state machine before it is registered with a router.
+    pub fn new(queue: &'a mut dyn EventQueue<(MachineId, E)>) -> Self {
+        Self { queue }
+    }
+
+    /// Emit an event to the given state machine. It is delivered once the
+    /// event that is being handled has run to completion. If the queue of the
+    /// router is full the event is handed back.
+    pub fn emit(&mut self, target: MachineId, event: E) -> Result<(), E> {
+        self.queue.push((target, event)).map_err(|(_, event)| event)
+    }
+}
+
+/// A state machine that can be registered with a [`Router`].
+pub trait Routable<E> {
+    /// Handle an event, with the outbox for the events it emits.
+    fn deliver(&mut self, event: &E, outbox: &mut Outbox<'_, E>);
+}
+
+impl<M, E> Routable<E> for InitializedStateMachine<M>
+where
+    for<'ctx> M: IntoStateMachine<Event<'static> = E, Context<'ctx> = Outbox<'ctx, E>>,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    fn deliver(&mut self, event: &E, outbox: &mut Outbox<'_, E>) {
+        self.handle_with_context(event, outbox);
+    }
+}
+
+/// Delivers the events that up to `N` state machines emit to each other, so
+/// they can cooperate like actors without knowing about each other's types.
+///
+/// Handlers emit events through the [`Outbox`] they get as their context. The
+/// events wait in the queue `Q` of the router, and are delivered in the order
+/// in which they were emitted, each one after the previous one has run to
+/// completion.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::{MachineId, Outbox, Router};
+/// # use statig::ArrayQueue;
+/// pub struct Ball;
+///
+/// pub struct Player {
+///     opponent: MachineId,
+///     hits: usize,
+/// }
+///
+/// #[state_machine(initial = "State::playing()")]
+/// impl Player {
+///     #[state]
+///     fn playing(&mut self, context: &mut Outbox<'_, Ball>, event: &Ball) -> Response<State> {
+///         self.hits += 1;
+///         let _ = context.emit(self.opponent, Ball);
+///         Handled
+///     }
+/// }
+///
+/// let mut queue = ArrayQueue::<(MachineId, Ball), 4>::new();
+/// let mut ping = Player { opponent: MachineId(1), hits: 0 }
+///     .uninitialized_state_machine()
+///     .init_with_context(&mut Outbox::new(&mut queue));
+/// let mut pong = Player { opponent: MachineId(0), hits: 0 }
+///     .uninitialized_state_machine()
+///     .init_with_context(&mut Outbox::new(&mut queue));
+///
+/// let mut router: Router<Ball, _, 2> = Router::new(queue);
+/// router.register(&mut ping).ok();
+/// router.register(&mut pong).ok();
+///
+/// router.send(MachineId(0), Ball).ok();
+/// router.run_for(5);
+/// drop(router);
+///
+/// assert_eq!(ping.hits, 3);
+/// assert_eq!(pong.hits, 2);
+/// ```
+pub struct Router<'a, E, Q, const N: usize> {
+    machines: [Option<&'a mut dyn Routable<E>>; N],
+    queue: Q,
+    undeliverable: usize,
+}
+
+impl<'a, E, Q, const N: usize> Router<'a, E, Q, N>
+where
+    Q: EventQueue<(MachineId, E)>,
+{
+    /// Create a router without any state machines, that keeps the events
+    /// that are waiting to be delivered in the given queue.
+    pub fn new(queue: Q) -> Self {
+        Self {
+            machines: [(); N].map(|_| None),
+            queue,
+            undeliverable: 0,
+        }
+    }
+
+    /// Register a state machine and get its address. If all `N` slots are
+    /// taken the state machine is handed back.
+    pub fn register(
+        &mut self,
+        machine: &'a mut dyn Routable<E>,
+    ) -> Result<MachineId, &'a mut dyn Routable<E>> {
+        match self.machines.iter().position(Option::is_none) {
+            Some(index) => {
+                self.machines[index] = Some(machine);
+                Ok(MachineId(index))
+            }
+            None => Err(machine),
+        }
+    }
+
+    /// Send an event to the given state machine from outside. If the queue is
+    /// full the event is handed back.
+    pub fn send(&mut self, target: MachineId, event: E) -> Result<(), E> {
+        self.queue.push((target, event)).map_err(|(_, event)| event)
+    }
+
+    /// Deliver the next event in the queue. Returns `false` if the queue was
+    /// empty.
+    ///
+    /// An event that is addressed to a state machine that isn't registered is
+    /// dropped, and counted in [`undeliverable`](Self::undeliverable).
+    pub fn step(&mut self) -> bool {
+        let Some((target, event)) = self.queue.pop() else {
+            return false;
+        };
+        match self.machines.get_mut(target.0) {
+            Some(Some(machine)) => machine.deliver(
+                &event,
+                &mut Outbox {
+                    queue: &mut self.queue,
+                },
+            ),
+            _ => self.undeliverable += 1,
+        }
+        true
+    }
+
+    /// Deliver events until the queue is empty. Returns the number of events
+    /// that were taken from the queue.
+    ///
+    /// State machines that keep emitting events to each other never leave the
+    /// queue empty, so bound those with [`run_for`](Self::run_for) instead.
+    pub fn run(&mut self) -> usize {
+        let mut delivered = 0;
+        while self.step() {
+            delivered += 1;
+        }
+        delivered
+    }
+
+    /// Deliver at most `limit` events. Returns the number of events that were
+    /// taken from the queue.
+    pub fn run_for(&mut self, limit: usize) -> usize {
+        (0..limit).take_while(|_| self.step()).count()
+    }
+
+    /// Get the number of events that are waiting to be delivered.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Get the number of events that were dropped because no state machine
+    /// is registered at their address.
+    pub fn undeliverable(&self) -> usize {
+        self.undeliverable
+    }
+}
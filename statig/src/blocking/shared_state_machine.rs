@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::blocking::{self, StateMachine};
+use crate::IntoStateMachine;
+
+/// A handle to a state machine that is shared between threads.
+///
+/// The state machine is wrapped in a mutex and the handle can be cloned
+/// cheaply, so multiple subsystems can feed events to the same state machine.
+/// Events are handled one at a time, in the order in which the handles
+/// acquire the lock.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::SharedStateMachine;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event {
+/// #     TimerElapsed,
+/// # }
+/// #
+/// # #[state_machine(initial = "State::on()", state(derive(Clone, Debug, PartialEq)))]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// let state_machine = SharedStateMachine::new(Blinky::default());
+///
+/// let timer = state_machine.clone();
+/// std::thread::spawn(move || timer.handle(&Event::TimerElapsed))
+///     .join()
+///     .unwrap();
+///
+/// assert_eq!(state_machine.state_snapshot(), State::off());
+/// ```
+///
+/// Calling a method of the handle from within a handler or action of the same
+/// state machine deadlocks.
+pub struct SharedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    state_machine: Arc<Mutex<StateMachine<M>>>,
+}
+
+impl<M> SharedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create a shared state machine that will be lazily initialized when the
+    /// first event is handled.
+    pub fn new(shared_storage: M) -> Self {
+        Self::from(StateMachine::new(shared_storage))
+    }
+
+    /// Lock the state machine. A panic in another thread does not prevent the
+    /// state machine from being used, as transitions that were interrupted by
+    /// the panic are tracked by [`is_poisoned`](StateMachine::is_poisoned).
+    fn lock(&self) -> MutexGuard<'_, StateMachine<M>> {
+        self.state_machine
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get exclusive access to the state machine while the closure runs.
+    pub fn with<R>(&self, f: impl FnOnce(&mut StateMachine<M>) -> R) -> R {
+        f(&mut self.lock())
+    }
+}
+
+impl<M> SharedStateMachine<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Handle an event. If the state machine is still uninitialized, it will be
+    /// initialized before handling the event.
+    pub fn handle(&self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.lock().handle(event);
+    }
+
+    /// Handle an event. If the state machine is still uninitialized, it will be
+    /// initialized before handling the event.
+    pub fn handle_with_context(&self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        self.lock().handle_with_context(event, context);
+    }
+
+    /// Get a copy of the current state.
+    pub fn state_snapshot(&self) -> M::State
+    where
+        M::State: Clone,
+    {
+        self.lock().state().clone()
+    }
+}
+
+impl<M> From<StateMachine<M>> for SharedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn from(state_machine: StateMachine<M>) -> Self {
+        Self {
+            state_machine: Arc::new(Mutex::new(state_machine)),
+        }
+    }
+}
+
+impl<M> Clone for SharedStateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    fn clone(&self) -> Self {
+        Self {
+            state_machine: Arc::clone(&self.state_machine),
+        }
+    }
+}
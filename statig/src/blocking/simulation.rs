@@ -0,0 +1,123 @@
+use alloc::vec::Vec;
+
+use crate::blocking::{self, InitializedStateMachine};
+use crate::inner::Inner;
+use crate::IntoStateMachine;
+
+/// A transition that was taken during a [simulation](InitializedStateMachine::simulate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedTransition<S> {
+    /// The index of the event that caused the transition.
+    pub event: usize,
+    /// The state before the event was handled.
+    pub source: S,
+    /// The state after the event was handled.
+    pub target: S,
+}
+
+/// The outcome of a [simulation](InitializedStateMachine::simulate).
+pub struct SimulationResult<M>
+where
+    M: IntoStateMachine,
+{
+    /// The clone of the state machine after it handled the events.
+    pub state_machine: InitializedStateMachine<M>,
+    /// The transitions that were taken, in order.
+    pub transitions: Vec<SimulatedTransition<M::State>>,
+}
+
+impl<M> SimulationResult<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+{
+    /// Get the state the state machine would end up in.
+    pub fn state(&self) -> &M::State {
+        self.state_machine.state()
+    }
+}
+
+impl<M> InitializedStateMachine<M>
+where
+    M: IntoStateMachine + Clone,
+    M::State: blocking::State<M> + Clone,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Handle the given events on a clone of the state machine, and report the
+    /// state it would end up in and the transitions it would take, without
+    /// affecting this state machine. This lets planners and user interfaces
+    /// preview the effect of a sequence of events.
+    ///
+    /// Side effects of the handlers and actions outside of the shared storage
+    /// (e.g. I/O) still happen, so only simulate state machines whose handlers
+    /// keep their effects in the shared storage. This includes the
+    /// [`BEFORE_TRANSITION`](IntoStateMachine::BEFORE_TRANSITION),
+    /// [`AFTER_TRANSITION`](IntoStateMachine::AFTER_TRANSITION) and
+    /// [`ON_TRANSITION`](IntoStateMachine::ON_TRANSITION) hooks, which run for
+    /// the simulated transitions on the clone of the shared storage.
+    ///
+    /// The [`STATE_ID_MIRROR`](IntoStateMachine::STATE_ID_MIRROR) follows the
+    /// simulated states while the simulation runs, and is restored to the state
+    /// of this state machine when it is done.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # #[derive(Default, Clone)]
+    /// # pub struct Blinky;
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(initial = "State::on()", state(derive(Clone, Debug, PartialEq)))]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+    /// #
+    /// #     #[state]
+    /// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+    /// # }
+    /// #
+    /// let state_machine = Blinky::default().uninitialized_state_machine().init();
+    ///
+    /// let result = state_machine.simulate(&[Event, Event, Event]);
+    ///
+    /// assert_eq!(result.state(), &State::off());
+    /// assert_eq!(result.transitions.len(), 3);
+    /// assert_eq!(state_machine.state(), &State::on());
+    /// ```
+    pub fn simulate(&self, events: &[M::Event<'_>]) -> SimulationResult<M>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.simulate_with_context(events, &mut ())
+    }
+
+    /// Handle the given events on a clone of the state machine, and report the
+    /// state it would end up in and the transitions it would take, without
+    /// affecting this state machine.
+    ///
+    /// The handlers and actions do get the given context, so pass a scratch
+    /// context to keep the simulation from affecting the live one.
+    pub fn simulate_with_context(
+        &self,
+        events: &[M::Event<'_>],
+        context: &mut M::Context<'_>,
+    ) -> SimulationResult<M> {
+        let mut state_machine = self.clone();
+        let mut transitions = Vec::new();
+        for (index, event) in events.iter().enumerate() {
+            let source = state_machine.state().clone();
+            if state_machine.inner.handle_with_context(event, context) {
+                transitions.push(SimulatedTransition {
+                    event: index,
+                    source,
+                    target: state_machine.state().clone(),
+                });
+            }
+        }
+        Inner::<M>::mirror_state_id(blocking::State::state_id(self.state()));
+        SimulationResult {
+            state_machine,
+            transitions,
+        }
+    }
+}
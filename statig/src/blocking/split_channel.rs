@@ -0,0 +1,229 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::blocking::{self, InitializedStateMachine};
+use crate::IntoStateMachine;
+
+/// A lock-free channel with a capacity of `N` events, which is split into an
+/// [`EventProducer`] and an [`EventConsumer`], so interrupt service routines
+/// can send events to a task that owns the state machine without locking it.
+/// This fits the ownership model of [RTIC](https://rtic.rs), where the
+/// producer is a local resource of the interrupt handler and the consumer and
+/// the state machine are local resources of the task.
+///
+/// There can only be a single producer and a single consumer, which is
+/// enforced by [`split`](Self::split) taking the channel by mutable
+/// reference. The producer only needs atomic loads and stores, so it also
+/// works on targets without compare-and-swap instructions.
+///
+/// # Memory ordering
+///
+/// Pushing an event releases it to the consumer and popping it acquires it,
+/// so all writes made by the producer before an event is pushed are visible
+/// to the handler that processes the event.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::SplitChannel;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event {
+/// #     TimerElapsed,
+/// # }
+/// #
+/// # #[state_machine(initial = "State::on()")]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// let mut channel: SplitChannel<Event, 8> = SplitChannel::new();
+/// let (mut producer, mut consumer) = channel.split();
+/// let mut state_machine = Blinky.uninitialized_state_machine().init();
+///
+/// // In an interrupt service routine.
+/// let _ = producer.push(Event::TimerElapsed);
+///
+/// // In the task that owns the state machine.
+/// consumer.process(&mut state_machine);
+/// ```
+pub struct SplitChannel<E, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<E>>; N],
+    /// The position of the next event that is popped, in `0..2 * N`.
+    head: AtomicUsize,
+    /// The position of the next event that is pushed, in `0..2 * N`.
+    tail: AtomicUsize,
+}
+
+// The slots are only accessed by the single producer and the single consumer,
+// which hand them over to each other through `head` and `tail`.
+unsafe impl<E, const N: usize> Sync for SplitChannel<E, N> where E: Send {}
+
+impl<E, const N: usize> SplitChannel<E, N> {
+    // The constant is only used to initialize the array of slots.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: UnsafeCell<MaybeUninit<E>> = UnsafeCell::new(MaybeUninit::uninit());
+
+    /// Create an empty channel.
+    pub const fn new() -> Self {
+        Self {
+            slots: [Self::EMPTY; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Split the channel into the producer and the consumer of its events.
+    pub fn split(&mut self) -> (EventProducer<'_, E, N>, EventConsumer<'_, E, N>) {
+        (
+            EventProducer { channel: self },
+            EventConsumer { channel: self },
+        )
+    }
+
+    /// Get the number of events between the given positions. The positions
+    /// are kept in `0..2 * N`, so a full channel can be told apart from an
+    /// empty one.
+    fn len(head: usize, tail: usize) -> usize {
+        (tail + 2 * N - head) % (2 * N)
+    }
+
+    fn next(position: usize) -> usize {
+        (position + 1) % (2 * N)
+    }
+}
+
+impl<E, const N: usize> Default for SplitChannel<E, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, const N: usize> Drop for SplitChannel<E, N> {
+    fn drop(&mut self) {
+        let (mut head, tail) = (*self.head.get_mut(), *self.tail.get_mut());
+        while head != tail {
+            // The events between `head` and `tail` have been pushed but not
+            // popped, so they are initialized.
+            unsafe { self.slots[head % N].get_mut().assume_init_drop() };
+            head = Self::next(head);
+        }
+    }
+}
+
+/// The side of an [`SplitChannel`] that pushes events, e.g. from an interrupt
+/// service routine.
+pub struct EventProducer<'a, E, const N: usize> {
+    channel: &'a SplitChannel<E, N>,
+}
+
+impl<'a, E, const N: usize> EventProducer<'a, E, N> {
+    /// Push an event to the channel. If the channel is full the event is
+    /// handed back.
+    pub fn push(&mut self, event: E) -> Result<(), E> {
+        let tail = self.channel.tail.load(Ordering::Relaxed);
+        let head = self.channel.head.load(Ordering::Acquire);
+        if SplitChannel::<E, N>::len(head, tail) == N {
+            return Err(event);
+        }
+        // The slot at `tail` is not in use by the consumer until the event is
+        // released by storing the new `tail`.
+        unsafe { (*self.channel.slots[tail % N].get()).write(event) };
+        self.channel
+            .tail
+            .store(SplitChannel::<E, N>::next(tail), Ordering::Release);
+        Ok(())
+    }
+
+    /// Get the number of events that are waiting in the channel.
+    pub fn len(&self) -> usize {
+        let head = self.channel.head.load(Ordering::Acquire);
+        let tail = self.channel.tail.load(Ordering::Relaxed);
+        SplitChannel::<E, N>::len(head, tail)
+    }
+
+    /// Check whether the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check whether the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+}
+
+/// The side of an [`SplitChannel`] that pops events, e.g. in the task that
+/// owns the state machine.
+pub struct EventConsumer<'a, E, const N: usize> {
+    channel: &'a SplitChannel<E, N>,
+}
+
+impl<'a, E, const N: usize> EventConsumer<'a, E, N> {
+    /// Pop the oldest event from the channel.
+    pub fn pop(&mut self) -> Option<E> {
+        let head = self.channel.head.load(Ordering::Relaxed);
+        let tail = self.channel.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // The event at `head` was released by the producer, and the slot is
+        // not reused until it is handed back by storing the new `head`.
+        let event = unsafe { (*self.channel.slots[head % N].get()).assume_init_read() };
+        self.channel
+            .head
+            .store(SplitChannel::<E, N>::next(head), Ordering::Release);
+        Some(event)
+    }
+
+    /// Get the number of events that are waiting in the channel.
+    pub fn len(&self) -> usize {
+        let head = self.channel.head.load(Ordering::Relaxed);
+        let tail = self.channel.tail.load(Ordering::Acquire);
+        SplitChannel::<E, N>::len(head, tail)
+    }
+
+    /// Check whether the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Handle all the events in the channel, including the ones that are
+    /// pushed while processing. Returns the number of events that were
+    /// handled.
+    pub fn process<M>(&mut self, state_machine: &mut InitializedStateMachine<M>) -> usize
+    where
+        M: IntoStateMachine<Event<'static> = E>,
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M::State: blocking::State<M>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.process_with_context(state_machine, &mut ())
+    }
+
+    /// Handle all the events in the channel, including the ones that are
+    /// pushed while processing. Returns the number of events that were
+    /// handled.
+    pub fn process_with_context<M>(
+        &mut self,
+        state_machine: &mut InitializedStateMachine<M>,
+        context: &mut M::Context<'_>,
+    ) -> usize
+    where
+        M: IntoStateMachine<Event<'static> = E>,
+        M::State: blocking::State<M>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        let mut handled = 0;
+        while let Some(event) = self.pop() {
+            state_machine.handle_with_context(&event, context);
+            handled += 1;
+        }
+        handled
+    }
+}
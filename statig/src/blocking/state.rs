@@ -18,6 +18,22 @@ where
         context: &mut M::Context<'_>,
     ) -> Response<Self>;
 
+    /// Call the handler for the current state and let it handle the given
+    /// event. Also returns how many levels above the current state the
+    /// transition that was returned is declared, which is the source of the
+    /// transition with UML [`Semantics`](crate::Semantics).
+    ///
+    /// The default implementation returns `0`. The `state_machine` macro
+    /// overrides it when the state machine has UML semantics.
+    fn call_handler_with_source(
+        &mut self,
+        shared_storage: &mut M,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Response<Self>, usize) {
+        (self.call_handler(shared_storage, event, context), 0)
+    }
+
     #[allow(unused)]
     /// Call the entry action for the current state.
     fn call_entry_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
@@ -26,10 +42,161 @@ where
     /// Call the exit action for the current state.
     fn call_exit_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
 
+    #[allow(unused)]
+    /// Call the activity for the current state, which is called on every tick
+    /// while the state is active.
+    fn call_activity(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
+
     /// Return the superstate of the current state, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>> {
         None
     }
+
+    /// Whether every state can be recreated from its id with
+    /// [`from_state_id`](Self::from_state_id), because none of them has
+    /// state-local storage.
+    ///
+    /// The `state_machine` macro sets it when no state has state-local storage.
+    const RECREATABLE: bool = false;
+
+    /// Return the id of the current state, if the states are numbered.
+    ///
+    /// The `state_machine` macro numbers the states in the order in which they
+    /// are declared, starting from zero.
+    fn state_id(&self) -> Option<usize> {
+        None
+    }
+
+    /// Create the state with the given id, if it has no state-local storage.
+    ///
+    /// The `state_machine` macro implements it for every state without
+    /// state-local storage.
+    fn from_state_id(_state_id: usize) -> Option<Self> {
+        None
+    }
+
+    /// Return the depth of the current state if it is known up front, so it
+    /// doesn't have to be computed by walking up the hierarchy.
+    ///
+    /// The `state_machine` macro overrides it with a lookup in a const table.
+    fn lookup_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return the transition path from the current state to the target state
+    /// if it is known up front, so it doesn't have to be computed by walking up
+    /// the hierarchy.
+    ///
+    /// The `state_machine` macro overrides it with a lookup in a const table
+    /// of the transitions to the states that the handlers of the state and its
+    /// superstates reference.
+    fn lookup_transition_path(&self, _target: &Self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Call the entry action of the superstate that is `height` levels above
+    /// the current state.
+    ///
+    /// The default implementation walks up the hierarchy recursively. The
+    /// `state_machine` macro overrides it with a flat lookup, so no recursion
+    /// happens when entering or exiting states.
+    fn call_ancestor_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) where
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => (),
+            1 => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_entry_action(shared_storage, context);
+                }
+            }
+            _ => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_ancestor_entry_action(shared_storage, context, height - 1);
+                }
+            }
+        }
+    }
+
+    /// Call the exit action of the superstate that is `height` levels above
+    /// the current state.
+    ///
+    /// The default implementation walks up the hierarchy recursively. The
+    /// `state_machine` macro overrides it with a flat lookup, so no recursion
+    /// happens when entering or exiting states.
+    fn call_ancestor_exit_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) where
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => (),
+            1 => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_exit_action(shared_storage, context);
+                }
+            }
+            _ => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_ancestor_exit_action(shared_storage, context, height - 1);
+                }
+            }
+        }
+    }
+
+    /// Call the entry action of the current state, or of the superstate that
+    /// is `height` levels above it, and return whether the transition can
+    /// proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) -> bool
+    where
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => self.call_entry_action(shared_storage, context),
+            _ => self.call_ancestor_entry_action(shared_storage, context, height),
+        }
+        true
+    }
+
+    /// Call the exit action of the current state, or of the superstate that
+    /// is `height` levels above it, and return whether the transition can
+    /// proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_exit_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) -> bool
+    where
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => self.call_exit_action(shared_storage, context),
+            _ => self.call_ancestor_exit_action(shared_storage, context, height),
+        }
+        true
+    }
 }
 
 /// Extensions for `State` trait.
@@ -46,6 +213,10 @@ where
 
     /// Get the depth of the current state.
     fn depth(&mut self) -> usize {
+        if let Some(depth) = self.lookup_depth() {
+            return depth;
+        }
+
         match self.superstate() {
             Some(mut superstate) => superstate.depth() + 1,
             None => 1,
@@ -68,6 +239,10 @@ where
     /// the target state. The returned tuple respectively represents the levels that need
     /// to be exited, and the levels that need to be entered.
     fn transition_path(&mut self, target: &mut Self) -> (usize, usize) {
+        if let Some(path) = self.lookup_transition_path(target) {
+            return path;
+        }
+
         if Self::same_state(self, target) {
             return (1, 1);
         }
@@ -93,17 +268,32 @@ where
         event: &M::Event<'_>,
         context: &mut M::Context<'_>,
     ) -> Response<Self>
+    where
+        Self: Sized,
+    {
+        self.handle_with_source(shared_storage, event, context).0
+    }
+
+    /// Handle the given event in the current state. Also returns how many
+    /// levels above the current state the state or superstate is that took
+    /// the transition, if one was taken.
+    fn handle_with_source(
+        &mut self,
+        shared_storage: &mut M,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Response<Self>, usize)
     where
         Self: Sized,
     {
         M::BEFORE_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
-        let response = self.call_handler(shared_storage, event, context);
+        let (response, source) = self.call_handler_with_source(shared_storage, event, context);
 
         M::AFTER_DISPATCH(shared_storage, StateOrSuperstate::State(self), event);
 
         match response {
-            Response::Handled => Response::Handled,
+            Response::Handled => (Response::Handled, 0),
             Response::Super => match self.superstate() {
                 Some(mut superstate) => {
                     M::BEFORE_DISPATCH(
@@ -112,7 +302,8 @@ where
                         event,
                     );
 
-                    let response = superstate.handle(shared_storage, event, context);
+                    let (response, source) =
+                        superstate.handle_with_source(shared_storage, event, context);
 
                     M::AFTER_DISPATCH(
                         shared_storage,
@@ -120,42 +311,45 @@ where
                         event,
                     );
 
-                    response
+                    (response, source + 1)
+                }
+                None => (Response::Super, 0),
+            },
+            Response::Transition(state) => (Response::Transition(state), source),
+            Response::Completed => match self.superstate() {
+                Some(mut superstate) => {
+                    let (response, source) = superstate.complete_with_source();
+                    (response, source + 1)
                 }
-                None => Response::Super,
+                None => (Response::Completed, 0),
             },
-            Response::Transition(state) => Response::Transition(state),
         }
     }
 
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// entry actions while going back down to the current state.
-    fn enter(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>, levels: usize) {
-        match levels {
-            0 => (),
-            1 => self.call_entry_action(shared_storage, context),
-            _ => {
-                if let Some(mut superstate) = self.superstate() {
-                    superstate.enter(shared_storage, context, levels - 1);
-                }
-                self.call_entry_action(shared_storage, context);
-            }
-        }
+    /// entry actions while going back down to the current state. Returns `false`
+    /// when an action failed and the remaining actions were skipped.
+    fn enter(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        levels: usize,
+    ) -> bool {
+        (0..levels)
+            .rev()
+            .all(|height| self.try_call_entry_action(shared_storage, context, height))
     }
 
     /// Starting from the current state, climb a given amount of levels and execute all the
-    /// the exit actions while going up to a certain superstate.
-    fn exit(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>, levels: usize) {
-        match levels {
-            0 => (),
-            1 => self.call_exit_action(shared_storage, context),
-            _ => {
-                self.call_exit_action(shared_storage, context);
-                if let Some(mut superstate) = self.superstate() {
-                    superstate.exit(shared_storage, context, levels - 1);
-                }
-            }
-        }
+    /// the exit actions while going up to a certain superstate. Returns `false`
+    /// when an action failed and the remaining actions were skipped.
+    fn exit(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        levels: usize,
+    ) -> bool {
+        (0..levels).all(|height| self.try_call_exit_action(shared_storage, context, height))
     }
 }
 
@@ -1,6 +1,8 @@
 use core::fmt::Debug;
 
 use super::blocking;
+#[cfg(feature = "std")]
+use crate::HandlerPanic;
 use crate::{Inner, IntoStateMachine};
 
 /// A state machine where the shared storage is of type `Self`.
@@ -13,24 +15,13 @@ where
     where
         Self: Sized,
     {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
-        StateMachine {
-            inner,
-            initialized: false,
-        }
+        StateMachine::new(self)
     }
 
     /// Create an uninitialized state machine that must be explicitly initialized with
     /// [`init`](UninitializedStateMachine::init).
     fn uninitialized_state_machine(self) -> UninitializedStateMachine<Self> {
-        let inner = Inner {
-            shared_storage: self,
-            state: Self::INITIAL,
-        };
-        UninitializedStateMachine { inner }
+        UninitializedStateMachine::new(self)
     }
 }
 
@@ -50,6 +41,45 @@ where
     initialized: bool,
 }
 
+impl<M> StateMachine<M>
+where
+    M: IntoStateMachine,
+{
+    /// Create a state machine that will be lazily initialized.
+    ///
+    /// Unlike [`state_machine`](IntoStateMachineExt::state_machine) this is a
+    /// `const fn`, so the state machine can be stored in a `static`.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # use statig::blocking::StateMachine;
+    /// # use std::sync::Mutex;
+    /// # pub struct Blinky {
+    /// #     led: bool,
+    /// # }
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(initial = "State::on()")]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { Handled }
+    /// # }
+    /// #
+    /// static STATE_MACHINE: Mutex<StateMachine<Blinky>> =
+    ///     Mutex::new(StateMachine::new(Blinky { led: false }));
+    ///
+    /// STATE_MACHINE.lock().unwrap().handle(&Event);
+    /// ```
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self {
+            inner,
+            initialized: false,
+        }
+    }
+}
+
 impl<M> StateMachine<M>
 where
     M: IntoStateMachine,
@@ -76,6 +106,12 @@ where
 
     /// Handle an event. If the state machine is still uninitialized, it will be initialized
     /// before handling the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
     pub fn handle(&mut self, event: &M::Event<'_>)
     where
         for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
@@ -85,6 +121,12 @@ where
 
     /// Handle an event. If the state machine is still uninitialized, it will be initialized
     /// before handling the event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
     pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
         if !self.initialized {
             self.inner.init_with_context(context);
@@ -93,11 +135,65 @@ where
         self.inner.handle_with_context(event, context);
     }
 
+    /// Convert a value into an event and handle it. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub fn handle_from<'evt>(&mut self, event: impl Into<M::Event<'evt>>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(&event.into(), &mut ());
+    }
+
+    /// Convert a value into an event and handle it. If the state machine is still
+    /// uninitialized, it will be initialized before handling the event.
+    pub fn handle_from_with_context<'evt>(
+        &mut self,
+        event: impl Into<M::Event<'evt>>,
+        context: &mut M::Context<'_>,
+    ) {
+        self.handle_with_context(&event.into(), context);
+    }
+
+    /// Handle an event and catch any panic that occurs in a handler or action.
+    /// If it does, the state machine is rolled back to the state it was in
+    /// before the event was dispatched and the panic is returned as an error.
+    #[cfg(feature = "std")]
+    pub fn try_handle(&mut self, event: &M::Event<'_>) -> Result<(), HandlerPanic>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        M::State: Clone,
+    {
+        self.try_handle_with_context(event, &mut ())
+    }
+
+    /// Handle an event and catch any panic that occurs in a handler or action.
+    /// If it does, the state machine is rolled back to the state it was in
+    /// before the event was dispatched and the panic is returned as an error.
+    #[cfg(feature = "std")]
+    pub fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), HandlerPanic>
+    where
+        M::State: Clone,
+    {
+        let initialized = self.initialized;
+        let result = self.inner.catch_unwind(|inner| {
+            if !initialized {
+                inner.init_with_context(context);
+            }
+            inner.handle_with_context(event, context);
+        });
+        self.initialized = initialized || result.is_ok();
+        result
+    }
+
     pub fn step(&mut self)
     where
         for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
     {
-        self.handle_with_context(&(), &mut ());
+        self.step_with_context(&mut ());
     }
 
     pub fn step_with_context(&mut self, context: &mut M::Context<'_>)
@@ -105,6 +201,35 @@ where
         for<'evt> M: IntoStateMachine<Event<'evt> = ()>,
     {
         self.handle_with_context(&(), context);
+        self.inner.tick_with_context(context);
+    }
+
+    /// Call the activity of the current state. If the state machine is still
+    /// uninitialized, it will be initialized first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn tick(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.tick_with_context(&mut ());
+    }
+
+    /// Call the activity of the current state. If the state machine is still
+    /// uninitialized, it will be initialized first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn tick_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.init_with_context(context);
+        self.inner.tick_with_context(context);
     }
 
     /// Get the current state.
@@ -112,6 +237,73 @@ where
         &self.inner.state
     }
 
+    /// Check if the state machine is poisoned. This happens when a transition
+    /// was interrupted by a panic, or by an action that failed with
+    /// `action_failure = "abort"`, leaving the state machine in between the
+    /// source and the target state. A poisoned state machine panics when it
+    /// handles an event, ticks or shuts down, until it is brought back into a
+    /// well-defined state with [`recover`](Self::recover).
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.poisoned
+    }
+
+    /// Check if the state machine reached a terminate pseudo-state. A
+    /// terminated state machine ignores the events it handles, and shutting
+    /// it down executes no exit actions.
+    pub fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub fn recover(&mut self, state: M::State)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.recover_with_context(state, &mut ());
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub fn recover_with_context(&mut self, state: M::State, context: &mut M::Context<'_>) {
+        self.inner.recover_with_context(state, context);
+        self.initialized = true;
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage. If the
+    /// state machine was never initialized, no exit actions are executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn shutdown(self) -> M
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.shutdown_with_context(&mut ())
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage. If the
+    /// state machine was never initialized, no exit actions are executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn shutdown_with_context(mut self, context: &mut M::Context<'_>) -> M {
+        if self.initialized {
+            self.inner.shutdown_with_context(context);
+        }
+        self.inner.shared_storage
+    }
+
     /// Get a reference to the [StateMachine]'s underlying type.
     ///
     /// ```
@@ -238,10 +430,7 @@ where
     M: IntoStateMachine + Default,
 {
     fn default() -> Self {
-        let inner = Inner {
-            shared_storage: M::default(),
-            state: M::INITIAL,
-        };
+        let inner = Inner::new(M::default(), M::INITIAL);
         Self {
             inner,
             initialized: false,
@@ -308,53 +497,30 @@ pub struct InitializedStateMachine<M>
 where
     M: IntoStateMachine,
 {
-    inner: Inner<M>,
+    pub(crate) inner: Inner<M>,
 }
 
 impl<M> InitializedStateMachine<M>
 where
     M: IntoStateMachine,
-    M::State: blocking::State<M>,
 {
-    /// Handle the given event.
-    pub fn handle(&mut self, event: &M::Event<'_>)
-    where
-        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        self.handle_with_context(event, &mut ());
-    }
-
-    /// Handle the given event.
-    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>)
-    where
-        M: IntoStateMachine,
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        self.inner.handle_with_context(event, context);
-    }
-
-    /// This is the same as `handle(())` in the case `Event` is of type `()`.
-    pub fn step(&mut self)
-    where
-        for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        self.handle(&());
+    /// Get a reference to the shared storage of the state machine, the same
+    /// as [`inner`](Self::inner). Unlike a method call through `Deref`, it
+    /// can't be shadowed by a method of the state machine, which keeps generic
+    /// code unambiguous.
+    pub fn shared(&self) -> &M {
+        self.inner()
     }
 
-    /// This is the same as `handle(())` in the case `Event` is of type `()`.
-    pub fn step_with_context(&mut self, context: &mut M::Context<'_>)
-    where
-        for<'evt> M: IntoStateMachine<Event<'evt> = ()>,
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        self.handle_with_context(&(), context);
-    }
-
-    /// Get an immutable reference to the current state of the state machine.
-    pub fn state(&self) -> &M::State {
-        &self.inner.state
+    /// Get a mutable reference to the shared storage of the state machine, the
+    /// same as [`inner_mut`](Self::inner_mut).
+    ///
+    /// # Safety
+    ///
+    /// - The user is responsible for validating that mutating a
+    ///   [InitializedStateMachine] does not break any invariants.
+    pub unsafe fn shared_mut(&mut self) -> &mut M {
+        self.inner_mut()
     }
 
     /// Get a reference to the [InitializedStateMachine]'s underlying type.
@@ -416,6 +582,257 @@ where
     pub unsafe fn inner_mut(&mut self) -> &mut M {
         &mut self.inner.shared_storage
     }
+}
+
+impl<M> InitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+{
+    /// Handle the given event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_context(event, &mut ());
+    }
+
+    /// Handle the given event.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>)
+    where
+        M: IntoStateMachine,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.handle_with_context(event, context);
+    }
+
+    /// Convert a value into an event and handle it.
+    pub fn handle_from<'evt>(&mut self, event: impl Into<M::Event<'evt>>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_context(&event.into(), &mut ());
+    }
+
+    /// Convert a value into an event and handle it.
+    pub fn handle_from_with_context<'evt>(
+        &mut self,
+        event: impl Into<M::Event<'evt>>,
+        context: &mut M::Context<'_>,
+    ) where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_context(&event.into(), context);
+    }
+
+    /// Handle the same event in each of the given state machines.
+    ///
+    /// A state machine only stores its state and shared storage, while the
+    /// dispatch logic is shared by all instances of the same type. Keeping many
+    /// instances in a contiguous slice and dispatching one event to all of them
+    /// keeps both the code and the data hot in the cache.
+    pub fn handle_all(state_machines: &mut [Self], event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        for state_machine in state_machines {
+            state_machine.inner.handle_with_context(event, &mut ());
+        }
+    }
+
+    /// Handle the given event and catch any panic that occurs in a handler or
+    /// action. If it does, the state machine is rolled back to the state it was
+    /// in before the event was dispatched and the panic is returned as an error.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # #[derive(Default)]
+    /// # pub struct Blinky;
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(initial = "State::on()", state(derive(Clone, Debug, PartialEq)))]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { panic!("oops") }
+    /// # }
+    /// #
+    /// let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+    ///
+    /// let error = state_machine.try_handle(&Event).unwrap_err();
+    ///
+    /// assert_eq!(error.message(), Some("oops"));
+    /// assert_eq!(state_machine.state(), &State::on());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_handle(&mut self, event: &M::Event<'_>) -> Result<(), HandlerPanic>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: Clone,
+    {
+        self.try_handle_with_context(event, &mut ())
+    }
+
+    /// Handle the given event and catch any panic that occurs in a handler or
+    /// action. If it does, the state machine is rolled back to the state it was
+    /// in before the event was dispatched and the panic is returned as an error.
+    #[cfg(feature = "std")]
+    pub fn try_handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), HandlerPanic>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+        M::State: Clone,
+    {
+        self.inner.catch_unwind(|inner| {
+            inner.handle_with_context(event, context);
+        })
+    }
+
+    /// This is the same as `handle(())` followed by `tick()` in the case
+    /// `Event` is of type `()`.
+    pub fn step(&mut self)
+    where
+        for<'evt, 'ctx> M: IntoStateMachine<Event<'evt> = (), Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.step_with_context(&mut ());
+    }
+
+    /// This is the same as `handle(())` followed by `tick()` in the case
+    /// `Event` is of type `()`.
+    pub fn step_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'evt> M: IntoStateMachine<Event<'evt> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.handle_with_context(&(), context);
+        self.tick_with_context(context);
+    }
+
+    /// Call the activity of the current state, which is called on every tick
+    /// while the state is active (between its entry and exit actions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn tick(&mut self)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.tick_with_context(&mut ());
+    }
+
+    /// Call the activity of the current state, which is called on every tick
+    /// while the state is active (between its entry and exit actions).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn tick_with_context(&mut self, context: &mut M::Context<'_>)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.tick_with_context(context);
+    }
+
+    /// Get an immutable reference to the current state of the state machine.
+    pub fn state(&self) -> &M::State {
+        &self.inner.state
+    }
+
+    /// Check if the state machine is poisoned. This happens when a transition
+    /// was interrupted by a panic, or by an action that failed with
+    /// `action_failure = "abort"`, leaving the state machine in between the
+    /// source and the target state. A poisoned state machine panics when it
+    /// handles an event, ticks or shuts down, until it is brought back into a
+    /// well-defined state with [`recover`](Self::recover).
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.poisoned
+    }
+
+    /// Check if the state machine reached a terminate pseudo-state. A
+    /// terminated state machine ignores the events it handles, and shutting
+    /// it down executes no exit actions.
+    pub fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub fn recover(&mut self, state: M::State)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.recover_with_context(state, &mut ());
+    }
+
+    /// Bring the state machine into the given state by executing all entry
+    /// actions towards it, as when initializing. No exit actions are executed
+    /// for the current state. This also clears the poisoning.
+    pub fn recover_with_context(&mut self, state: M::State, context: &mut M::Context<'_>)
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.recover_with_context(state, context);
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn shutdown(self) -> M
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.shutdown_with_context(&mut ())
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top, and return the shared storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state machine is poisoned (see
+    /// [`is_poisoned`](Self::is_poisoned)). Bring it back into a well-defined
+    /// state with [`recover`](Self::recover) first.
+    pub fn shutdown_with_context(mut self, context: &mut M::Context<'_>) -> M
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        self.inner.shutdown_with_context(context);
+        self.inner.shared_storage
+    }
 
     /// Get a mutable reference to the [InitializedStateMachine]'s current state.
     ///
@@ -550,14 +967,16 @@ where
 impl<M> UninitializedStateMachine<M>
 where
     M: IntoStateMachine,
-    M::State: blocking::State<M>,
 {
-    /// Initialize the state machine by executing all entry actions towards
-    /// the initial state.
+    /// Create an uninitialized state machine that must be explicitly initialized
+    /// with [`init`](Self::init).
+    ///
+    /// Unlike [`uninitialized_state_machine`](IntoStateMachineExt::uninitialized_state_machine)
+    /// this is a `const fn`, so the state machine can be stored in a `static`.
     ///
     /// ```
     /// # use statig::prelude::*;
-    /// # #[derive(Default)]
+    /// # use statig::blocking::UninitializedStateMachine;
     /// # pub struct Blinky {
     /// #     led: bool,
     /// # }
@@ -570,24 +989,60 @@ where
     /// #     fn on(event: &Event) -> Response<State> { Handled }
     /// # }
     /// #
+    /// const UNINITIALIZED_STATE_MACHINE: UninitializedStateMachine<Blinky> =
+    ///     UninitializedStateMachine::new(Blinky { led: false });
+    ///
+    /// let state_machine = UNINITIALIZED_STATE_MACHINE.init();
+    /// ```
+    pub const fn new(shared_storage: M) -> Self {
+        let inner = Inner::new(shared_storage, M::INITIAL);
+        Self { inner }
+    }
+
+    /// Get a reference to the shared storage of the state machine, the same
+    /// as [`inner`](Self::inner). Unlike a method call through `Deref`, it
+    /// can't be shadowed by a method of the state machine, which keeps generic
+    /// code unambiguous.
+    pub fn shared(&self) -> &M {
+        self.inner()
+    }
+
+    /// Get a mutable reference to the shared storage of the state machine, the
+    /// same as [`inner_mut`](Self::inner_mut), e.g. to configure it before the
+    /// state machine is initialized.
+    pub fn shared_mut(&mut self) -> &mut M {
+        self.inner_mut()
+    }
+
+    /// Get a reference to the [UninitializedStateMachine]'s underlying type.
+    ///
+    /// ```
+    /// # use statig::prelude::*;
+    /// # #[derive(Default)]
+    /// # pub struct Blinky {
+    /// #     led: bool,
+    /// # }
+    /// #
+    /// # pub struct Event;
+    /// #
+    /// # #[state_machine(
+    /// #     initial = "State::on()",
+    /// #     state(derive(Debug, PartialEq, Eq))
+    /// # )]
+    /// # impl Blinky {
+    /// #     #[state]
+    /// #     fn on(event: &Event) -> Response<State> { Handled }
+    /// # }
+    /// #
     /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// // The uninitialized state machine is consumed to create the initialized
-    /// // state machine.
-    /// let initialized_state_machine = uninitialized_state_machine.init();
+    /// assert_eq!(uninitialized_state_machine.inner().led, false);
     /// ```
-    pub fn init(self) -> InitializedStateMachine<M>
-    where
-        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
-        state_machine.inner.init_with_context(&mut ());
-        state_machine
+    pub fn inner(&self) -> &M {
+        &self.inner.shared_storage
     }
 
-    /// Initialize the state machine by executing all entry actions towards
-    /// the initial state.
+    /// Get a mutable reference to the [UninitializedStateMachine]'s underlying type.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -604,22 +1059,22 @@ where
     /// #     fn on(event: &Event) -> Response<State> { Handled }
     /// # }
     /// #
-    /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
+    /// let mut uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// // The uninitialized state machine is consumed to create the initialized
-    /// // state machine.
-    /// let initialized_state_machine = uninitialized_state_machine.init();
+    /// uninitialized_state_machine.inner_mut().led = true;
     /// ```
-    pub fn init_with_context(self, context: &mut M::Context<'_>) -> InitializedStateMachine<M>
-    where
-        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
-    {
-        let mut state_machine = InitializedStateMachine { inner: self.inner };
-        state_machine.inner.init_with_context(context);
-        state_machine
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.inner.shared_storage
     }
+}
 
-    /// Get a reference to the [UninitializedStateMachine]'s underlying type.
+impl<M> UninitializedStateMachine<M>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+{
+    /// Initialize the state machine by executing all entry actions towards
+    /// the initial state.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -630,10 +1085,7 @@ where
     /// #
     /// # pub struct Event;
     /// #
-    /// # #[state_machine(
-    /// #     initial = "State::on()",
-    /// #     state(derive(Debug, PartialEq, Eq))
-    /// # )]
+    /// # #[state_machine(initial = "State::on()")]
     /// # impl Blinky {
     /// #     #[state]
     /// #     fn on(event: &Event) -> Response<State> { Handled }
@@ -641,13 +1093,22 @@ where
     /// #
     /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// assert_eq!(uninitialized_state_machine.inner().led, false);
+    /// // The uninitialized state machine is consumed to create the initialized
+    /// // state machine.
+    /// let initialized_state_machine = uninitialized_state_machine.init();
     /// ```
-    pub fn inner(&self) -> &M {
-        &self.inner.shared_storage
+    pub fn init(self) -> InitializedStateMachine<M>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        state_machine.inner.init_with_context(&mut ());
+        state_machine
     }
 
-    /// Get a mutable reference to the [UninitializedStateMachine]'s underlying type.
+    /// Initialize the state machine by executing all entry actions towards
+    /// the initial state.
     ///
     /// ```
     /// # use statig::prelude::*;
@@ -664,12 +1125,19 @@ where
     /// #     fn on(event: &Event) -> Response<State> { Handled }
     /// # }
     /// #
-    /// let mut uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
+    /// let uninitialized_state_machine = Blinky::default().uninitialized_state_machine();
     ///
-    /// uninitialized_state_machine.inner_mut().led = true;
+    /// // The uninitialized state machine is consumed to create the initialized
+    /// // state machine.
+    /// let initialized_state_machine = uninitialized_state_machine.init();
     /// ```
-    pub fn inner_mut(&mut self) -> &mut M {
-        &mut self.inner.shared_storage
+    pub fn init_with_context(self, context: &mut M::Context<'_>) -> InitializedStateMachine<M>
+    where
+        for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    {
+        let mut state_machine = InitializedStateMachine { inner: self.inner };
+        state_machine.inner.init_with_context(context);
+        state_machine
     }
 
     /// Get a mutable reference to the [StateMachine]'s current state.
@@ -25,6 +25,36 @@ where
     /// Call the exit action for the current superstate.
     fn call_exit_action(&mut self, shared_storage: &mut M, context: &mut M::Context<'_>) {}
 
+    /// Call the entry action for the current superstate and return whether the
+    /// transition can proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+    ) -> bool {
+        self.call_entry_action(shared_storage, context);
+        true
+    }
+
+    /// Call the exit action for the current superstate and return whether the
+    /// transition can proceed.
+    ///
+    /// The default implementation never fails. The `state_machine` macro
+    /// overrides it when actions are fallible and a failure aborts the
+    /// transition.
+    fn try_call_exit_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+    ) -> bool {
+        self.call_exit_action(shared_storage, context);
+        true
+    }
+
     /// Return the superstate of the current superstate, if there is one.
     fn superstate(&mut self) -> Option<M::Superstate<'_>>
     where
@@ -32,6 +62,64 @@ where
     {
         None
     }
+
+    /// Return the target of the completion transition of the current
+    /// superstate, if it declares one.
+    fn completion(&mut self) -> Option<M::State> {
+        None
+    }
+
+    /// Call the entry action of the superstate that is `height` levels above
+    /// the current superstate.
+    fn call_ancestor_entry_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) where
+        Self: Sized,
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => (),
+            1 => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_entry_action(shared_storage, context);
+                }
+            }
+            _ => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_ancestor_entry_action(shared_storage, context, height - 1);
+                }
+            }
+        }
+    }
+
+    /// Call the exit action of the superstate that is `height` levels above
+    /// the current superstate.
+    fn call_ancestor_exit_action(
+        &mut self,
+        shared_storage: &mut M,
+        context: &mut M::Context<'_>,
+        height: usize,
+    ) where
+        Self: Sized,
+        for<'b> M::Superstate<'b>: Superstate<M>,
+    {
+        match height {
+            0 => (),
+            1 => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_exit_action(shared_storage, context);
+                }
+            }
+            _ => {
+                if let Some(mut superstate) = self.superstate() {
+                    superstate.call_ancestor_exit_action(shared_storage, context, height - 1);
+                }
+            }
+        }
+    }
 }
 
 /// Extensions for `Superstate` trait.
@@ -87,6 +175,28 @@ where
         }
     }
 
+    /// Take the completion transition of the nearest superstate that declares
+    /// one, starting from the current superstate.
+    fn complete(&mut self) -> Response<M::State> {
+        self.complete_with_source().0
+    }
+
+    /// Take the completion transition of the nearest superstate that declares
+    /// one, starting from the current superstate. Also returns how many levels
+    /// above the current superstate that superstate is.
+    fn complete_with_source(&mut self) -> (Response<M::State>, usize) {
+        match self.completion() {
+            Some(state) => (Response::Transition(state), 0),
+            None => match self.superstate() {
+                Some(mut superstate) => {
+                    let (response, source) = superstate.complete_with_source();
+                    (response, source + 1)
+                }
+                None => (Response::Completed, 0),
+            },
+        }
+    }
+
     /// Handle the given event in the current superstate.
     fn handle(
         &mut self,
@@ -94,13 +204,28 @@ where
         event: &M::Event<'_>,
         context: &mut M::Context<'_>,
     ) -> Response<M::State>
+    where
+        Self: Sized,
+    {
+        self.handle_with_source(shared_storage, event, context).0
+    }
+
+    /// Handle the given event in the current superstate. Also returns how many
+    /// levels above the current superstate the superstate is that took the
+    /// transition, if one was taken.
+    fn handle_with_source(
+        &mut self,
+        shared_storage: &mut M,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> (Response<M::State>, usize)
     where
         Self: Sized,
     {
         let response = self.call_handler(shared_storage, event, context);
 
         match response {
-            Response::Handled => Response::Handled,
+            Response::Handled => (Response::Handled, 0),
             Response::Super => match self.superstate() {
                 Some(mut superstate) => {
                     M::BEFORE_DISPATCH(
@@ -109,7 +234,8 @@ where
                         event,
                     );
 
-                    let response = superstate.handle(shared_storage, event, context);
+                    let (response, source) =
+                        superstate.handle_with_source(shared_storage, event, context);
 
                     M::AFTER_DISPATCH(
                         shared_storage,
@@ -117,11 +243,18 @@ where
                         event,
                     );
 
-                    response
+                    (response, source + 1)
+                }
+                None => (Response::Super, 0),
+            },
+            Response::Transition(state) => (Response::Transition(state), 0),
+            Response::Completed => match self.superstate() {
+                Some(mut superstate) => {
+                    let (response, source) = superstate.complete_with_source();
+                    (response, source + 1)
                 }
-                None => Response::Super,
+                None => (Response::Completed, 0),
             },
-            Response::Transition(state) => Response::Transition(state),
         }
     }
 
@@ -0,0 +1,214 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::IntoStateMachine;
+
+/// Which children a [`Supervisor`] restarts when one of them fails.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RestartStrategy {
+    /// Only restart the child that failed.
+    #[default]
+    OneForOne,
+    /// Restart all the children, for children that depend on each other.
+    OneForAll,
+}
+
+/// Returned by a [`Supervisor`] when a child keeps failing, so the failure can
+/// be handled one level up, e.g. by the supervisor of the supervisor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Escalation {
+    /// The index of the child that failed.
+    pub child: usize,
+    /// The number of times the child has failed.
+    pub failures: usize,
+}
+
+/// Owns `N` state machines and restarts them when they fail, in the style of
+/// the supervision trees of Erlang/OTP.
+///
+/// A child has failed when it reaches a state for which `failed` returns
+/// `true`, or when it is poisoned. It is restarted by bringing it back into its
+/// initial state as when it is initialized, without running the exit actions
+/// of the failed state. Once a child has failed more than `max_restarts`
+/// times, the supervisor stops restarting it and returns an [`Escalation`].
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::{Escalation, Supervisor};
+/// # #[derive(Default)]
+/// # pub struct Link;
+/// #
+/// # pub enum Event { Connect, Drop }
+/// #
+/// # #[state_machine(initial = "State::connecting()", state(derive(Debug, PartialEq)))]
+/// # impl Link {
+/// #     #[state]
+/// #     fn connecting(event: &Event) -> Response<State> {
+/// #         match event {
+/// #             Event::Connect => Transition(State::connected()),
+/// #             Event::Drop => Transition(State::failed()),
+/// #         }
+/// #     }
+/// #
+/// #     #[state]
+/// #     fn connected(event: &Event) -> Response<State> {
+/// #         match event {
+/// #             Event::Connect => Handled,
+/// #             Event::Drop => Transition(State::failed()),
+/// #         }
+/// #     }
+/// #
+/// #     #[state]
+/// #     fn failed(event: &Event) -> Response<State> { Handled }
+/// # }
+/// #
+/// let children = [Link.uninitialized_state_machine().init()];
+/// let mut supervisor = Supervisor::new(children, |state| matches!(state, State::Failed {}), 1);
+///
+/// assert_eq!(supervisor.handle(0, &Event::Drop), Ok(()));
+/// assert_eq!(supervisor.child(0).state(), &State::connecting());
+///
+/// assert_eq!(
+///     supervisor.handle(0, &Event::Drop),
+///     Err(Escalation { child: 0, failures: 2 })
+/// );
+/// ```
+pub struct Supervisor<M, const N: usize>
+where
+    M: IntoStateMachine,
+{
+    children: [InitializedStateMachine<M>; N],
+    failures: [usize; N],
+    failed: fn(&M::State) -> bool,
+    max_restarts: usize,
+    strategy: RestartStrategy,
+}
+
+impl<M, const N: usize> Supervisor<M, N>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+{
+    /// Supervise the given children, restarting each of them at most
+    /// `max_restarts` times when it reaches a state for which `failed` returns
+    /// `true`.
+    pub fn new(
+        children: [InitializedStateMachine<M>; N],
+        failed: fn(&M::State) -> bool,
+        max_restarts: usize,
+    ) -> Self {
+        Self {
+            children,
+            failures: [0; N],
+            failed,
+            max_restarts,
+            strategy: RestartStrategy::OneForOne,
+        }
+    }
+
+    /// Set which children are restarted when one of them fails.
+    pub fn with_strategy(mut self, strategy: RestartStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Let the given child handle an event, and restart it if it failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `child` is not below `N`.
+    pub fn handle(&mut self, child: usize, event: &M::Event<'_>) -> Result<(), Escalation>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(child, event, &mut ())
+    }
+
+    /// Let the given child handle an event, and restart it if it failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `child` is not below `N`.
+    pub fn handle_with_context(
+        &mut self,
+        child: usize,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> Result<(), Escalation> {
+        // A poisoned child refuses to handle events, so it is restarted first,
+        // e.g. when the caller caught the panic that poisoned it.
+        if self.children[child].is_poisoned() {
+            self.supervise(child, context)?;
+        }
+        self.children[child]
+            .inner
+            .handle_with_context(event, context);
+        self.supervise(child, context)
+    }
+
+    /// Let every child handle an event, and restart the children that failed.
+    /// Returns the escalation of the first child that failed too often.
+    pub fn handle_all(&mut self, event: &M::Event<'_>) -> Result<(), Escalation>
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        let mut result = Ok(());
+        for child in 0..N {
+            let supervised = self.handle(child, event);
+            result = result.and(supervised);
+        }
+        result
+    }
+
+    /// Restart the child if it has failed, or escalate if it failed too often.
+    fn supervise(&mut self, child: usize, context: &mut M::Context<'_>) -> Result<(), Escalation> {
+        let state_machine = &self.children[child];
+        if !state_machine.is_poisoned() && !(self.failed)(state_machine.state()) {
+            return Ok(());
+        }
+        self.failures[child] += 1;
+        let failures = self.failures[child];
+        if failures > self.max_restarts {
+            return Err(Escalation { child, failures });
+        }
+        match self.strategy {
+            RestartStrategy::OneForOne => self.restart(child, context),
+            RestartStrategy::OneForAll => (0..N).for_each(|child| self.restart(child, context)),
+        }
+        Ok(())
+    }
+
+    fn restart(&mut self, child: usize, context: &mut M::Context<'_>) {
+        self.children[child]
+            .inner
+            .recover_with_context(M::INITIAL, context);
+    }
+
+    /// Get a reference to a child.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `child` is not below `N`.
+    pub fn child(&self, child: usize) -> &InitializedStateMachine<M> {
+        &self.children[child]
+    }
+
+    /// Get the number of times a child has failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `child` is not below `N`.
+    pub fn failures(&self, child: usize) -> usize {
+        self.failures[child]
+    }
+
+    /// Forget the failures of all children, e.g. after they have been running
+    /// without failing for a while.
+    pub fn reset_failures(&mut self) {
+        self.failures = [0; N];
+    }
+
+    /// Get the children back.
+    pub fn into_children(self) -> [InitializedStateMachine<M>; N] {
+        self.children
+    }
+}
@@ -0,0 +1,148 @@
+use crate::blocking::{self, InitializedStateMachine};
+use crate::{IntoStateMachine, Timer};
+
+/// A state machine that takes the timed transitions returned by
+/// [`AFTER`](IntoStateMachine::AFTER), driven by a [`Timer`].
+///
+/// Whenever a state is entered, the timer is started with the duration of the
+/// timed transition of that state. Call [`poll`](Self::poll) regularly (e.g.
+/// from the main loop or when the timer interrupt fires) to take the timed
+/// transition once the timer has expired.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use statig::prelude::*;
+/// # use statig::blocking::TimedStateMachine;
+/// # use statig::Timer;
+/// # #[derive(Default)]
+/// # pub struct Timeout { elapsed: bool }
+/// # impl Timer for Timeout {
+/// #     fn start(&mut self, _: Duration) { self.elapsed = false; }
+/// #     fn cancel(&mut self) {}
+/// #     fn poll_expired(&mut self) -> bool { !core::mem::replace(&mut self.elapsed, true) }
+/// # }
+/// #[derive(Default)]
+/// pub struct Blinky;
+///
+/// pub struct Event;
+///
+/// #[state_machine(initial = "State::on()", after = "Self::after", state(derive(Debug, PartialEq)))]
+/// impl Blinky {
+///     fn after(state: &State) -> Option<(Duration, State)> {
+///         match state {
+///             State::On {} => Some((Duration::from_millis(500), State::off())),
+///             State::Off {} => Some((Duration::from_millis(500), State::on())),
+///         }
+///     }
+///
+///     #[state]
+///     fn on(event: &Event) -> Response<State> { Handled }
+///
+///     #[state]
+///     fn off(event: &Event) -> Response<State> { Handled }
+/// }
+///
+/// let state_machine = Blinky::default().uninitialized_state_machine().init();
+/// let mut state_machine = TimedStateMachine::new(state_machine, Timeout::default());
+///
+/// assert!(state_machine.poll());
+/// assert_eq!(state_machine.state(), &State::off());
+/// ```
+pub struct TimedStateMachine<M, T>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    timer: T,
+}
+
+impl<M, T> TimedStateMachine<M, T>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    T: Timer,
+{
+    /// Create a timed state machine and start the timer for the timed
+    /// transition of the current state.
+    pub fn new(state_machine: InitializedStateMachine<M>, timer: T) -> Self {
+        let mut timed = Self {
+            state_machine,
+            timer,
+        };
+        timed.start_timer();
+        timed
+    }
+
+    /// Handle the given event. If a transition is taken, the timer is started
+    /// for the timed transition of the new state.
+    pub fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ());
+    }
+
+    /// Handle the given event. If a transition is taken, the timer is started
+    /// for the timed transition of the new state.
+    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        if self.state_machine.inner.handle_with_context(event, context) {
+            self.start_timer();
+        }
+    }
+
+    /// Take the timed transition of the current state if its timer has
+    /// expired. Returns whether the transition was taken.
+    pub fn poll(&mut self) -> bool
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.poll_with_context(&mut ())
+    }
+
+    /// Take the timed transition of the current state if its timer has
+    /// expired. Returns whether the transition was taken.
+    pub fn poll_with_context(&mut self, context: &mut M::Context<'_>) -> bool {
+        if !self.timer.poll_expired() {
+            return false;
+        }
+        match M::AFTER(self.state_machine.state()) {
+            Some((_, target)) => {
+                self.state_machine.inner.transition(target, context);
+                self.start_timer();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start the timer for the timed transition of the current state, or
+    /// cancel it if the current state has none.
+    fn start_timer(&mut self) {
+        match M::AFTER(self.state_machine.state()) {
+            Some((duration, _)) => self.timer.start(duration),
+            None => self.timer.cancel(),
+        }
+    }
+
+    /// Get a reference to the timer.
+    pub fn timer(&self) -> &T {
+        &self.timer
+    }
+
+    /// Get the state machine and the timer back.
+    pub fn into_parts(self) -> (InitializedStateMachine<M>, T) {
+        (self.state_machine, self.timer)
+    }
+}
+
+impl<M, T> core::ops::Deref for TimedStateMachine<M, T>
+where
+    M: IntoStateMachine,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
@@ -0,0 +1,129 @@
+use core::time::Duration;
+
+use crate::blocking::{self, InitializedStateMachine};
+use crate::{Clock, IntoStateMachine};
+
+/// A state machine that measures how long it takes to handle each event, and
+/// calls a callback when that exceeds a budget.
+///
+/// Handlers are expected to run to completion quickly, so a handler that
+/// blocks (e.g. on I/O or a busy loop) delays every event behind it. The
+/// watchdog catches those handlers: the callback receives the state the
+/// handler was called in and the time it took.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use statig::prelude::*;
+/// # use statig::blocking::WatchdogStateMachine;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub struct Event;
+/// #
+/// # #[state_machine(initial = "State::on()", state(derive(Clone, Debug)))]
+/// # impl Blinky {
+/// #     #[state]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// # }
+/// #
+/// let start = std::time::Instant::now();
+/// let state_machine = Blinky::default().uninitialized_state_machine().init();
+/// let mut state_machine = WatchdogStateMachine::new(
+///     state_machine,
+///     move || start.elapsed(),
+///     Duration::from_millis(10),
+///     |state: &State, elapsed| eprintln!("handling an event in {state:?} took {elapsed:?}"),
+/// );
+///
+/// state_machine.handle(&Event);
+/// ```
+pub struct WatchdogStateMachine<M, C, F>
+where
+    M: IntoStateMachine,
+{
+    state_machine: InitializedStateMachine<M>,
+    clock: C,
+    budget: Duration,
+    on_overrun: F,
+    overruns: usize,
+}
+
+impl<M, C, F> WatchdogStateMachine<M, C, F>
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M> + Clone,
+    for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
+    C: Clock,
+    F: FnMut(&M::State, Duration),
+{
+    /// Create a state machine that calls `on_overrun` whenever handling an
+    /// event takes longer than `budget`, as measured by `clock`.
+    pub fn new(
+        state_machine: InitializedStateMachine<M>,
+        clock: C,
+        budget: Duration,
+        on_overrun: F,
+    ) -> Self {
+        Self {
+            state_machine,
+            clock,
+            budget,
+            on_overrun,
+            overruns: 0,
+        }
+    }
+
+    /// Handle the given event and check the time it took against the budget.
+    pub fn handle(&mut self, event: &M::Event<'_>)
+    where
+        for<'ctx> M: IntoStateMachine<Context<'ctx> = ()>,
+    {
+        self.handle_with_context(event, &mut ());
+    }
+
+    /// Handle the given event and check the time it took against the budget.
+    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
+        let state = self.state_machine.state().clone();
+        let start = self.clock.now();
+        self.state_machine.inner.handle_with_context(event, context);
+        let elapsed = self.clock.now().saturating_sub(start);
+        if elapsed > self.budget {
+            self.overruns += 1;
+            (self.on_overrun)(&state, elapsed);
+        }
+    }
+
+    /// Get the time handling an event may take.
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Set the time handling an event may take.
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+
+    /// Get the number of events that took longer than the budget.
+    pub fn overruns(&self) -> usize {
+        self.overruns
+    }
+
+    /// Get the state machine and the clock back.
+    pub fn into_parts(self) -> (InitializedStateMachine<M>, C) {
+        (self.state_machine, self.clock)
+    }
+}
+
+impl<M, C, F> core::ops::Deref for WatchdogStateMachine<M, C, F>
+where
+    M: IntoStateMachine,
+{
+    type Target = InitializedStateMachine<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state_machine
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::io::{self, Cursor};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{IntoStateMachine, StateCodec};
+
+/// Codec that serializes the state as CBOR, including any state-local storage.
+///
+/// States are written with their names rather than raw discriminants, so a
+/// stored state can be inspected with any CBOR diagnostic tool.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CborCodec;
+
+/// Error that occurs when a state can not be encoded or decoded by the
+/// [`CborCodec`].
+#[derive(Debug)]
+pub enum CborCodecError {
+    /// The state could not be serialized.
+    Serialize(ciborium::ser::Error<io::Error>),
+    /// The state could not be deserialized.
+    Deserialize(ciborium::de::Error<io::Error>),
+}
+
+impl fmt::Display for CborCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize(error) => write!(f, "{error}"),
+            Self::Deserialize(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for CborCodecError {}
+
+impl<M> StateCodec<M> for CborCodec
+where
+    M: IntoStateMachine,
+    M::State: Serialize + DeserializeOwned,
+{
+    type Error = CborCodecError;
+
+    fn encode<'buf>(
+        &self,
+        state: &M::State,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error> {
+        let mut cursor = Cursor::new(&mut *buffer);
+        ciborium::into_writer(state, &mut cursor).map_err(CborCodecError::Serialize)?;
+        let len = cursor.position() as usize;
+        Ok(&buffer[..len])
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<M::State, Self::Error> {
+        ciborium::from_reader(bytes).map_err(CborCodecError::Deserialize)
+    }
+}
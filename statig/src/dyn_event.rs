@@ -0,0 +1,111 @@
+use alloc::boxed::Box;
+use core::any::{type_name, Any};
+use core::fmt::{self, Debug};
+
+/// Event of which the type is erased, so a single state machine can handle
+/// events of arbitrary types, e.g. the events of plugins that aren't known
+/// when the state machine is written.
+///
+/// Use it as the event type of the handlers (`event: &DynEvent<'_>`) and get
+/// the concrete event back with [`downcast_ref`](Self::downcast_ref). An event
+/// is erased by borrowing it with [`new`](Self::new) or `From`, so it can be
+/// passed to `handle_from`, or by taking ownership of it with
+/// [`boxed`](Self::boxed), e.g. to put it in a queue.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::DynEvent;
+/// pub struct Ping;
+/// pub struct Volume(u8);
+///
+/// #[derive(Default)]
+/// pub struct Speaker {
+///     volume: u8,
+/// }
+///
+/// #[state_machine(initial = "State::on()")]
+/// impl Speaker {
+///     #[state]
+///     fn on(&mut self, event: &DynEvent<'_>) -> Response<State> {
+///         match event.downcast_ref::<Volume>() {
+///             Some(Volume(volume)) => {
+///                 self.volume = *volume;
+///                 Handled
+///             }
+///             None => Super,
+///         }
+///     }
+/// }
+///
+/// let mut state_machine = Speaker::default().state_machine();
+/// state_machine.handle_from(&Volume(7));
+/// state_machine.handle_from(&Ping);
+/// state_machine.handle(&DynEvent::boxed(Volume(3)));
+///
+/// assert_eq!(state_machine.volume, 3);
+/// ```
+pub struct DynEvent<'a> {
+    event: Erased<'a>,
+    type_name: &'static str,
+}
+
+enum Erased<'a> {
+    Borrowed(&'a (dyn Any + Send + Sync)),
+    Boxed(Box<dyn Any + Send + Sync>),
+}
+
+impl<'a> DynEvent<'a> {
+    /// Erase the type of a borrowed event.
+    pub fn new<T: Any + Send + Sync>(event: &'a T) -> Self {
+        Self {
+            event: Erased::Borrowed(event),
+            type_name: type_name::<T>(),
+        }
+    }
+
+    /// Erase the type of an event by taking ownership of it.
+    pub fn boxed<T: Any + Send + Sync>(event: T) -> Self {
+        Self {
+            event: Erased::Boxed(Box::new(event)),
+            type_name: type_name::<T>(),
+        }
+    }
+
+    /// Check whether the event is of the given type.
+    pub fn is<T: Any>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Get the event as the given type, or `None` if it is of another type.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    /// Get the name of the type of the event, e.g. to log events that are not
+    /// handled.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        match &self.event {
+            Erased::Borrowed(event) => *event,
+            Erased::Boxed(event) => event.as_ref(),
+        }
+    }
+}
+
+impl<'a, T> From<&'a T> for DynEvent<'a>
+where
+    T: Any + Send + Sync,
+{
+    fn from(event: &'a T) -> Self {
+        Self::new(event)
+    }
+}
+
+impl Debug for DynEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DynEvent").field(&self.type_name).finish()
+    }
+}
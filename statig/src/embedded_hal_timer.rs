@@ -0,0 +1,122 @@
+use core::time::Duration;
+
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::timer::CountDown;
+
+use crate::Timer;
+
+/// [`Timer`] on top of an `embedded-hal` [`CountDown`] timer, so timed
+/// transitions work on bare-metal HALs without an OS.
+///
+/// HALs use their own types for the duration of a count down, so a function
+/// that converts a [`Duration`] into it has to be provided.
+pub struct CountDownTimer<C, F>
+where
+    C: CountDown,
+    F: Fn(Duration) -> C::Time,
+{
+    count_down: C,
+    convert: F,
+    running: bool,
+}
+
+impl<C, F> CountDownTimer<C, F>
+where
+    C: CountDown,
+    F: Fn(Duration) -> C::Time,
+{
+    /// Create a timer from a count down and a function that converts a
+    /// [`Duration`] into the time type of the count down.
+    pub fn new(count_down: C, convert: F) -> Self {
+        Self {
+            count_down,
+            convert,
+            running: false,
+        }
+    }
+
+    /// Get the count down back.
+    pub fn free(self) -> C {
+        self.count_down
+    }
+}
+
+impl<C, F> Timer for CountDownTimer<C, F>
+where
+    C: CountDown,
+    F: Fn(Duration) -> C::Time,
+{
+    fn start(&mut self, duration: Duration) {
+        self.count_down.start((self.convert)(duration));
+        self.running = true;
+    }
+
+    fn cancel(&mut self) {
+        self.running = false;
+    }
+
+    fn poll_expired(&mut self) -> bool {
+        if self.running && self.count_down.wait().is_ok() {
+            self.running = false;
+            return true;
+        }
+        false
+    }
+}
+
+/// [`Timer`] on top of an `embedded-hal` [`DelayUs`] provider.
+///
+/// A delay can't run in the background, so polling the timer blocks until it
+/// expires. This suits state machines that are only driven by timeouts, e.g.
+/// a blinking LED in a super loop.
+pub struct DelayTimer<D>
+where
+    D: DelayUs<u32>,
+{
+    delay: D,
+    remaining: Option<Duration>,
+}
+
+impl<D> DelayTimer<D>
+where
+    D: DelayUs<u32>,
+{
+    /// Create a timer from a delay provider.
+    pub fn new(delay: D) -> Self {
+        Self {
+            delay,
+            remaining: None,
+        }
+    }
+
+    /// Get the delay provider back.
+    pub fn free(self) -> D {
+        self.delay
+    }
+}
+
+impl<D> Timer for DelayTimer<D>
+where
+    D: DelayUs<u32>,
+{
+    fn start(&mut self, duration: Duration) {
+        self.remaining = Some(duration);
+    }
+
+    fn cancel(&mut self) {
+        self.remaining = None;
+    }
+
+    fn poll_expired(&mut self) -> bool {
+        let Some(remaining) = self.remaining.take() else {
+            return false;
+        };
+        let mut micros = remaining.as_micros();
+        while micros > 0 {
+            let step = micros.min(u32::MAX as u128) as u32;
+            self.delay.delay_us(step);
+            micros -= step as u128;
+        }
+        true
+    }
+}
@@ -0,0 +1,55 @@
+use crate::IntoStateMachine;
+
+/// One of a closed set of event types that a state machine handles, each of
+/// which is routed to the handlers that take it by its type.
+///
+/// The `state_machine` macro implements it for every type given with
+/// `event_types(...)`. The event of the state machine is then an enum that
+/// only borrows the event, so every event keeps a payload type of its own
+/// without being wrapped, and handlers are selected with a `match` instead of
+/// dynamic dispatch. Generic code can bound on it to accept exactly the
+/// events of a state machine.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::blocking::InitializedStateMachine;
+/// pub struct Coin(pub u32);
+/// pub struct Push;
+///
+/// #[derive(Default)]
+/// pub struct Turnstile {
+///     paid: u32,
+/// }
+///
+/// #[state_machine(initial = "State::locked()", event_types("Coin", "Push"))]
+/// impl Turnstile {
+///     #[state]
+///     fn locked(&mut self, event: &Coin) -> Response<State> {
+///         self.paid += event.0;
+///         Transition(State::unlocked())
+///     }
+///
+///     #[state]
+///     fn unlocked(event: &Push) -> Response<State> {
+///         Transition(State::locked())
+///     }
+/// }
+///
+/// fn send<E: statig::Event<Turnstile>>(
+///     state_machine: &mut InitializedStateMachine<Turnstile>,
+///     event: &E,
+/// ) {
+///     state_machine.handle(&event.as_event());
+/// }
+///
+/// let mut state_machine = Turnstile::default().uninitialized_state_machine().init();
+/// send(&mut state_machine, &Push);
+/// send(&mut state_machine, &Coin(50));
+/// state_machine.handle_from(&Push);
+///
+/// assert_eq!(state_machine.paid, 50);
+/// ```
+pub trait Event<M: IntoStateMachine> {
+    /// Get the event of the state machine that borrows this event.
+    fn as_event(&self) -> M::Event<'_>;
+}
@@ -0,0 +1,132 @@
+/// Fixed-capacity pool of events, so events with large payloads can be
+/// allocated by producers (e.g. interrupt handlers) and recycled once they are
+/// dispatched, without an allocator.
+///
+/// Allocating an event moves it into a free slot of the pool and hands out a
+/// [`PooledEvent`], which is only as large as an index. The handle is what
+/// travels through an [`EventQueue`](crate::EventQueue), while the payload
+/// stays in place. After the event is dispatched, releasing the handle makes
+/// its slot available again. Both take constant time.
+///
+/// To allocate events from an interrupt handler, share the pool through a
+/// `critical_section::Mutex<RefCell<EventPool<T, N>>>`.
+///
+/// ```
+/// # use statig::{ArrayQueue, EventPool, PooledEvent};
+/// pub struct Frame {
+///     data: [u8; 64],
+/// }
+///
+/// let mut pool: EventPool<Frame, 4> = EventPool::new();
+/// let mut queue: ArrayQueue<PooledEvent, 4> = ArrayQueue::new();
+///
+/// // Producer side.
+/// let event = pool.alloc(Frame { data: [0xAA; 64] }).ok().unwrap();
+/// queue.push(event).unwrap();
+/// assert_eq!(pool.available(), 3);
+///
+/// // Consumer side.
+/// while let Some(event) = queue.pop() {
+///     assert_eq!(pool.get(&event).data[0], 0xAA);
+///     pool.release(event);
+/// }
+/// assert_eq!(pool.available(), 4);
+/// ```
+pub struct EventPool<T, const N: usize> {
+    slots: [Option<T>; N],
+    free: [usize; N],
+    free_len: usize,
+}
+
+/// Handle to an event that is allocated in an [`EventPool`]. It can't be
+/// copied, so the event is released exactly once, and it must be used with
+/// the pool that allocated it.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "the slot of the event is only recycled when it is released"]
+pub struct PooledEvent {
+    index: usize,
+}
+
+impl<T, const N: usize> EventPool<T, N> {
+    const EMPTY: Option<T> = None;
+
+    /// Create a pool in which every slot is free.
+    pub const fn new() -> Self {
+        let mut free = [0; N];
+        let mut index = 0;
+        while index < N {
+            free[index] = index;
+            index += 1;
+        }
+        Self {
+            slots: [Self::EMPTY; N],
+            free,
+            free_len: N,
+        }
+    }
+
+    /// Move an event into a free slot of the pool. If every slot is taken the
+    /// event is handed back.
+    pub fn alloc(&mut self, event: T) -> Result<PooledEvent, T> {
+        if self.free_len == 0 {
+            return Err(event);
+        }
+        self.free_len -= 1;
+        let index = self.free[self.free_len];
+        self.slots[index] = Some(event);
+        Ok(PooledEvent { index })
+    }
+
+    /// Get the event the handle refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event was allocated by another pool.
+    pub fn get(&self, event: &PooledEvent) -> &T {
+        self.slots[event.index]
+            .as_ref()
+            .expect("event was allocated by another pool")
+    }
+
+    /// Get the event the handle refers to, to modify it before it is
+    /// dispatched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event was allocated by another pool.
+    pub fn get_mut(&mut self, event: &PooledEvent) -> &mut T {
+        self.slots[event.index]
+            .as_mut()
+            .expect("event was allocated by another pool")
+    }
+
+    /// Recycle the slot of the event, and move the event out of the pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event was allocated by another pool.
+    pub fn release(&mut self, event: PooledEvent) -> T {
+        let taken = self.slots[event.index]
+            .take()
+            .expect("event was allocated by another pool");
+        self.free[self.free_len] = event.index;
+        self.free_len += 1;
+        taken
+    }
+
+    /// Get the number of free slots.
+    pub fn available(&self) -> usize {
+        self.free_len
+    }
+
+    /// Get the maximum number of events the pool can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Default for EventPool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,52 @@
+use std::any::Any;
+use std::boxed::Box;
+use std::fmt;
+
+/// Error returned by `try_handle` when a handler or action panicked while the
+/// event was being dispatched.
+///
+/// The state machine has been rolled back to the state it was in before the
+/// event was dispatched. Changes that were made to the shared storage before
+/// the panic occurred are *not* rolled back.
+pub struct HandlerPanic {
+    payload: Box<dyn Any + Send + 'static>,
+}
+
+impl HandlerPanic {
+    pub(crate) fn new(payload: Box<dyn Any + Send + 'static>) -> Self {
+        Self { payload }
+    }
+
+    /// Get the panic message, if the panic was raised with a string message.
+    pub fn message(&self) -> Option<&str> {
+        match self.payload.downcast_ref::<&'static str>() {
+            Some(message) => Some(message),
+            None => self.payload.downcast_ref::<String>().map(String::as_str),
+        }
+    }
+
+    /// Get the payload the panic was raised with, so it can be passed on to
+    /// [`resume_unwind`](std::panic::resume_unwind).
+    pub fn into_payload(self) -> Box<dyn Any + Send + 'static> {
+        self.payload
+    }
+}
+
+impl fmt::Debug for HandlerPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerPanic")
+            .field("message", &self.message())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for HandlerPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            Some(message) => write!(f, "handler panicked: {message}"),
+            None => write!(f, "handler panicked"),
+        }
+    }
+}
+
+impl std::error::Error for HandlerPanic {}
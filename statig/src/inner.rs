@@ -1,7 +1,11 @@
+use core::sync::atomic::Ordering;
+
 #[cfg(feature = "async")]
 use crate::awaitable::{self, StateExt as _};
 use crate::blocking::{self, StateExt as _};
-use crate::{IntoStateMachine, Response};
+#[cfg(feature = "std")]
+use crate::HandlerPanic;
+use crate::{IntoStateMachine, Response, STATE_ID_UNKNOWN};
 
 /// Private internal representation of a state machine that is used for the public types.
 pub(crate) struct Inner<M>
@@ -10,6 +14,68 @@ where
 {
     pub shared_storage: M,
     pub state: M::State,
+    /// Set while a transition is in progress, so a transition that was
+    /// interrupted by a panic leaves the state machine poisoned.
+    pub poisoned: bool,
+}
+
+impl<M> Inner<M>
+where
+    M: IntoStateMachine,
+{
+    pub const fn new(shared_storage: M, state: M::State) -> Self {
+        Self {
+            shared_storage,
+            state,
+            poisoned: false,
+        }
+    }
+
+    /// Panic if the state machine is poisoned.
+    fn assert_not_poisoned(&self) {
+        assert!(
+            !self.poisoned,
+            "the state machine is poisoned by a transition that did not complete, \
+             call `recover` to bring it back into a well-defined state"
+        );
+    }
+
+    /// Get the state that is selected by the given state, if it is a choice
+    /// or a junction.
+    pub fn select(shared_storage: &mut M, state: &M::State) -> Option<M::State> {
+        M::CHOICE(shared_storage, state).or_else(|| M::JUNCTION(shared_storage, state))
+    }
+
+    /// Replace the target of a transition by the state that is selected by
+    /// the junction, as long as it is a junction.
+    pub fn resolve_junctions(shared_storage: &mut M, target: &mut M::State) {
+        while let Some(state) = M::JUNCTION(shared_storage, target) {
+            *target = state;
+        }
+    }
+
+    /// Replace the state the state machine starts in by the state that is
+    /// selected by the choice or junction, as long as it is one.
+    fn resolve_initial_pseudo_states(&mut self) {
+        while let Some(state) = Self::select(&mut self.shared_storage, &self.state) {
+            self.state = state;
+        }
+    }
+
+    /// Whether the state machine reached a terminate pseudo-state.
+    pub fn is_terminated(&self) -> bool {
+        M::TERMINATE(&self.state)
+    }
+
+    /// Update the state id mirror, if the state machine has one.
+    pub fn mirror_state_id(state_id: Option<usize>) {
+        if let Some(mirror) = M::STATE_ID_MIRROR {
+            let state_id = state_id
+                .and_then(|state_id| u8::try_from(state_id).ok())
+                .unwrap_or(STATE_ID_UNKNOWN);
+            mirror.store(state_id, Ordering::Release);
+        }
+    }
 }
 
 impl<M> Inner<M>
@@ -19,40 +85,189 @@ where
     for<'sub> M::Superstate<'sub>: blocking::Superstate<M>,
 {
     /// Initialize the state machine by executing all entry actions towards the initial state.
-    pub fn init_with_context(&mut self, context: &mut M::Context<'_>) {
-        let enter_levels = self.state.depth();
-        self.state
+    /// Returns `false` when an action failed and left the state machine poisoned.
+    pub fn init_with_context(&mut self, context: &mut M::Context<'_>) -> bool {
+        self.resolve_initial_pseudo_states();
+        let enter_levels = match self.is_terminated() {
+            true => 0,
+            false => self.state.depth(),
+        };
+        let entered = self
+            .state
             .enter(&mut self.shared_storage, context, enter_levels);
+        Self::mirror_state_id(blocking::State::state_id(&self.state));
+        self.poisoned |= !entered;
+        if entered {
+            M::ON_INIT(&mut self.shared_storage, &self.state);
+        }
+        entered
     }
 
-    /// Handle the given event.
-    pub fn handle_with_context(&mut self, event: &M::Event<'_>, context: &mut M::Context<'_>) {
-        let response = self.state.handle(&mut self.shared_storage, event, context);
-        match response {
-            Response::Super => {}
-            Response::Handled => {}
-            Response::Transition(state) => self.transition(state, context),
+    /// Handle the given event. Returns whether a transition was taken.
+    pub fn handle_with_context(
+        &mut self,
+        event: &M::Event<'_>,
+        context: &mut M::Context<'_>,
+    ) -> bool {
+        self.assert_not_poisoned();
+        if self.is_terminated() {
+            return false;
+        }
+        let (response, source) =
+            self.state
+                .handle_with_source(&mut self.shared_storage, event, context);
+        let transitioned = match response {
+            Response::Super => false,
+            Response::Handled => false,
+            Response::Completed => false,
+            Response::Transition(state) => {
+                self.transition_on(state, Some(event), source, context);
+                true
+            }
+        };
+
+        // Take the condition transition of the current state if its condition
+        // holds now that the event was dispatched.
+        match M::WHEN(&self.shared_storage, &self.state) {
+            Some(state) if !self.poisoned => {
+                self.transition_on(state, Some(event), 0, context);
+                true
+            }
+            _ => transitioned,
         }
     }
 
+    /// Call the activity of the current state.
+    pub fn tick_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.assert_not_poisoned();
+        if !self.is_terminated() {
+            blocking::State::call_activity(&mut self.state, &mut self.shared_storage, context);
+        }
+    }
+
+    /// Shut the state machine down by executing all exit actions from the
+    /// current state up to the top.
+    pub fn shutdown_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.assert_not_poisoned();
+        let exit_levels = match self.is_terminated() {
+            true => 0,
+            false => self.state.depth(),
+        };
+        self.poisoned = !self
+            .state
+            .exit(&mut self.shared_storage, context, exit_levels);
+    }
+
+    /// Bring a poisoned state machine back into a well-defined state by
+    /// entering the given state from the top, as when initializing.
+    pub fn recover_with_context(&mut self, state: M::State, context: &mut M::Context<'_>) {
+        self.state = state;
+        self.poisoned = true;
+        self.poisoned = !self.init_with_context(context);
+    }
+
+    /// Run the given closure and catch it when it panics. If it does, the state
+    /// and its mirror are rolled back to what they were before the closure was
+    /// called.
+    #[cfg(feature = "std")]
+    pub fn catch_unwind<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> Result<R, HandlerPanic>
+    where
+        M::State: Clone,
+    {
+        let (state, poisoned) = (self.state.clone(), self.poisoned);
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(self))).map_err(|payload| {
+            self.state = state;
+            self.poisoned = poisoned;
+            Self::mirror_state_id(blocking::State::state_id(&self.state));
+            HandlerPanic::new(payload)
+        })
+    }
+
     /// Transition from the current state to the given target state.
-    pub fn transition(&mut self, mut target: M::State, context: &mut M::Context<'_>) {
+    pub fn transition(&mut self, target: M::State, context: &mut M::Context<'_>) {
+        self.transition_on(target, None, 0, context);
+    }
+
+    /// Transition from the current state to the given target state, in
+    /// response to the given event if there is one. The source of the
+    /// transition is the state or superstate `source` levels above the current
+    /// state.
+    fn transition_on(
+        &mut self,
+        mut target: M::State,
+        event: Option<&M::Event<'_>>,
+        source: usize,
+        context: &mut M::Context<'_>,
+    ) {
+        // A junction selects the target before any state is exited.
+        Self::resolve_junctions(&mut self.shared_storage, &mut target);
         M::BEFORE_TRANSITION(&mut self.shared_storage, &target, &self.state);
+        self.poisoned = true;
         // Get the transition path we need to perform from one state to the next.
-        let (exit_levels, enter_levels) = self.state.transition_path(&mut target);
+        // A terminate stops the state machine without exiting or entering any
+        // state, but the action of the transition itself is still performed.
+        let path = self.state.transition_path(&mut target);
+        let (mut exit_levels, mut enter_levels) = match M::TERMINATE(&target) {
+            true => (0, 0),
+            false => M::SEMANTICS.transition_path(path, source),
+        };
 
         // Perform the exit from the previous state towards the common ancestor state.
-        self.state
-            .exit(&mut self.shared_storage, context, exit_levels);
+        // A failed action aborts the transition and leaves the state machine poisoned.
+        if !self
+            .state
+            .exit(&mut self.shared_storage, context, exit_levels)
+        {
+            return;
+        }
+
+        // A choice selects the target now that the states are exited, and so
+        // does a junction that is selected by a choice. The states between the
+        // common ancestor and the choice are not entered, and more states are
+        // exited when the selected target is outside them.
+        while let Some(mut selected) = Self::select(&mut self.shared_storage, &target) {
+            if M::TERMINATE(&selected) {
+                enter_levels = 0;
+                target = selected;
+                break;
+            }
+            let (choice_exit_levels, choice_enter_levels) = target.transition_path(&mut selected);
+            let more_exit_levels = choice_exit_levels.saturating_sub(enter_levels);
+            if !(exit_levels..exit_levels + more_exit_levels).all(|height| {
+                blocking::State::try_call_exit_action(
+                    &mut self.state,
+                    &mut self.shared_storage,
+                    context,
+                    height,
+                )
+            }) {
+                return;
+            }
+            exit_levels += more_exit_levels;
+            enter_levels = choice_enter_levels + enter_levels.saturating_sub(choice_exit_levels);
+            target = selected;
+        }
+
+        // Perform the action of the transition itself.
+        M::TRANSITION_ACTION(&mut self.shared_storage, &self.state, &target);
 
         // Update the state.
         core::mem::swap(&mut self.state, &mut target);
 
         // Perform the entry actions from the common ancestor state into the new state.
-        self.state
+        let entered = self
+            .state
             .enter(&mut self.shared_storage, context, enter_levels);
+        Self::mirror_state_id(blocking::State::state_id(&self.state));
+        if !entered {
+            return;
+        }
+        self.poisoned = false;
 
         M::AFTER_TRANSITION(&mut self.shared_storage, &target, &self.state);
+        if let Some(event) = event {
+            M::ON_TRANSITION(&mut self.shared_storage, &target, &self.state, event);
+        }
     }
 }
 
@@ -65,49 +280,181 @@ where
     M::State: awaitable::State<M> + Send + 'static,
     for<'sub> M::Superstate<'sub>: awaitable::Superstate<M> + Send,
 {
-    pub async fn async_init_with_context(&mut self, context: &mut M::Context<'_>) {
-        let enter_levels = self.state.depth();
-        self.state
+    pub async fn async_init_with_context(&mut self, context: &mut M::Context<'_>) -> bool {
+        self.resolve_initial_pseudo_states();
+        let enter_levels = match self.is_terminated() {
+            true => 0,
+            false => self.state.depth(),
+        };
+        let entered = self
+            .state
             .enter(&mut self.shared_storage, context, enter_levels)
             .await;
+        Self::mirror_state_id(awaitable::State::state_id(&self.state));
+        self.poisoned |= !entered;
+        if entered {
+            M::ON_INIT(&mut self.shared_storage, &self.state);
+        }
+        entered
+    }
+
+    pub async fn async_recover_with_context(
+        &mut self,
+        state: M::State,
+        context: &mut M::Context<'_>,
+    ) {
+        self.state = state;
+        self.poisoned = true;
+        self.poisoned = !self.async_init_with_context(context).await;
+    }
+
+    pub async fn async_shutdown_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.assert_not_poisoned();
+        let exit_levels = match self.is_terminated() {
+            true => 0,
+            false => self.state.depth(),
+        };
+        self.poisoned = !self
+            .state
+            .exit(&mut self.shared_storage, context, exit_levels)
+            .await;
     }
 
+    /// Handle the given event. Returns whether a transition was taken.
     pub async fn async_handle_with_context(
         &mut self,
         event: &M::Event<'_>,
         context: &mut M::Context<'_>,
-    ) {
-        let response = self
+    ) -> bool {
+        self.assert_not_poisoned();
+        if self.is_terminated() {
+            return false;
+        }
+        let (response, source) = self
             .state
-            .handle(&mut self.shared_storage, event, context)
+            .handle_with_source(&mut self.shared_storage, event, context)
             .await;
-        match response {
-            Response::Super => {}
-            Response::Handled => {}
-            Response::Transition(state) => self.async_transition(state, context).await,
+        let transitioned = match response {
+            Response::Super => false,
+            Response::Handled => false,
+            Response::Completed => false,
+            Response::Transition(state) => {
+                self.async_transition_on(state, Some(event), source, context)
+                    .await;
+                true
+            }
+        };
+
+        // Take the condition transition of the current state if its condition
+        // holds now that the event was dispatched.
+        match M::WHEN(&self.shared_storage, &self.state) {
+            Some(state) if !self.poisoned => {
+                self.async_transition_on(state, Some(event), 0, context)
+                    .await;
+                true
+            }
+            _ => transitioned,
+        }
+    }
+
+    /// Call the activity of the current state.
+    pub async fn async_tick_with_context(&mut self, context: &mut M::Context<'_>) {
+        self.assert_not_poisoned();
+        if !self.is_terminated() {
+            awaitable::State::call_activity(&mut self.state, &mut self.shared_storage, context)
+                .await;
         }
     }
 
     /// Transition from the current state to the given target state.
-    pub async fn async_transition(&mut self, mut target: M::State, context: &mut M::Context<'_>) {
+    pub async fn async_transition(&mut self, target: M::State, context: &mut M::Context<'_>) {
+        self.async_transition_on(target, None, 0, context).await;
+    }
+
+    /// Transition from the current state to the given target state, in
+    /// response to the given event if there is one. The source of the
+    /// transition is the state or superstate `source` levels above the current
+    /// state.
+    async fn async_transition_on(
+        &mut self,
+        mut target: M::State,
+        event: Option<&M::Event<'_>>,
+        source: usize,
+        context: &mut M::Context<'_>,
+    ) {
+        // A junction selects the target before any state is exited.
+        Self::resolve_junctions(&mut self.shared_storage, &mut target);
         M::BEFORE_TRANSITION(&mut self.shared_storage, &target, &self.state);
+        self.poisoned = true;
         // Get the transition path we need to perform from one state to the next.
-        let (exit_levels, enter_levels) = self.state.transition_path(&mut target);
+        // A terminate stops the state machine without exiting or entering any
+        // state, but the action of the transition itself is still performed.
+        let path = self.state.transition_path(&mut target);
+        let (mut exit_levels, mut enter_levels) = match M::TERMINATE(&target) {
+            true => (0, 0),
+            false => M::SEMANTICS.transition_path(path, source),
+        };
 
         // Perform the exit from the previous state towards the common ancestor state.
-        self.state
+        // A failed action aborts the transition and leaves the state machine poisoned.
+        if !self
+            .state
             .exit(&mut self.shared_storage, context, exit_levels)
-            .await;
+            .await
+        {
+            return;
+        }
+
+        // A choice selects the target now that the states are exited, and so
+        // does a junction that is selected by a choice. The states between the
+        // common ancestor and the choice are not entered, and more states are
+        // exited when the selected target is outside them.
+        while let Some(mut selected) = Self::select(&mut self.shared_storage, &target) {
+            if M::TERMINATE(&selected) {
+                enter_levels = 0;
+                target = selected;
+                break;
+            }
+            let (choice_exit_levels, choice_enter_levels) = target.transition_path(&mut selected);
+            let more_exit_levels = choice_exit_levels.saturating_sub(enter_levels);
+            for height in exit_levels..exit_levels + more_exit_levels {
+                if !awaitable::State::try_call_exit_action(
+                    &mut self.state,
+                    &mut self.shared_storage,
+                    context,
+                    height,
+                )
+                .await
+                {
+                    return;
+                }
+            }
+            exit_levels += more_exit_levels;
+            enter_levels = choice_enter_levels + enter_levels.saturating_sub(choice_exit_levels);
+            target = selected;
+        }
+
+        // Perform the action of the transition itself.
+        M::TRANSITION_ACTION(&mut self.shared_storage, &self.state, &target);
 
         // Update the state.
         core::mem::swap(&mut self.state, &mut target);
 
         // Perform the entry actions from the common ancestor state into the new state.
-        self.state
+        let entered = self
+            .state
             .enter(&mut self.shared_storage, context, enter_levels)
             .await;
+        Self::mirror_state_id(awaitable::State::state_id(&self.state));
+        if !entered {
+            return;
+        }
+        self.poisoned = false;
 
         M::AFTER_TRANSITION(&mut self.shared_storage, &target, &self.state);
+        if let Some(event) = event {
+            M::ON_TRANSITION(&mut self.shared_storage, &target, &self.state, event);
+        }
     }
 }
 
@@ -120,6 +467,7 @@ where
         Self {
             shared_storage: self.shared_storage.clone(),
             state: self.state.clone(),
+            poisoned: self.poisoned,
         }
     }
 }
@@ -232,11 +580,7 @@ where
                 let state = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
-                let inner = Inner {
-                    shared_storage,
-                    state,
-                };
-                Ok(inner)
+                Ok(Inner::new(shared_storage, state))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
@@ -264,11 +608,7 @@ where
                 let shared_storage = shared_storage
                     .ok_or_else(|| serde::de::Error::missing_field("shared_storage"))?;
                 let state = state.ok_or_else(|| serde::de::Error::missing_field("state"))?;
-                let inner = Inner {
-                    shared_storage,
-                    state,
-                };
-                Ok(inner)
+                Ok(Inner::new(shared_storage, state))
             }
         }
 
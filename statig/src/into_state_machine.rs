@@ -1,4 +1,6 @@
-use crate::StateOrSuperstate;
+use core::sync::atomic::AtomicU8;
+
+use crate::{EventRoute, StateInfo, StateOrSuperstate, TimedTransition, TransitionInfo};
 
 /// Trait for transorming a type into a state machine.
 pub trait IntoStateMachine
@@ -22,6 +24,12 @@ where
     /// Initial state of the state machine.
     const INITIAL: Self::State;
 
+    /// Method that is called once the state machine is initialized, i.e. after
+    /// the entry actions into the initial state were executed, with the state
+    /// it starts in. It is also called when a poisoned state machine is
+    /// recovered, as that enters the given state from the top.
+    const ON_INIT: fn(&mut Self, &Self::State) = |_, _| {};
+
     /// Method that is called *before* an event is dispatched to a state or
     /// superstate handler.
     const BEFORE_DISPATCH: fn(&mut Self, StateOrSuperstate<'_, '_, Self>, &Self::Event<'_>) =
@@ -37,4 +45,180 @@ where
 
     /// Method that is called *after* every transition.
     const AFTER_TRANSITION: fn(&mut Self, &Self::State, &Self::State) = |_, _, _| {};
+
+    /// Method that is called during every transition, with the source and the
+    /// target state, after the exit actions and before the entry actions. This
+    /// is where the actions of specific transitions are called (e.g. to send a
+    /// NACK only when going from `AwaitAck` to `Error`).
+    const TRANSITION_ACTION: fn(&mut Self, &Self::State, &Self::State) = |_, _, _| {};
+
+    /// Method that is called after every transition that is taken in response
+    /// to an event, with the source state, the target state and the event that
+    /// triggered it, so it can be recorded *why* a transition happened. It is
+    /// called right after [`AFTER_TRANSITION`](IntoStateMachine::AFTER_TRANSITION),
+    /// but not for transitions that aren't triggered by an event, such as timed
+    /// transitions.
+    const ON_TRANSITION: fn(&mut Self, &Self::State, &Self::State, &Self::Event<'_>) =
+        |_, _, _, _| {};
+
+    /// Optional atomic that mirrors the [id](crate::blocking::State::state_id)
+    /// of the current state. It is updated when the state machine is initialized
+    /// and after every transition, so the current state can be queried without
+    /// access to the state machine (e.g. from an interrupt handler or a watchdog
+    /// task).
+    ///
+    /// The id is stored with [`Release`](core::sync::atomic::Ordering::Release)
+    /// ordering, so a load with `Acquire` ordering also observes every write the
+    /// state machine made before the transition. Until the state machine is
+    /// initialized, or if the id of the state does not fit in a `u8`, the
+    /// mirror holds [`STATE_ID_UNKNOWN`](crate::STATE_ID_UNKNOWN).
+    const STATE_ID_MIRROR: Option<&'static AtomicU8> = None;
+
+    /// Upper bound on the number of states and superstates that are exited and
+    /// entered during a single transition, i.e. the worst-case number of exit
+    /// and entry actions that are called when an event is handled.
+    ///
+    /// The `state_machine` macro computes it from the hierarchy, so the
+    /// worst-case execution time of `handle()` can be bounded analytically. It
+    /// defaults to `usize::MAX` when it is not known.
+    const MAX_TRANSITION_DEPTH: usize = usize::MAX;
+
+    /// Method that returns the timed transition of a state, if it has one: the
+    /// duration after which the state machine transitions to the returned
+    /// target state, unless it left the state before that.
+    ///
+    /// Timed transitions are driven by a [`Timer`](crate::Timer) through a
+    /// [`TimedStateMachine`](crate::blocking::TimedStateMachine).
+    const AFTER: fn(&Self::State) -> Option<TimedTransition<Self::State>> = |_| None;
+
+    /// Method that returns the condition transition of a state, if its
+    /// condition holds: the target state the state machine transitions to
+    /// without a dedicated event. It is evaluated once after every event that
+    /// is handled, so threshold-driven changes don't need a synthetic "check"
+    /// event.
+    ///
+    /// The `state_machine` macro generates it from the `when` and `transition`
+    /// options of the states.
+    const WHEN: fn(&Self, &Self::State) -> Option<Self::State> = |_, _| None;
+
+    /// Method that evaluates a choice pseudo-state: when the given state is a
+    /// choice, it returns the state the choice selects. A transition that
+    /// targets a choice first exits the states up to the common ancestor of
+    /// the current state and the choice, and only then calls this method, so
+    /// the selection can depend on what the exit actions did. The transition
+    /// then continues to the selected state, which can be another choice.
+    ///
+    /// The `state_machine` macro generates it from the states that are marked
+    /// with the `choice` option.
+    const CHOICE: fn(&mut Self, &Self::State) -> Option<Self::State> = |_, _| None;
+
+    /// Method that evaluates a junction pseudo-state: when the given state is
+    /// a junction, it returns the state the junction selects. Unlike a choice,
+    /// a junction is evaluated as soon as the transition is taken, before any
+    /// exit actions, so the states that are exited and entered are those on
+    /// the path to the selected state. Several transitions can route through
+    /// one junction to share the actions its handler calls.
+    ///
+    /// The `state_machine` macro generates it from the states that are marked
+    /// with the `junction` option.
+    const JUNCTION: fn(&mut Self, &Self::State) -> Option<Self::State> = |_, _| None;
+
+    /// Method that returns whether the given state is a terminate
+    /// pseudo-state. A transition that reaches a terminate stops the state
+    /// machine immediately: the states that were not exited yet are not
+    /// exited, and from then on events are ignored, activities are no longer
+    /// called and shutting down executes no exit actions. Unlike a state that
+    /// simply handles every event, this models an emergency stop faithfully.
+    ///
+    /// The `state_machine` macro generates it from the states that are marked
+    /// with the `terminate` option.
+    const TERMINATE: fn(&Self::State) -> bool = |_| false;
+
+    /// Static table of the states and superstates, so tooling can inspect the
+    /// hierarchy and group or filter states by their metadata.
+    ///
+    /// The `state_machine` macro lays out every state and superstate in the
+    /// order in which they are declared. It is empty when it is not known.
+    const HIERARCHY: &'static [StateInfo] = &[];
+
+    /// Static table of the declared transitions, so tooling can draw or export
+    /// the state machine without parsing the source.
+    ///
+    /// The `state_machine` macro lists the initial transition, followed by the
+    /// transitions declared with the `transitions` option of the states and
+    /// superstates, in declaration order. Transitions that are returned from
+    /// the body of a handler are not listed.
+    const TRANSITIONS: &'static [TransitionInfo] = &[];
+
+    /// Static table of how every event is handled in every state, so audit
+    /// tooling can prove that an event is always handled.
+    ///
+    /// The `state_machine` macro generates it when the `event_routing` option
+    /// is set, for every state and every variant of the event enum, in
+    /// declaration order. It is computed from the declared transitions: an
+    /// event is handled at the nearest level that declares a transition for it
+    /// without a guard, and events that are only handled in the body of a
+    /// handler are listed as unhandled.
+    const EVENT_ROUTING: &'static [EventRoute] = &[];
+
+    /// The semantics that decide which states are exited and entered during
+    /// a transition.
+    const SEMANTICS: Semantics = Semantics::Pragmatic;
 }
+
+/// The semantics that decide which states are exited and entered during a
+/// transition, set with `#[state_machine(semantics = "...")]`.
+///
+/// They only differ for transitions that are taken by a superstate, or that
+/// are declared on a superstate, which UML calls the source of the
+/// transition. Take a state `led_on` within a superstate `blinking`, where
+/// `blinking` takes a transition to `led_on` while `led_on` is the current
+/// state:
+///
+/// - [`Pragmatic`](Semantics::Pragmatic) exits `led_on` and enters `led_on`.
+/// - [`Uml`](Semantics::Uml) exits `led_on` and `blinking`, and enters
+///   `blinking` and `led_on`.
+/// - [`UmlLocal`](Semantics::UmlLocal) exits `led_on` and enters `led_on`.
+///   Unlike the pragmatic semantics, it would also exit and enter any
+///   superstate between `led_on` and `blinking`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Semantics {
+    /// Exit the states from the current state up to the common ancestor of
+    /// the current state and the target, regardless of which state or
+    /// superstate took the transition.
+    #[default]
+    Pragmatic,
+    /// UML statechart semantics with external transitions: the source of the
+    /// transition is exited and entered again, even when the target is within
+    /// the source. A self-transition of a superstate exits and enters it.
+    Uml,
+    /// UML statechart semantics with local transitions: when the target is
+    /// within the source of the transition, the states within the source are
+    /// exited and entered again, but the source itself is not.
+    UmlLocal,
+}
+
+impl Semantics {
+    /// Adjust the transition path from the current state to the target to
+    /// the semantics, for a transition whose source is `source` levels above
+    /// the current state.
+    pub(crate) fn transition_path(
+        self,
+        (exit_levels, enter_levels): (usize, usize),
+        source: usize,
+    ) -> (usize, usize) {
+        let uml_exit_levels = match self {
+            Semantics::Pragmatic => exit_levels,
+            Semantics::Uml => exit_levels.max(source + 1),
+            Semantics::UmlLocal => exit_levels.max(source),
+        };
+        (
+            uml_exit_levels,
+            enter_levels + uml_exit_levels - exit_levels,
+        )
+    }
+}
+
+/// Value of the [`STATE_ID_MIRROR`](IntoStateMachine::STATE_ID_MIRROR) when the
+/// id of the current state is not known.
+pub const STATE_ID_UNKNOWN: u8 = u8::MAX;
@@ -0,0 +1,36 @@
+use std::io::Cursor;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{IntoStateMachine, StateCodec};
+
+/// Codec that serializes the state as JSON, including any state-local storage.
+///
+/// States are written with their names rather than raw discriminants, e.g.
+/// `{"Counting":{"count":3}}`, so a stored state can be inspected by eye.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JsonCodec;
+
+impl<M> StateCodec<M> for JsonCodec
+where
+    M: IntoStateMachine,
+    M::State: Serialize + DeserializeOwned,
+{
+    type Error = serde_json::Error;
+
+    fn encode<'buf>(
+        &self,
+        state: &M::State,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error> {
+        let mut cursor = Cursor::new(&mut *buffer);
+        serde_json::to_writer(&mut cursor, state)?;
+        let len = cursor.position() as usize;
+        Ok(&buffer[..len])
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<M::State, Self::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
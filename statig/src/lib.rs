@@ -2,10 +2,41 @@
 #![cfg_attr(all(not(feature = "std"), not(doc)), no_std)]
 #![allow(incomplete_features)]
 
+#[cfg(any(feature = "alloc", doc))]
+#[doc(hidden)]
+pub extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "cbor")]
+mod cbor_codec;
+#[cfg(feature = "alloc")]
+mod dyn_event;
+#[cfg(feature = "embedded-hal")]
+mod embedded_hal_timer;
+mod event;
+mod event_pool;
+#[cfg(feature = "std")]
+mod handler_panic;
 mod inner;
 mod into_state_machine;
+#[cfg(feature = "json")]
+mod json_codec;
+#[cfg(feature = "json")]
+mod model;
+#[cfg(any(feature = "async", doc))]
+mod notifier;
+#[cfg(feature = "postcard")]
+mod postcard_codec;
+mod queue;
+mod rate_limit;
 mod response;
+mod state_info;
 mod state_or_superstate;
+mod state_store;
+mod timer;
+#[cfg(feature = "json")]
+mod xstate;
 
 /// Macro for deriving the state and superstate enum.
 ///
@@ -16,6 +47,16 @@ mod state_or_superstate;
 /// implement the [`State`](crate::blocking::State) trait for the state enum and the
 /// [`Superstate`](crate::blocking::Superstate) trait for the superstate enum.
 ///
+/// The event can be a generic parameter of the `impl` block (e.g.
+/// `impl<E: Frame> Link<E>` with handlers that take `event: &E`), so a single
+/// definition can serve several event types. Bounds that only some handlers
+/// need can be put in the where-clause of those handlers.
+///
+/// The `impl` block can also implement a trait (e.g. `impl Protocol for Tcp`),
+/// in which case the handlers and actions are methods of that trait, so a
+/// family of state machines can share a common interface. Associated types of
+/// the trait can refer to the generated enums (e.g. `type State = State;`).
+///
 /// To override the default configuration you can use the following attributes.
 ///
 /// - `#[state_machine(state(name = "CustomStateName"))]`
@@ -49,6 +90,234 @@ mod state_or_superstate;
 ///   _Default_: `()`
 ///
 ///   <br/>
+///
+/// - `#[state_machine(state(non_exhaustive), superstate(non_exhaustive))]`
+///
+///   Mark the state or superstate enum as `#[non_exhaustive]`, so states can be
+///   added later without breaking downstream crates that match on the enum.
+///
+///   _Default_: exhaustive
+///
+///   <br/>
+///
+/// - `#[state_machine(module = "blinky_fsm")]`
+///
+///   Generate the state and superstate enums, together with the items that
+///   belong to them, in a module with the given name and the same visibility
+///   as the enums, so several state machines can live in the same file. The
+///   module sees everything in the scope of the `impl` block, while the
+///   handlers refer to the enums through the module (e.g.
+///   `blinky_fsm::State::led_on()`) or import them.
+///
+///   _Default_: no module
+///
+///   <br/>
+///
+/// - `#[state_machine(extendable = "heater")]`
+///
+///   Make the state machine available as the base of other state machines,
+///   through a template macro with the given name. A derived state machine
+///   sets `extends = "heater"` (or the path of the template) and inherits the
+///   states, superstates, actions and arguments of the base. Its own methods
+///   replace the methods of the base with the same name, or with the name
+///   given by `#[state(overrides = "on")]` (also on superstates and actions),
+///   and its own arguments take precedence.
+///
+///   The methods of the base are copied into the derived state machine, so
+///   they resolve their names where the derived state machine is declared.
+///   This means that:
+///   - The derived state machine has to be declared in another module than
+///     its base, as both generate a `State` and `Superstate` enum, which the
+///     methods of the base refer to by that name.
+///   - The items the methods of the base use (e.g. the event type) have to be
+///     imported in the module of the derived state machine.
+///   - The shared storage of the derived state machine needs the fields that
+///     the methods of the base use, with the same names and types.
+///
+///   _Default_: not extendable
+///
+///   <br/>
+///
+/// - `#[state_machine(event = "cmd")]`
+///
+///   Set the name of the handler input that receives the event, so handlers
+///   can use the vocabulary of their domain. Alias of `event_identifier`, next
+///   to which there is `context_identifier` for the context. An input can also
+///   receive the event by destructuring it with a pattern instead (e.g.
+///   `Event::Button { id }: &Event`).
+///
+///   _Default_: `"event"`
+///
+///   <br/>
+///
+/// - `#[state_machine(event_from("Button(ButtonEvent)", "Timer(TimerEvent)"))]`
+///
+///   Generate a `From` impl for every type that is wrapped by the given
+///   variants of the event enum, so events can be handled with
+///   `handle_from(ButtonEvent::Pressed)` instead of wrapping them at every
+///   call site.
+///
+///   _Default_: no `From` impls
+///
+///   <br/>
+///
+/// - `#[state_machine(events("Control(ControlEvent)", "Data(DataEvent)"))]`
+///
+///   Generate an event enum with a variant for every event type, together
+///   with the `From` impls of `event_from`. A handler that takes one of the
+///   event types (e.g. `event: &DataEvent`) is only called for the events of
+///   that type, and defers the other events to its superstate. Handlers can
+///   still take the whole enum. The enum is named `Event`, unless it is given
+///   a `name = "..."`, and derives the traits in `derive(...)`.
+///
+///   _Default_: the event type of the handlers
+///
+///   <br/>
+///
+/// - `#[state_machine(event_types("Coin", "Push"))]`
+///
+///   Route a closed set of event types to the handlers that take them, like
+///   `events(...)`, but without wrapping the events. The generated enum only
+///   borrows them (`Event::Coin(&'event Coin)`) and every type implements
+///   [`Event`](crate::Event), so an event is handled as it is with
+///   `handle_from(&Coin(50))` or `handle(&coin.as_event())`. The variants are
+///   named after the types, and `name = "..."` and `derive(...)` are
+///   supported as for `events(...)`.
+///
+///   _Default_: the event type of the handlers
+///
+///   <br/>
+///
+/// - `#[state_machine(event_routing)]`
+///
+///   Generate the [`EVENT_ROUTING`](crate::IntoStateMachine::EVENT_ROUTING)
+///   table, which lists for every state and every variant of the event enum
+///   whether the event is handled by a declared transition of the state, of
+///   one of its superstates, or not at all. Requires `events(...)` or
+///   `event_types(...)`, so the variants are known.
+///
+///   _Default_: an empty table
+///
+///   <br/>
+///
+/// - `#[state_machine(max_state_size = 32)]`
+///
+///   Fail the build when the size of the state enum exceeds the given number
+///   of bytes. Useful to guard a RAM budget against state-local storage that
+///   grows over time.
+///
+///   _Default_: no limit
+///
+///   <br/>
+///
+/// - `#[state_machine(unreachable_states = "deny")]`
+///
+///   Set how states are reported that are neither the initial state nor the
+///   target of a transition in any of the methods in the `impl` block, which
+///   are likely left behind by a refactor. Either `"allow"`, `"warn"` or
+///   `"deny"`. The warning is reported as the use of a deprecated item, as
+///   procedural macros can't emit warnings of their own. States that are
+///   only entered from outside the `impl` block (e.g. with `recover`) are
+///   reported as well, which is why the lint is opt-in.
+///
+///   _Default_: `"allow"`
+///
+///   <br/>
+///
+/// - `#[state_machine(on_action_error = "Self::on_action_error")]`
+///
+///   Set the function that receives the error of an action or handler that
+///   returns a `Result`, e.g. `fn on_action_error(&mut self, error: SocketError)`.
+///   Required when any of the actions or handlers is fallible.
+///
+///   _Default_: none
+///
+///   <br/>
+///
+/// - `#[state_machine(action_failure = "proceed")]`
+///
+///   Set what happens to a transition when one of its actions fails. With
+///   `"abort"` the remaining entry and exit actions are skipped and the state
+///   machine is left poisoned, in the state it was in when the action failed,
+///   until it is brought back with `recover`. With `"proceed"` the remaining
+///   actions are executed as if the action succeeded.
+///
+///   _Default_: `"abort"`
+///
+///   <br/>
+///
+/// - `#[state_machine(semantics = "uml")]`
+///
+///   Set the [`Semantics`] that decide which states are exited and entered
+///   during a transition. With `"pragmatic"` the states are exited up to the
+///   common ancestor of the current state and the target. With `"uml"` the
+///   state or superstate that took the transition is exited and entered again
+///   when the target is within it, as with external transitions in UML. With
+///   `"uml_local"` it is not exited, but the states within it are, as with
+///   local transitions in UML. Initialization enters the states from the top
+///   down with any semantics.
+///
+///   _Default_: `"pragmatic"`
+///
+///   <br/>
+///
+/// - `#[state_machine(on_init = "Self::on_init")]`
+///
+///   Set the function that is called once the state machine is initialized,
+///   e.g. `fn on_init(&mut self, state: &State)`, so the state it starts in can
+///   be published the same way as the transitions that follow. See
+///   [`ON_INIT`](crate::IntoStateMachine::ON_INIT).
+///
+///   _Default_: none
+///
+///   <br/>
+///
+/// - `#[state_machine(on_transition = "Self::on_transition")]`
+///
+///   Set the function that is called after every transition that is taken in
+///   response to an event, e.g.
+///   `fn on_transition(&mut self, source: &State, target: &State, event: &Event)`,
+///   so logging can record why the transition happened. See
+///   [`ON_TRANSITION`](crate::IntoStateMachine::ON_TRANSITION). Next to it
+///   there are `before_transition` and `after_transition`, which receive only
+///   the source and target state.
+///
+///   _Default_: none
+///
+///   <br/>
+///
+/// - `#[state_machine(on_dispatch = "Self::on_dispatch")]`
+///
+///   Set the function that is called before an event is dispatched to a state
+///   or superstate handler, e.g.
+///   `fn on_dispatch(&mut self, state: StateOrSuperstate<Self>, event: &Event)`.
+///   See [`BEFORE_DISPATCH`](crate::IntoStateMachine::BEFORE_DISPATCH). Alias
+///   of `before_dispatch`, next to which there is `after_dispatch`.
+///
+///   _Default_: none
+///
+///   <br/>
+///
+/// - `#[state_machine(state_id_mirror = "STATE_ID")]`
+///
+///   Mirror the id of the current state to the given `static AtomicU8` after
+///   every transition, so it can be read without access to the state machine.
+///   States are numbered in the order in which they are declared. See
+///   [`STATE_ID_MIRROR`](crate::IntoStateMachine::STATE_ID_MIRROR).
+///
+///   _Default_: no mirror
+///
+///   <br/>
+///
+/// - `#[state_machine(after = "Self::after")]`
+///
+///   Set the function that returns the timed transition of a state, i.e. the
+///   duration after which the state machine transitions to another state. See
+///   [`AFTER`](crate::IntoStateMachine::AFTER).
+///
+///   _Default_: no timed transitions
+///
+///   <br/>
 #[cfg(feature = "macro")]
 pub use statig_macro::state_machine;
 
@@ -73,7 +342,12 @@ pub use statig_macro::state_machine;
 ///
 /// - `#[state(entry_action = "entry_action_name")]`
 ///
-///   Set the entry action of the state.
+///   Set the entry action of the state. Next to the name of an action in the
+///   `impl` block, this can be the path to a free function with at least two
+///   segments (e.g. `crate::actions::start_motor` or `self::start_motor`),
+///   which is called with the shared storage and the context, so actions can
+///   be shared by several state machines. This also applies to `exit_action`
+///   and to superstates.
 ///
 ///   <br/>
 ///
@@ -83,11 +357,296 @@ pub use statig_macro::state_machine;
 ///
 ///   <br/>
 ///
+/// - `#[state(activity = "activity_name")]`
+///
+///   Set the activity of the state, an action that is called on every
+///   `tick()` (and every `step()`) while the state is active, i.e. between its
+///   entry and exit actions. This formalizes the pattern of polling something
+///   while in a state.
+///
+///   <br/>
+///
 /// - `#[state(local_storage("field_name_a: FieldTypeA", "field_name_b: FieldTypeB"))]`
 ///
 ///   Add local storage to this state. These will be added as fields to the enum variant.
 ///
+///   Attributes on local storage, either in this attribute or on the inputs of
+///   the handler, are moved to the fields of the variant. This allows to derive
+///   `Serialize` and `Deserialize` on the state enum while skipping transient
+///   data with `#[serde(skip)]`, which is then initialized with its `Default`
+///   implementation on deserialization.
+///
+///   <br/>
+///
+/// - `#[state(transitions("Event::TimerElapsed => State::led_off()"))]`
+///
+///   Declare transitions as match arms on the event (guards are allowed).
+///   The declared transitions of a state are matched right before its handler
+///   is called. Those of its superstates are flattened into the dispatch of
+///   the state and matched once its handler returns `Super`, starting from the
+///   direct superstate, so they fire without walking up the hierarchy. A
+///   substate that handles an event itself therefore takes precedence over the
+///   transitions its superstates declare for that event, while the declared
+///   transitions of all superstates are matched before any superstate handler
+///   is called.
+///
+///   The transitions of a state or superstate are matched in the order in
+///   which they are declared, unless they are given a priority (e.g.
+///   `"#[priority = 1] Event::Alarm if fire => State::open()"`), in which case
+///   the highest priority is matched first. A transition is rejected as
+///   ambiguous when an unguarded transition of the same priority before it
+///   already matches all of its events (e.g. `Event::A | Event::B` or `_`
+///   before `Event::A`), or when both are guarded and their event patterns
+///   overlap. A guarded transition followed by its fallback, or specific
+///   transitions followed by a wildcard, are not ambiguous.
+///
+///   Priorities only order the transitions of a single state or superstate.
+///   The transitions of a state and its superstates are not checked for
+///   conflicts, as those of a superstate only apply to the events the state
+///   defers. A condition transition is evaluated after the event was
+///   dispatched, unless it is given a priority (see below).
+///
+///   The declared transitions are listed in
+///   [`IntoStateMachine::TRANSITIONS`], e.g. to export the state machine to
+///   XState with [`to_xstate`] (with the `json` feature). The other way
+///   around, [`from_xstate`] generates a state machine with declared
+///   transitions from an XState machine.
+///
+///   <br/>
+///
+/// - `#[state(when = "self.pressure > LIMIT", transition = "venting")]`
+///
+///   Declare a condition transition, which is taken after an event was
+///   dispatched when the condition holds, so threshold-driven changes don't
+///   need a synthetic "check" event. The condition refers to the shared
+///   storage as `self`, and the target is the name of a state without local
+///   storage or a state (e.g. `State::venting()`). The condition is evaluated
+///   once after every event, so a condition that holds in the target is only
+///   taken after the next event.
+///
+///   With a priority (e.g. `priority = 1`), the condition transition is
+///   instead matched among the declared transitions of the state, as a guarded
+///   transition for every event (`_ if condition`) declared after the others.
+///   It is then evaluated before the event is dispatched, and takes
+///   precedence over the declared transitions of a lower priority.
+///
+///   <br/>
+///
+/// - `#[state(transition_actions("error => send_nack"))]`
+///
+///   Call an action on the transitions from this state to the given target
+///   states, after the exit actions and before the entry actions. The action
+///   is a method on the shared storage, e.g. `fn send_nack(&mut self)`. See
+///   [`TRANSITION_ACTION`](crate::IntoStateMachine::TRANSITION_ACTION).
+///
+///   <br/>
+///
+/// - `#[state(when = "all(is_charged, not(is_locked))", transition = "ready")]`
+///
+///   Combine named guards with `all(...)`, `any(...)` and `not(...)`, in the
+///   conditions of condition transitions as well as the guards of declared
+///   transitions. Within them, the name of a guard is a call to the method of
+///   that name on the shared storage, e.g. `fn is_locked(&self) -> bool`, and
+///   any other expression is used as is.
+///
+///   <br/>
+///
+/// - `#[state(choice)]`
+///
+///   Make the state a choice pseudo-state, which transitions can target to
+///   select one of several states at runtime. The handler takes only `self`
+///   and returns the selected state, e.g.
+///   `fn check_stock(&self) -> State`. It is called after the exit actions
+///   of the transition, so it sees what they did, and the transition then
+///   continues to the selected state. The state machine never rests in a
+///   choice, so it can't have local storage or actions. See
+///   [`CHOICE`](crate::IntoStateMachine::CHOICE).
+///
+///   <br/>
+///
+/// - `#[state(junction)]`
+///
+///   Make the state a junction pseudo-state, which several transitions can
+///   route through to share the actions of a common segment before they fan
+///   out. The handler is written like that of a choice, e.g.
+///   `fn pay(&mut self) -> State`, but it is called as soon as the transition
+///   is taken, before the exit actions, and the transition then goes directly
+///   to the selected state. See
+///   [`JUNCTION`](crate::IntoStateMachine::JUNCTION).
+///
+///   <br/>
+///
+/// - `#[state(terminate)]`
+///
+///   Make the state a terminate pseudo-state, e.g. `fn emergency_stop() {}`.
+///   A transition that reaches it stops the state machine on the spot: only
+///   the action of the transition itself is performed, no state is exited or
+///   entered, and afterwards events are ignored and
+///   `is_terminated()` returns `true`. See
+///   [`TERMINATE`](crate::IntoStateMachine::TERMINATE).
+///
+///   Choices, junctions and terminates are the only pseudo-states. Fork and
+///   join are not supported, and are rejected as such (e.g. `#[state(fork)]`),
+///   as they enter and leave several orthogonal regions at once, while a
+///   state machine is always in exactly one state.
+///   Regions can be modelled as separate state machines instead, with a
+///   [`Router`](crate::blocking::Router) delivering the events between them.
+///
+///   <br/>
+///
+/// - `#[state(payload = "cause: Cause")]`
+///
+///   Declare a payload that is passed to the state by the transition that
+///   enters it, with `Transition(State::error_with(cause))` (or
+///   `build_with(cause)` for a state with a builder). It is kept as
+///   `cause: Option<Cause>` next to the local storage, so the entry action and
+///   the handler can react to the reason for the transition, and it is `None`
+///   when the state is entered through `State::error()`.
+///
+///   Only the states that declare a payload get a constructor that takes one,
+///   so passing a payload to a state that doesn't take it fails to compile:
+///
+///   ```compile_fail
+///   # use statig::prelude::*;
+///   # #[derive(Default)]
+///   # pub struct Controller;
+///   #
+///   # pub enum Cause { Timeout }
+///   #
+///   # pub struct Event;
+///   #
+///   # #[state_machine(initial = "State::running()")]
+///   # impl Controller {
+///   #[state]
+///   fn running(event: &Event) -> Response<State> {
+///       Transition(State::idle_with(Cause::Timeout))
+///   }
+///
+///   #[state]
+///   fn idle(event: &Event) -> Response<State> {
+///       Handled
+///   }
+///   # }
+///   ```
+///
+///   <br/>
+///
+/// - `#[state(boxed)]`
+///
+///   Store the local storage of this state on the heap, so a state that
+///   carries a large amount of data does not increase the size of the state
+///   enum. Every field is wrapped in a `Box` and the constructor of the state
+///   is no longer a `const fn`. Requires the `alloc` feature.
+///
+///   <br/>
+///
+/// - `#[state(builder)]`
+///
+///   Construct the state with a builder, so adding local storage doesn't
+///   break every transition to the state, e.g.
+///   `State::downloading().url(url).retries(3).build()`. Fields that are not
+///   set keep their `Default` value, or the value that is given with a
+///   `#[default(3)]` attribute on the field. When every field has such a
+///   default value, the state can be built in a const context, e.g. as the
+///   initial state, as long as its local storage doesn't need to be dropped.
+///   Not supported for state machines with generics.
+///
+///   <br/>
+///
+/// - `#[state(derive(Serialize))]`
+///
+///   Apply derives to the local storage of only this state. The local storage
+///   is kept in a struct that is named after the variant (e.g. `LedOnStorage`),
+///   which also receives the derives of the state enum, and the variant becomes
+///   `State::LedOn(LedOnStorage { .. })`. The local storage can not use the
+///   generics of the shared storage.
+///
+///   <br/>
+///
+/// - `#[state(const(TIMEOUT_MS = 500u32, LABEL = "dialing"))]`
+///
+///   Declare constants of the state, so per-state configuration lives next to
+///   its handler. For every constant a `const fn` is added to the state enum
+///   that returns its value for the current state, or `None` when the state
+///   doesn't declare it (e.g. `State::dialing().timeout_ms() == Some(500)`).
+///   The type of a constant follows from the suffix of any of its values, and
+///   otherwise defaults to the type Rust would infer (e.g. `i32`).
+///
 ///   <br/>
+///
+/// - `#[state(meta(owner = "comms", severity = "high"))]`
+///
+///   Attach string metadata to the state, which is listed in the
+///   [`HIERARCHY`](crate::IntoStateMachine::HIERARCHY) of the state machine
+///   so tooling can look it up without parsing the source. This also applies
+///   to superstates.
+///
+///   <br/>
+///
+/// A handler receives the event by reference (e.g. `event: &Event`), or by
+/// value when the event is `Copy` (e.g. `event: Event`). The event can't be
+/// received by mutable reference, as it is shared with the superstates and
+/// the dispatch hooks, so data is taken out of an event with interior
+/// mutability instead (e.g. a `Cell<Option<Buffer>>`). A handler that takes
+/// `event: &mut Event` is rejected at compile time:
+///
+/// ```compile_fail
+/// # use statig::prelude::*;
+/// # enum Event { Data(Vec<u8>) }
+/// # #[derive(Default)]
+/// # struct Receiver;
+/// #[state_machine(initial = "State::receiving()")]
+/// impl Receiver {
+///     #[state]
+///     fn receiving(event: &mut Event) -> Response<State> {
+///         match event {
+///             Event::Data(buffer) => {
+///                 let _buffer = core::mem::take(buffer);
+///                 Handled
+///             }
+///         }
+///     }
+/// }
+/// ```
+///
+/// A handler without a return type, or one that returns `()`, handles every
+/// event it receives as if it returned `Handled`, so states that only have
+/// side effects (e.g. logging or counting) don't need to return a response.
+/// A handler can also return the target state directly (e.g. `-> State`), or
+/// anything else that implements `Into<Response<State>>`, which is converted
+/// into a response. Any of these can be wrapped in a `Result`, so fallible
+/// APIs can be called with `?` and their errors are converted with `From`
+/// into the error type of the handler. The error is passed to the
+/// `on_action_error` function of the state machine and the event is then
+/// considered handled. This also applies to superstates.
+///
+/// A handler whose name collides with a keyword can be named with a raw
+/// identifier (e.g. `fn r#loop`), which is also how it is referenced in the
+/// attributes of other handlers (e.g. `superstate = "r#loop"`). The `r#` prefix
+/// is dropped from the name of the variant, so the state becomes `State::Loop`.
+///
+/// A state or superstate can be conditionally compiled by adding a `#[cfg]`
+/// attribute to its handler, e.g. `#[cfg(feature = "factory")]`. The attribute
+/// is applied to everything that is generated for it, and the ids of the other
+/// states don't change when it's compiled out. A state must repeat the `cfg`
+/// attributes of its superstate, so the superstate never disappears from under
+/// it. Likewise, every handler, statement, match arm or declared transition
+/// that references a conditionally compiled state must repeat its `cfg`
+/// attributes. An entry or exit action that is compiled out is not called.
+///
+/// Two handlers can share a name when they are compiled under different `cfg`
+/// attributes (e.g. `#[cfg(test)] fn idle` next to `#[cfg(not(test))] fn
+/// idle`), as long as they only differ in their body. They describe the same
+/// state, which is compiled whenever either of them is. Any other handlers
+/// with the same name are rejected.
+///
+/// Doc comments and `#[deprecated]` attributes on a state or superstate handler
+/// are forwarded to the generated variant, and for states also to the
+/// constructor, so transitions to a deprecated state are flagged. The same
+/// goes for `#[allow]` and `#[expect]` attributes, where the latter is
+/// forwarded as an `#[allow]`, so lints silenced on a handler are also silenced
+/// on the code generated for it. The generated enums are documented and don't
+/// trigger `missing_docs` for undocumented variants.
 #[cfg(feature = "macro")]
 pub use statig_macro::state;
 
@@ -122,6 +681,27 @@ pub use statig_macro::state;
 ///
 ///   <br/>
 ///
+/// - `#[superstate(default = "otherwise")]`
+///
+///   Set the handler that receives the events that don't match the event
+///   pattern of the superstate handler (e.g. `Event::Reset: &Event`), instead
+///   of deferring them to the next superstate. This gives every level of the
+///   hierarchy an "otherwise" arm. The default handler is a method in the
+///   `impl` block without an attribute, which takes the same kind of inputs as
+///   a handler.
+///
+///   <br/>
+///
+/// - `#[superstate(completion = "State::stopped()")]`
+///
+///   Set the completion transition of the superstate, which is taken when one
+///   of its states (or superstates) returns `Completed` to signal that it is
+///   done, like a completion event of a composite state in UML. When the
+///   superstate doesn't declare one, the completion is passed on to the next
+///   superstate, and otherwise it is considered handled.
+///
+///   <br/>
+///
 /// - `#[superstate(local_storage("field_name_a: &'a mut FieldTypeA"))]`
 ///
 ///   Add local storage to this superstate. These will be added as fields to
@@ -131,6 +711,13 @@ pub use statig_macro::state;
 ///   associated lifetime `'a`.
 ///
 ///   <br/>
+///
+/// - `#[superstate(transitions("Event::ButtonPressed => State::idle()"))]`
+///
+///   Declare transitions that apply to every state that is nested within this
+///   superstate. See the `state` attribute.
+///
+///   <br/>
 #[cfg(feature = "macro")]
 pub use statig_macro::superstate;
 
@@ -138,6 +725,16 @@ pub use statig_macro::superstate;
 ///
 /// This macro does nothing on its own but is detected by the `state_machine`
 /// macro when added to a method.
+///
+/// An action can be an `async fn`, which makes the state machine awaitable.
+/// The action is then awaited when it is executed during `init`, a transition
+/// or `shutdown`, so entry actions can acquire resources asynchronously. Free
+/// functions that are used as actions are always called synchronously.
+///
+/// An action can also return a `Result<(), E>`, in which case the error is
+/// passed to the `on_action_error` function of the state machine and the
+/// transition is aborted or proceeds according to `action_failure`. Free
+/// functions that are used as actions can't fail.
 #[cfg(feature = "macro")]
 pub use statig_macro::action;
 
@@ -159,6 +756,31 @@ pub mod awaitable;
 
 pub(crate) use inner::*;
 
+#[cfg(feature = "cbor")]
+pub use cbor_codec::*;
+#[cfg(feature = "alloc")]
+pub use dyn_event::*;
+#[cfg(feature = "embedded-hal")]
+pub use embedded_hal_timer::*;
+pub use event::*;
+pub use event_pool::*;
+#[cfg(feature = "std")]
+pub use handler_panic::*;
 pub use into_state_machine::*;
+#[cfg(feature = "json")]
+pub use json_codec::*;
+#[cfg(feature = "json")]
+pub use model::*;
+#[cfg(any(feature = "async", doc))]
+pub use notifier::*;
+#[cfg(feature = "postcard")]
+pub use postcard_codec::*;
+pub use queue::*;
+pub use rate_limit::*;
 pub use response::*;
+pub use state_info::*;
 pub use state_or_superstate::*;
+pub use state_store::*;
+pub use timer::*;
+#[cfg(feature = "json")]
+pub use xstate::*;
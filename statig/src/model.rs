@@ -0,0 +1,289 @@
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::{IntoStateMachine, StateInfo};
+
+/// The structure of a state machine, built from its
+/// [`HIERARCHY`](IntoStateMachine::HIERARCHY) and written as JSON, so the
+/// models of two versions of the firmware can be [compared](Model::compare)
+/// before an update.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # use statig::Model;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub struct Event;
+/// #
+/// # #[state_machine(initial = "State::on()")]
+/// # impl Blinky {
+/// #     #[state(superstate = "blinking")]
+/// #     fn on(event: &Event) -> Response<State> { Transition(State::off()) }
+/// #
+/// #     #[state(superstate = "blinking")]
+/// #     fn off(event: &Event) -> Response<State> { Transition(State::on()) }
+/// #
+/// #     #[superstate]
+/// #     fn blinking(event: &Event) -> Response<State> { Super }
+/// # }
+/// #
+/// let json = Model::of::<Blinky>().to_json().to_string();
+///
+/// // In the build of the next version of the firmware.
+/// let old = Model::from_json(&serde_json::from_str(&json).unwrap()).unwrap();
+/// let diff = old.compare(&Model::of::<Blinky>());
+///
+/// assert!(diff.is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    /// The states and superstates, in declaration order.
+    pub states: Vec<ModelState>,
+}
+
+/// A state or superstate of a [`Model`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelState {
+    /// The name of the handler of the state or superstate.
+    pub name: String,
+    /// The name of the superstate, if there is one.
+    pub superstate: Option<String>,
+    /// Whether this is a superstate.
+    pub is_superstate: bool,
+}
+
+impl From<&StateInfo> for ModelState {
+    fn from(info: &StateInfo) -> Self {
+        Self {
+            name: info.name.to_owned(),
+            superstate: info.superstate.map(str::to_owned),
+            is_superstate: info.is_superstate,
+        }
+    }
+}
+
+/// Error that occurs when a model is read from JSON that doesn't describe a
+/// model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidModel(String);
+
+impl fmt::Display for InvalidModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid model: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidModel {}
+
+impl Model {
+    /// Get the model of the state machine `M`.
+    pub fn of<M>() -> Self
+    where
+        M: IntoStateMachine,
+    {
+        Self::from_hierarchy(M::HIERARCHY)
+    }
+
+    /// Build a model from a hierarchy.
+    pub fn from_hierarchy(hierarchy: &[StateInfo]) -> Self {
+        Self {
+            states: hierarchy.iter().map(ModelState::from).collect(),
+        }
+    }
+
+    /// Write the model as JSON, e.g.
+    /// `{"states":[{"name":"on","superstate":"blinking","is_superstate":false}]}`.
+    pub fn to_json(&self) -> Value {
+        let states: Vec<Value> = self
+            .states
+            .iter()
+            .map(|state| {
+                json!({
+                    "name": state.name,
+                    "superstate": state.superstate,
+                    "is_superstate": state.is_superstate,
+                })
+            })
+            .collect();
+        json!({ "states": states })
+    }
+
+    /// Read a model that was written with [`to_json`](Self::to_json).
+    pub fn from_json(json: &Value) -> Result<Self, InvalidModel> {
+        let states = json
+            .get("states")
+            .and_then(Value::as_array)
+            .ok_or_else(|| InvalidModel("expected an array of `states`".to_owned()))?;
+        let states = states
+            .iter()
+            .map(|state| {
+                let name = state
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| InvalidModel("expected the `name` of a state".to_owned()))?;
+                let superstate = match state.get("superstate") {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(superstate)) => Some(superstate.clone()),
+                    Some(_) => {
+                        return Err(InvalidModel(format!(
+                            "expected the `superstate` of `{name}` to be a string or null"
+                        )))
+                    }
+                };
+                let is_superstate = state
+                    .get("is_superstate")
+                    .map(|is_superstate| {
+                        is_superstate.as_bool().ok_or_else(|| {
+                            InvalidModel(format!(
+                                "expected `is_superstate` of `{name}` to be a bool"
+                            ))
+                        })
+                    })
+                    .transpose()?
+                    .unwrap_or(false);
+                Ok(ModelState {
+                    name: name.to_owned(),
+                    superstate,
+                    is_superstate,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { states })
+    }
+
+    /// Get a state or superstate by name.
+    pub fn state(&self, name: &str) -> Option<&ModelState> {
+        self.states.iter().find(|state| state.name == name)
+    }
+
+    /// Get the names of the states, without the superstates, in the order of
+    /// the variants of the state enum.
+    pub fn leaf_states(&self) -> impl Iterator<Item = &str> {
+        self.states
+            .iter()
+            .filter(|state| !state.is_superstate)
+            .map(|state| state.name.as_str())
+    }
+
+    /// Compare this model with the model of a newer version of the state
+    /// machine.
+    pub fn compare(&self, new: &Model) -> ModelDiff {
+        let added = new
+            .states
+            .iter()
+            .filter(|state| self.state(&state.name).is_none())
+            .map(|state| state.name.clone())
+            .collect();
+        let removed = self
+            .states
+            .iter()
+            .filter(|state| new.state(&state.name).is_none())
+            .map(|state| state.name.clone())
+            .collect();
+        let moved = self
+            .states
+            .iter()
+            .filter_map(|old| {
+                let new = new.state(&old.name)?;
+                (old.superstate != new.superstate).then(|| MovedState {
+                    name: old.name.clone(),
+                    old_superstate: old.superstate.clone(),
+                    new_superstate: new.superstate.clone(),
+                })
+            })
+            .collect();
+
+        // A state that was stored by name can be restored if there still is a
+        // state (not a superstate) with that name.
+        let unrestorable_by_name = self
+            .leaf_states()
+            .filter(|name| new.state(name).map_or(true, |state| state.is_superstate))
+            .map(str::to_owned)
+            .collect();
+
+        // A state that was stored by the index of its variant is restored as
+        // whichever state now has that index.
+        let mut new_leaf_states = new.leaf_states();
+        let unrestorable_by_index = self
+            .leaf_states()
+            .enumerate()
+            .filter_map(|(index, old)| {
+                let new = new_leaf_states.next();
+                (new != Some(old)).then(|| IndexMismatch {
+                    index,
+                    old: old.to_owned(),
+                    new: new.map(str::to_owned),
+                })
+            })
+            .collect();
+
+        ModelDiff {
+            added,
+            removed,
+            moved,
+            unrestorable_by_name,
+            unrestorable_by_index,
+        }
+    }
+}
+
+/// A state or superstate that has a different superstate in the new model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovedState {
+    /// The name of the state or superstate.
+    pub name: String,
+    /// The superstate in the old model.
+    pub old_superstate: Option<String>,
+    /// The superstate in the new model.
+    pub new_superstate: Option<String>,
+}
+
+/// A state that is stored by the index of its variant, and which would be
+/// restored as a different state by the new model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexMismatch {
+    /// The index of the variant.
+    pub index: usize,
+    /// The state with this index in the old model.
+    pub old: String,
+    /// The state with this index in the new model, if there is one.
+    pub new: Option<String>,
+}
+
+/// The differences between two models, as reported by [`Model::compare`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelDiff {
+    /// States and superstates that were added.
+    pub added: Vec<String>,
+    /// States and superstates that were removed.
+    pub removed: Vec<String>,
+    /// States and superstates that were moved to another superstate.
+    pub moved: Vec<MovedState>,
+    /// Persisted states that can't be restored by a codec that stores states
+    /// by name (e.g. [`JsonCodec`](crate::JsonCodec)).
+    pub unrestorable_by_name: Vec<String>,
+    /// Persisted states that would be restored as another state by a codec
+    /// that stores states by the index of their variant (e.g. postcard).
+    pub unrestorable_by_index: Vec<IndexMismatch>,
+}
+
+impl ModelDiff {
+    /// Check whether the models are the same.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Check whether every state persisted by the old model can be restored
+    /// by the new model with a codec that stores states by name.
+    pub fn restores_by_name(&self) -> bool {
+        self.unrestorable_by_name.is_empty()
+    }
+
+    /// Check whether every state persisted by the old model can be restored
+    /// by the new model with a codec that stores states by index.
+    pub fn restores_by_index(&self) -> bool {
+        self.unrestorable_by_index.is_empty()
+    }
+}
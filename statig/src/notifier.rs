@@ -0,0 +1,155 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+
+/// Wakes an async dispatcher when an event arrives, so the same dispatcher
+/// works across runtimes and bare-metal targets.
+///
+/// A notification is latched: when [`notify`](Self::notify) is called while
+/// nobody is waiting, the next [`notified`](Self::notified) future completes
+/// immediately, so an event that arrives between checking the queue and
+/// waiting is never missed.
+///
+/// With the `tokio` feature this is implemented for
+/// [`tokio::sync::Notify`](https://docs.rs/tokio/latest/tokio/sync/struct.Notify.html),
+/// with the `embassy` feature for
+/// [`embassy_sync::signal::Signal<_, ()>`](https://docs.rs/embassy-sync/latest/embassy_sync/signal/struct.Signal.html),
+/// and [`SpinNotifier`] works without a runtime.
+pub trait Notifier {
+    /// Future that completes once the notifier is notified.
+    type Notified<'a>: Future<Output = ()>
+    where
+        Self: 'a;
+
+    /// Notify the waiting dispatcher, or the next one that waits.
+    fn notify(&self);
+
+    /// Wait until the notifier is notified.
+    fn notified(&self) -> Self::Notified<'_>;
+}
+
+#[cfg(feature = "tokio")]
+impl Notifier for tokio::sync::Notify {
+    type Notified<'a> = tokio::sync::futures::Notified<'a>;
+
+    fn notify(&self) {
+        self.notify_one();
+    }
+
+    fn notified(&self) -> Self::Notified<'_> {
+        tokio::sync::Notify::notified(self)
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<M> Notifier for embassy_sync::signal::Signal<M, ()>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    type Notified<'a>
+        = SignalNotified<'a, M>
+    where
+        Self: 'a;
+
+    fn notify(&self) {
+        self.signal(());
+    }
+
+    fn notified(&self) -> Self::Notified<'_> {
+        SignalNotified { signal: self }
+    }
+}
+
+/// Future returned by [`Notifier::notified`] for an embassy
+/// [`Signal`](embassy_sync::signal::Signal).
+#[cfg(feature = "embassy")]
+pub struct SignalNotified<'a, M>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    signal: &'a embassy_sync::signal::Signal<M, ()>,
+}
+
+#[cfg(feature = "embassy")]
+impl<'a, M> Future for SignalNotified<'a, M>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Waiting on the signal keeps no state besides the waker it registers
+        // with the signal, so a fresh wait can be polled every time.
+        let mut wait = self.signal.wait();
+        Pin::new(&mut wait).poll(cx)
+    }
+}
+
+/// [`Notifier`] backed by an atomic flag, for when there is no runtime to wake
+/// the dispatcher. While it waits, the dispatcher calls the `idle` function
+/// and polls the flag again, e.g. with `cortex_m::asm::wfi` as `idle` the core
+/// sleeps until the next interrupt, which is where the events come from.
+#[derive(Debug)]
+pub struct SpinNotifier {
+    notified: AtomicBool,
+    idle: fn(),
+}
+
+impl SpinNotifier {
+    /// Create a notifier that spins while the dispatcher waits.
+    pub const fn new() -> Self {
+        Self::with_idle(core::hint::spin_loop)
+    }
+
+    /// Create a notifier that calls `idle` while the dispatcher waits.
+    pub const fn with_idle(idle: fn()) -> Self {
+        Self {
+            notified: AtomicBool::new(false),
+            idle,
+        }
+    }
+}
+
+impl Default for SpinNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier for SpinNotifier {
+    type Notified<'a> = SpinNotified<'a>;
+
+    fn notify(&self) {
+        self.notified.store(true, Ordering::SeqCst);
+    }
+
+    fn notified(&self) -> Self::Notified<'_> {
+        SpinNotified { notifier: self }
+    }
+}
+
+/// Future returned by [`SpinNotifier::notified`].
+#[derive(Debug)]
+pub struct SpinNotified<'a> {
+    notifier: &'a SpinNotifier,
+}
+
+impl<'a> Future for SpinNotified<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Only loads and stores are used, as targets like `thumbv6m` have no
+        // atomic swap. A notification that arrives between the load and the
+        // store is not lost: its event was queued before the flag was set, and
+        // the dispatcher checks the queue after it is woken.
+        if self.notifier.notified.load(Ordering::SeqCst) {
+            self.notifier.notified.store(false, Ordering::SeqCst);
+            return Poll::Ready(());
+        }
+        (self.notifier.idle)();
+        // Nothing wakes the task when the flag is set, so it polls again.
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
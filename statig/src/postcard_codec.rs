@@ -0,0 +1,96 @@
+use core::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{IntoStateMachine, StateCodec};
+
+/// Number of bytes of the checksum that follows the payload.
+const CHECKSUM_LEN: usize = 4;
+
+/// Codec that serializes the state with [`postcard`](::postcard), including
+/// any state-local storage.
+///
+/// The payload is followed by a CRC-32 checksum, so a state that was corrupted
+/// in storage (e.g. by a flash page that was only partially written) is
+/// detected when it's decoded. Use
+/// [`restore_or_initial`](crate::blocking::PersistentStateMachine::restore_or_initial)
+/// to start from the initial state in that case.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PostcardCodec;
+
+/// Error that occurs when a state can not be encoded or decoded by the
+/// [`PostcardCodec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostcardCodecError {
+    /// The state could not be serialized or deserialized.
+    Postcard(::postcard::Error),
+    /// The checksum does not match the payload.
+    Corrupted,
+}
+
+impl fmt::Display for PostcardCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Postcard(error) => write!(f, "{error}"),
+            Self::Corrupted => write!(f, "checksum does not match the stored state"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PostcardCodecError {}
+
+impl<M> StateCodec<M> for PostcardCodec
+where
+    M: IntoStateMachine,
+    M::State: Serialize + DeserializeOwned,
+{
+    type Error = PostcardCodecError;
+
+    fn encode<'buf>(
+        &self,
+        state: &M::State,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error> {
+        let capacity =
+            buffer
+                .len()
+                .checked_sub(CHECKSUM_LEN)
+                .ok_or(PostcardCodecError::Postcard(
+                    ::postcard::Error::SerializeBufferFull,
+                ))?;
+        let len = ::postcard::to_slice(state, &mut buffer[..capacity])
+            .map_err(PostcardCodecError::Postcard)?
+            .len();
+        let checksum = crc32(&buffer[..len]);
+        buffer[len..len + CHECKSUM_LEN].copy_from_slice(&checksum.to_le_bytes());
+        Ok(&buffer[..len + CHECKSUM_LEN])
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<M::State, Self::Error> {
+        let len = bytes
+            .len()
+            .checked_sub(CHECKSUM_LEN)
+            .ok_or(PostcardCodecError::Corrupted)?;
+        let (payload, checksum) = bytes.split_at(len);
+        if checksum != crc32(payload).to_le_bytes() {
+            return Err(PostcardCodecError::Corrupted);
+        }
+        ::postcard::from_bytes(payload).map_err(PostcardCodecError::Postcard)
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of the given bytes, computed bit by bit so it doesn't
+/// need a lookup table.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,291 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+
+/// FIFO queue of events that are waiting to be handled.
+///
+/// The queue can be backed by different kinds of storage, so the same state
+/// machine code runs on targets with and without an allocator:
+///
+/// - [`ArrayQueue`] has a fixed capacity given by a const generic and needs no
+///   allocator.
+/// - `VecDeque` grows on the heap and is available with the `alloc` feature.
+/// - [`SliceQueue`] uses storage supplied by the user, e.g. a `static` buffer
+///   or a region of memory that is shared with other tasks.
+///
+/// What happens when a bounded queue is full is decided by wrapping it in an
+/// [`OverflowQueue`] with an [`OverflowPolicy`]. Without one, the event that
+/// doesn't fit is handed back.
+pub trait EventQueue<E> {
+    /// Push an event to the back of the queue. If the queue is full the event
+    /// is handed back.
+    fn push(&mut self, event: E) -> Result<(), E>;
+
+    /// Pop an event from the front of the queue.
+    fn pop(&mut self) -> Option<E>;
+
+    /// Get the number of events in the queue.
+    fn len(&self) -> usize;
+
+    /// Check whether the queue is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Fixed-capacity FIFO queue backed by an array, so it can be used without an
+/// allocator and constructed in a `const` context.
+pub struct ArrayQueue<T, const N: usize> {
+    items: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayQueue<T, N> {
+    const EMPTY: Option<T> = None;
+
+    /// Create an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            items: [Self::EMPTY; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Push an item to the back of the queue. If the queue is full the item is
+    /// handed back.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(item);
+        }
+        self.items[(self.head + self.len) % N] = Some(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Pop an item from the front of the queue.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let item = self.items[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        item
+    }
+
+    /// Get the number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the maximum number of items the queue can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Default for ArrayQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, const N: usize> EventQueue<E> for ArrayQueue<E, N> {
+    fn push(&mut self, event: E) -> Result<(), E> {
+        ArrayQueue::push(self, event)
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        ArrayQueue::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// FIFO queue that stores its items in a slice supplied by the user. The
+/// capacity of the queue is the length of the slice.
+///
+/// ```
+/// # use statig::{EventQueue, SliceQueue};
+/// let mut storage: [Option<u8>; 4] = Default::default();
+/// let mut queue = SliceQueue::new(&mut storage);
+///
+/// queue.push(1).unwrap();
+/// queue.push(2).unwrap();
+///
+/// assert_eq!(queue.pop(), Some(1));
+/// assert_eq!(queue.len(), 1);
+/// ```
+pub struct SliceQueue<'a, T> {
+    items: &'a mut [Option<T>],
+    head: usize,
+    len: usize,
+}
+
+impl<'a, T> SliceQueue<'a, T> {
+    /// Create an empty queue on top of the given storage. Any items that are
+    /// still in the storage are dropped.
+    pub fn new(items: &'a mut [Option<T>]) -> Self {
+        items.iter_mut().for_each(|item| *item = None);
+        Self {
+            items,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Get the maximum number of items the queue can hold.
+    pub fn capacity(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl<'a, E> EventQueue<E> for SliceQueue<'a, E> {
+    fn push(&mut self, event: E) -> Result<(), E> {
+        let capacity = self.capacity();
+        if self.len == capacity {
+            return Err(event);
+        }
+        self.items[(self.head + self.len) % capacity] = Some(event);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        if self.len == 0 {
+            return None;
+        }
+        let event = self.items[self.head].take();
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
+        event
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E> EventQueue<E> for VecDeque<E> {
+    fn push(&mut self, event: E) -> Result<(), E> {
+        self.push_back(event);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        self.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+}
+
+/// What an [`OverflowQueue`] does with an event that is pushed while it is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the event at the front of the queue to make room for the new one.
+    DropOldest,
+    /// Drop the new event and keep the queue as it is.
+    DropNewest,
+    /// Hand the new event back to the caller.
+    #[default]
+    Error,
+}
+
+/// Wraps a bounded [`EventQueue`] to apply an [`OverflowPolicy`] when it is
+/// full, and counts the events that were dropped because of it.
+///
+/// Together with the capacity of an [`ArrayQueue`] this lets you budget
+/// exactly how much memory the events of a state machine can take, and decide
+/// which events are lost when that budget is exceeded.
+///
+/// ```
+/// # use statig::{ArrayQueue, EventQueue, OverflowPolicy, OverflowQueue};
+/// let mut queue = OverflowQueue::new(ArrayQueue::<u8, 2>::new(), OverflowPolicy::DropOldest);
+///
+/// queue.push(1).unwrap();
+/// queue.push(2).unwrap();
+/// queue.push(3).unwrap();
+///
+/// assert_eq!(queue.pop(), Some(2));
+/// assert_eq!(queue.dropped(), 1);
+/// ```
+pub struct OverflowQueue<Q> {
+    queue: Q,
+    policy: OverflowPolicy,
+    dropped: usize,
+}
+
+impl<Q> OverflowQueue<Q> {
+    /// Apply the given policy to the queue.
+    pub const fn new(queue: Q, policy: OverflowPolicy) -> Self {
+        Self {
+            queue,
+            policy,
+            dropped: 0,
+        }
+    }
+
+    /// Get the policy that is applied when the queue is full.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Get the number of events that were dropped because the queue was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Get a reference to the wrapped queue.
+    pub fn inner(&self) -> &Q {
+        &self.queue
+    }
+
+    /// Get the wrapped queue back.
+    pub fn into_inner(self) -> Q {
+        self.queue
+    }
+}
+
+impl<E, Q> EventQueue<E> for OverflowQueue<Q>
+where
+    Q: EventQueue<E>,
+{
+    fn push(&mut self, event: E) -> Result<(), E> {
+        let event = match self.queue.push(event) {
+            Ok(()) => return Ok(()),
+            Err(event) => event,
+        };
+        match self.policy {
+            OverflowPolicy::DropOldest => {
+                if self.queue.pop().is_some() {
+                    self.dropped += 1;
+                }
+                self.queue.push(event)
+            }
+            OverflowPolicy::DropNewest => {
+                self.dropped += 1;
+                Ok(())
+            }
+            OverflowPolicy::Error => Err(event),
+        }
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        self.queue.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
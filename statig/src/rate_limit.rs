@@ -0,0 +1,198 @@
+use core::time::Duration;
+
+use crate::{Clock, EventQueue};
+
+/// Token bucket rate limit: up to `burst` events are let through at once, and
+/// after that one event for every `interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of events that can be let through at once.
+    pub burst: u32,
+    /// The time it takes to earn the right to let through one more event.
+    pub interval: Duration,
+}
+
+impl RateLimit {
+    /// Let through `burst` events at once, and one event for every `interval`
+    /// after that.
+    pub const fn new(burst: u32, interval: Duration) -> Self {
+        Self { burst, interval }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    limit: Option<RateLimit>,
+    tokens: u32,
+    refilled_at: Duration,
+}
+
+impl Bucket {
+    const UNLIMITED: Self = Self {
+        limit: None,
+        tokens: 0,
+        refilled_at: Duration::ZERO,
+    };
+
+    fn take(&mut self, now: Duration) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+        let elapsed = now.saturating_sub(self.refilled_at);
+        let earned = match limit.interval.as_nanos() {
+            0 => u128::from(limit.burst),
+            interval => elapsed.as_nanos() / interval,
+        };
+        let tokens = u128::from(self.tokens) + earned;
+        if tokens >= u128::from(limit.burst) {
+            self.tokens = limit.burst;
+            self.refilled_at = now;
+        } else {
+            // Keep the time that was not yet enough to earn another token.
+            self.tokens = tokens as u32;
+            self.refilled_at += limit.interval * earned as u32;
+        }
+        match self.tokens {
+            0 => false,
+            _ => {
+                self.tokens -= 1;
+                true
+            }
+        }
+    }
+
+    /// Give back a token that was taken for an event that didn't get through.
+    fn refund(&mut self) {
+        if let Some(limit) = self.limit {
+            self.tokens = (self.tokens + 1).min(limit.burst);
+        }
+    }
+}
+
+/// Queue middleware that enforces a rate limit for every kind of event before
+/// the events reach the state machine, e.g. for a state machine that is
+/// exposed to an untrusted source of events.
+///
+/// The kind of an event is an index below `K` given by the `kind` function.
+/// Kinds without a limit, and indices of `K` or above, are not limited. An
+/// event that exceeds the limit of its kind is handed back by
+/// [`push`](EventQueue::push), as is an event that the wrapped queue has no
+/// room for, which doesn't use up a token of its kind.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use statig::{ArrayQueue, EventQueue, RateLimit, RateLimitedQueue};
+/// enum Event {
+///     Ping,
+///     Command(u8),
+/// }
+///
+/// fn kind(event: &Event) -> usize {
+///     match event {
+///         Event::Ping => 0,
+///         Event::Command(_) => 1,
+///     }
+/// }
+///
+/// let now = || Duration::ZERO;
+/// let mut queue: RateLimitedQueue<_, _, Event, 2> =
+///     RateLimitedQueue::new(ArrayQueue::<Event, 8>::new(), now, kind)
+///         .with_limit(0, RateLimit::new(1, Duration::from_secs(1)));
+///
+/// assert!(queue.push(Event::Ping).is_ok());
+/// assert!(queue.push(Event::Ping).is_err());
+/// assert!(queue.push(Event::Command(1)).is_ok());
+/// ```
+pub struct RateLimitedQueue<Q, C, E, const K: usize> {
+    queue: Q,
+    clock: C,
+    kind: fn(&E) -> usize,
+    buckets: [Bucket; K],
+    rejected: usize,
+}
+
+impl<Q, C, E, const K: usize> RateLimitedQueue<Q, C, E, K>
+where
+    Q: EventQueue<E>,
+    C: Clock,
+{
+    /// Limit the rate at which events are pushed to the queue, with the rate
+    /// limits measured by the given clock. No kind of event is limited until a
+    /// limit is set with [`with_limit`](Self::with_limit).
+    pub fn new(queue: Q, clock: C, kind: fn(&E) -> usize) -> Self {
+        Self {
+            queue,
+            clock,
+            kind,
+            buckets: [Bucket::UNLIMITED; K],
+            rejected: 0,
+        }
+    }
+
+    /// Set the rate limit of the given kind of event.
+    pub fn with_limit(mut self, kind: usize, limit: RateLimit) -> Self {
+        self.set_limit(kind, Some(limit));
+        self
+    }
+
+    /// Set or remove the rate limit of the given kind of event. A kind with a
+    /// new limit starts with a full bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kind` is not below `K`.
+    pub fn set_limit(&mut self, kind: usize, limit: Option<RateLimit>) {
+        self.buckets[kind] = Bucket {
+            limit,
+            tokens: limit.map_or(0, |limit| limit.burst),
+            refilled_at: self.clock.now(),
+        };
+    }
+
+    /// Get the number of events that were handed back, because they exceeded
+    /// the rate limit of their kind or because the wrapped queue was full.
+    pub fn rejected(&self) -> usize {
+        self.rejected
+    }
+
+    /// Get a reference to the wrapped queue.
+    pub fn inner(&self) -> &Q {
+        &self.queue
+    }
+
+    /// Get the wrapped queue back.
+    pub fn into_inner(self) -> Q {
+        self.queue
+    }
+}
+
+impl<Q, C, E, const K: usize> EventQueue<E> for RateLimitedQueue<Q, C, E, K>
+where
+    Q: EventQueue<E>,
+    C: Clock,
+{
+    fn push(&mut self, event: E) -> Result<(), E> {
+        let kind = (self.kind)(&event);
+        if let Some(bucket) = self.buckets.get_mut(kind) {
+            if !bucket.take(self.clock.now()) {
+                self.rejected += 1;
+                return Err(event);
+            }
+        }
+        self.queue.push(event).map_err(|event| {
+            if let Some(bucket) = self.buckets.get_mut(kind) {
+                bucket.refund();
+            }
+            self.rejected += 1;
+            event
+        })
+    }
+
+    fn pop(&mut self) -> Option<E> {
+        self.queue.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
@@ -8,6 +8,17 @@ pub enum Response<S> {
     Super,
     /// Transition to the given state.
     Transition(S),
+    /// Signal that the state is done, which takes the completion transition
+    /// of the nearest superstate that declares one.
+    Completed,
+}
+
+/// A bare state is a transition to that state, so handlers can return the
+/// target state directly.
+impl<S> From<S> for Response<S> {
+    fn from(state: S) -> Self {
+        Self::Transition(state)
+    }
 }
 
 impl<S> PartialEq for Response<S>
@@ -19,6 +30,7 @@ where
             (Self::Handled, Self::Handled) => true,
             (Self::Super, Self::Super) => true,
             (Self::Transition(s), Self::Transition(o)) => s == o,
+            (Self::Completed, Self::Completed) => true,
             _ => false,
         }
     }
@@ -38,6 +50,7 @@ where
                 .debug_tuple("Transition")
                 .field(state as &dyn Debug)
                 .finish(),
+            Self::Completed => f.debug_tuple("Completed").finish(),
         }
     }
 }
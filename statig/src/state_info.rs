@@ -0,0 +1,65 @@
+/// Static information about a state or superstate, as it is laid out in the
+/// [`HIERARCHY`](crate::IntoStateMachine::HIERARCHY) of a state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateInfo {
+    /// The name of the handler of the state or superstate (e.g. `"led_on"`).
+    pub name: &'static str,
+    /// The name of the superstate, if there is one.
+    pub superstate: Option<&'static str>,
+    /// Whether this is a superstate.
+    pub is_superstate: bool,
+    /// The key-value metadata that is attached to the state or superstate
+    /// (e.g. `#[state(meta(owner = "comms"))]`).
+    pub meta: &'static [(&'static str, &'static str)],
+}
+
+impl StateInfo {
+    /// Get the metadata value for the given key, if it is attached.
+    pub fn meta(&self, key: &str) -> Option<&'static str> {
+        self.meta
+            .iter()
+            .find(|(meta_key, _)| *meta_key == key)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Static information about a declared transition, as it is listed in the
+/// [`TRANSITIONS`](crate::IntoStateMachine::TRANSITIONS) of a state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransitionInfo {
+    /// The name of the state or superstate that declares the transition, or
+    /// `None` for the initial transition of the state machine.
+    pub source: Option<&'static str>,
+    /// The event that triggers the transition, as the name of its variant
+    /// (e.g. `"TimerElapsed"`), or `None` for the initial transition.
+    pub event: Option<&'static str>,
+    /// The guard of the transition, if it has one.
+    pub guard: Option<&'static str>,
+    /// The name of the target state.
+    pub target: &'static str,
+}
+
+/// How an event is handled in a state, as it is listed in the
+/// [`EVENT_ROUTING`](crate::IntoStateMachine::EVENT_ROUTING) of a state
+/// machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventRoute {
+    /// The name of the state (e.g. `"led_on"`).
+    pub state: &'static str,
+    /// The event, as the name of its variant (e.g. `"TimerElapsed"`).
+    pub event: &'static str,
+    /// The level at which the event is handled.
+    pub handling: Handling,
+}
+
+/// The level at which an event is handled by the declared transitions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handling {
+    /// The state itself declares a transition for the event.
+    State,
+    /// The superstate with the given name declares a transition for the event.
+    Superstate(&'static str),
+    /// No declared transition handles the event in every case. The handlers
+    /// might still handle it.
+    Unhandled,
+}
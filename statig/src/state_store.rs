@@ -0,0 +1,245 @@
+use core::fmt;
+
+use crate::blocking;
+use crate::IntoStateMachine;
+
+/// Backend that persists the serialized state of a state machine, e.g. a
+/// file, a page of flash or an EEPROM.
+///
+/// Implement this trait to store the state on your own medium. Stores are only
+/// written to after a transition, so wear-sensitive media are written to as
+/// little as possible.
+pub trait StateStore {
+    /// Error that occurs when reading from or writing to the store.
+    type Error;
+
+    /// Replace the stored state with the given bytes.
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read the stored state into the given buffer and return the part of the
+    /// buffer that was filled, or `None` if no state has been stored yet.
+    fn read<'buf>(&mut self, buffer: &'buf mut [u8]) -> Result<Option<&'buf [u8]>, Self::Error>;
+}
+
+/// Format in which the state of the state machine `M` is written to a
+/// [`StateStore`].
+pub trait StateCodec<M>
+where
+    M: IntoStateMachine,
+{
+    /// Error that occurs when encoding or decoding a state.
+    type Error;
+
+    /// Encode the state into the given buffer and return the part of the
+    /// buffer that was filled.
+    fn encode<'buf>(
+        &self,
+        state: &M::State,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error>;
+
+    /// Decode a state that was encoded with [`encode`](StateCodec::encode).
+    fn decode(&self, bytes: &[u8]) -> Result<M::State, Self::Error>;
+}
+
+/// Error that occurs when a buffer is too small to hold the state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall;
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "buffer is too small to hold the state")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmall {}
+
+/// Error that occurs when persisting or restoring the state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistError<S, C> {
+    /// The store could not be read from or written to.
+    Store(S),
+    /// The state could not be encoded or decoded.
+    Codec(C),
+}
+
+impl<S, C> fmt::Display for PersistError<S, C>
+where
+    S: fmt::Display,
+    C: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(error) => write!(f, "state store error: {error}"),
+            Self::Codec(error) => write!(f, "state codec error: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S, C> std::error::Error for PersistError<S, C>
+where
+    S: fmt::Debug + fmt::Display,
+    C: fmt::Debug + fmt::Display,
+{
+}
+
+/// Store that keeps up to `N` bytes of serialized state in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryStore<const N: usize> {
+    bytes: [u8; N],
+    len: Option<usize>,
+}
+
+impl<const N: usize> MemoryStore<N> {
+    /// Create an empty store.
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: None,
+        }
+    }
+
+    /// Get the stored bytes, or `None` if no state has been stored yet.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.len.map(|len| &self.bytes[..len])
+    }
+}
+
+impl<const N: usize> Default for MemoryStore<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> StateStore for MemoryStore<N> {
+    type Error = BufferTooSmall;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bytes
+            .get_mut(..bytes.len())
+            .ok_or(BufferTooSmall)?
+            .copy_from_slice(bytes);
+        self.len = Some(bytes.len());
+        Ok(())
+    }
+
+    fn read<'buf>(&mut self, buffer: &'buf mut [u8]) -> Result<Option<&'buf [u8]>, Self::Error> {
+        let Some(bytes) = self.bytes() else {
+            return Ok(None);
+        };
+        let buffer = buffer.get_mut(..bytes.len()).ok_or(BufferTooSmall)?;
+        buffer.copy_from_slice(bytes);
+        Ok(Some(buffer))
+    }
+}
+
+/// Store that keeps the serialized state in a file.
+///
+/// The state is first written to a temporary file next to it, which then
+/// replaces the file, so a crash while writing never leaves a partially
+/// written state behind.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStore {
+    path: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileStore {
+    /// Create a store that keeps the state in the file at the given path.
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Get the path of the file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "std")]
+impl StateStore for FileStore {
+    type Error = std::io::Error;
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(&temporary, &self.path)
+    }
+
+    fn read<'buf>(&mut self, buffer: &'buf mut [u8]) -> Result<Option<&'buf [u8]>, Self::Error> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let buffer = buffer
+            .get_mut(..bytes.len())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, BufferTooSmall))?;
+        buffer.copy_from_slice(&bytes);
+        Ok(Some(buffer))
+    }
+}
+
+/// Codec that encodes a state as its [id](blocking::State::state_id), in two
+/// bytes. It only supports states without state-local storage.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StateIdCodec;
+
+/// Error that occurs when a state can not be encoded or decoded by the
+/// [`StateIdCodec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateIdCodecError {
+    /// The buffer is too small to hold the state id.
+    BufferTooSmall,
+    /// The state has state-local storage or is not numbered.
+    Unsupported,
+    /// The bytes don't hold the id of a state without state-local storage.
+    Invalid,
+}
+
+impl fmt::Display for StateIdCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BufferTooSmall => write!(f, "buffer is too small to hold the state id"),
+            Self::Unsupported => write!(f, "state can not be encoded as an id"),
+            Self::Invalid => write!(f, "bytes do not hold a valid state id"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateIdCodecError {}
+
+impl<M> StateCodec<M> for StateIdCodec
+where
+    M: IntoStateMachine,
+    M::State: blocking::State<M>,
+{
+    type Error = StateIdCodecError;
+
+    fn encode<'buf>(
+        &self,
+        state: &M::State,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Self::Error> {
+        let state_id = blocking::State::state_id(state)
+            .filter(|state_id| <M::State as blocking::State<M>>::from_state_id(*state_id).is_some())
+            .and_then(|state_id| u16::try_from(state_id).ok())
+            .ok_or(StateIdCodecError::Unsupported)?;
+        let buffer = buffer
+            .get_mut(..2)
+            .ok_or(StateIdCodecError::BufferTooSmall)?;
+        buffer.copy_from_slice(&state_id.to_le_bytes());
+        Ok(buffer)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<M::State, Self::Error> {
+        let bytes: [u8; 2] = bytes.try_into().map_err(|_| StateIdCodecError::Invalid)?;
+        let state_id = u16::from_le_bytes(bytes) as usize;
+        <M::State as blocking::State<M>>::from_state_id(state_id).ok_or(StateIdCodecError::Invalid)
+    }
+}
@@ -0,0 +1,171 @@
+use core::time::Duration;
+
+/// Timed transition of a state: the duration after which the state machine
+/// transitions to the target state.
+pub type TimedTransition<S> = (Duration, S);
+
+/// Timer that drives the timed transitions of a state machine, declared with
+/// [`AFTER`](crate::IntoStateMachine::AFTER).
+///
+/// Implement this trait to run timed transitions on top of your own clock.
+/// Only one timer is running at a time: the one of the current state.
+pub trait Timer {
+    /// Start the timer so it expires after the given duration. A timer that
+    /// is already running is restarted.
+    fn start(&mut self, duration: Duration);
+
+    /// Stop the timer without letting it expire.
+    fn cancel(&mut self);
+
+    /// Check whether the timer has expired. This returns `true` only once for
+    /// every time the timer is started.
+    fn poll_expired(&mut self) -> bool;
+}
+
+/// Timer that drives the timed transitions of an async state machine, declared
+/// with [`AFTER`](crate::IntoStateMachine::AFTER).
+///
+/// Implement this trait to run timed transitions on the runtime of your
+/// choice. With the `tokio`, `async-std` and `smol` features,
+/// [`TokioTimer`](crate::TokioTimer), [`AsyncStdTimer`](crate::AsyncStdTimer)
+/// and [`SmolTimer`](crate::SmolTimer) sleep on those runtimes, and
+/// [`SleepTimer`] sleeps with the sleep function of any other runtime.
+#[cfg(any(feature = "async", doc))]
+pub trait AsyncTimer {
+    /// Future that completes once the duration has elapsed.
+    type Sleep: core::future::Future<Output = ()>;
+
+    /// Sleep for the given duration.
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep;
+}
+
+/// [`AsyncTimer`] that sleeps with [`tokio::time::sleep`], so it follows the
+/// (possibly paused) clock of the tokio runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl AsyncTimer for TokioTimer {
+    type Sleep = tokio::time::Sleep;
+
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// [`AsyncTimer`] that sleeps with [`async_std::task::sleep`].
+#[cfg(feature = "async-std")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AsyncStdTimer;
+
+#[cfg(feature = "async-std")]
+impl AsyncTimer for AsyncStdTimer {
+    // The future returned by `async_std::task::sleep` can't be named.
+    type Sleep = core::pin::Pin<alloc::boxed::Box<dyn core::future::Future<Output = ()> + Send>>;
+
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep {
+        alloc::boxed::Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// [`AsyncTimer`] that sleeps with [`smol::Timer`].
+#[cfg(feature = "smol")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SmolTimer;
+
+#[cfg(feature = "smol")]
+impl AsyncTimer for SmolTimer {
+    type Sleep = SmolSleep;
+
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep {
+        SmolSleep(smol::Timer::after(duration))
+    }
+}
+
+/// Future returned by [`SmolTimer::sleep`].
+#[cfg(feature = "smol")]
+#[derive(Debug)]
+pub struct SmolSleep(smol::Timer);
+
+#[cfg(feature = "smol")]
+impl core::future::Future for SmolSleep {
+    type Output = ();
+
+    fn poll(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        core::pin::Pin::new(&mut self.0).poll(cx).map(|_| ())
+    }
+}
+
+/// [`AsyncTimer`] that sleeps with the given function, so timed transitions can
+/// be driven by any other runtime, e.g.
+/// `SleepTimer(futures_timer::Delay::new)`.
+#[cfg(any(feature = "async", doc))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SleepTimer<F>(pub F);
+
+#[cfg(any(feature = "async", doc))]
+impl<F, S> AsyncTimer for SleepTimer<F>
+where
+    F: FnMut(Duration) -> S,
+    S: core::future::Future<Output = ()>,
+{
+    type Sleep = S;
+
+    fn sleep(&mut self, duration: Duration) -> Self::Sleep {
+        (self.0)(duration)
+    }
+}
+
+/// Monotonic clock that measures how long it takes to handle an event, e.g.
+/// for a [`WatchdogStateMachine`](crate::blocking::WatchdogStateMachine).
+///
+/// Any function that returns the current time is a clock, so a cycle counter
+/// or the tick count of an RTOS can be used directly.
+pub trait Clock {
+    /// Get the time that has passed since a fixed point in the past.
+    fn now(&mut self) -> Duration;
+}
+
+impl<F> Clock for F
+where
+    F: FnMut() -> Duration,
+{
+    fn now(&mut self) -> Duration {
+        self()
+    }
+}
+
+/// [`Clock`] that reads [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Create a clock that starts counting now.
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&mut self) -> Duration {
+        self.start.elapsed()
+    }
+}
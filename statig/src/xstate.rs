@@ -0,0 +1,778 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use crate::{IntoStateMachine, StateInfo, TransitionInfo};
+
+/// Export the state machine `M` as the JSON definition of an
+/// [XState](https://stately.ai/docs/xstate) machine with the given id, so it
+/// can be visualized and simulated in the Stately editor.
+///
+/// The states and superstates are taken from the
+/// [`HIERARCHY`](IntoStateMachine::HIERARCHY), and the transitions from the
+/// declared [`TRANSITIONS`](IntoStateMachine::TRANSITIONS). Every state gets
+/// its name as id, so transitions target states with `#name`. Guards are
+/// exported as the source of their condition, and a wildcard pattern (`_`)
+/// becomes the wildcard event `*`.
+///
+/// ```
+/// # use statig::prelude::*;
+/// # #[derive(Default)]
+/// # pub struct Blinky;
+/// #
+/// # pub enum Event { TimerElapsed }
+/// #
+/// # #[state_machine(initial = "State::on()")]
+/// # impl Blinky {
+/// #     #[state(transitions("Event::TimerElapsed => State::off()"))]
+/// #     fn on(event: &Event) -> Response<State> { Handled }
+/// #
+/// #     #[state(transitions("Event::TimerElapsed => State::on()"))]
+/// #     fn off(event: &Event) -> Response<State> { Handled }
+/// # }
+/// #
+/// let machine = statig::to_xstate::<Blinky>("blinky");
+///
+/// assert_eq!(
+///     machine,
+///     serde_json::json!({
+///         "id": "blinky",
+///         "initial": "on",
+///         "states": {
+///             "on": { "id": "on", "on": { "TimerElapsed": [{ "target": "#off" }] } },
+///             "off": { "id": "off", "on": { "TimerElapsed": [{ "target": "#on" }] } },
+///         },
+///     })
+/// );
+/// ```
+pub fn to_xstate<M>(id: &str) -> Value
+where
+    M: IntoStateMachine,
+{
+    let initial = M::TRANSITIONS
+        .iter()
+        .find(|transition| transition.source.is_none())
+        .map(|transition| transition.target);
+    // The states that are entered by the initial transition, from the top down.
+    let mut initial_path = Vec::new();
+    let mut ancestor = initial;
+    while let Some(name) = ancestor {
+        initial_path.insert(0, name);
+        ancestor = state_info::<M>(name).and_then(|info| info.superstate);
+    }
+
+    let mut machine = Map::new();
+    machine.insert("id".to_owned(), json!(id));
+    if let Some(top) = initial_path.first() {
+        machine.insert("initial".to_owned(), json!(top));
+    }
+    machine.insert("states".to_owned(), children::<M>(None, &initial_path));
+    Value::Object(machine)
+}
+
+fn state_info<M>(name: &str) -> Option<&'static StateInfo>
+where
+    M: IntoStateMachine,
+{
+    M::HIERARCHY.iter().find(|info| info.name == name)
+}
+
+/// The state nodes of the children of the given superstate, or of the top
+/// level when it is `None`.
+fn children<M>(superstate: Option<&str>, initial_path: &[&str]) -> Value
+where
+    M: IntoStateMachine,
+{
+    let states = M::HIERARCHY
+        .iter()
+        .filter(|info| info.superstate == superstate)
+        .map(|info| (info.name.to_owned(), node::<M>(info, initial_path)))
+        .collect();
+    Value::Object(states)
+}
+
+fn node<M>(info: &StateInfo, initial_path: &[&str]) -> Value
+where
+    M: IntoStateMachine,
+{
+    let mut node = Map::new();
+    node.insert("id".to_owned(), json!(info.name));
+    if info.is_superstate {
+        let initial = initial_path
+            .iter()
+            .position(|name| *name == info.name)
+            .and_then(|index| initial_path.get(index + 1));
+        if let Some(initial) = initial {
+            node.insert("initial".to_owned(), json!(initial));
+        }
+        node.insert(
+            "states".to_owned(),
+            children::<M>(Some(info.name), initial_path),
+        );
+    }
+
+    let mut on = Map::new();
+    let declared = M::TRANSITIONS
+        .iter()
+        .filter(|transition| transition.source == Some(info.name));
+    for TransitionInfo {
+        event,
+        guard,
+        target,
+        ..
+    } in declared
+    {
+        let event = match event.unwrap_or_default() {
+            "_" => "*",
+            event => event,
+        };
+        let mut transition = Map::new();
+        transition.insert("target".to_owned(), json!(format!("#{target}")));
+        if let Some(guard) = guard {
+            transition.insert("guard".to_owned(), json!(guard));
+        }
+        match on
+            .entry(event.to_owned())
+            .or_insert_with(|| Value::Array(Vec::new()))
+        {
+            Value::Array(transitions) => transitions.push(Value::Object(transition)),
+            _ => unreachable!(),
+        }
+    }
+    if !on.is_empty() {
+        node.insert("on".to_owned(), Value::Object(on));
+    }
+    Value::Object(node)
+}
+
+/// Error that occurs when a JSON definition can't be converted with
+/// [`from_xstate`], because it isn't an XState machine or because it uses a
+/// feature that statig doesn't have (e.g. parallel states).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidXState(String);
+
+impl fmt::Display for InvalidXState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid XState machine: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidXState {}
+
+/// Convert the JSON definition of an [XState](https://stately.ai/docs/xstate)
+/// machine into the source of a `#[state_machine]` skeleton, so a state
+/// machine can be designed in the Stately editor and implemented in Rust.
+///
+/// The skeleton contains:
+///
+/// - An `Event` enum with a variant for every event, e.g. `Event::TimerElapsed`
+///   for `TIMER_ELAPSED` or `timerElapsed`.
+/// - A struct for the shared storage, named after the id of the machine.
+/// - A handler stub for every state and superstate, with the transitions of
+///   the state declared with `transitions(...)` and its description as doc
+///   comment.
+/// - A trait with a method for every guard, named after the shared storage
+///   (e.g. `PlayerGuards`), of which the methods are called with `all(...)`
+///   in the guards of the transitions. The skeleton compiles once the shared
+///   storage implements it.
+///
+/// Targets are resolved as XState does, so a transition to a compound state
+/// enters its initial state. The wildcard event `*` becomes the pattern `_`,
+/// and a partial wildcard like `mouse.*` matches every event that starts with
+/// `mouse.`. Actions, context and invoked services are not converted, and
+/// parallel states, history states and eventless or delayed transitions are
+/// rejected.
+///
+/// The source is typically written from a build script and included with
+/// `include!(concat!(env!("OUT_DIR"), "/blinky.rs"))`, or written once and
+/// then edited by hand.
+///
+/// ```
+/// let machine = serde_json::json!({
+///     "id": "blinky",
+///     "initial": "on",
+///     "states": {
+///         "on": { "on": { "TIMER_ELAPSED": "off" } },
+///         "off": { "on": { "TIMER_ELAPSED": "on" } },
+///     },
+/// });
+///
+/// let source = statig::from_xstate(&machine).unwrap();
+///
+/// assert!(source.contains("pub enum Event {\n    TimerElapsed,\n}"));
+/// assert!(source.contains("#[state(transitions(\"Event::TimerElapsed => State::off()\"))]"));
+/// ```
+pub fn from_xstate(machine: &Value) -> Result<String, InvalidXState> {
+    Import::new(machine)?.generate()
+}
+
+/// The maximum width of a line, as in rustfmt.
+const MAX_WIDTH: usize = 100;
+
+/// The maximum width of the arguments of an attribute before they are split
+/// over multiple lines, as in rustfmt.
+const ATTR_FN_LIKE_WIDTH: usize = 70;
+
+/// The maximum width of the arguments of a call before they are split over
+/// multiple lines, as in rustfmt.
+const FN_CALL_WIDTH: usize = 60;
+
+fn invalid(message: impl Into<String>) -> InvalidXState {
+    InvalidXState(message.into())
+}
+
+/// A state node of the XState machine.
+struct Node<'a> {
+    key: &'a str,
+    path: Vec<&'a str>,
+    config: &'a Map<String, Value>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// The name of the handler of the state or superstate.
+    name: String,
+}
+
+struct Import<'a> {
+    id: &'a str,
+    root: &'a Map<String, Value>,
+    nodes: Vec<Node<'a>>,
+    top: Vec<usize>,
+    /// The names of the events in XState and of their variants.
+    events: Vec<(&'a str, String)>,
+    /// The names of the methods of the guards.
+    guards: Vec<String>,
+}
+
+impl<'a> Import<'a> {
+    fn new(machine: &'a Value) -> Result<Self, InvalidXState> {
+        let root = machine
+            .as_object()
+            .ok_or_else(|| invalid("expected the machine to be an object"))?;
+        let id = match root.get("id") {
+            None => "machine",
+            Some(Value::String(id)) => id,
+            Some(_) => return Err(invalid("expected the `id` of the machine to be a string")),
+        };
+        if root.contains_key("on") {
+            return Err(invalid(
+                "transitions on the machine itself are not supported, declare them on a state",
+            ));
+        }
+        let mut import = Self {
+            id,
+            root,
+            nodes: Vec::new(),
+            top: Vec::new(),
+            events: Vec::new(),
+            guards: Vec::new(),
+        };
+        import.top = import.collect_nodes(root.get("states"), None, &[])?;
+        if import.top.is_empty() {
+            return Err(invalid("the machine has no states"));
+        }
+        import.name_nodes()?;
+        import.collect_events()?;
+        Ok(import)
+    }
+
+    /// Collect the given states and their descendants, and return their
+    /// indices.
+    fn collect_nodes(
+        &mut self,
+        states: Option<&'a Value>,
+        parent: Option<usize>,
+        path: &[&'a str],
+    ) -> Result<Vec<usize>, InvalidXState> {
+        let Some(states) = states else {
+            return Ok(Vec::new());
+        };
+        let states = states
+            .as_object()
+            .ok_or_else(|| invalid("expected `states` to be an object"))?;
+        let mut indices = Vec::new();
+        for (key, config) in states {
+            let config = config
+                .as_object()
+                .ok_or_else(|| invalid(format!("expected the state `{key}` to be an object")))?;
+            match config.get("type").and_then(Value::as_str) {
+                Some("parallel") => {
+                    return Err(invalid(format!(
+                        "the parallel state `{key}` is not supported"
+                    )))
+                }
+                Some("history") => {
+                    return Err(invalid(format!(
+                        "the history state `{key}` is not supported"
+                    )))
+                }
+                _ => {}
+            }
+            for unsupported in ["always", "after"] {
+                if config.contains_key(unsupported) {
+                    return Err(invalid(format!(
+                        "the `{unsupported}` transitions of `{key}` are not supported"
+                    )));
+                }
+            }
+            let mut path = path.to_vec();
+            path.push(key);
+            let index = self.nodes.len();
+            self.nodes.push(Node {
+                key,
+                path: path.clone(),
+                config,
+                parent,
+                children: Vec::new(),
+                name: String::new(),
+            });
+            self.nodes[index].children =
+                self.collect_nodes(config.get("states"), Some(index), &path)?;
+            indices.push(index);
+        }
+        Ok(indices)
+    }
+
+    /// Name the handlers after the keys of the states, or after their paths
+    /// when a key is used more than once.
+    fn name_nodes(&mut self) -> Result<(), InvalidXState> {
+        let keys: Vec<String> = self.nodes.iter().map(|node| snake_case(node.key)).collect();
+        for index in 0..self.nodes.len() {
+            let node = &self.nodes[index];
+            let name = if keys.iter().filter(|key| **key == keys[index]).count() > 1 {
+                snake_case(&node.path.join("_"))
+            } else {
+                keys[index].clone()
+            };
+            let name = identifier(&name, node.key)?;
+            if self.nodes[..index].iter().any(|other| other.name == name) {
+                return Err(invalid(format!(
+                    "the state `{}` has the same name as another state",
+                    node.path.join(".")
+                )));
+            }
+            self.nodes[index].name = name;
+        }
+        Ok(())
+    }
+
+    fn collect_events(&mut self) -> Result<(), InvalidXState> {
+        for node in &self.nodes {
+            for event in on(node)?.into_iter().flat_map(Map::keys) {
+                if event == "*" || event.ends_with(".*") {
+                    continue;
+                }
+                if self.events.iter().any(|(name, _)| name == event) {
+                    continue;
+                }
+                let variant = identifier(&pascal_case(event), event)?;
+                if let Some((other, _)) = self.events.iter().find(|(_, other)| *other == variant) {
+                    return Err(invalid(format!(
+                        "the events `{other}` and `{event}` have the same name `{variant}`"
+                    )));
+                }
+                self.events.push((event, variant));
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the state a transition of the given node targets.
+    fn resolve(&self, source: usize, target: &str) -> Result<usize, InvalidXState> {
+        let index = if let Some(id) = target.strip_prefix('#') {
+            self.nodes
+                .iter()
+                .position(|node| self.node_id(node) == id)
+                .ok_or_else(|| invalid(format!("there is no state with the id `{id}`")))?
+        } else if let Some(path) = target.strip_prefix('.') {
+            self.descend(&self.nodes[source].children, path)?
+        } else {
+            let siblings = match self.nodes[source].parent {
+                Some(parent) => &self.nodes[parent].children,
+                None => &self.top,
+            };
+            self.descend(siblings, target)?
+        };
+        self.initial_leaf(index)
+    }
+
+    fn node_id(&self, node: &Node) -> String {
+        match node.config.get("id").and_then(Value::as_str) {
+            Some(id) => id.to_owned(),
+            None => format!("{}.{}", self.id, node.path.join(".")),
+        }
+    }
+
+    /// Find a state by its path of keys, starting from the given states.
+    fn descend<'s>(&'s self, mut states: &'s [usize], path: &str) -> Result<usize, InvalidXState> {
+        let mut found = None;
+        for key in path.split('.') {
+            let index = states
+                .iter()
+                .copied()
+                .find(|index| self.nodes[*index].key == key)
+                .ok_or_else(|| invalid(format!("there is no state `{path}`")))?;
+            states = &self.nodes[index].children;
+            found = Some(index);
+        }
+        found.ok_or_else(|| invalid(format!("there is no state `{path}`")))
+    }
+
+    /// Follow the initial states of a compound state down to a leaf state.
+    fn initial_leaf(&self, mut index: usize) -> Result<usize, InvalidXState> {
+        while !self.nodes[index].children.is_empty() {
+            let node = &self.nodes[index];
+            let initial = node
+                .config
+                .get("initial")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid(format!("the state `{}` has no initial state", node.key)))?;
+            index = self.descend(&node.children, initial)?;
+        }
+        Ok(index)
+    }
+
+    /// The declared transitions of a node, with the transitions on exact
+    /// events first, then those on partial wildcards and then those on the
+    /// wildcard, as XState selects them.
+    fn transitions(&mut self, index: usize) -> Result<Vec<String>, InvalidXState> {
+        let node = &self.nodes[index];
+        let mut on: Vec<(&String, &Value)> = on(node)?.into_iter().flatten().collect();
+        on.sort_by_key(|(event, _)| match event.as_str() {
+            "*" => 2,
+            event if event.ends_with(".*") => 1,
+            _ => 0,
+        });
+
+        let mut transitions = Vec::new();
+        for (event, config) in on {
+            let pattern = match event.as_str() {
+                "*" => "_".to_owned(),
+                event if event.ends_with(".*") => {
+                    let prefix = &event[..event.len() - 1];
+                    let variants: Vec<String> = self
+                        .events
+                        .iter()
+                        .filter(|(name, _)| name.starts_with(prefix))
+                        .map(|(_, variant)| format!("Event::{variant}"))
+                        .collect();
+                    if variants.is_empty() {
+                        return Err(invalid(format!("no events match `{event}`")));
+                    }
+                    variants.join(" | ")
+                }
+                event => {
+                    let (_, variant) = self
+                        .events
+                        .iter()
+                        .find(|(name, _)| *name == event)
+                        .expect("events are collected");
+                    format!("Event::{variant}")
+                }
+            };
+            for TransitionConfig { target, guard } in transition_configs(config)? {
+                // Transitions without a target are left to the handler.
+                let Some(target) = target else {
+                    continue;
+                };
+                let target = &self.nodes[self.resolve(index, target)?].name;
+                let guard = match guard {
+                    Some(guard) => {
+                        let guard = identifier(&snake_case(guard), guard)?;
+                        if !self.guards.contains(&guard) {
+                            self.guards.push(guard.clone());
+                        }
+                        format!(" if all({guard})")
+                    }
+                    None => String::new(),
+                };
+                transitions.push(format!("{pattern}{guard} => State::{target}()"));
+            }
+        }
+        Ok(transitions)
+    }
+
+    fn generate(mut self) -> Result<String, InvalidXState> {
+        let initial = match self.root.get("initial") {
+            Some(Value::String(initial)) => self.descend(&self.top, initial)?,
+            Some(_) => return Err(invalid("expected the `initial` state to be a string")),
+            None => return Err(invalid("the machine has no initial state")),
+        };
+        let initial = &self.nodes[self.initial_leaf(initial)?].name;
+        let storage = identifier(&pascal_case(self.id), self.id)?;
+
+        let mut source = format!("// Generated from the XState machine `{}`.\n\n", self.id);
+        source += "use statig::prelude::*;\n\n";
+
+        source += "#[derive(Debug)]\npub enum Event {\n";
+        for (_, variant) in &self.events {
+            source += &format!("    {variant},\n");
+        }
+        source += "}\n\n";
+
+        source += &format!("#[derive(Default)]\npub struct {storage};\n\n");
+
+        source += &attribute(
+            "",
+            "state_machine",
+            &[Arg::Plain(format!(
+                "initial = {:?}",
+                format!("State::{initial}()")
+            ))],
+        );
+        source += &format!("impl {storage} {{\n");
+        for index in 0..self.nodes.len() {
+            let transitions = self.transitions(index)?;
+            let node = &self.nodes[index];
+            if index > 0 {
+                source += "\n";
+            }
+            if let Some(description) = node.config.get("description").and_then(Value::as_str) {
+                for line in description.lines() {
+                    source += format!("    /// {line}").trim_end();
+                    source += "\n";
+                }
+            }
+            let mut args = Vec::new();
+            if let Some(parent) = node.parent {
+                args.push(Arg::Plain(format!(
+                    "superstate = {:?}",
+                    self.nodes[parent].name
+                )));
+            }
+            if !transitions.is_empty() {
+                let transitions = transitions.iter().map(|arm| format!("{arm:?}")).collect();
+                args.push(Arg::List("transitions", transitions));
+            }
+            let kind = match node.children.is_empty() {
+                true => "state",
+                false => "superstate",
+            };
+            source += &attribute("    ", kind, &args);
+            source += &format!(
+                "    fn {}(event: &Event) -> Response<State> {{\n",
+                node.name
+            );
+            source += "        let _ = event;\n";
+            source += "        Super\n";
+            source += "    }\n";
+        }
+        source += "}\n";
+
+        // The guards can't be generated, so they are declared in a trait that
+        // the shared storage has to implement before the skeleton compiles.
+        if !self.guards.is_empty() {
+            source += &format!("\n/// The guards of the transitions of `{storage}`.\n");
+            source += &format!("pub trait {storage}Guards {{\n");
+            for (index, guard) in self.guards.iter().enumerate() {
+                if index > 0 {
+                    source += "\n";
+                }
+                source += &format!("    fn {guard}(&self) -> bool;\n");
+            }
+            source += "}\n";
+        }
+        Ok(source)
+    }
+}
+
+/// The transitions of a node by event.
+fn on<'a>(node: &Node<'a>) -> Result<Option<&'a Map<String, Value>>, InvalidXState> {
+    match node.config.get("on") {
+        None => Ok(None),
+        Some(Value::Object(on)) => Ok(Some(on)),
+        Some(_) => Err(invalid(format!(
+            "expected the transitions of `{}` to be an object",
+            node.key
+        ))),
+    }
+}
+
+/// The target and guard of a transition.
+struct TransitionConfig<'a> {
+    target: Option<&'a str>,
+    guard: Option<&'a str>,
+}
+
+/// The transitions on an event, which can be given as a target, as a
+/// transition object or as an array of either.
+fn transition_configs(config: &Value) -> Result<Vec<TransitionConfig<'_>>, InvalidXState> {
+    fn transition(config: &Value) -> Result<TransitionConfig<'_>, InvalidXState> {
+        let config = match config {
+            Value::String(target) => {
+                return Ok(TransitionConfig {
+                    target: Some(target),
+                    guard: None,
+                })
+            }
+            Value::Object(config) => config,
+            _ => return Err(invalid("expected a transition to be a string or an object")),
+        };
+        let target = match config.get("target") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(target)) => Some(target.as_str()),
+            Some(Value::Array(targets)) => match targets.as_slice() {
+                [] => None,
+                [Value::String(target)] => Some(target.as_str()),
+                _ => return Err(invalid("transitions to multiple targets are not supported")),
+            },
+            Some(_) => {
+                return Err(invalid(
+                    "expected the `target` of a transition to be a string",
+                ))
+            }
+        };
+        // XState v5 calls it `guard`, earlier versions `cond`.
+        let guard = match config.get("guard").or_else(|| config.get("cond")) {
+            None => None,
+            Some(Value::String(guard)) => Some(guard.as_str()),
+            Some(Value::Object(guard)) => match guard.get("type") {
+                Some(Value::String(guard)) => Some(guard.as_str()),
+                _ => return Err(invalid("expected a guard object to have a `type`")),
+            },
+            Some(_) => return Err(invalid("expected a guard to be a string or an object")),
+        };
+        Ok(TransitionConfig { target, guard })
+    }
+
+    match config {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(configs) => configs.iter().map(transition).collect(),
+        config => Ok(vec![transition(config)?]),
+    }
+}
+
+/// An argument of an attribute.
+enum Arg {
+    Plain(String),
+    List(&'static str, Vec<String>),
+}
+
+impl Arg {
+    fn single_line(&self) -> String {
+        match self {
+            Arg::Plain(arg) => arg.clone(),
+            Arg::List(name, items) => format!("{name}({})", items.join(", ")),
+        }
+    }
+}
+
+/// Write an attribute, split over multiple lines the way rustfmt would.
+fn attribute(indent: &str, name: &str, args: &[Arg]) -> String {
+    if args.is_empty() {
+        return format!("{indent}#[{name}]\n");
+    }
+    let single_line: Vec<String> = args.iter().map(Arg::single_line).collect();
+    let single_line = single_line.join(", ");
+    let line = format!("{indent}#[{name}({single_line})]");
+    if single_line.len() <= ATTR_FN_LIKE_WIDTH && line.len() <= MAX_WIDTH {
+        return line + "\n";
+    }
+
+    // A single list overflows onto the lines of the attribute itself.
+    if let [Arg::List(list, items)] = args {
+        let mut attribute = format!("{indent}#[{name}({list}(\n");
+        attribute += &list_items(indent, items);
+        attribute += &format!("{indent}))]\n");
+        return attribute;
+    }
+
+    let mut attribute = format!("{indent}#[{name}(\n");
+    for (index, arg) in args.iter().enumerate() {
+        let separator = if index + 1 < args.len() { "," } else { "" };
+        let line = format!("{indent}    {}{separator}", arg.single_line());
+        match arg {
+            Arg::List(list, items)
+                if line.len() > MAX_WIDTH
+                    || items.len() > 1 && items.join(", ").len() > FN_CALL_WIDTH =>
+            {
+                attribute += &format!("{indent}    {list}(\n");
+                attribute += &list_items(&format!("{indent}    "), items);
+                attribute += &format!("{indent}    ){separator}\n");
+            }
+            _ => attribute += &format!("{line}\n"),
+        }
+    }
+    attribute += &format!("{indent})]\n");
+    attribute
+}
+
+/// Write the items of a list that is split over multiple lines.
+fn list_items(indent: &str, items: &[String]) -> String {
+    let mut lines = String::new();
+    for (index, item) in items.iter().enumerate() {
+        let separator = if index + 1 < items.len() { "," } else { "" };
+        lines += &format!("{indent}    {item}{separator}\n");
+    }
+    lines
+}
+
+/// Split a name into words at separators and at changes of case, e.g.
+/// `mouse.click`, `TIMER_ELAPSED`, `timerElapsed` and `HTTPRequest`.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let previous = chars[index - 1];
+            let next_is_lowercase = chars.get(index + 1).map_or(false, |c| c.is_lowercase());
+            if !previous.is_uppercase() || next_is_lowercase {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().into_iter().flat_map(char::to_uppercase);
+            first
+                .chain(chars.flat_map(char::to_lowercase))
+                .collect::<String>()
+        })
+        .collect()
+}
+
+/// Check that a converted name is an identifier, and make it a raw identifier
+/// if it is a keyword.
+fn identifier(name: &str, original: &str) -> Result<String, InvalidXState> {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let",
+        "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+        "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+        "virtual", "where", "while", "yield",
+    ];
+    match name {
+        "" | "self" | "Self" | "super" | "crate" => Err(invalid(format!(
+            "`{original}` can't be converted into an identifier"
+        ))),
+        name if name.starts_with(|c: char| c.is_numeric()) => Err(invalid(format!(
+            "`{original}` can't be converted into an identifier"
+        ))),
+        name if KEYWORDS.contains(&name) => Ok(format!("r#{name}")),
+        name => Ok(name.to_owned()),
+    }
+}
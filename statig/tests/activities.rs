@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tick {
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Sensor {
+        samples: Vec<u32>,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Sensor {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::measuring(0)),
+                Event::Stop => Handled,
+            }
+        }
+
+        #[state(activity = "poll_sensor")]
+        fn measuring(count: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Start => {
+                    *count = 0;
+                    Handled
+                }
+                Event::Stop => Transition(State::idle()),
+            }
+        }
+
+        #[action]
+        fn poll_sensor(&mut self, count: &mut u32) {
+            *count += 1;
+            self.samples.push(*count);
+        }
+    }
+
+    #[test]
+    fn activity_is_called_on_every_tick_while_the_state_is_active() {
+        let mut state_machine = Sensor::default().state_machine();
+        state_machine.tick();
+        assert!(state_machine.inner().samples.is_empty());
+
+        state_machine.handle(&Event::Start);
+        state_machine.tick();
+        state_machine.tick();
+        assert_eq!(state_machine.state(), &State::measuring(2));
+
+        state_machine.handle(&Event::Stop);
+        state_machine.tick();
+        assert_eq!(state_machine.inner().samples, [1, 2]);
+    }
+}
+
+#[cfg(test)]
+mod step {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    struct Blinky {
+        blinks: usize,
+    }
+
+    #[state_machine(initial = "State::on()", state(derive(Debug, PartialEq, Eq)))]
+    impl Blinky {
+        #[state(activity = "blink")]
+        fn on() -> Response<State> {
+            Transition(State::off())
+        }
+
+        #[state]
+        fn off() -> Response<State> {
+            Transition(State::on())
+        }
+
+        #[action]
+        fn blink(&mut self) {
+            self.blinks += 1;
+        }
+    }
+
+    #[test]
+    fn activity_is_called_on_every_step() {
+        let mut state_machine = Blinky::default().state_machine();
+        state_machine.step();
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(state_machine.inner().blinks, 0);
+
+        state_machine.step();
+        assert_eq!(state_machine.state(), &State::on());
+        assert_eq!(state_machine.inner().blinks, 1);
+    }
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "actix")]
+
+#[cfg(test)]
+mod tests {
+    use actix::{Actor, Context, Handler, Message, MessageResult};
+    use statig::blocking::{Dispatch, GetState, StateMachineActor, Subscribe, TransitionTaken};
+    use statig::prelude::*;
+
+    pub struct PaymentReceived;
+
+    pub struct Shipped;
+
+    pub enum Event {
+        Payment(PaymentReceived),
+        Shipping(Shipped),
+    }
+
+    #[derive(Default)]
+    struct Order;
+
+    #[state_machine(
+        initial = "State::awaiting_payment()",
+        event_from("Payment(PaymentReceived)", "Shipping(Shipped)"),
+        state(derive(Clone, Debug, PartialEq, Eq))
+    )]
+    impl Order {
+        #[state]
+        fn awaiting_payment(event: &Event) -> Response<State> {
+            match event {
+                Event::Payment(_) => Transition(State::awaiting_shipment()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn awaiting_shipment(event: &Event) -> Response<State> {
+            match event {
+                Event::Shipping(_) => Transition(State::delivered()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn delivered() -> Response<State> {
+            Handled
+        }
+    }
+
+    /// Actor that records the transitions it is sent.
+    #[derive(Default)]
+    struct Audit(Vec<TransitionTaken<State>>);
+
+    /// Message that returns the transitions the audit recorded.
+    struct Recorded;
+
+    impl Message for Recorded {
+        type Result = Vec<TransitionTaken<State>>;
+    }
+
+    impl Actor for Audit {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<TransitionTaken<State>> for Audit {
+        type Result = ();
+
+        fn handle(&mut self, transition: TransitionTaken<State>, _: &mut Self::Context) {
+            self.0.push(transition);
+        }
+    }
+
+    impl Handler<Recorded> for Audit {
+        type Result = MessageResult<Recorded>;
+
+        fn handle(&mut self, _: Recorded, _: &mut Self::Context) -> Self::Result {
+            MessageResult(self.0.clone())
+        }
+    }
+
+    #[actix::test]
+    async fn messages_are_dispatched_as_events() {
+        let state_machine = Order.uninitialized_state_machine().init();
+        let order = StateMachineActor::new(state_machine).start();
+        let audit = Audit::default().start();
+
+        order.do_send(Subscribe(audit.clone().recipient()));
+        order.do_send(Dispatch(Shipped));
+        order.do_send(Dispatch(PaymentReceived));
+        order.do_send(Dispatch(Shipped));
+
+        let state = order.send(GetState::default()).await.unwrap();
+        assert_eq!(state, State::delivered());
+
+        // The transitions were sent to the audit before it receives this.
+        assert_eq!(
+            audit.send(Recorded).await.unwrap(),
+            [
+                TransitionTaken {
+                    source: State::awaiting_payment(),
+                    target: State::awaiting_shipment(),
+                },
+                TransitionTaken {
+                    source: State::awaiting_shipment(),
+                    target: State::delivered(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Connect,
+        Disconnect,
+    }
+
+    #[derive(Default)]
+    struct Client {
+        log: Vec<&'static str>,
+    }
+
+    async fn yield_now() {
+        tokio::task::yield_now().await;
+    }
+
+    // Only the actions are async, which is enough to generate an awaitable
+    // state machine.
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Client {
+        #[state(superstate = "powered", entry_action = "enter_idle")]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Transition(State::connected()),
+                Event::Disconnect => Handled,
+            }
+        }
+
+        #[state(
+            superstate = "powered",
+            entry_action = "open_socket",
+            exit_action = "close_socket"
+        )]
+        fn connected(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Handled,
+                Event::Disconnect => Transition(State::idle()),
+            }
+        }
+
+        #[superstate(entry_action = "power_on", exit_action = "power_off")]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect | Event::Disconnect => Handled,
+            }
+        }
+
+        #[action]
+        async fn power_on(&mut self) {
+            yield_now().await;
+            self.log.push("power on");
+        }
+
+        #[action]
+        async fn power_off(&mut self) {
+            yield_now().await;
+            self.log.push("power off");
+        }
+
+        #[action]
+        fn enter_idle(&mut self) {
+            self.log.push("enter idle");
+        }
+
+        #[action]
+        async fn open_socket(&mut self) {
+            yield_now().await;
+            self.log.push("open socket");
+        }
+
+        #[action]
+        async fn close_socket(&mut self) {
+            yield_now().await;
+            self.log.push("close socket");
+        }
+    }
+
+    #[tokio::test]
+    async fn async_actions_are_awaited() {
+        let mut state_machine = Client::default().uninitialized_state_machine().init().await;
+        assert_eq!(state_machine.inner().log, ["power on", "enter idle"]);
+
+        state_machine.handle(&Event::Connect).await;
+        state_machine.handle(&Event::Disconnect).await;
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(
+            state_machine.inner().log,
+            [
+                "power on",
+                "enter idle",
+                "open socket",
+                "close socket",
+                "enter idle"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn shutdown_awaits_exit_actions() {
+        let mut state_machine = Client::default().state_machine();
+        state_machine.handle(&Event::Connect).await;
+
+        let client = state_machine.shutdown().await;
+        assert_eq!(
+            client.log,
+            [
+                "power on",
+                "enter idle",
+                "open socket",
+                "close socket",
+                "power off"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn shutdown_of_uninitialized_state_machine_does_nothing() {
+        let client = Client::default().state_machine().shutdown().await;
+        assert!(client.log.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "tokio")]
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use statig::awaitable::{InitializedStateMachine, TimedStateMachine};
+    use statig::prelude::*;
+    use statig::TokioTimer;
+    use tokio::sync::mpsc;
+    use tokio::time::Instant;
+
+    enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    struct Light {
+        switched_off_at: Option<Instant>,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        after = "Self::after",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Light {
+        fn after(state: &State) -> Option<(Duration, State)> {
+            match state {
+                State::On {} => Some((Duration::from_secs(5), State::off())),
+                State::Off {} => None,
+            }
+        }
+
+        #[state]
+        async fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+            }
+        }
+
+        #[state(exit_action = "leave_on")]
+        async fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+            }
+        }
+
+        #[action]
+        async fn leave_on(&mut self) {
+            self.switched_off_at = Some(Instant::now());
+        }
+    }
+
+    async fn run(
+        mut state_machine: TimedStateMachine<Light, TokioTimer>,
+        mut receiver: mpsc::Receiver<Event>,
+    ) -> InitializedStateMachine<Light> {
+        loop {
+            tokio::select! {
+                event = receiver.recv() => match event {
+                    Some(event) => state_machine.handle(&event).await,
+                    None => break,
+                },
+                _ = state_machine.timeout() => {}
+            }
+        }
+        state_machine.into_parts().0
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fires_inside_actor_loop() {
+        let start = Instant::now();
+        let state_machine = Light::default().uninitialized_state_machine().init().await;
+        let state_machine = TimedStateMachine::new(state_machine, TokioTimer);
+        let (sender, receiver) = mpsc::channel(8);
+        let actor = tokio::spawn(run(state_machine, receiver));
+
+        sender.send(Event::Press).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        // Pressing again re-enters the state, which restarts its timer.
+        sender.send(Event::Press).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        drop(sender);
+
+        let state_machine = actor.await.unwrap();
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(
+            state_machine.switched_off_at,
+            Some(start + Duration::from_secs(8))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropped_timeout_keeps_the_sleep() {
+        let start = Instant::now();
+        let state_machine = Light::default().uninitialized_state_machine().init().await;
+        let mut state_machine = TimedStateMachine::new(state_machine, TokioTimer);
+        state_machine.handle(&Event::Press).await;
+
+        // Cancel the timeout before the sleep completes.
+        let timeout = tokio::time::timeout(Duration::from_secs(3), state_machine.timeout());
+        assert!(timeout.await.is_err());
+        assert_eq!(state_machine.state(), &State::on());
+
+        // Awaiting it again continues the same sleep instead of restarting it.
+        state_machine.timeout().await;
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(
+            state_machine.switched_off_at,
+            Some(start + Duration::from_secs(5))
+        );
+    }
+}
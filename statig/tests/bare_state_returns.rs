@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+        Hold,
+    }
+
+    #[derive(Default)]
+    struct TrafficLight;
+
+    #[state_machine(initial = "State::green()", state(derive(Debug, PartialEq, Eq)))]
+    impl TrafficLight {
+        #[state]
+        fn green() -> State {
+            State::yellow()
+        }
+
+        #[state]
+        fn yellow() -> State {
+            State::red()
+        }
+
+        #[state]
+        fn red(event: &Event) -> impl Into<Response<State>> {
+            match event {
+                Event::Next => Transition(State::green()),
+                Event::Hold => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn bare_states_are_transitions() {
+        let mut state_machine = TrafficLight.state_machine();
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::yellow());
+
+        state_machine.handle(&Event::Hold);
+        assert_eq!(state_machine.state(), &State::red());
+
+        state_machine.handle(&Event::Hold);
+        assert_eq!(state_machine.state(), &State::red());
+
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::green());
+    }
+}
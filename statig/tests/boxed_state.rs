@@ -0,0 +1,81 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    enum Event {
+        Record(u8),
+        Stop,
+        Start,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        recorded: usize,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Recorder {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::recording([0; 1024], 0)),
+                _ => Super,
+            }
+        }
+
+        #[state(boxed, superstate = "active", exit_action = "exit_recording")]
+        fn recording(buffer: &mut [u8; 1024], len: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Record(byte) => {
+                    buffer[*len] = *byte;
+                    *len += 1;
+                    Handled
+                }
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn active(len: &usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Stop if *len > 0 => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn exit_recording(&mut self, len: &usize) {
+            self.recorded += *len;
+        }
+    }
+
+    #[test]
+    fn boxed_state_keeps_enum_small() {
+        assert!(core::mem::size_of::<State>() < 1024);
+    }
+
+    #[test]
+    fn boxed_state_local_storage() {
+        let mut state_machine = Recorder::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Start);
+        state_machine.handle(&Event::Record(1));
+        state_machine.handle(&Event::Record(2));
+
+        match state_machine.state() {
+            State::Recording { buffer, len } => {
+                assert_eq!(**len, 2);
+                assert_eq!(buffer[..2], [1, 2]);
+            }
+            state => panic!("unexpected state: {state:?}"),
+        }
+
+        state_machine.handle(&Event::Stop);
+
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.recorded, 2);
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Start(&'static str),
+        Fail,
+    }
+
+    #[derive(Default)]
+    struct Downloader;
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Downloader {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start(url) => {
+                    Transition(State::downloading().url(url.to_string()).retries(3).build())
+                }
+                Event::Fail => Handled,
+            }
+        }
+
+        #[state(builder)]
+        fn downloading(
+            url: &mut String,
+            retries: &mut u8,
+            received: &mut usize,
+            event: &Event,
+        ) -> Response<State> {
+            let _ = (url, received);
+            match event {
+                Event::Start(_) => Handled,
+                Event::Fail if *retries > 0 => {
+                    *retries -= 1;
+                    Handled
+                }
+                Event::Fail => Transition(State::idle()),
+            }
+        }
+    }
+
+    #[test]
+    fn state_is_built() {
+        let mut state_machine = Downloader.state_machine();
+
+        state_machine.handle(&Event::Start("https://example.com"));
+        state_machine.handle(&Event::Fail);
+
+        assert_eq!(
+            state_machine.state(),
+            &State::Downloading {
+                url: String::from("https://example.com"),
+                retries: 2,
+                received: 0,
+            }
+        );
+    }
+}
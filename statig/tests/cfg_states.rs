@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::State as _;
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Device;
+
+    // The tests are always compiled with `cfg(test)`, so the test doesn't
+    // depend on the features it's compiled with.
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Device {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                #[cfg(test)]
+                Event::Start => Transition(State::running()),
+                #[cfg(not(test))]
+                Event::Start => Handled,
+                Event::Stop => Handled,
+            }
+        }
+
+        #[cfg(not(test))]
+        #[state(superstate = "factory")]
+        fn calibration(event: &Event) -> Response<State> {
+            match event {
+                Event::Start | Event::Stop => Transition(State::idle()),
+            }
+        }
+
+        #[cfg(not(test))]
+        #[superstate]
+        fn factory(event: &Event) -> Response<State> {
+            match event {
+                Event::Start | Event::Stop => Handled,
+            }
+        }
+
+        #[cfg(test)]
+        #[state(superstate = "operating")]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Handled,
+                Event::Stop => Super,
+            }
+        }
+
+        #[cfg(test)]
+        #[superstate]
+        fn operating(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Handled,
+                Event::Stop => Transition(State::idle()),
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_states_are_handled() {
+        let mut state_machine = Device.state_machine();
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::running());
+
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+
+    #[test]
+    fn state_ids_are_stable_across_configurations() {
+        assert_eq!(State::idle().state_id(), Some(0));
+        assert_eq!(State::running().state_id(), Some(2));
+        assert_eq!(State::from_state_id(1), None);
+        assert_eq!(State::from_state_id(2), Some(State::running()));
+    }
+
+    mod shared_names {
+        use statig::prelude::*;
+
+        enum Event {
+            Start,
+            Stop,
+        }
+
+        #[derive(Default)]
+        struct Device;
+
+        // Only one of the handlers named `idle` is compiled, so they describe
+        // the same state.
+        #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+        impl Device {
+            #[cfg(test)]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                match event {
+                    Event::Start => Transition(State::running()),
+                    Event::Stop => Handled,
+                }
+            }
+
+            #[cfg(not(test))]
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                match event {
+                    Event::Start | Event::Stop => Handled,
+                }
+            }
+
+            #[state]
+            fn running(event: &Event) -> Response<State> {
+                match event {
+                    Event::Start => Handled,
+                    Event::Stop => Transition(State::idle()),
+                }
+            }
+        }
+
+        #[test]
+        fn compiled_handler_is_called() {
+            let mut state_machine = Device.state_machine();
+
+            state_machine.handle(&Event::Start);
+            assert_eq!(state_machine.state(), &State::running());
+
+            state_machine.handle(&Event::Stop);
+            assert_eq!(state_machine.state(), &State::idle());
+        }
+
+        #[test]
+        fn shared_name_is_listed_once() {
+            use statig::IntoStateMachine;
+
+            let names: Vec<&str> = Device::HIERARCHY.iter().map(|info| info.name).collect();
+            assert_eq!(names, ["idle", "running"]);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        Calibrate,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Device {
+        log: Vec<&'static str>,
+    }
+
+    // The tests are always compiled with `cfg(test)`, so the test doesn't
+    // depend on the features it's compiled with.
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Device {
+        #[state(
+            entry_action = "enter_idle",
+            exit_action = "exit_idle",
+            transitions("#[cfg(not(test))] Event::Calibrate => State::calibration()")
+        )]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::running()),
+                #[cfg(not(test))]
+                Event::Calibrate => Transition(State::calibration()),
+                #[cfg(test)]
+                Event::Calibrate => Handled,
+                Event::Stop => Handled,
+            }
+        }
+
+        #[cfg(not(test))]
+        #[action]
+        fn enter_idle(&mut self) {
+            self.log.push("enter idle");
+        }
+
+        #[cfg(test)]
+        #[action]
+        fn exit_idle(&mut self) {
+            self.log.push("exit idle");
+        }
+
+        #[cfg(not(test))]
+        #[state]
+        fn calibration(event: &Event) -> Response<State> {
+            match event {
+                Event::Start | Event::Calibrate | Event::Stop => Transition(State::idle()),
+            }
+        }
+
+        #[state]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Stop => Transition(State::idle()),
+                Event::Start | Event::Calibrate => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn transitions_to_compiled_out_states_are_skipped() {
+        let mut state_machine = Device::default().state_machine();
+
+        state_machine.handle(&Event::Calibrate);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::running());
+    }
+
+    #[test]
+    fn compiled_out_actions_are_not_called() {
+        let mut state_machine = Device::default().state_machine();
+
+        state_machine.handle(&Event::Start);
+        state_machine.handle(&Event::Stop);
+
+        assert_eq!(state_machine.log, ["exit idle"]);
+    }
+}
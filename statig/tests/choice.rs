@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Coin,
+        Dispensed,
+        Restock(u32),
+    }
+
+    struct Vending {
+        stock: u32,
+        log: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::check_stock()", state(derive(Debug, PartialEq, Eq)))]
+    impl Vending {
+        #[state(superstate = "operating")]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Coin => Transition(State::dispensing()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "operating", exit_action = "take_item")]
+        fn dispensing(event: &Event) -> Response<State> {
+            match event {
+                Event::Dispensed => Transition(State::check_stock()),
+                _ => Super,
+            }
+        }
+
+        /// Whether there is anything left to sell, which is only known once
+        /// the item was taken when leaving `dispensing`.
+        #[state(choice, superstate = "operating")]
+        fn check_stock(&mut self) -> State {
+            self.log.push("check stock");
+            match self.stock {
+                0 => State::sold_out(),
+                _ => State::idle(),
+            }
+        }
+
+        #[superstate(entry_action = "enter_operating", exit_action = "exit_operating")]
+        fn operating(event: &Event) -> Response<State> {
+            match event {
+                Event::Restock(_) => Handled,
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn sold_out(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Restock(amount) => {
+                    self.stock += amount;
+                    Transition(State::check_stock())
+                }
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn take_item(&mut self) {
+            self.stock -= 1;
+            self.log.push("take item");
+        }
+
+        #[action]
+        fn enter_operating(&mut self) {
+            self.log.push("enter operating");
+        }
+
+        #[action]
+        fn exit_operating(&mut self) {
+            self.log.push("exit operating");
+        }
+    }
+
+    fn vending(stock: u32) -> statig::blocking::InitializedStateMachine<Vending> {
+        Vending { stock, log: vec![] }
+            .uninitialized_state_machine()
+            .init()
+    }
+
+    #[test]
+    fn initial_choice_selects_the_initial_state() {
+        let state_machine = vending(2);
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.log, ["check stock", "enter operating"]);
+
+        let state_machine = vending(0);
+        assert_eq!(state_machine.state(), &State::sold_out());
+        assert_eq!(state_machine.log, ["check stock"]);
+    }
+
+    #[test]
+    fn choice_is_evaluated_after_the_exit_actions() {
+        let mut state_machine = vending(2);
+
+        state_machine.handle(&Event::Coin);
+        state_machine.handle(&Event::Dispensed);
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.log[2..], ["take item", "check stock"]);
+
+        // The last item is taken by the exit action, so the choice selects a
+        // state outside of `operating`, which is exited as well.
+        state_machine.handle(&Event::Coin);
+        state_machine.handle(&Event::Dispensed);
+        assert_eq!(state_machine.state(), &State::sold_out());
+        assert_eq!(
+            state_machine.log[4..],
+            ["take item", "check stock", "exit operating"]
+        );
+    }
+
+    #[test]
+    fn choice_enters_the_states_of_the_selected_target() {
+        let mut state_machine = vending(0);
+
+        state_machine.handle(&Event::Restock(0));
+        assert_eq!(state_machine.state(), &State::sold_out());
+        assert_eq!(state_machine.log[1..], ["check stock"]);
+
+        state_machine.handle(&Event::Restock(3));
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.log[2..], ["check stock", "enter operating"]);
+    }
+
+    #[cfg(feature = "async")]
+    mod awaitable {
+        use statig::prelude::*;
+
+        pub enum Event {
+            Dispensed,
+        }
+
+        pub struct Vending {
+            pub stock: u32,
+        }
+
+        #[state_machine(initial = "State::dispensing()", state(derive(Debug, PartialEq, Eq)))]
+        impl Vending {
+            #[state(exit_action = "take_item")]
+            async fn dispensing(event: &Event) -> Response<State> {
+                match event {
+                    Event::Dispensed => Transition(State::check_stock()),
+                }
+            }
+
+            #[state(choice)]
+            fn check_stock(&self) -> State {
+                match self.stock {
+                    0 => State::sold_out(),
+                    _ => State::dispensing(),
+                }
+            }
+
+            #[state]
+            async fn sold_out() {}
+
+            #[action]
+            async fn take_item(&mut self) {
+                self.stock -= 1;
+            }
+        }
+
+        #[test]
+        fn choice_is_evaluated_after_the_exit_actions() {
+            futures::executor::block_on(async {
+                let mut state_machine = Vending { stock: 1 }
+                    .uninitialized_state_machine()
+                    .init()
+                    .await;
+                state_machine.handle(&Event::Dispensed).await;
+                assert_eq!(state_machine.state(), &State::sold_out());
+            });
+        }
+    }
+}
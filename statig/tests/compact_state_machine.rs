@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::CompactStateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+        Disable,
+    }
+
+    #[derive(Default)]
+    struct Switch;
+
+    #[state_machine(
+        initial = "State::off()",
+        context_identifier = "toggles",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Switch {
+        #[state(superstate = "enabled")]
+        fn off(toggles: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    *toggles += 1;
+                    Transition(State::on())
+                }
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "enabled")]
+        fn on(toggles: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    *toggles += 1;
+                    Transition(State::off())
+                }
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn enabled(event: &Event) -> Response<State> {
+            match event {
+                Event::Disable => Transition(State::disabled()),
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn disabled() -> Response<State> {
+            Handled
+        }
+    }
+
+    #[test]
+    fn handles_events_with_external_data() {
+        // The number of toggles of every instance is owned by the caller.
+        let mut state_machines: [CompactStateMachine<Switch>; 3] = Default::default();
+        let mut counts = [0; 3];
+
+        for (instance, state_machine) in state_machines.iter_mut().enumerate() {
+            assert_eq!(state_machine.state(), None);
+            for _ in 0..instance + 1 {
+                state_machine.handle_with_context(&Event::Toggle, &mut counts[instance]);
+            }
+        }
+
+        assert_eq!(counts, [1, 2, 3]);
+        assert_eq!(state_machines[0].state(), Some(State::on()));
+        assert_eq!(state_machines[1].state(), Some(State::off()));
+        assert_eq!(state_machines[2].state(), Some(State::on()));
+
+        state_machines[0].handle_with_context(&Event::Disable, &mut counts[0]);
+        state_machines[0].handle_with_context(&Event::Toggle, &mut counts[0]);
+
+        assert_eq!(state_machines[0].state(), Some(State::disabled()));
+        assert_eq!(counts, [1, 2, 3]);
+    }
+
+    #[test]
+    fn footprint() {
+        assert_eq!(core::mem::size_of::<CompactStateMachine<Switch>>(), 1);
+        assert_eq!(core::mem::size_of::<CompactStateMachine<Switch, u16>>(), 2);
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+        Eject,
+    }
+
+    #[derive(Default)]
+    struct Player;
+
+    #[state_machine(initial = "State::first_track()", state(derive(Debug, PartialEq, Eq)))]
+    impl Player {
+        #[state(superstate = "playlist")]
+        fn first_track(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::last_track()),
+                Event::Eject => Super,
+            }
+        }
+
+        #[state(superstate = "playlist")]
+        fn last_track(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Completed,
+                Event::Eject => Super,
+            }
+        }
+
+        #[superstate(superstate = "loaded", completion = "State::stopped()")]
+        fn playlist(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+                Event::Eject => Completed,
+            }
+        }
+
+        #[state(superstate = "parked")]
+        fn stopped(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Completed,
+                Event::Eject => Super,
+            }
+        }
+
+        #[superstate(superstate = "loaded")]
+        fn parked(event: &Event) -> Response<State> {
+            match event {
+                Event::Next | Event::Eject => Super,
+            }
+        }
+
+        #[superstate(completion = "State::ejected()")]
+        fn loaded(event: &Event) -> Response<State> {
+            match event {
+                Event::Next | Event::Eject => Handled,
+            }
+        }
+
+        #[state]
+        fn ejected(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Completed,
+                Event::Eject => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn completed_state_takes_the_completion_transition_of_its_superstate() {
+        let mut state_machine = Player.state_machine();
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::last_track());
+
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::stopped());
+    }
+
+    #[test]
+    fn completed_superstate_takes_the_completion_transition_of_its_superstate() {
+        let mut state_machine = Player.state_machine();
+        state_machine.handle(&Event::Eject);
+        assert_eq!(state_machine.state(), &State::ejected());
+    }
+
+    #[test]
+    fn completion_without_a_completion_transition_is_handled() {
+        let mut state_machine = Player.state_machine();
+        state_machine.handle(&Event::Eject);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::ejected());
+    }
+
+    #[test]
+    fn completion_is_passed_on_to_the_next_superstate() {
+        let mut state_machine = Player.state_machine();
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::ejected());
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    const LIMIT: u32 = 100;
+
+    enum Event {
+        Pump(u32),
+        Release(u32),
+    }
+
+    #[derive(Default)]
+    struct Boiler {
+        pressure: u32,
+        log: Vec<&'static str>,
+    }
+
+    impl Boiler {
+        fn is_safe(&self) -> bool {
+            self.pressure < LIMIT / 2
+        }
+    }
+
+    #[state_machine(initial = "State::heating()", state(derive(Debug, PartialEq, Eq)))]
+    impl Boiler {
+        #[state(when = "self.pressure > LIMIT", transition = "venting")]
+        fn heating(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Pump(amount) => self.pressure += amount,
+                Event::Release(amount) => self.pressure -= amount,
+            }
+            Handled
+        }
+
+        #[state(
+            entry_action = "open_valve",
+            when = "self.is_safe()",
+            transition = "State::heating()"
+        )]
+        fn venting(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Pump(_) => {}
+                Event::Release(amount) => self.pressure -= amount,
+            }
+            Handled
+        }
+
+        #[action]
+        fn open_valve(&mut self) {
+            self.log.push("open valve");
+        }
+    }
+
+    #[test]
+    fn condition_transition_is_taken_when_the_condition_holds() {
+        let mut state_machine = Boiler::default().state_machine();
+        state_machine.handle(&Event::Pump(80));
+        assert_eq!(state_machine.state(), &State::heating());
+
+        state_machine.handle(&Event::Pump(30));
+        assert_eq!(state_machine.state(), &State::venting());
+        assert_eq!(state_machine.inner().log, ["open valve"]);
+
+        state_machine.handle(&Event::Release(40));
+        assert_eq!(state_machine.state(), &State::venting());
+
+        state_machine.handle(&Event::Release(40));
+        assert_eq!(state_machine.state(), &State::heating());
+    }
+}
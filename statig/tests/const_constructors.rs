@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Tick,
+        Pause,
+    }
+
+    #[derive(Default)]
+    struct Metronome;
+
+    // Both constructors are evaluated at compile time.
+    const PAUSED: State = State::paused(120);
+    const TICKING: State = State::ticking().build();
+
+    #[state_machine(
+        initial = "State::ticking().build()",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Metronome {
+        #[state(builder)]
+        fn ticking(
+            #[default(120)] bpm: &mut u16,
+            #[default(0)] ticks: &mut u32,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Tick => {
+                    *ticks += 1;
+                    Handled
+                }
+                Event::Pause => Transition(State::paused(*bpm)),
+            }
+        }
+
+        #[state]
+        fn paused(bpm: &mut u16, event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::ticking().bpm(*bpm).build()),
+                Event::Pause => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn constructors_are_const() {
+        let mut state_machine = Metronome.state_machine();
+        assert_eq!(state_machine.state(), &TICKING);
+
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Pause);
+        assert_eq!(state_machine.state(), &PAUSED);
+
+        state_machine.handle(&Event::Tick);
+        assert_eq!(
+            state_machine.state(),
+            &State::Ticking { bpm: 120, ticks: 0 }
+        );
+    }
+}
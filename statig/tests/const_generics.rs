@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Sample(u8),
+        Flush,
+    }
+
+    #[derive(Default)]
+    struct Buffered<const N: usize> {
+        flushed: Vec<Vec<u8>>,
+    }
+
+    #[state_machine(
+        initial = "State::filling([0; N], 0)",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl<const N: usize> Buffered<N> {
+        #[state(superstate = "buffering")]
+        fn filling(
+            &mut self,
+            buffer: &mut [u8; N],
+            len: &mut usize,
+            event: &Event,
+        ) -> Response<State<N>> {
+            match event {
+                Event::Sample(sample) if *len < N => {
+                    buffer[*len] = *sample;
+                    *len += 1;
+                    Handled
+                }
+                Event::Sample(_) => Transition(State::full()),
+                Event::Flush => Super,
+            }
+        }
+
+        #[superstate]
+        fn buffering(
+            &mut self,
+            buffer: &mut [u8; N],
+            len: &mut usize,
+            event: &Event,
+        ) -> Response<State<N>> {
+            match event {
+                Event::Flush => {
+                    self.flushed.push(buffer[..*len].to_vec());
+                    Transition(State::filling([0; N], 0))
+                }
+                Event::Sample(_) => Handled,
+            }
+        }
+
+        #[state]
+        fn full(&mut self, event: &Event) -> Response<State<N>> {
+            match event {
+                Event::Sample(_) => Handled,
+                Event::Flush => Transition(State::filling([0; N], 0)),
+            }
+        }
+    }
+
+    #[test]
+    fn buffer_size_is_carried_onto_state() {
+        let mut state_machine = Buffered::<2>::default().state_machine();
+
+        state_machine.handle(&Event::Sample(1));
+        state_machine.handle(&Event::Sample(2));
+        assert_eq!(state_machine.state(), &State::filling([1, 2], 2));
+
+        state_machine.handle(&Event::Sample(3));
+        assert_eq!(state_machine.state(), &State::full());
+
+        state_machine.handle(&Event::Flush);
+        state_machine.handle(&Event::Sample(4));
+        state_machine.handle(&Event::Flush);
+        assert_eq!(state_machine.flushed, [vec![4]]);
+    }
+
+    #[test]
+    fn instances_with_different_sizes() {
+        let mut small = Buffered::<1>::default().state_machine();
+        let mut large = Buffered::<4>::default().state_machine();
+
+        for sample in 0..3 {
+            small.handle(&Event::Sample(sample));
+            large.handle(&Event::Sample(sample));
+        }
+
+        assert_eq!(small.state(), &State::full());
+        assert_eq!(large.state(), &State::filling([0, 1, 2, 0], 3));
+    }
+}
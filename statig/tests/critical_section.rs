@@ -0,0 +1,155 @@
+#![cfg(feature = "critical-section")]
+
+#[cfg(test)]
+mod tests {
+    use statig::blocking::CriticalSectionStateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Increment,
+        Toggle,
+    }
+
+    struct Counter {
+        count: usize,
+    }
+
+    #[state_machine(initial = "State::counting()", state(derive(Debug, PartialEq, Eq)))]
+    impl Counter {
+        #[state]
+        fn counting(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Increment => {
+                    self.count += 1;
+                    Handled
+                }
+                Event::Toggle => Transition(State::paused()),
+            }
+        }
+
+        #[state]
+        fn paused(event: &Event) -> Response<State> {
+            match event {
+                Event::Increment => Handled,
+                Event::Toggle => Transition(State::counting()),
+            }
+        }
+    }
+
+    #[test]
+    fn queued_events_are_processed_in_order() {
+        let state_machine: CriticalSectionStateMachine<Counter, 4> =
+            CriticalSectionStateMachine::new(Counter { count: 0 });
+
+        assert!(state_machine.push(Event::Increment).is_ok());
+        assert!(state_machine.push(Event::Toggle).is_ok());
+        assert!(state_machine.push(Event::Increment).is_ok());
+        assert!(state_machine.push(Event::Toggle).is_ok());
+        assert!(state_machine.push(Event::Increment).is_err());
+        assert_eq!(state_machine.queued(), 4);
+
+        assert_eq!(state_machine.process(), 4);
+        assert_eq!(state_machine.queued(), 0);
+
+        state_machine.with(|state_machine| {
+            assert_eq!(state_machine.count, 1);
+            assert_eq!(state_machine.state(), &State::counting());
+        });
+
+        assert!(state_machine.handle(Event::Increment).is_ok());
+        assert_eq!(state_machine.with(|state_machine| state_machine.count), 2);
+    }
+
+    #[test]
+    fn events_pushed_from_other_threads() {
+        static STATE_MACHINE: CriticalSectionStateMachine<Counter, 16> =
+            CriticalSectionStateMachine::new(Counter { count: 0 });
+
+        let producers: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    let mut pushed = 0;
+                    while pushed < 100 {
+                        if STATE_MACHINE.push(Event::Increment).is_ok() {
+                            pushed += 1;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let mut handled = 0;
+        while handled < 400 {
+            handled += STATE_MACHINE.process();
+        }
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(STATE_MACHINE.with(|state_machine| state_machine.count), 400);
+    }
+
+    mod reentrancy {
+        use statig::blocking::CriticalSectionStateMachine;
+        use statig::prelude::*;
+
+        enum Message {
+            Ping,
+            Pong,
+        }
+
+        struct Relay {
+            log: Vec<&'static str>,
+        }
+
+        static RELAY: CriticalSectionStateMachine<Relay, 4> =
+            CriticalSectionStateMachine::new(Relay { log: Vec::new() });
+
+        #[state_machine(initial = "State::waiting()")]
+        impl Relay {
+            #[state]
+            fn waiting(&mut self, event: &Message) -> Response<State> {
+                match event {
+                    Message::Ping => {
+                        self.log.push("ping");
+                        // Reentrant call, the event is handled after this one.
+                        assert!(RELAY.handle(Message::Pong).is_ok());
+                        self.log.push("ping done");
+                        Transition(State::replied())
+                    }
+                    Message::Pong => Handled,
+                }
+            }
+
+            #[state(entry_action = "enter_replied")]
+            fn replied(&mut self, event: &Message) -> Response<State> {
+                match event {
+                    Message::Pong => {
+                        self.log.push("pong");
+                        Handled
+                    }
+                    Message::Ping => Transition(State::waiting()),
+                }
+            }
+
+            #[action]
+            fn enter_replied(&mut self) {
+                self.log.push("enter replied");
+            }
+        }
+
+        #[test]
+        fn reentrant_handle_is_deferred() {
+            assert!(RELAY.handle(Message::Ping).is_ok());
+
+            assert_eq!(RELAY.queued(), 0);
+            RELAY.with(|state_machine| {
+                assert_eq!(
+                    state_machine.log,
+                    ["ping", "ping done", "enter replied", "pong"]
+                );
+            });
+        }
+    }
+}
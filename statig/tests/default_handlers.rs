@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Play,
+        Pause,
+        Stop,
+        Volume(u8),
+    }
+
+    #[derive(Default)]
+    struct Player {
+        volume: u8,
+        ignored: usize,
+    }
+
+    #[state_machine(initial = "State::paused()", state(derive(Debug, PartialEq, Eq)))]
+    impl Player {
+        #[state(superstate = "powered")]
+        fn paused(Event::Play: &Event) -> Response<State> {
+            Transition(State::playing())
+        }
+
+        #[state(superstate = "powered")]
+        fn playing(Event::Pause: &Event) -> Response<State> {
+            Transition(State::paused())
+        }
+
+        #[superstate(default = "otherwise")]
+        fn powered(&mut self, Event::Volume(volume): &Event) -> Response<State> {
+            self.volume = *volume;
+            Handled
+        }
+
+        #[state]
+        fn off(Event::Play: &Event) -> Response<State> {
+            Transition(State::paused())
+        }
+
+        fn otherwise(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Stop => Transition(State::off()),
+                _ => {
+                    self.ignored += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn events_that_no_handler_matches_go_to_the_default_handler() {
+        let mut state_machine = Player::default().state_machine();
+        state_machine.handle(&Event::Play);
+        state_machine.handle(&Event::Volume(7));
+        state_machine.handle(&Event::Play);
+        assert_eq!(state_machine.state(), &State::playing());
+        assert_eq!(state_machine.inner().volume, 7);
+        assert_eq!(state_machine.inner().ignored, 1);
+
+        state_machine.handle(&Event::Pause);
+        state_machine.handle(&Event::Stop);
+        assert_eq!(state_machine.state(), &State::off());
+    }
+}
@@ -0,0 +1,87 @@
+#![cfg(any(feature = "std", feature = "critical-section"))]
+
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Push(u8),
+        Seal,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        received: Vec<u8>,
+    }
+
+    #[state_machine(initial = "State::open()", state(derive(Debug, PartialEq, Eq)))]
+    impl Recorder {
+        #[state]
+        fn open(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Push(value) => {
+                    self.received.push(*value);
+                    Handled
+                }
+                Event::Seal => Transition(State::sealed()),
+            }
+        }
+
+        #[state]
+        fn sealed() -> Response<State> {
+            Handled
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn dispatcher_handles_events_in_order() {
+        use statig::blocking::Dispatcher;
+
+        let (dispatcher, sender) = Dispatcher::new(Recorder::default().state_machine(), 2);
+        let dispatcher = dispatcher.spawn();
+
+        let producer = {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for value in 0..100 {
+                    sender.send(Event::Push(value)).unwrap();
+                }
+            })
+        };
+        producer.join().unwrap();
+        sender.send(Event::Seal).unwrap();
+        sender.send(Event::Push(100)).unwrap();
+        drop(sender);
+
+        let state_machine = dispatcher.join().unwrap();
+        assert_eq!(state_machine.received, (0..100).collect::<Vec<_>>());
+        assert_eq!(state_machine.state(), &State::sealed());
+    }
+
+    #[cfg(feature = "critical-section")]
+    #[test]
+    fn channel_dispatcher_handles_events_in_order() {
+        use statig::blocking::{ChannelDispatcher, EventChannel};
+
+        static CHANNEL: EventChannel<Event, 4> = EventChannel::new();
+
+        let mut dispatcher = ChannelDispatcher::new(Recorder::default().state_machine(), &CHANNEL);
+        let sender = CHANNEL.sender();
+
+        assert!(sender.send(Event::Push(1)).is_ok());
+        assert!(sender.send(Event::Push(2)).is_ok());
+        assert_eq!(dispatcher.dispatch_pending(), 2);
+
+        assert!(sender.send(Event::Push(3)).is_ok());
+        assert!(sender.send(Event::Seal).is_ok());
+        assert!(sender.send(Event::Push(4)).is_ok());
+        assert!(sender.send(Event::Push(5)).is_ok());
+        assert!(sender.send(Event::Push(6)).is_err());
+        assert_eq!(dispatcher.dispatch_pending(), 4);
+        assert_eq!(dispatcher.dispatch_pending(), 0);
+
+        assert_eq!(dispatcher.state_machine().received, [1, 2, 3]);
+        assert_eq!(dispatcher.state_machine().state(), &State::sealed());
+    }
+}
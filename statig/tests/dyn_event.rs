@@ -0,0 +1,106 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+    use statig::DynEvent;
+
+    /// Events of two plugins that know nothing about each other.
+    mod plugins {
+        pub struct Play(pub &'static str);
+        pub struct Stop;
+        pub struct Brightness(pub u8);
+    }
+
+    use plugins::{Brightness, Play, Stop};
+
+    #[derive(Default)]
+    struct Host {
+        playing: Option<&'static str>,
+        brightness: u8,
+        unhandled: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Host {
+        #[state(superstate = "running")]
+        fn idle(&mut self, event: &DynEvent<'_>) -> Response<State> {
+            match event.downcast_ref::<Play>() {
+                Some(Play(track)) => {
+                    self.playing = Some(*track);
+                    Transition(State::playing())
+                }
+                None => Super,
+            }
+        }
+
+        #[state(superstate = "running")]
+        fn playing(&mut self, event: &DynEvent<'_>) -> Response<State> {
+            match event.is::<Stop>() {
+                true => {
+                    self.playing = None;
+                    Transition(State::idle())
+                }
+                false => Super,
+            }
+        }
+
+        #[superstate]
+        fn running(&mut self, event: &DynEvent<'_>) -> Response<State> {
+            match event.downcast_ref::<Brightness>() {
+                Some(Brightness(brightness)) => {
+                    self.brightness = *brightness;
+                    Handled
+                }
+                None => {
+                    self.unhandled.push(event.type_name());
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn events_of_any_type_are_routed_through_one_state_machine() {
+        let mut state_machine = Host::default().state_machine();
+
+        state_machine.handle_from(&Play("intro"));
+        assert_eq!(state_machine.state(), &State::playing());
+        assert_eq!(state_machine.playing, Some("intro"));
+
+        state_machine.handle_from(&Brightness(80));
+        assert_eq!(state_machine.brightness, 80);
+
+        state_machine.handle(&DynEvent::boxed(Stop));
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.playing, None);
+    }
+
+    #[test]
+    fn unknown_events_report_their_type() {
+        let mut state_machine = Host::default().state_machine();
+
+        state_machine.handle_from(&Stop);
+        state_machine.handle_from(&42u32);
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.unhandled.len(), 2);
+        assert!(state_machine.unhandled[0].ends_with("plugins::Stop"));
+        assert_eq!(state_machine.unhandled[1], "u32");
+    }
+
+    #[test]
+    fn boxed_events_can_be_queued() {
+        let queue: Vec<DynEvent<'static>> = vec![
+            DynEvent::boxed(Play("outro")),
+            DynEvent::boxed(Brightness(10)),
+        ];
+        let mut state_machine = Host::default().state_machine();
+
+        for event in queue {
+            state_machine.handle(&event);
+        }
+        assert_eq!(state_machine.playing, Some("outro"));
+        assert_eq!(state_machine.brightness, 10);
+        assert!(format!("{:?}", DynEvent::new(&Stop)).contains("Stop"));
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Command {
+        Start,
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Pump;
+
+    #[state_machine(
+        initial = "State::idle()",
+        event = "cmd",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Pump {
+        #[state]
+        fn idle(cmd: &Command) -> Response<State> {
+            match cmd {
+                Command::Start => Transition(State::pumping()),
+                Command::Stop => Handled,
+            }
+        }
+
+        #[state]
+        fn pumping(cmd: &Command) -> Response<State> {
+            match cmd {
+                Command::Start => Handled,
+                Command::Stop => Transition(State::idle()),
+            }
+        }
+    }
+
+    #[test]
+    fn event_is_bound_to_the_configured_name() {
+        let mut state_machine = Pump.state_machine();
+        state_machine.handle(&Command::Start);
+        assert_eq!(state_machine.state(), &State::pumping());
+
+        state_machine.handle(&Command::Stop);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Clone, Copy)]
+    enum Event {
+        Press,
+        Hold(u32),
+    }
+
+    #[derive(Default)]
+    struct Button {
+        held_for: u32,
+    }
+
+    #[state_machine(initial = "State::released()", state(derive(Debug, PartialEq, Eq)))]
+    impl Button {
+        #[state(superstate = "active")]
+        fn released(event: Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::pressed()),
+                Event::Hold(_) => Super,
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn pressed(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::released()),
+                Event::Hold(_) => Super,
+            }
+        }
+
+        #[superstate]
+        fn active(&mut self, event: Event) -> Response<State> {
+            match event {
+                Event::Press => Handled,
+                Event::Hold(duration) => {
+                    self.held_for += duration;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copy_events_can_be_received_by_value() {
+        let mut state_machine = Button::default().state_machine();
+        state_machine.handle(&Event::Hold(5));
+        state_machine.handle(&Event::Press);
+        state_machine.handle(&Event::Hold(10));
+
+        assert_eq!(state_machine.state(), &State::pressed());
+        assert_eq!(state_machine.inner().held_for, 15);
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{EventFilters, FilterVerdict, MiddlewareStateMachine};
+    use statig::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Event {
+        Button(u32),
+        Lock,
+        Unlock,
+    }
+
+    #[derive(Default)]
+    struct Panel {
+        buttons: Vec<u32>,
+    }
+
+    #[state_machine(initial = "State::unlocked()", state(derive(Debug, PartialEq)))]
+    impl Panel {
+        #[state]
+        fn unlocked(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Button(button) => {
+                    self.buttons.push(*button);
+                    Handled
+                }
+                Event::Lock => Transition(State::locked()),
+                Event::Unlock => Handled,
+            }
+        }
+
+        #[state]
+        fn locked(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Button(button) => {
+                    self.buttons.push(*button);
+                    Handled
+                }
+                Event::Unlock => Transition(State::unlocked()),
+                Event::Lock => Handled,
+            }
+        }
+    }
+
+    fn ignore_buttons_while_locked(state: &State, event: &Event) -> FilterVerdict<Event> {
+        match (state, event) {
+            (State::Locked {}, Event::Button(_)) => FilterVerdict::Drop,
+            _ => FilterVerdict::Pass,
+        }
+    }
+
+    fn double_buttons(_: &State, event: &Event) -> FilterVerdict<Event> {
+        match event {
+            Event::Button(button) => FilterVerdict::Rewrite(Event::Button(button * 2)),
+            _ => FilterVerdict::Pass,
+        }
+    }
+
+    fn increment_buttons(_: &State, event: &Event) -> FilterVerdict<Event> {
+        match event {
+            Event::Button(button) => FilterVerdict::Rewrite(Event::Button(button + 1)),
+            _ => FilterVerdict::Pass,
+        }
+    }
+
+    #[test]
+    fn filters_are_registered_and_removed_at_runtime() {
+        let state_machine = Panel::default().uninitialized_state_machine().init();
+        let mut state_machine =
+            MiddlewareStateMachine::new(state_machine, EventFilters::<Panel, 2>::new());
+
+        state_machine.handle(&Event::Lock);
+        state_machine.handle(&Event::Button(1));
+
+        let filter = state_machine
+            .middleware_mut()
+            .register(ignore_buttons_while_locked)
+            .unwrap();
+        state_machine.handle(&Event::Button(2));
+        state_machine.handle(&Event::Unlock);
+        state_machine.handle(&Event::Button(3));
+
+        assert!(state_machine.middleware_mut().remove(filter));
+        assert!(!state_machine.middleware_mut().remove(filter));
+        state_machine.handle(&Event::Lock);
+        state_machine.handle(&Event::Button(4));
+
+        assert_eq!(state_machine.buttons, [1, 3, 4]);
+    }
+
+    #[test]
+    fn rewrites_are_seen_by_the_filters_after_them() {
+        let state_machine = Panel::default().uninitialized_state_machine().init();
+        let mut state_machine =
+            MiddlewareStateMachine::new(state_machine, EventFilters::<Panel, 2>::new());
+        let filters = state_machine.middleware_mut();
+
+        let double = filters.register(double_buttons).unwrap();
+        filters.register(increment_buttons).unwrap();
+        assert!(filters.register(ignore_buttons_while_locked).is_err());
+        assert_eq!(filters.len(), 2);
+
+        state_machine.handle(&Event::Button(1));
+
+        // Removing a filter keeps the others in order.
+        state_machine.middleware_mut().remove(double);
+        state_machine
+            .middleware_mut()
+            .register(double_buttons)
+            .unwrap();
+        state_machine.handle(&Event::Button(1));
+
+        state_machine.middleware_mut().clear();
+        assert!(state_machine.middleware().is_empty());
+        state_machine.handle(&Event::Button(1));
+
+        assert_eq!(state_machine.buttons, [3, 4, 1]);
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum ButtonEvent {
+        Pressed,
+    }
+
+    enum TimerEvent {
+        Elapsed,
+    }
+
+    enum Event {
+        Button(ButtonEvent),
+        Timer(TimerEvent),
+    }
+
+    struct Lamp;
+
+    #[state_machine(
+        initial = "State::off()",
+        event_from("Button(ButtonEvent)", "Timer(TimerEvent)"),
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Lamp {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Button(ButtonEvent::Pressed) => Transition(State::on()),
+                Event::Timer(_) => Handled,
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Button(ButtonEvent::Pressed) => Handled,
+                Event::Timer(TimerEvent::Elapsed) => Transition(State::off()),
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_events_are_converted() {
+        let mut state_machine = Lamp.uninitialized_state_machine().init();
+
+        state_machine.handle_from(ButtonEvent::Pressed);
+        assert_eq!(state_machine.state(), &State::on());
+
+        state_machine.handle_from(TimerEvent::Elapsed);
+        assert_eq!(state_machine.state(), &State::off());
+
+        state_machine.handle(&Event::from(ButtonEvent::Pressed));
+        assert_eq!(state_machine.state(), &State::on());
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Button { id: u32 },
+        Key(char),
+        Tick,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Panel {
+        pressed: Vec<u32>,
+        typed: String,
+        resets: usize,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Panel {
+        #[state(superstate = "active")]
+        fn idle(&mut self, Event::Button { id }: &Event) -> Response<State> {
+            self.pressed.push(*id);
+            Transition(State::typing(0))
+        }
+
+        #[state(superstate = "active")]
+        fn typing(&mut self, count: &mut usize, Event::Key(key): &Event) -> Response<State> {
+            self.typed.push(*key);
+            *count += 1;
+            Handled
+        }
+
+        #[superstate]
+        fn active(&mut self, Event::Reset: &Event) -> Response<State> {
+            self.resets += 1;
+            Transition(State::idle())
+        }
+    }
+
+    #[test]
+    fn matching_event_is_destructured() {
+        let mut state_machine = Panel::default().state_machine();
+
+        state_machine.handle(&Event::Button { id: 3 });
+        state_machine.handle(&Event::Key('a'));
+        state_machine.handle(&Event::Key('b'));
+
+        assert_eq!(state_machine.pressed, [3]);
+        assert_eq!(state_machine.typed, "ab");
+        assert_eq!(state_machine.state(), &State::typing(2));
+    }
+
+    #[test]
+    fn unmatched_event_is_deferred_to_superstate() {
+        let mut state_machine = Panel::default().state_machine();
+
+        state_machine.handle(&Event::Key('a'));
+        state_machine.handle(&Event::Reset);
+        state_machine.handle(&Event::Button { id: 1 });
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Button { id: 2 });
+        state_machine.handle(&Event::Reset);
+
+        assert_eq!(state_machine.pressed, [1]);
+        assert_eq!(state_machine.typed, "");
+        assert_eq!(state_machine.resets, 2);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+    use statig::{ArrayQueue, EventPool, PooledEvent};
+
+    pub enum Event {
+        Frame([u8; 32]),
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Receiver {
+        checksum: u32,
+    }
+
+    #[state_machine(initial = "State::receiving()", state(derive(Debug, PartialEq, Eq)))]
+    impl Receiver {
+        #[state]
+        fn receiving(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Frame(data) => {
+                    self.checksum += data.iter().map(|byte| *byte as u32).sum::<u32>();
+                    Handled
+                }
+                Event::Reset => {
+                    self.checksum = 0;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pool_hands_back_events_when_full() {
+        let mut pool: EventPool<Event, 2> = EventPool::new();
+
+        let first = pool.alloc(Event::Reset).ok().unwrap();
+        let _second = pool.alloc(Event::Reset).ok().unwrap();
+        assert!(matches!(
+            pool.alloc(Event::Frame([1; 32])),
+            Err(Event::Frame(_))
+        ));
+        assert_eq!(pool.available(), 0);
+
+        pool.release(first);
+        assert_eq!(pool.available(), 1);
+        assert!(pool.alloc(Event::Frame([1; 32])).is_ok());
+    }
+
+    #[test]
+    fn pooled_events_are_recycled_after_dispatch() {
+        let mut pool: EventPool<Event, 2> = EventPool::new();
+        let mut queue: ArrayQueue<PooledEvent, 2> = ArrayQueue::new();
+        let mut state_machine = Receiver::default().state_machine();
+
+        // More events pass through than the pool can hold at once.
+        for round in 1..=3u8 {
+            for _ in 0..2 {
+                let event = pool.alloc(Event::Frame([round; 32])).ok().unwrap();
+                queue.push(event).unwrap();
+            }
+            assert_eq!(pool.available(), 0);
+
+            while let Some(event) = queue.pop() {
+                state_machine.handle(pool.get(&event));
+                pool.release(event);
+            }
+            assert_eq!(pool.available(), pool.capacity());
+        }
+
+        assert_eq!(state_machine.checksum, (1 + 2 + 3) * 2 * 32);
+    }
+
+    #[test]
+    fn pooled_event_can_be_modified_before_dispatch() {
+        let mut pool: EventPool<Event, 1> = EventPool::new();
+        let mut state_machine = Receiver::default().state_machine();
+
+        let event = pool.alloc(Event::Frame([0; 32])).ok().unwrap();
+        if let Event::Frame(data) = pool.get_mut(&event) {
+            data[0] = 7;
+        }
+        state_machine.handle(pool.get(&event));
+
+        assert!(matches!(pool.release(event), Event::Frame(_)));
+        assert_eq!(state_machine.checksum, 7);
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{BoundedStateMachine, QueuedStateMachine};
+    use statig::prelude::*;
+    use statig::{ArrayQueue, EventQueue, OverflowPolicy, OverflowQueue, SliceQueue};
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Event {
+        Print(u32),
+        Ready,
+        Jam,
+    }
+
+    #[derive(Default)]
+    struct Printer {
+        printed: Vec<u32>,
+    }
+
+    #[state_machine(initial = "State::warming_up()", state(derive(Debug, PartialEq)))]
+    impl Printer {
+        #[state]
+        fn warming_up(event: &Event) -> Response<State> {
+            match event {
+                Event::Ready => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Print(job) => {
+                    self.printed.push(*job);
+                    Handled
+                }
+                Event::Jam => Transition(State::warming_up()),
+                Event::Ready => Handled,
+            }
+        }
+    }
+
+    fn defers(state: &State, event: &Event) -> bool {
+        matches!((state, event), (State::WarmingUp {}, Event::Print(_)))
+    }
+
+    fn drain<Q: EventQueue<u32>>(queue: &mut Q) -> Vec<u32> {
+        core::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn array_queue_hands_back_events_when_full() {
+        let mut queue: ArrayQueue<u32, 2> = ArrayQueue::new();
+
+        assert_eq!(EventQueue::push(&mut queue, 1), Ok(()));
+        assert_eq!(EventQueue::push(&mut queue, 2), Ok(()));
+        assert_eq!(EventQueue::push(&mut queue, 3), Err(3));
+        assert_eq!(EventQueue::len(&queue), 2);
+        assert_eq!(drain(&mut queue), [1, 2]);
+    }
+
+    #[test]
+    fn slice_queue_wraps_around_its_storage() {
+        let mut storage: [Option<u32>; 3] = Default::default();
+        let mut queue = SliceQueue::new(&mut storage);
+
+        for item in 0..3 {
+            assert_eq!(queue.push(item), Ok(()));
+        }
+        assert_eq!(queue.push(3), Err(3));
+        assert_eq!(queue.pop(), Some(0));
+        assert_eq!(queue.push(3), Ok(()));
+        assert_eq!(drain(&mut queue), [1, 2, 3]);
+        assert!(queue.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec_deque_grows() {
+        let mut queue = std::collections::VecDeque::new();
+
+        for item in 0..100 {
+            assert_eq!(EventQueue::push(&mut queue, item), Ok(()));
+        }
+        assert_eq!(drain(&mut queue), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn deferred_events_are_recalled_after_a_transition() {
+        let state_machine = Printer::default().uninitialized_state_machine().init();
+        let mut state_machine = QueuedStateMachine::new(
+            state_machine,
+            ArrayQueue::<Event, 4>::new(),
+            ArrayQueue::<Event, 4>::new(),
+        )
+        .with_deferral(defers);
+
+        state_machine.post(Event::Print(1)).unwrap();
+        state_machine.post(Event::Print(2)).unwrap();
+        assert_eq!(state_machine.pending(), 2);
+
+        assert_eq!(state_machine.dispatch_pending(), Ok(0));
+        assert_eq!(state_machine.deferred(), 2);
+        assert!(state_machine.printed.is_empty());
+
+        state_machine.post(Event::Ready).unwrap();
+        state_machine.post(Event::Print(3)).unwrap();
+        assert_eq!(state_machine.dispatch_pending(), Ok(4));
+        assert_eq!(state_machine.deferred(), 0);
+        assert_eq!(state_machine.printed, [1, 2, 3]);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+
+    #[test]
+    fn full_deferred_queue_hands_the_event_back() {
+        let state_machine = Printer::default().uninitialized_state_machine().init();
+        let mut storage: [Option<Event>; 1] = Default::default();
+        let mut state_machine = QueuedStateMachine::new(
+            state_machine,
+            ArrayQueue::<Event, 4>::new(),
+            SliceQueue::new(&mut storage),
+        )
+        .with_deferral(defers);
+
+        state_machine.post(Event::Print(1)).unwrap();
+        state_machine.post(Event::Print(2)).unwrap();
+        state_machine.post(Event::Ready).unwrap();
+
+        assert_eq!(state_machine.dispatch_pending(), Err(Event::Print(2)));
+        assert_eq!(state_machine.deferred(), 1);
+        assert_eq!(state_machine.pending(), 1);
+
+        assert_eq!(state_machine.dispatch_pending(), Ok(2));
+        assert_eq!(state_machine.printed, [1]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn same_machine_runs_on_heap_backed_queues() {
+        use std::collections::VecDeque;
+
+        let state_machine = Printer::default().uninitialized_state_machine().init();
+        let mut state_machine =
+            QueuedStateMachine::new(state_machine, VecDeque::new(), VecDeque::new())
+                .with_deferral(defers);
+
+        for job in 0..10 {
+            state_machine.post(Event::Print(job)).unwrap();
+        }
+        state_machine.post(Event::Ready).unwrap();
+        state_machine.post(Event::Jam).unwrap();
+        state_machine.post(Event::Print(10)).unwrap();
+
+        assert_eq!(state_machine.dispatch_pending(), Ok(12));
+        assert_eq!(state_machine.printed, (0..10).collect::<Vec<_>>());
+        assert_eq!(state_machine.deferred(), 1);
+        assert_eq!(state_machine.state(), &State::warming_up());
+    }
+
+    #[test]
+    fn overflow_policies() {
+        let push_all = |policy| {
+            let mut queue = OverflowQueue::new(ArrayQueue::<u32, 3>::new(), policy);
+            let results: Vec<_> = (0..5).map(|item| queue.push(item)).collect();
+            (results, queue.dropped(), drain(&mut queue))
+        };
+
+        assert_eq!(
+            push_all(OverflowPolicy::DropOldest),
+            (vec![Ok(()); 5], 2, vec![2, 3, 4])
+        );
+        assert_eq!(
+            push_all(OverflowPolicy::DropNewest),
+            (vec![Ok(()); 5], 2, vec![0, 1, 2])
+        );
+        assert_eq!(
+            push_all(OverflowPolicy::Error),
+            (
+                vec![Ok(()), Ok(()), Ok(()), Err(3), Err(4)],
+                0,
+                vec![0, 1, 2]
+            )
+        );
+    }
+
+    #[test]
+    fn bounded_state_machine_applies_its_policies() {
+        let state_machine = Printer::default().uninitialized_state_machine().init();
+        let mut state_machine: BoundedStateMachine<Printer, 2, 1> = BoundedStateMachine::bounded(
+            state_machine,
+            OverflowPolicy::DropOldest,
+            OverflowPolicy::DropNewest,
+        )
+        .with_deferral(defers);
+
+        for job in 0..4 {
+            state_machine.post(Event::Print(job)).unwrap();
+        }
+        assert_eq!(state_machine.pending(), 2);
+        assert_eq!(state_machine.dispatch_pending(), Ok(0));
+        assert_eq!(state_machine.deferred(), 1);
+        assert_eq!(state_machine.dropped(), 3);
+
+        state_machine.post(Event::Ready).unwrap();
+        assert_eq!(state_machine.dispatch_pending(), Ok(2));
+        assert_eq!(state_machine.printed, [2]);
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    pub enum ControlEvent {
+        Open,
+        Close,
+    }
+
+    #[derive(Debug)]
+    pub enum DataEvent {
+        Payload(u8),
+    }
+
+    #[derive(Default)]
+    struct Connection {
+        received: Vec<u8>,
+        dropped: usize,
+    }
+
+    #[state_machine(
+        initial = "State::closed()",
+        events("Control(ControlEvent)", "Data(DataEvent)", derive(Debug)),
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Connection {
+        #[state(superstate = "control")]
+        fn closed(event: &ControlEvent) -> Response<State> {
+            match event {
+                ControlEvent::Open => Transition(State::open()),
+                ControlEvent::Close => Handled,
+            }
+        }
+
+        #[state(superstate = "control")]
+        fn open(&mut self, event: &DataEvent) -> Response<State> {
+            match event {
+                DataEvent::Payload(byte) => {
+                    self.received.push(*byte);
+                    Handled
+                }
+            }
+        }
+
+        #[superstate]
+        fn control(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Control(ControlEvent::Close) => Transition(State::closed()),
+                Event::Control(ControlEvent::Open) => Handled,
+                Event::Data(_) => {
+                    self.dropped += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn events_are_routed_to_the_handlers_that_accept_them() {
+        let mut state_machine = Connection::default().uninitialized_state_machine().init();
+
+        state_machine.handle_from(DataEvent::Payload(1));
+        assert_eq!(state_machine.state(), &State::closed());
+        assert_eq!(state_machine.inner().dropped, 1);
+
+        state_machine.handle_from(ControlEvent::Open);
+        assert_eq!(state_machine.state(), &State::open());
+
+        state_machine.handle(&Event::Data(DataEvent::Payload(2)));
+        assert_eq!(state_machine.inner().received, [2]);
+
+        state_machine.handle_from(ControlEvent::Close);
+        assert_eq!(state_machine.state(), &State::closed());
+    }
+}
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+    use statig::{EventRoute, Handling};
+
+    #[derive(Debug)]
+    pub enum Button {
+        Pressed,
+        Held,
+    }
+
+    #[derive(Debug)]
+    pub struct Tick;
+
+    #[derive(Default)]
+    struct Pump;
+
+    #[state_machine(
+        initial = "State::idle()",
+        events("Button(Button)", "Tick(Tick)", "Fault(u8)"),
+        event_routing,
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Pump {
+        #[state(
+            superstate = "powered",
+            transitions("Event::Button(Button::Pressed) => State::running()")
+        )]
+        fn idle() -> Response<State> {
+            Super
+        }
+
+        #[state(
+            superstate = "powered",
+            transitions(
+                "Event::Button(_) => State::idle()",
+                "Event::Tick(_) => State::running()"
+            )
+        )]
+        fn running() -> Response<State> {
+            Super
+        }
+
+        #[superstate(transitions(
+            "Event::Button(_) => State::idle()",
+            "Event::Fault(code) if *code > 3 => State::failed()"
+        ))]
+        fn powered() -> Response<State> {
+            Super
+        }
+
+        #[state(transitions("Event::Button(Button::Held) | Event::Fault(_) => State::idle()"))]
+        fn failed() -> Response<State> {
+            Handled
+        }
+    }
+
+    fn route(state: &'static str, event: &'static str, handling: Handling) -> EventRoute {
+        EventRoute {
+            state,
+            event,
+            handling,
+        }
+    }
+
+    #[test]
+    fn every_event_is_routed_in_every_state() {
+        use Handling::{State as Itself, Superstate, Unhandled};
+
+        assert_eq!(
+            <Pump as IntoStateMachine>::EVENT_ROUTING,
+            &[
+                route("idle", "Button", Superstate("powered")),
+                route("idle", "Tick", Unhandled),
+                route("idle", "Fault", Unhandled),
+                route("running", "Button", Itself),
+                route("running", "Tick", Itself),
+                route("running", "Fault", Unhandled),
+                route("failed", "Button", Unhandled),
+                route("failed", "Tick", Unhandled),
+                route("failed", "Fault", Itself),
+            ]
+        );
+    }
+
+    #[test]
+    fn routing_agrees_with_the_state_machine() {
+        let mut state_machine = Pump.uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Button(Button::Held));
+        assert_eq!(state_machine.state(), &State::idle());
+
+        state_machine.handle(&Event::Button(Button::Pressed));
+        assert_eq!(state_machine.state(), &State::running());
+
+        state_machine.handle(&Event::Fault(5));
+        assert_eq!(state_machine.state(), &State::failed());
+
+        state_machine.handle(&Event::Fault(0));
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    pub struct Connect {
+        pub address: [u8; 4],
+    }
+
+    #[derive(Debug)]
+    pub struct Payload(pub Vec<u8>);
+
+    #[derive(Debug)]
+    pub struct Disconnect;
+
+    #[derive(Default)]
+    struct Link {
+        address: Option<[u8; 4]>,
+        received: Vec<u8>,
+        ignored: usize,
+    }
+
+    #[state_machine(
+        initial = "State::disconnected()",
+        event_types("Connect", "Payload", "Disconnect", derive(Debug)),
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Link {
+        #[state(superstate = "link")]
+        fn disconnected(&mut self, event: &Connect) -> Response<State> {
+            self.address = Some(event.address);
+            Transition(State::connected())
+        }
+
+        #[state(superstate = "link")]
+        fn connected(&mut self, event: &Payload) -> Response<State> {
+            self.received.extend(&event.0);
+            Handled
+        }
+
+        #[superstate]
+        fn link(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Disconnect(_) => {
+                    self.address = None;
+                    Transition(State::disconnected())
+                }
+                Event::Connect(_) | Event::Payload(_) => {
+                    self.ignored += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    fn send<E: statig::Event<Link>>(
+        state_machine: &mut statig::blocking::InitializedStateMachine<Link>,
+        event: &E,
+    ) {
+        state_machine.handle(&event.as_event());
+    }
+
+    #[test]
+    fn events_are_routed_to_the_handlers_that_take_their_type() {
+        let mut state_machine = Link::default().uninitialized_state_machine().init();
+
+        state_machine.handle_from(&Payload(vec![1]));
+        state_machine.handle_from(&Connect {
+            address: [10, 0, 0, 1],
+        });
+        assert_eq!(state_machine.state(), &State::connected());
+        assert_eq!(state_machine.address, Some([10, 0, 0, 1]));
+
+        state_machine.handle_from(&Payload(vec![2, 3]));
+        state_machine.handle_from(&Connect {
+            address: [10, 0, 0, 2],
+        });
+        assert_eq!(state_machine.received, [2, 3]);
+        assert_eq!(state_machine.ignored, 2);
+
+        state_machine.handle_from(&Disconnect);
+        assert_eq!(state_machine.state(), &State::disconnected());
+        assert_eq!(state_machine.address, None);
+    }
+
+    #[test]
+    fn event_trait_turns_an_event_into_the_event_of_the_state_machine() {
+        let mut state_machine = Link::default().uninitialized_state_machine().init();
+
+        send(&mut state_machine, &Connect { address: [0; 4] });
+        send(&mut state_machine, &Payload(vec![4]));
+        assert_eq!(state_machine.received, [4]);
+
+        let payload = Payload(vec![5]);
+        assert!(matches!(
+            statig::Event::<Link>::as_event(&payload),
+            Event::Payload(borrowed) if core::ptr::eq(borrowed, &payload)
+        ));
+    }
+}
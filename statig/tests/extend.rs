@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    enum Event {
+        Toggle,
+        Boost,
+    }
+
+    #[derive(Default)]
+    struct Heater {
+        log: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        extendable = "heater",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Heater {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+                Event::Boost => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_on")]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+                Event::Boost => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_on(&mut self) {
+            self.log.push("on");
+        }
+    }
+
+    mod premium {
+        use super::Event;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        struct PremiumHeater {
+            log: Vec<&'static str>,
+        }
+
+        // Inherits the states of `Heater`, overrides the handler of `on` and
+        // adds a `boosted` state.
+        #[state_machine(extends = "super::heater", state(derive(Debug, PartialEq, Eq)))]
+        impl PremiumHeater {
+            #[state(overrides = "on", entry_action = "enter_on")]
+            fn on_with_boost(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::off()),
+                    Event::Boost => Transition(State::boosted()),
+                }
+            }
+
+            #[state(entry_action = "enter_boosted")]
+            fn boosted(event: &Event) -> Response<State> {
+                match event {
+                    Event::Toggle => Transition(State::on()),
+                    Event::Boost => Handled,
+                }
+            }
+
+            #[action]
+            fn enter_boosted(&mut self) {
+                self.log.push("boosted");
+            }
+        }
+
+        #[test]
+        fn derived_state_machine_inherits_and_overrides_states() {
+            let mut state_machine = PremiumHeater::default().state_machine();
+            state_machine.handle(&Event::Toggle);
+            state_machine.handle(&Event::Boost);
+            assert_eq!(state_machine.state(), &State::boosted());
+
+            state_machine.handle(&Event::Toggle);
+            state_machine.handle(&Event::Toggle);
+            assert_eq!(state_machine.state(), &State::off());
+            assert_eq!(state_machine.inner().log, ["on", "boosted", "on"]);
+        }
+    }
+
+    #[test]
+    fn base_state_machine_is_unchanged() {
+        let mut state_machine = Heater::default().state_machine();
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Boost);
+        assert_eq!(state_machine.state(), &State::on());
+        assert_eq!(state_machine.inner().log, ["on"]);
+    }
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod abort {
+    use statig::prelude::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Event {
+        Connect,
+        Disconnect,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct SocketError;
+
+    #[derive(Default)]
+    struct Client {
+        reachable: bool,
+        log: Vec<&'static str>,
+        errors: Vec<SocketError>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        on_action_error = "Self::on_action_error",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Client {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Transition(State::connected()),
+                Event::Disconnect => Handled,
+            }
+        }
+
+        #[state(superstate = "online", entry_action = "open_socket")]
+        fn connected(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Handled,
+                Event::Disconnect => Transition(State::idle()),
+            }
+        }
+
+        #[superstate(entry_action = "enable_radio")]
+        fn online(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect | Event::Disconnect => Handled,
+            }
+        }
+
+        #[action]
+        fn enable_radio(&mut self) {
+            self.log.push("enable radio");
+        }
+
+        #[action]
+        fn open_socket(&mut self) -> Result<(), SocketError> {
+            match self.reachable {
+                true => {
+                    self.log.push("open socket");
+                    Ok(())
+                }
+                false => Err(SocketError),
+            }
+        }
+
+        fn on_action_error(&mut self, error: SocketError) {
+            self.errors.push(error);
+        }
+    }
+
+    #[test]
+    fn failed_action_aborts_the_transition() {
+        let mut state_machine = Client::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Connect);
+        assert!(state_machine.is_poisoned());
+        assert_eq!(state_machine.inner().log, ["enable radio"]);
+        assert_eq!(state_machine.inner().errors, [SocketError]);
+
+        state_machine.recover(State::idle());
+        assert!(!state_machine.is_poisoned());
+    }
+
+    #[test]
+    fn successful_action_completes_the_transition() {
+        let client = Client {
+            reachable: true,
+            ..Client::default()
+        };
+        let mut state_machine = client.uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Connect);
+        assert!(!state_machine.is_poisoned());
+        assert_eq!(state_machine.state(), &State::connected());
+        assert_eq!(state_machine.inner().log, ["enable radio", "open socket"]);
+
+        state_machine.handle(&Event::Disconnect);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
+
+#[cfg(test)]
+mod proceed {
+    use statig::prelude::*;
+
+    enum Event {
+        Stop,
+    }
+
+    #[derive(Default)]
+    struct Motor {
+        log: Vec<&'static str>,
+        errors: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::running()",
+        on_action_error = "Self::on_action_error",
+        action_failure = "proceed",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Motor {
+        #[state(superstate = "powered", exit_action = "brake")]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Stop => Transition(State::stopped()),
+            }
+        }
+
+        #[superstate(exit_action = "power_off")]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::Stop => Handled,
+            }
+        }
+
+        #[state]
+        fn stopped(event: &Event) -> Response<State> {
+            match event {
+                Event::Stop => Handled,
+            }
+        }
+
+        #[action]
+        fn brake(&mut self) -> Result<(), &'static str> {
+            Err("brake is worn")
+        }
+
+        #[action]
+        fn power_off(&mut self) {
+            self.log.push("power off");
+        }
+
+        fn on_action_error(&mut self, error: &'static str) {
+            self.errors.push(error);
+        }
+    }
+
+    #[test]
+    fn failed_action_is_reported_and_the_transition_proceeds() {
+        let mut state_machine = Motor::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Stop);
+        assert!(!state_machine.is_poisoned());
+        assert_eq!(state_machine.state(), &State::stopped());
+        assert_eq!(state_machine.inner().log, ["power off"]);
+        assert_eq!(state_machine.inner().errors, ["brake is worn"]);
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+mod awaitable {
+    use statig::prelude::*;
+
+    enum Event {
+        Connect,
+    }
+
+    #[derive(Default)]
+    struct Client {
+        errors: Vec<&'static str>,
+    }
+
+    #[state_machine(
+        initial = "State::idle()",
+        on_action_error = "Self::on_action_error",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Client {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Transition(State::connected()),
+            }
+        }
+
+        #[state(entry_action = "open_socket")]
+        fn connected(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Handled,
+            }
+        }
+
+        #[action]
+        async fn open_socket(&mut self) -> Result<(), &'static str> {
+            Err("host unreachable")
+        }
+
+        fn on_action_error(&mut self, error: &'static str) {
+            self.errors.push(error);
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_async_action_aborts_the_transition() {
+        let mut state_machine = Client::default().uninitialized_state_machine().init().await;
+
+        state_machine.handle(&Event::Connect).await;
+        assert!(state_machine.is_poisoned());
+        assert_eq!(state_machine.inner().errors, ["host unreachable"]);
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Sample,
+        Stop,
+    }
+
+    struct BusError;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum SensorError {
+        Bus,
+        OutOfRange(u16),
+    }
+
+    impl From<BusError> for SensorError {
+        fn from(_: BusError) -> Self {
+            SensorError::Bus
+        }
+    }
+
+    #[derive(Default)]
+    struct Sensor {
+        readings: Vec<Result<u16, BusError>>,
+        samples: Vec<u16>,
+        errors: Vec<SensorError>,
+    }
+
+    impl Sensor {
+        fn read(&mut self) -> Result<u16, BusError> {
+            self.readings.remove(0)
+        }
+    }
+
+    #[state_machine(
+        initial = "State::sampling()",
+        on_action_error = "Self::on_action_error",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Sensor {
+        #[state]
+        fn sampling(&mut self, event: &Event) -> Result<Response<State>, SensorError> {
+            match event {
+                Event::Sample => {
+                    let sample = self.read()?;
+                    if sample > 1000 {
+                        return Err(SensorError::OutOfRange(sample));
+                    }
+                    self.samples.push(sample);
+                    Ok(Handled)
+                }
+                Event::Stop => Ok(Transition(State::stopped())),
+            }
+        }
+
+        #[state]
+        fn stopped(&mut self) -> Result<(), SensorError> {
+            self.read()?;
+            Ok(())
+        }
+
+        fn on_action_error(&mut self, error: SensorError) {
+            self.errors.push(error);
+        }
+    }
+
+    #[test]
+    fn errors_of_handlers_are_passed_to_on_action_error() {
+        let sensor = Sensor {
+            readings: vec![Ok(42), Err(BusError), Ok(2000), Err(BusError)],
+            ..Sensor::default()
+        };
+        let mut state_machine = sensor.state_machine();
+        state_machine.handle(&Event::Sample);
+        state_machine.handle(&Event::Sample);
+        state_machine.handle(&Event::Sample);
+        assert_eq!(state_machine.state(), &State::sampling());
+
+        state_machine.handle(&Event::Stop);
+        state_machine.handle(&Event::Sample);
+        assert_eq!(state_machine.state(), &State::stopped());
+        assert!(!state_machine.is_poisoned());
+        assert_eq!(state_machine.inner().samples, [42]);
+        assert_eq!(
+            state_machine.inner().errors,
+            [
+                SensorError::Bus,
+                SensorError::OutOfRange(2000),
+                SensorError::Bus
+            ]
+        );
+    }
+}
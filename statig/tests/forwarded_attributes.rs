@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Machine;
+
+    #[state_machine(initial = "State::current()", state(derive(Debug, PartialEq, Eq)))]
+    impl Machine {
+        /// The state the machine starts in.
+        #[state(superstate = "documented")]
+        fn current(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+
+        /// A state that is kept for compatibility.
+        #[deprecated(note = "use `current` instead")]
+        #[state(superstate = "documented")]
+        fn legacy(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::current()),
+            }
+        }
+
+        /// A superstate with documentation.
+        #[superstate]
+        fn documented(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_state_can_still_be_used() {
+        let mut state_machine = Machine.state_machine();
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::current());
+
+        let legacy = State::legacy();
+        assert!(matches!(legacy, State::Legacy {}));
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    /// Actions that are shared by several state machines.
+    mod actions {
+        pub trait Motor {
+            fn set_running(&mut self, running: bool);
+        }
+
+        pub fn start_motor<M: Motor>(motor: &mut M, starts: &mut usize) {
+            *starts += 1;
+            motor.set_running(true);
+        }
+
+        pub fn stop_motor<M: Motor>(motor: &mut M, _: &mut usize) {
+            motor.set_running(false);
+        }
+    }
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Conveyor {
+        running: bool,
+    }
+
+    impl actions::Motor for Conveyor {
+        fn set_running(&mut self, running: bool) {
+            self.running = running;
+        }
+    }
+
+    #[state_machine(initial = "State::stopped()", state(derive(Debug, PartialEq, Eq)))]
+    impl Conveyor {
+        #[state(
+            entry_action = "actions::start_motor",
+            exit_action = "self::actions::stop_motor"
+        )]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::stopped()),
+            }
+        }
+
+        /// The motor may only be started twice.
+        #[state]
+        fn stopped(context: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle if *context < 2 => Transition(State::running()),
+                Event::Toggle => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn free_functions_are_called() {
+        let mut starts = 0;
+        let mut state_machine = Conveyor::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut starts);
+
+        state_machine.handle_with_context(&Event::Toggle, &mut starts);
+        assert!(state_machine.running);
+
+        state_machine.handle_with_context(&Event::Toggle, &mut starts);
+        assert!(!state_machine.running);
+
+        state_machine.handle_with_context(&Event::Toggle, &mut starts);
+        state_machine.handle_with_context(&Event::Toggle, &mut starts);
+        state_machine.handle_with_context(&Event::Toggle, &mut starts);
+        assert_eq!(starts, 2);
+        assert_eq!(state_machine.state(), &State::stopped());
+    }
+}
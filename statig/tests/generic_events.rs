@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use statig::prelude::*;
+
+    /// Frame of a wire format.
+    trait Frame {
+        fn is_ack(&self) -> bool;
+    }
+
+    struct Binary(u8);
+
+    impl Frame for Binary {
+        fn is_ack(&self) -> bool {
+            self.0 == 0x06
+        }
+    }
+
+    struct Text(&'static str);
+
+    impl Frame for Text {
+        fn is_ack(&self) -> bool {
+            self.0 == "ACK"
+        }
+    }
+
+    struct Link<E> {
+        acks: usize,
+        marker: PhantomData<E>,
+    }
+
+    impl<E> Default for Link<E> {
+        fn default() -> Self {
+            Self {
+                acks: 0,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    #[state_machine(initial = "State::waiting()", state(derive(Debug, PartialEq, Eq)))]
+    impl<E> Link<E> {
+        #[state(superstate = "connected")]
+        fn waiting(&mut self, event: &E) -> Response<State>
+        where
+            E: Frame,
+        {
+            match event.is_ack() {
+                true => {
+                    self.acks += 1;
+                    Transition(State::acknowledged())
+                }
+                false => Handled,
+            }
+        }
+
+        #[state(superstate = "connected")]
+        fn acknowledged(event: &E) -> Response<State>
+        where
+            E: Frame,
+        {
+            match event.is_ack() {
+                true => Handled,
+                false => Super,
+            }
+        }
+
+        #[superstate]
+        fn connected() -> Response<State> {
+            Transition(State::waiting())
+        }
+    }
+
+    #[test]
+    fn one_definition_serves_several_event_types() {
+        let mut binary = Link::<Binary>::default().state_machine();
+        binary.handle(&Binary(0x15));
+        binary.handle(&Binary(0x06));
+        assert_eq!(binary.state(), &State::acknowledged());
+
+        let mut text = Link::<Text>::default().state_machine();
+        text.handle(&Text("ACK"));
+        text.handle(&Text("NAK"));
+        text.handle(&Text("ACK"));
+        assert_eq!(text.state(), &State::acknowledged());
+        assert_eq!(text.inner().acks, 2);
+    }
+}
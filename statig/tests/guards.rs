@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Vent,
+        Check,
+    }
+
+    #[derive(Default)]
+    struct Boiler {
+        pressure: u32,
+        locked: bool,
+        manual: bool,
+    }
+
+    impl Boiler {
+        fn is_locked(&self) -> bool {
+            self.locked
+        }
+
+        fn is_manual(&self) -> bool {
+            self.manual
+        }
+
+        fn is_overpressure(&self) -> bool {
+            self.pressure > 10
+        }
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Boiler {
+        #[state(
+            transitions("Event::Vent if all(any(is_manual, is_overpressure), not(is_locked)) => State::venting()"),
+            when = "all(is_overpressure, not(is_locked), self.pressure < 100)",
+            transition = "alarm"
+        )]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Vent | Event::Check => Handled,
+            }
+        }
+
+        #[state]
+        fn venting(event: &Event) -> Response<State> {
+            match event {
+                Event::Vent | Event::Check => Handled,
+            }
+        }
+
+        #[state]
+        fn alarm(event: &Event) -> Response<State> {
+            match event {
+                Event::Vent | Event::Check => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn combined_guards_enable_declared_transitions() {
+        let boiler = Boiler {
+            manual: true,
+            locked: true,
+            ..Boiler::default()
+        };
+        let mut state_machine = boiler.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Vent);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        let boiler = Boiler {
+            manual: true,
+            ..Boiler::default()
+        };
+        let mut state_machine = boiler.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Vent);
+        assert_eq!(state_machine.state(), &State::venting());
+    }
+
+    #[test]
+    fn combined_guards_enable_condition_transitions() {
+        let boiler = Boiler {
+            pressure: 200,
+            ..Boiler::default()
+        };
+        let mut state_machine = boiler.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Check);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        let boiler = Boiler {
+            pressure: 20,
+            ..Boiler::default()
+        };
+        let mut state_machine = boiler.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Check);
+        assert_eq!(state_machine.state(), &State::alarm());
+    }
+}
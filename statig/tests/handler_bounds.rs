@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    trait OutputPin {
+        fn set_high(&mut self);
+        fn set_low(&mut self);
+    }
+
+    #[derive(Default)]
+    struct Led {
+        toggles: usize,
+        high: bool,
+    }
+
+    impl OutputPin for Led {
+        fn set_high(&mut self) {
+            self.toggles += 1;
+            self.high = true;
+        }
+
+        fn set_low(&mut self) {
+            self.toggles += 1;
+            self.high = false;
+        }
+    }
+
+    enum Event {
+        TimerElapsed,
+    }
+
+    #[derive(Default)]
+    struct Blinky<P> {
+        pin: P,
+    }
+
+    #[state_machine(initial = "State::off()", state(derive(Debug, PartialEq, Eq)))]
+    impl<P> Blinky<P> {
+        #[state(entry_action = "enter_on")]
+        fn on<'evt>(event: &'evt Event) -> Response<State>
+        where
+            Event: 'evt,
+        {
+            match event {
+                Event::TimerElapsed => Transition(State::off()),
+            }
+        }
+
+        #[action]
+        fn enter_on(&mut self)
+        where
+            P: OutputPin,
+        {
+            self.pin.set_high();
+        }
+
+        #[state(entry_action = "enter_off")]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::on()),
+            }
+        }
+
+        #[action]
+        fn enter_off(&mut self)
+        where
+            P: OutputPin,
+        {
+            self.pin.set_low();
+        }
+    }
+
+    #[test]
+    fn handler_bounds_are_propagated() {
+        let mut state_machine = Blinky::<Led>::default().state_machine();
+
+        state_machine.handle(&Event::TimerElapsed);
+
+        assert_eq!(state_machine.state(), &State::on());
+        assert!(state_machine.pin.high);
+        assert_eq!(state_machine.pin.toggles, 2);
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Switch {
+        log: Vec<String>,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        on_init = "Self::publish_initial_state",
+        on_transition = "Self::log_transition",
+        on_dispatch = "Self::trace_dispatch",
+        state(derive(Debug)),
+        superstate(derive(Debug))
+    )]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+    }
+
+    impl Switch {
+        fn publish_initial_state(&mut self, state: &State) {
+            self.log.push(format!("start in {state:?}"));
+        }
+
+        fn log_transition(&mut self, source: &State, target: &State, event: &Event) {
+            self.log
+                .push(format!("{source:?} -> {target:?} on {event:?}"));
+        }
+
+        fn trace_dispatch(&mut self, state: StateOrSuperstate<Self>, event: &Event) {
+            self.log.push(format!("{state:?} <- {event:?}"));
+        }
+    }
+
+    #[test]
+    fn hooks_set_with_attributes_are_called() {
+        let mut state_machine = Switch::default().state_machine();
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Toggle);
+
+        assert_eq!(
+            state_machine.inner().log,
+            [
+                "start in Off",
+                "State(Off) <- Toggle",
+                "Off -> On on Toggle",
+                "State(On) <- Toggle",
+                "On -> Off on Toggle"
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Buy,
+        QuickBuy,
+    }
+
+    struct Shop {
+        balance: u32,
+        log: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::browsing()", state(derive(Debug, PartialEq, Eq)))]
+    impl Shop {
+        #[state(exit_action = "leave_browsing")]
+        fn browsing(event: &Event) -> Response<State> {
+            match event {
+                Event::Buy => Transition(State::cart()),
+                Event::QuickBuy => Transition(State::pay()),
+            }
+        }
+
+        #[state]
+        fn cart(event: &Event) -> Response<State> {
+            match event {
+                Event::Buy => Transition(State::pay()),
+                Event::QuickBuy => Handled,
+            }
+        }
+
+        /// Both ways of buying are charged the same way, and only then is it
+        /// known whether the purchase went through.
+        #[state(junction, superstate = "checkout")]
+        fn pay(&mut self) -> State {
+            self.log.push("charge");
+            match self.balance {
+                0..=9 => State::declined(),
+                _ => {
+                    self.balance -= 10;
+                    State::receipt()
+                }
+            }
+        }
+
+        #[superstate(entry_action = "enter_checkout")]
+        fn checkout() -> Response<State> {
+            Super
+        }
+
+        #[state(superstate = "checkout")]
+        fn receipt() {}
+
+        #[state]
+        fn declined() {}
+
+        #[action]
+        fn leave_browsing(&mut self) {
+            self.log.push("leave browsing");
+        }
+
+        #[action]
+        fn enter_checkout(&mut self) {
+            self.log.push("enter checkout");
+        }
+    }
+
+    fn shop(balance: u32) -> statig::blocking::InitializedStateMachine<Shop> {
+        Shop {
+            balance,
+            log: vec![],
+        }
+        .uninitialized_state_machine()
+        .init()
+    }
+
+    #[test]
+    fn junction_is_evaluated_before_the_exit_actions() {
+        let mut state_machine = shop(10);
+
+        state_machine.handle(&Event::QuickBuy);
+        assert_eq!(state_machine.state(), &State::receipt());
+        assert_eq!(state_machine.balance, 0);
+        assert_eq!(
+            state_machine.log,
+            ["charge", "leave browsing", "enter checkout"]
+        );
+    }
+
+    #[test]
+    fn transitions_share_the_segment_of_the_junction() {
+        let mut state_machine = shop(20);
+
+        state_machine.handle(&Event::Buy);
+        state_machine.handle(&Event::Buy);
+        assert_eq!(state_machine.state(), &State::receipt());
+        assert_eq!(state_machine.balance, 10);
+        assert_eq!(
+            state_machine.log,
+            ["leave browsing", "charge", "enter checkout"]
+        );
+    }
+
+    #[test]
+    fn junction_only_enters_the_states_of_the_selected_target() {
+        let mut state_machine = shop(5);
+
+        // The junction is within `checkout`, but the declined purchase never
+        // enters it.
+        state_machine.handle(&Event::QuickBuy);
+        assert_eq!(state_machine.state(), &State::declined());
+        assert_eq!(state_machine.balance, 5);
+        assert_eq!(state_machine.log, ["charge", "leave browsing"]);
+    }
+}
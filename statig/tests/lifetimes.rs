@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Byte(u8),
+        End,
+    }
+
+    struct Tokenizer<'a> {
+        input: &'a str,
+        position: usize,
+        tokens: Vec<&'a str>,
+    }
+
+    impl<'a> Tokenizer<'a> {
+        fn new(input: &'a str) -> Self {
+            Self {
+                input,
+                position: 0,
+                tokens: Vec::new(),
+            }
+        }
+    }
+
+    #[state_machine(initial = "State::whitespace()", state(derive(Debug, PartialEq, Eq)))]
+    impl<'a> Tokenizer<'a> {
+        #[state(superstate = "tokenizing")]
+        fn whitespace(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Byte(b' ') => {
+                    self.position += 1;
+                    Handled
+                }
+                Event::Byte(_) => {
+                    self.position += 1;
+                    Transition(State::word(self.position - 1))
+                }
+                Event::End => Super,
+            }
+        }
+
+        #[state(superstate = "tokenizing")]
+        fn word(&mut self, start: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Byte(b' ') => {
+                    self.tokens.push(&self.input[*start..self.position]);
+                    self.position += 1;
+                    Transition(State::whitespace())
+                }
+                Event::Byte(_) => {
+                    self.position += 1;
+                    Handled
+                }
+                Event::End => {
+                    self.tokens.push(&self.input[*start..self.position]);
+                    Super
+                }
+            }
+        }
+
+        #[superstate]
+        fn tokenizing(event: &Event) -> Response<State> {
+            match event {
+                Event::End => Transition(State::done()),
+                Event::Byte(_) => Handled,
+            }
+        }
+
+        #[state]
+        fn done(event: &Event) -> Response<State> {
+            match event {
+                Event::Byte(_) | Event::End => Handled,
+            }
+        }
+    }
+
+    fn tokenize(input: &str) -> Vec<&str> {
+        let mut state_machine = Tokenizer::new(input).state_machine();
+        for byte in input.bytes() {
+            state_machine.handle(&Event::Byte(byte));
+        }
+        state_machine.handle(&Event::End);
+        assert_eq!(state_machine.state(), &State::done());
+        state_machine.tokens.clone()
+    }
+
+    #[test]
+    fn tokens_borrow_from_input() {
+        let input = String::from("zero copy  parser");
+
+        let tokens = tokenize(&input);
+
+        assert_eq!(tokens, ["zero", "copy", "parser"]);
+        assert!(tokens
+            .iter()
+            .all(|token| input.as_bytes().as_ptr_range().contains(&token.as_ptr())));
+    }
+}
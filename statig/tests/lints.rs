@@ -0,0 +1,78 @@
+//! The generated code should not trigger lints in crates that deny them.
+#![deny(missing_docs, clippy::pedantic)]
+
+#[cfg(test)]
+/// The state machine is public, so its generated items are linted.
+pub mod tests {
+    use statig::prelude::*;
+
+    /// Event of the state machine.
+    pub enum Event {
+        /// Advance to the next state.
+        Next,
+    }
+
+    /// Counts the number of times it has been enabled.
+    #[derive(Default)]
+    pub struct Counter {
+        /// Number of times the `on` state has been entered.
+        pub count: u32,
+    }
+
+    #[state_machine(
+        initial = "State::off()",
+        state(derive(Debug, PartialEq, Eq)),
+        superstate(derive(Debug))
+    )]
+    impl Counter {
+        /// The counter is enabled.
+        #[state(superstate = "enabled", entry_action = "enter_on")]
+        #[expect(clippy::too_many_arguments)]
+        fn on(
+            red: &mut u32,
+            green: &mut u32,
+            blue: &mut u32,
+            cyan: &mut u32,
+            magenta: &mut u32,
+            yellow: &mut u32,
+            black: &mut u32,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Next => {
+                    *red += *green + *blue + *cyan + *magenta + *yellow + *black;
+                    Transition(State::off())
+                }
+            }
+        }
+
+        #[state(superstate = "enabled")]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Super,
+            }
+        }
+
+        #[superstate]
+        fn enabled(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::on(0, 0, 0, 0, 0, 0, 0)),
+            }
+        }
+
+        #[action]
+        fn enter_on(&mut self) {
+            self.count += 1;
+        }
+    }
+
+    #[test]
+    fn generated_code_is_lint_clean() {
+        let mut state_machine = Counter::default().state_machine();
+
+        state_machine.handle(&Event::Next);
+
+        assert_eq!(state_machine.state(), &State::on(0, 0, 0, 0, 0, 0, 0));
+        assert_eq!(state_machine.count, 1);
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{InitializedStateMachine, Middleware, MiddlewareStateMachine, Next};
+    use statig::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Event {
+        Press,
+        Lock,
+        Unlock,
+        Admin(Box<Event>),
+    }
+
+    #[derive(Default)]
+    struct Door {
+        presses: usize,
+    }
+
+    #[state_machine(
+        initial = "State::unlocked()",
+        state(derive(Debug, Clone, Copy, PartialEq))
+    )]
+    impl Door {
+        #[state]
+        fn unlocked(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Press => {
+                    self.presses += 1;
+                    Handled
+                }
+                Event::Lock => Transition(State::locked()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn locked(event: &Event) -> Response<State> {
+            match event {
+                Event::Unlock => Transition(State::unlocked()),
+                _ => Handled,
+            }
+        }
+    }
+
+    /// Records the events it sees, tagged with its name.
+    struct Log<'a> {
+        name: &'static str,
+        entries: &'a std::cell::RefCell<Vec<String>>,
+    }
+
+    impl Middleware<Door> for Log<'_> {
+        fn around_dispatch(
+            &mut self,
+            state_machine: &mut InitializedStateMachine<Door>,
+            event: &Event,
+            next: Next<'_, Door>,
+        ) {
+            self.entries
+                .borrow_mut()
+                .push(format!("{} before {:?}", self.name, event));
+            next.run(state_machine, event);
+            self.entries.borrow_mut().push(format!(
+                "{} after {:?}",
+                self.name,
+                state_machine.state()
+            ));
+        }
+    }
+
+    /// Only lets `Unlock` through when it is wrapped in `Admin`.
+    struct Authz;
+
+    impl Middleware<Door> for Authz {
+        fn around_dispatch(
+            &mut self,
+            state_machine: &mut InitializedStateMachine<Door>,
+            event: &Event,
+            next: Next<'_, Door>,
+        ) {
+            match event {
+                Event::Unlock => {}
+                Event::Admin(event) => next.run(state_machine, event),
+                event => next.run(state_machine, event),
+            }
+        }
+    }
+
+    /// Counts the transitions that were taken.
+    #[derive(Default)]
+    struct Transitions(usize);
+
+    impl Middleware<Door> for Transitions {
+        fn around_dispatch(
+            &mut self,
+            state_machine: &mut InitializedStateMachine<Door>,
+            event: &Event,
+            next: Next<'_, Door>,
+        ) {
+            let source = *state_machine.state();
+            next.run(state_machine, event);
+            if *state_machine.state() != source {
+                self.0 += 1;
+            }
+        }
+    }
+
+    /// Dispatches every event twice.
+    struct Twice;
+
+    impl Middleware<Door> for Twice {
+        fn around_dispatch(
+            &mut self,
+            state_machine: &mut InitializedStateMachine<Door>,
+            event: &Event,
+            mut next: Next<'_, Door>,
+        ) {
+            next.by_ref().run(state_machine, event);
+            next.run(state_machine, event);
+        }
+    }
+
+    #[test]
+    fn middlewares_run_from_the_outermost_layer_in() {
+        let entries = std::cell::RefCell::new(Vec::new());
+        let state_machine = Door::default().uninitialized_state_machine().init();
+        let mut state_machine = MiddlewareStateMachine::new(
+            state_machine,
+            Log {
+                name: "inner",
+                entries: &entries,
+            },
+        )
+        .layer(Log {
+            name: "outer",
+            entries: &entries,
+        });
+
+        state_machine.handle(&Event::Lock);
+
+        assert_eq!(
+            entries.into_inner(),
+            [
+                "outer before Lock",
+                "inner before Lock",
+                "inner after Locked",
+                "outer after Locked",
+            ]
+        );
+    }
+
+    #[test]
+    fn middlewares_can_drop_and_rewrite_events() {
+        let state_machine = Door::default().uninitialized_state_machine().init();
+        let mut state_machine =
+            MiddlewareStateMachine::new(state_machine, (Authz, Transitions::default()));
+
+        state_machine.handle(&Event::Lock);
+        state_machine.handle(&Event::Unlock);
+        assert_eq!(state_machine.state(), &State::locked());
+
+        state_machine.handle(&Event::Admin(Box::new(Event::Unlock)));
+        assert_eq!(state_machine.state(), &State::unlocked());
+        assert_eq!(state_machine.middleware().1 .0, 2);
+    }
+
+    #[test]
+    fn next_can_be_run_more_than_once() {
+        let state_machine = Door::default().uninitialized_state_machine().init();
+        let mut state_machine = MiddlewareStateMachine::new(state_machine, ()).layer(Twice);
+
+        state_machine.handle(&Event::Press);
+
+        let (state_machine, _) = state_machine.into_parts();
+        assert_eq!(state_machine.presses, 2);
+    }
+}
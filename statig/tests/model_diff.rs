@@ -0,0 +1,169 @@
+#![cfg(feature = "json")]
+
+mod v1 {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    pub struct Door;
+
+    pub enum Event {
+        Open,
+        Close,
+        Jam,
+    }
+
+    #[state_machine(initial = "State::closed()")]
+    impl Door {
+        #[state]
+        fn closed(event: &Event) -> Response<State> {
+            match event {
+                Event::Open => Transition(State::opened()),
+                Event::Jam => Transition(State::jammed()),
+                Event::Close => Handled,
+            }
+        }
+
+        #[state]
+        fn opened(event: &Event) -> Response<State> {
+            match event {
+                Event::Close => Transition(State::closed()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn jammed() -> Response<State> {
+            Handled
+        }
+    }
+}
+
+mod v2 {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    pub struct Door;
+
+    pub enum Event {
+        Open,
+        Close,
+        Lock,
+    }
+
+    #[state_machine(initial = "State::closed()")]
+    impl Door {
+        #[state(superstate = "shut")]
+        fn closed(event: &Event) -> Response<State> {
+            match event {
+                Event::Open => Transition(State::opened()),
+                Event::Lock => Transition(State::locked()),
+                Event::Close => Super,
+            }
+        }
+
+        #[state(superstate = "shut")]
+        fn locked() -> Response<State> {
+            Super
+        }
+
+        #[superstate]
+        fn shut() -> Response<State> {
+            Handled
+        }
+
+        #[state]
+        fn opened(event: &Event) -> Response<State> {
+            match event {
+                Event::Close => Transition(State::closed()),
+                _ => Handled,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use statig::{IndexMismatch, Model, MovedState};
+
+    #[test]
+    fn model_round_trips_through_json() {
+        let model = Model::of::<super::v2::Door>();
+        let json = model.to_json().to_string();
+
+        assert_eq!(
+            Model::from_json(&serde_json::from_str(&json).unwrap()),
+            Ok(model)
+        );
+        assert!(Model::from_json(&serde_json::json!({ "states": [{}] })).is_err());
+        assert!(Model::from_json(&serde_json::json!([])).is_err());
+    }
+
+    #[test]
+    fn diff_reports_changes_between_versions() {
+        let old = Model::of::<super::v1::Door>();
+        let new = Model::of::<super::v2::Door>();
+
+        let diff = old.compare(&new);
+
+        assert_eq!(diff.added, ["locked", "shut"]);
+        assert_eq!(diff.removed, ["jammed"]);
+        assert_eq!(
+            diff.moved,
+            [MovedState {
+                name: "closed".to_owned(),
+                old_superstate: None,
+                new_superstate: Some("shut".to_owned()),
+            }]
+        );
+        assert_eq!(diff.unrestorable_by_name, ["jammed"]);
+        assert_eq!(
+            diff.unrestorable_by_index,
+            [
+                IndexMismatch {
+                    index: 1,
+                    old: "opened".to_owned(),
+                    new: Some("locked".to_owned()),
+                },
+                IndexMismatch {
+                    index: 2,
+                    old: "jammed".to_owned(),
+                    new: Some("opened".to_owned()),
+                },
+            ]
+        );
+        assert!(!diff.restores_by_name());
+        assert!(!diff.restores_by_index());
+    }
+
+    #[test]
+    fn both_versions_run() {
+        use statig::prelude::*;
+
+        let mut v1 = super::v1::Door.uninitialized_state_machine().init();
+        for event in [
+            super::v1::Event::Open,
+            super::v1::Event::Close,
+            super::v1::Event::Jam,
+        ] {
+            v1.handle(&event);
+        }
+        let mut v2 = super::v2::Door.uninitialized_state_machine().init();
+        for event in [
+            super::v2::Event::Open,
+            super::v2::Event::Close,
+            super::v2::Event::Lock,
+        ] {
+            v2.handle(&event);
+        }
+    }
+
+    #[test]
+    fn identical_models_have_no_diff() {
+        let model = Model::of::<super::v1::Door>();
+
+        let diff = model.compare(&model);
+
+        assert!(diff.is_empty());
+        assert!(diff.restores_by_name() && diff.restores_by_index());
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Led;
+
+    #[state_machine(
+        initial = "State::off()",
+        module = "led",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Led {
+        #[state]
+        fn off(event: &Event) -> Response<led::State> {
+            match event {
+                Event::Toggle => Transition(led::State::on()),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<led::State> {
+            match event {
+                Event::Toggle => Transition(led::State::off()),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Door {
+        openings: usize,
+    }
+
+    // A second state machine in the same module, of which the state enum is
+    // imported so it can be referred to as `State`.
+    use door::State;
+
+    #[state_machine(
+        initial = "State::closed()",
+        module = "door",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Door {
+        #[state]
+        fn closed(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => {
+                    self.openings += 1;
+                    Transition(State::open())
+                }
+            }
+        }
+
+        #[state]
+        fn open(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::closed()),
+            }
+        }
+    }
+
+    #[test]
+    fn state_machines_in_modules_do_not_collide() {
+        let mut led = Led.state_machine();
+        let mut door = Door::default().state_machine();
+
+        led.handle(&Event::Toggle);
+        door.handle(&Event::Toggle);
+
+        assert_eq!(led.state(), &led::State::on());
+        assert_eq!(door.state(), &door::State::open());
+        assert_eq!(door.inner().openings, 1);
+    }
+}
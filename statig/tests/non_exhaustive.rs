@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Lamp;
+
+    #[state_machine(
+        initial = "State::off()",
+        state(non_exhaustive, derive(Debug, PartialEq, Eq)),
+        superstate(non_exhaustive)
+    )]
+    impl Lamp {
+        #[state(superstate = "powered")]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+            }
+        }
+
+        #[state(superstate = "powered")]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+            }
+        }
+
+        #[superstate]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Handled,
+            }
+        }
+    }
+
+    // Within the defining crate the enums can still be matched exhaustively.
+    fn is_lit(state: &State) -> bool {
+        match state {
+            State::Off {} => false,
+            State::On {} => true,
+        }
+    }
+
+    #[test]
+    fn non_exhaustive_enums_can_be_matched_within_the_crate() {
+        let mut state_machine = Lamp.state_machine();
+        state_machine.handle(&Event::Toggle);
+        assert!(is_lit(state_machine.state()));
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "tokio")]
+
+#[cfg(test)]
+mod tests {
+    use statig::awaitable::NotifiedDispatcher;
+    use statig::blocking::SplitChannel;
+    use statig::prelude::*;
+    use statig::{Notifier, SpinNotifier};
+    use tokio::sync::Notify;
+
+    pub struct Increment;
+
+    #[derive(Default)]
+    struct Counter {
+        count: usize,
+    }
+
+    #[state_machine(initial = "State::counting()")]
+    impl Counter {
+        #[state]
+        async fn counting(&mut self, event: &Increment) -> Response<State> {
+            match event {
+                Increment => {
+                    self.count += 1;
+                    Handled
+                }
+            }
+        }
+    }
+
+    fn count_to_100<W: Notifier + Sync>(notifier: &W) -> usize {
+        let mut channel: SplitChannel<Increment, 4> = SplitChannel::new();
+        let (mut producer, consumer) = channel.split();
+        let state_machine =
+            futures::executor::block_on(Counter::default().uninitialized_state_machine().init());
+        let mut dispatcher = NotifiedDispatcher::new(state_machine, consumer, notifier);
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for _ in 0..100 {
+                    while producer.push(Increment).is_err() {
+                        std::thread::yield_now();
+                    }
+                    notifier.notify();
+                }
+            });
+
+            futures::executor::block_on(
+                dispatcher.run_until(|state_machine| state_machine.count == 100),
+            );
+        });
+
+        dispatcher.into_state_machine().count
+    }
+
+    #[test]
+    fn tokio_notify_wakes_the_dispatcher() {
+        assert_eq!(count_to_100(&Notify::new()), 100);
+    }
+
+    #[test]
+    fn spin_notifier_wakes_the_dispatcher() {
+        assert_eq!(count_to_100(&SpinNotifier::new()), 100);
+    }
+
+    #[cfg(feature = "embassy")]
+    #[test]
+    fn embassy_signal_wakes_the_dispatcher() {
+        use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+        use embassy_sync::signal::Signal;
+
+        let signal: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+        assert_eq!(count_to_100(&signal), 100);
+    }
+}
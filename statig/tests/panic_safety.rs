@@ -0,0 +1,142 @@
+#![cfg(feature = "std")]
+
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Increment,
+        Divide(usize),
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Calculator {
+        value: usize,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Clone, Debug, PartialEq, Eq)))]
+    impl Calculator {
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Increment => {
+                    self.value += 1;
+                    Handled
+                }
+                Event::Divide(divisor) => {
+                    self.value /= divisor;
+                    Handled
+                }
+                Event::Next => Transition(State::busy()),
+            }
+        }
+
+        #[state(entry_action = "enter_busy")]
+        fn busy(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::idle()),
+                _ => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_busy(&mut self) {
+            if self.value > 1 {
+                panic!("too busy");
+            }
+        }
+    }
+
+    #[test]
+    fn panic_in_handler_is_reported() {
+        let mut state_machine = Calculator::default().uninitialized_state_machine().init();
+
+        assert!(state_machine.try_handle(&Event::Increment).is_ok());
+
+        let error = state_machine.try_handle(&Event::Divide(0)).unwrap_err();
+        assert!(error.message().unwrap().contains("divide by zero"));
+        assert_eq!(state_machine.state(), &State::idle());
+
+        // The state machine keeps working after the panic.
+        assert!(state_machine.try_handle(&Event::Increment).is_ok());
+        assert_eq!(state_machine.value, 2);
+    }
+
+    #[test]
+    fn panic_during_transition_restores_source_state() {
+        let mut state_machine = Calculator { value: 2 }.uninitialized_state_machine().init();
+
+        let error = state_machine.try_handle(&Event::Next).unwrap_err();
+        assert_eq!(error.message(), Some("too busy"));
+        assert_eq!(error.to_string(), "handler panicked: too busy");
+        assert_eq!(state_machine.state(), &State::idle());
+
+        state_machine.handle(&Event::Divide(2));
+        assert!(state_machine.try_handle(&Event::Next).is_ok());
+        assert_eq!(state_machine.state(), &State::busy());
+    }
+
+    #[test]
+    fn lazy_state_machine_stays_uninitialized_after_panic() {
+        let mut state_machine = Calculator::default().state_machine();
+
+        assert!(state_machine.try_handle(&Event::Divide(0)).is_err());
+        assert_eq!(state_machine.state(), &State::idle());
+
+        assert!(state_machine.try_handle(&Event::Increment).is_ok());
+        assert_eq!(state_machine.value, 1);
+    }
+
+    mod mirrored {
+        use std::sync::atomic::{AtomicU8, Ordering};
+
+        use statig::prelude::*;
+        use statig::STATE_ID_UNKNOWN;
+
+        static STATE_ID: AtomicU8 = AtomicU8::new(STATE_ID_UNKNOWN);
+
+        pub struct Toggle;
+
+        #[derive(Default)]
+        struct Switch;
+
+        #[state_machine(
+            initial = "State::off()",
+            state_id_mirror = "STATE_ID",
+            after_transition = "Self::after_transition",
+            state(derive(Clone, Debug, PartialEq))
+        )]
+        impl Switch {
+            #[state]
+            fn off(event: &Toggle) -> Response<State> {
+                match event {
+                    Toggle => Transition(State::on()),
+                }
+            }
+
+            #[state]
+            fn on(event: &Toggle) -> Response<State> {
+                match event {
+                    Toggle => Transition(State::off()),
+                }
+            }
+        }
+
+        impl Switch {
+            fn after_transition(&mut self, _source: &State, _target: &State) {
+                panic!("hook failed");
+            }
+        }
+
+        #[test]
+        fn panic_after_transition_restores_the_state_id_mirror() {
+            let mut state_machine = Switch.uninitialized_state_machine().init();
+            assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+
+            assert!(state_machine.try_handle(&Toggle).is_err());
+            assert_eq!(state_machine.state(), &State::off());
+            assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+        }
+    }
+}
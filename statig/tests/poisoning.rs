@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        Stop,
+        Jam,
+    }
+
+    #[derive(Default)]
+    struct Motor {
+        fail_on_exit: bool,
+        entered_idle: usize,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Motor {
+        #[state(entry_action = "enter_idle")]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::running()),
+                _ => Handled,
+            }
+        }
+
+        #[state(exit_action = "exit_running")]
+        fn running(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Handled,
+                Event::Stop => Transition(State::idle()),
+                Event::Jam => {
+                    self.fail_on_exit = true;
+                    Handled
+                }
+            }
+        }
+
+        #[action]
+        fn enter_idle(&mut self) {
+            self.entered_idle += 1;
+        }
+
+        #[action]
+        fn exit_running(&mut self) {
+            if self.fail_on_exit {
+                self.fail_on_exit = false;
+                panic!("motor is stuck");
+            }
+        }
+    }
+
+    #[test]
+    fn interrupted_transition_poisons_state_machine() {
+        let mut state_machine = Motor::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Start);
+        assert!(!state_machine.is_poisoned());
+
+        state_machine.handle(&Event::Jam);
+        let result = catch_unwind(AssertUnwindSafe(|| state_machine.handle(&Event::Stop)));
+        assert!(result.is_err());
+        assert!(state_machine.is_poisoned());
+
+        // Handling events is refused until the state machine is recovered.
+        let result = catch_unwind(AssertUnwindSafe(|| state_machine.handle(&Event::Start)));
+        assert!(result.is_err());
+        assert!(state_machine.is_poisoned());
+
+        state_machine.recover(State::idle());
+        assert!(!state_machine.is_poisoned());
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.entered_idle, 2);
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::running());
+    }
+
+    #[test]
+    fn panic_in_handler_does_not_poison() {
+        let mut state_machine = Motor::default().state_machine();
+
+        state_machine.handle(&Event::Start);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            state_machine.handle(&Event::Start);
+            panic!("panic outside of a transition");
+        }));
+        assert!(result.is_err());
+        assert!(!state_machine.is_poisoned());
+    }
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "postcard")]
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use statig::blocking::PersistentStateMachine;
+    use statig::prelude::*;
+    use statig::{MemoryStore, PersistError, PostcardCodec, PostcardCodecError, StateCodec};
+
+    enum Event {
+        Tick,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Counter;
+
+    #[state_machine(
+        initial = "State::idle()",
+        state(derive(Debug, PartialEq, Eq, Serialize, Deserialize))
+    )]
+    impl Counter {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::counting(1)),
+                Event::Reset => Handled,
+            }
+        }
+
+        #[state]
+        fn counting(count: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::counting(*count + 1)),
+                Event::Reset => Transition(State::idle()),
+            }
+        }
+    }
+
+    type Persistent = PersistentStateMachine<Counter, MemoryStore<16>, PostcardCodec, 16>;
+
+    #[test]
+    fn framed_with_checksum() {
+        let mut buffer = [0; 16];
+        let bytes = StateCodec::<Counter>::encode(&PostcardCodec, &State::counting(3), &mut buffer)
+            .unwrap();
+
+        // Variant index, varint encoded count and a CRC-32 in little endian.
+        assert_eq!(bytes.len(), 6);
+        assert_eq!(&bytes[..2], &[1, 3]);
+
+        let mut buffer = [0; 16];
+        let bytes = StateCodec::<Counter>::encode(&PostcardCodec, &State::counting(0), &mut buffer)
+            .unwrap();
+        assert_eq!(bytes, &[1, 0, 0xBE, 0x23, 0xC2, 0x58]);
+    }
+
+    #[test]
+    fn restores_state_local_storage() {
+        let mut state_machine =
+            Persistent::restore(Counter, MemoryStore::new(), PostcardCodec).unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+
+        let store = state_machine.into_store();
+        let mut state_machine = Persistent::restore(Counter, store, PostcardCodec).unwrap();
+        assert_eq!(state_machine.state(), &State::counting(3));
+
+        state_machine.handle(&Event::Reset).unwrap();
+        assert_eq!(state_machine.store().bytes().unwrap()[0], 0);
+    }
+
+    #[test]
+    fn corrupted_state_falls_back_to_initial() {
+        let mut state_machine =
+            Persistent::restore(Counter, MemoryStore::new(), PostcardCodec).unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+
+        let mut store = state_machine.into_store();
+        let mut bytes = store.bytes().unwrap().to_vec();
+        bytes[1] ^= 0xFF;
+        statig::StateStore::write(&mut store, &bytes).unwrap();
+
+        assert!(matches!(
+            Persistent::restore(Counter, store.clone(), PostcardCodec),
+            Err(PersistError::Codec(PostcardCodecError::Corrupted))
+        ));
+
+        let state_machine = Persistent::restore_or_initial(Counter, store, PostcardCodec).unwrap();
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+    use std::rc::Rc;
+
+    use statig::blocking::QueuedStateMachine;
+    use statig::prelude::*;
+    use statig::{ArrayQueue, EventQueue, RateLimit, RateLimitedQueue};
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Event {
+        Ping,
+        Command(u32),
+        Reset,
+    }
+
+    fn kind(event: &Event) -> usize {
+        match event {
+            Event::Ping => 0,
+            Event::Command(_) => 1,
+            Event::Reset => 2,
+        }
+    }
+
+    fn fake_clock() -> (Rc<Cell<u64>>, impl FnMut() -> Duration) {
+        let now = Rc::new(Cell::new(0));
+        let clock = {
+            let now = now.clone();
+            move || Duration::from_millis(now.get())
+        };
+        (now, clock)
+    }
+
+    #[derive(Default)]
+    struct Controller {
+        commands: Vec<u32>,
+    }
+
+    #[state_machine(initial = "State::running()")]
+    impl Controller {
+        #[state]
+        fn running(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Command(command) => self.commands.push(*command),
+                Event::Reset => self.commands.clear(),
+                Event::Ping => {}
+            }
+            Handled
+        }
+    }
+
+    #[test]
+    fn tokens_are_refilled_over_time() {
+        let (now, clock) = fake_clock();
+        let mut queue: RateLimitedQueue<_, _, Event, 2> =
+            RateLimitedQueue::new(ArrayQueue::<Event, 16>::new(), clock, kind)
+                .with_limit(1, RateLimit::new(2, Duration::from_millis(100)));
+
+        assert_eq!(queue.push(Event::Command(1)), Ok(()));
+        assert_eq!(queue.push(Event::Command(2)), Ok(()));
+        assert_eq!(queue.push(Event::Command(3)), Err(Event::Command(3)));
+
+        // Kinds without a limit, or outside the limited kinds, are let through.
+        for _ in 0..5 {
+            assert_eq!(queue.push(Event::Ping), Ok(()));
+        }
+        assert_eq!(queue.push(Event::Reset), Ok(()));
+
+        now.set(150);
+        assert_eq!(queue.push(Event::Command(4)), Ok(()));
+        assert_eq!(queue.push(Event::Command(5)), Err(Event::Command(5)));
+
+        // The 50 ms left over from the last refill count toward the next token.
+        now.set(200);
+        assert_eq!(queue.push(Event::Command(6)), Ok(()));
+
+        now.set(10_000);
+        assert_eq!(queue.push(Event::Command(7)), Ok(()));
+        assert_eq!(queue.push(Event::Command(8)), Ok(()));
+        assert_eq!(queue.push(Event::Command(9)), Err(Event::Command(9)));
+
+        assert_eq!(queue.rejected(), 3);
+    }
+
+    #[test]
+    fn limits_can_be_changed_at_runtime() {
+        let (_, clock) = fake_clock();
+        let mut queue: RateLimitedQueue<_, _, Event, 1> =
+            RateLimitedQueue::new(ArrayQueue::<Event, 16>::new(), clock, kind)
+                .with_limit(0, RateLimit::new(0, Duration::from_secs(1)));
+
+        assert_eq!(queue.push(Event::Ping), Err(Event::Ping));
+        queue.set_limit(0, None);
+        assert_eq!(queue.push(Event::Ping), Ok(()));
+    }
+
+    #[test]
+    fn full_queue_does_not_use_up_a_token() {
+        let (_, clock) = fake_clock();
+        let mut queue: RateLimitedQueue<_, _, Event, 2> =
+            RateLimitedQueue::new(ArrayQueue::<Event, 1>::new(), clock, kind)
+                .with_limit(1, RateLimit::new(2, Duration::from_secs(1)));
+
+        assert_eq!(queue.push(Event::Ping), Ok(()));
+        assert_eq!(queue.push(Event::Command(1)), Err(Event::Command(1)));
+        assert_eq!(queue.rejected(), 1);
+
+        // The refused command gave its token back, so two more fit the burst.
+        assert_eq!(queue.pop(), Some(Event::Ping));
+        assert_eq!(queue.push(Event::Command(2)), Ok(()));
+        assert_eq!(queue.pop(), Some(Event::Command(2)));
+        assert_eq!(queue.push(Event::Command(3)), Ok(()));
+        assert_eq!(queue.pop(), Some(Event::Command(3)));
+        assert_eq!(queue.push(Event::Command(4)), Err(Event::Command(4)));
+        assert_eq!(queue.rejected(), 2);
+    }
+
+    #[test]
+    fn rate_limited_events_never_reach_the_state_machine() {
+        let (now, clock) = fake_clock();
+        let queue =
+            RateLimitedQueue::<_, _, Event, 2>::new(ArrayQueue::<Event, 16>::new(), clock, kind)
+                .with_limit(1, RateLimit::new(1, Duration::from_millis(10)));
+        let state_machine = Controller::default().uninitialized_state_machine().init();
+        let mut state_machine =
+            QueuedStateMachine::new(state_machine, queue, ArrayQueue::<Event, 1>::new());
+
+        for command in 0..10 {
+            now.set(command * 5);
+            let _ = state_machine.post(Event::Command(command as u32));
+        }
+        state_machine.dispatch_pending().unwrap();
+
+        assert_eq!(state_machine.commands, [0, 2, 4, 6, 8]);
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Sequencer;
+
+    #[state_machine(
+        initial = "State::r#loop(0)",
+        state(derive(Debug, PartialEq, Eq)),
+        superstate(derive(Debug))
+    )]
+    impl Sequencer {
+        #[state(superstate = "r#async", entry_action = "r#enter")]
+        fn r#loop(r#ref: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::Next if *r#ref < 2 => {
+                    *r#ref += 1;
+                    Handled
+                }
+                Event::Next => Transition(State::r#final()),
+            }
+        }
+
+        #[state(superstate = "r#async")]
+        fn r#final(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Super,
+            }
+        }
+
+        #[superstate]
+        fn r#async(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+
+        #[action]
+        fn r#enter() {}
+    }
+
+    #[test]
+    fn raw_identifiers_are_states() {
+        let mut state_machine = Sequencer.state_machine();
+
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::Loop { r#ref: 2 });
+
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::Final {});
+
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::Final {});
+    }
+}
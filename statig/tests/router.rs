@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{MachineId, Outbox, Router};
+    use statig::ArrayQueue;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Message {
+        Move(u32),
+        Moved(u32),
+        Stop,
+    }
+
+    const CONTROLLER: MachineId = MachineId(0);
+    const ARM: MachineId = MachineId(1);
+
+    mod controller {
+        use super::*;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Controller {
+            pub completed: Vec<u32>,
+        }
+
+        #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq)))]
+        impl Controller {
+            #[state]
+            fn idle(context: &mut Outbox<'_, Message>, event: &Message) -> Response<State> {
+                match event {
+                    Message::Move(position) => {
+                        context.emit(ARM, Message::Move(*position)).unwrap();
+                        Transition(State::moving())
+                    }
+                    _ => Handled,
+                }
+            }
+
+            #[state]
+            fn moving(&mut self, event: &Message) -> Response<State> {
+                match event {
+                    Message::Moved(position) => {
+                        self.completed.push(*position);
+                        Transition(State::idle())
+                    }
+                    _ => Handled,
+                }
+            }
+        }
+    }
+
+    mod arm {
+        use super::*;
+        use statig::prelude::*;
+
+        #[derive(Default)]
+        pub struct Arm {
+            pub position: u32,
+            pub ignored: Vec<u32>,
+        }
+
+        #[state_machine(initial = "State::ready()")]
+        impl Arm {
+            #[state]
+            fn ready(
+                &mut self,
+                context: &mut Outbox<'_, Message>,
+                event: &Message,
+            ) -> Response<State> {
+                match event {
+                    Message::Move(position) => {
+                        self.position = *position;
+                        context.emit(CONTROLLER, Message::Moved(*position)).unwrap();
+                        Handled
+                    }
+                    Message::Stop => Transition(State::stopped()),
+                    Message::Moved(_) => Handled,
+                }
+            }
+
+            #[state]
+            fn stopped(&mut self, event: &Message) -> Response<State> {
+                if let Message::Move(position) = event {
+                    self.ignored.push(*position);
+                }
+                Handled
+            }
+        }
+    }
+
+    use statig::prelude::*;
+
+    #[test]
+    fn emitted_events_are_delivered_after_the_current_step() {
+        let mut queue = ArrayQueue::<(MachineId, Message), 4>::new();
+        let mut controller = controller::Controller::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut Outbox::new(&mut queue));
+        let mut arm = arm::Arm::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut Outbox::new(&mut queue));
+
+        {
+            let mut router: Router<Message, _, 2> = Router::new(queue);
+            assert_eq!(router.register(&mut controller).ok(), Some(CONTROLLER));
+            assert_eq!(router.register(&mut arm).ok(), Some(ARM));
+
+            router.send(CONTROLLER, Message::Move(10)).unwrap();
+            assert!(router.step());
+            // The arm hasn't moved yet: the event emitted by the controller is
+            // waiting in the queue.
+            assert_eq!(router.pending(), 1);
+
+            assert_eq!(router.run(), 2);
+            router.send(CONTROLLER, Message::Move(20)).unwrap();
+            router.send(ARM, Message::Stop).unwrap();
+            assert_eq!(router.run(), 3);
+            assert_eq!(router.pending(), 0);
+        }
+
+        // The arm was stopped before the second move reached it.
+        assert_eq!(controller.completed, [10]);
+        assert_eq!(controller.state(), &controller::State::Moving {});
+        assert_eq!(arm.position, 10);
+        assert_eq!(arm.ignored, [20]);
+    }
+
+    #[test]
+    fn events_for_unknown_machines_are_dropped() {
+        let mut queue = ArrayQueue::<(MachineId, Message), 2>::new();
+        let mut arm = arm::Arm::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut Outbox::new(&mut queue));
+        let mut other_arm = arm::Arm::default()
+            .uninitialized_state_machine()
+            .init_with_context(&mut Outbox::new(&mut queue));
+
+        let mut router: Router<Message, _, 1> = Router::new(queue);
+        assert!(router.register(&mut arm).is_ok());
+        assert!(router.register(&mut other_arm).is_err());
+
+        router.send(MachineId(5), Message::Stop).unwrap();
+        router.send(MachineId(0), Message::Stop).unwrap();
+        assert_eq!(router.send(MachineId(0), Message::Stop), Err(Message::Stop));
+        assert_eq!(router.run_for(1), 1);
+        assert_eq!(router.undeliverable(), 1);
+        assert_eq!(router.run(), 1);
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(any(feature = "json", feature = "cbor"))]
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use statig::blocking::PersistentStateMachine;
+    use statig::prelude::*;
+    use statig::MemoryStore;
+
+    enum Event {
+        Tick,
+    }
+
+    #[derive(Default)]
+    struct Counter;
+
+    #[state_machine(
+        initial = "State::idle()",
+        state(derive(Debug, PartialEq, Eq, Serialize, Deserialize))
+    )]
+    impl Counter {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::counting(1)),
+            }
+        }
+
+        #[state]
+        fn counting(count: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Transition(State::counting(*count + 1)),
+            }
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_uses_state_names() {
+        use statig::JsonCodec;
+
+        let mut state_machine = PersistentStateMachine::<_, _, _>::restore(
+            Counter,
+            MemoryStore::<64>::new(),
+            JsonCodec,
+        )
+        .unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+
+        let store = state_machine.into_store();
+        assert_eq!(store.bytes().unwrap(), br#"{"Counting":{"count":2}}"#);
+
+        let state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Counter, store, JsonCodec).unwrap();
+        assert_eq!(state_machine.state(), &State::counting(2));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn cbor_round_trip() {
+        use statig::CborCodec;
+
+        let mut state_machine = PersistentStateMachine::<_, _, _>::restore(
+            Counter,
+            MemoryStore::<64>::new(),
+            CborCodec,
+        )
+        .unwrap();
+        state_machine.handle(&Event::Tick).unwrap();
+
+        let store = state_machine.into_store();
+        let bytes = store.bytes().unwrap();
+        assert!(bytes.windows(8).any(|window| window == b"Counting"));
+
+        let state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Counter, store, CborCodec).unwrap();
+        assert_eq!(state_machine.state(), &State::counting(1));
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use statig::prelude::*;
+
+    enum Event {
+        Chunk(u32),
+        Disconnect,
+    }
+
+    /// Handle to a connection that can't be persisted.
+    #[derive(Debug, Default, PartialEq, Eq)]
+    pub struct Connection {
+        open: bool,
+    }
+
+    #[derive(Default)]
+    struct Download;
+
+    #[state_machine(
+        initial = "State::idle()",
+        state(derive(Debug, PartialEq, Eq, Serialize, Deserialize))
+    )]
+    impl Download {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Chunk(size) => Transition(State::downloading(
+                    Connection { open: true },
+                    *size,
+                    String::from("archive.tar"),
+                )),
+                Event::Disconnect => Handled,
+            }
+        }
+
+        #[state(local_storage("#[serde(default)] file_name: String"))]
+        fn downloading(
+            #[serde(skip)] connection: &mut Connection,
+            received: &mut u32,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Chunk(size) => {
+                    *received += size;
+                    Handled
+                }
+                Event::Disconnect => {
+                    connection.open = false;
+                    Transition(State::idle())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transient_storage_is_skipped() {
+        let mut state_machine = Download.state_machine();
+
+        state_machine.handle(&Event::Chunk(16));
+        state_machine.handle(&Event::Chunk(32));
+
+        let json = serde_json::to_string(state_machine.state()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Downloading":{"received":48,"file_name":"archive.tar"}}"#
+        );
+
+        let state: State = serde_json::from_str(r#"{"Downloading":{"received":48}}"#).unwrap();
+        assert_eq!(
+            state,
+            State::downloading(Connection::default(), 48, String::new())
+        );
+
+        state_machine.handle(&Event::Disconnect);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+}
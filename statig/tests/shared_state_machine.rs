@@ -0,0 +1,85 @@
+#![cfg(feature = "std")]
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use statig::blocking::SharedStateMachine;
+    use statig::prelude::*;
+
+    enum Event {
+        Increment,
+        Lock,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        count: usize,
+    }
+
+    #[state_machine(
+        initial = "State::counting()",
+        state(derive(Clone, Debug, PartialEq, Eq))
+    )]
+    impl Counter {
+        #[state]
+        fn counting(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Increment => {
+                    self.count += 1;
+                    Handled
+                }
+                Event::Lock => Transition(State::locked()),
+            }
+        }
+
+        #[state]
+        fn locked() -> Response<State> {
+            Handled
+        }
+    }
+
+    #[test]
+    fn events_from_multiple_threads() {
+        let state_machine = SharedStateMachine::new(Counter::default());
+
+        let producers: Vec<_> = (0..4)
+            .map(|_| {
+                let state_machine = state_machine.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        state_machine.handle(&Event::Increment);
+                    }
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        assert_eq!(state_machine.with(|state_machine| state_machine.count), 400);
+        assert_eq!(state_machine.state_snapshot(), State::counting());
+
+        state_machine.handle(&Event::Lock);
+        state_machine.handle(&Event::Increment);
+
+        assert_eq!(state_machine.state_snapshot(), State::locked());
+        assert_eq!(state_machine.with(|state_machine| state_machine.count), 400);
+    }
+
+    #[test]
+    fn usable_after_panic_in_other_thread() {
+        let state_machine = SharedStateMachine::new(Counter::default());
+
+        let handle = state_machine.clone();
+        let result = thread::spawn(move || {
+            handle.with(|_| panic!("panic while holding the lock"));
+        })
+        .join();
+        assert!(result.is_err());
+
+        state_machine.handle(&Event::Increment);
+        assert_eq!(state_machine.with(|state_machine| state_machine.count), 1);
+    }
+}
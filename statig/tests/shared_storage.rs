@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{InitializedStateMachine, IntoStateMachine};
+    use statig::prelude::*;
+
+    pub enum Event {
+        Insert(u32),
+    }
+
+    #[derive(Default)]
+    struct Vending {
+        credit: u32,
+        label: &'static str,
+    }
+
+    impl Vending {
+        /// A method with the same name as a method of the state machine.
+        fn state(&self) -> &'static str {
+            self.label
+        }
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Vending {
+        #[state]
+        fn idle(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Insert(amount) => {
+                    self.credit += amount;
+                    Handled
+                }
+            }
+        }
+    }
+
+    fn storage<M>(state_machine: &InitializedStateMachine<M>) -> &M
+    where
+        M: IntoStateMachine,
+    {
+        state_machine.shared()
+    }
+
+    #[test]
+    fn shared_storage_is_not_shadowed_by_the_state_machine() {
+        let mut uninitialized = Vending::default().uninitialized_state_machine();
+        uninitialized.shared_mut().label = "lobby";
+        assert_eq!(uninitialized.shared().state(), "lobby");
+
+        let mut state_machine = uninitialized.init();
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.shared().state(), "lobby");
+
+        state_machine.handle(&Event::Insert(50));
+        assert_eq!(storage(&state_machine).credit, 50);
+
+        unsafe {
+            state_machine.shared_mut().credit = 0;
+        }
+        assert_eq!(state_machine.shared().credit, 0);
+    }
+}
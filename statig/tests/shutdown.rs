@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Tick,
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        log: Vec<&'static str>,
+    }
+
+    #[state_machine(initial = "State::leaf()")]
+    impl Machine {
+        #[state(superstate = "parent", exit_action = "exit_leaf")]
+        fn leaf(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Handled,
+            }
+        }
+
+        #[superstate(exit_action = "exit_parent")]
+        fn parent(event: &Event) -> Response<State> {
+            match event {
+                Event::Tick => Handled,
+            }
+        }
+
+        #[action]
+        fn exit_leaf(&mut self) {
+            self.log.push("exit leaf");
+        }
+
+        #[action]
+        fn exit_parent(&mut self) {
+            self.log.push("exit parent");
+        }
+    }
+
+    #[test]
+    fn shutdown_executes_exit_actions_up_to_the_top() {
+        let mut state_machine = Machine::default().uninitialized_state_machine().init();
+        state_machine.handle(&Event::Tick);
+        let machine = state_machine.shutdown();
+        assert_eq!(machine.log, ["exit leaf", "exit parent"]);
+    }
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "alloc")]
+
+#[cfg(test)]
+mod tests {
+    use statig::blocking::SimulatedTransition;
+    use statig::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Event {
+        Coin,
+        Push,
+    }
+
+    #[derive(Default, Clone)]
+    struct Turnstile {
+        coins: usize,
+    }
+
+    #[state_machine(initial = "State::locked()", state(derive(Clone, Debug, PartialEq)))]
+    impl Turnstile {
+        #[state]
+        fn locked(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Coin => {
+                    self.coins += 1;
+                    Transition(State::unlocked())
+                }
+                Event::Push => Handled,
+            }
+        }
+
+        #[state]
+        fn unlocked(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Coin => {
+                    self.coins += 1;
+                    Handled
+                }
+                Event::Push => Transition(State::locked()),
+            }
+        }
+    }
+
+    #[test]
+    fn simulation_reports_transitions_without_affecting_the_live_machine() {
+        let mut state_machine = Turnstile::default().uninitialized_state_machine().init();
+        state_machine.handle(&Event::Coin);
+
+        let result = state_machine.simulate(&[Event::Coin, Event::Push, Event::Push, Event::Coin]);
+
+        assert_eq!(result.state(), &State::unlocked());
+        assert_eq!(result.state_machine.coins, 3);
+        assert_eq!(
+            result.transitions,
+            [
+                SimulatedTransition {
+                    event: 1,
+                    source: State::unlocked(),
+                    target: State::locked(),
+                },
+                SimulatedTransition {
+                    event: 3,
+                    source: State::locked(),
+                    target: State::unlocked(),
+                },
+            ]
+        );
+
+        assert_eq!(state_machine.state(), &State::unlocked());
+        assert_eq!(state_machine.coins, 1);
+    }
+
+    #[test]
+    fn simulating_no_events_leaves_the_state_as_is() {
+        let state_machine = Turnstile::default().uninitialized_state_machine().init();
+
+        let result = state_machine.simulate(&[]);
+
+        assert_eq!(result.state(), &State::locked());
+        assert!(result.transitions.is_empty());
+    }
+
+    mod mirrored {
+        use std::sync::atomic::{AtomicU8, Ordering};
+
+        use statig::prelude::*;
+        use statig::STATE_ID_UNKNOWN;
+
+        static STATE_ID: AtomicU8 = AtomicU8::new(STATE_ID_UNKNOWN);
+
+        pub struct Toggle;
+
+        #[derive(Default, Clone)]
+        struct Switch;
+
+        #[state_machine(
+            initial = "State::off()",
+            state_id_mirror = "STATE_ID",
+            state(derive(Clone, Debug, PartialEq))
+        )]
+        impl Switch {
+            #[state]
+            fn off(event: &Toggle) -> Response<State> {
+                match event {
+                    Toggle => Transition(State::on()),
+                }
+            }
+
+            #[state]
+            fn on(event: &Toggle) -> Response<State> {
+                match event {
+                    Toggle => Transition(State::off()),
+                }
+            }
+        }
+
+        #[test]
+        fn simulation_restores_the_state_id_mirror() {
+            let state_machine = Switch.uninitialized_state_machine().init();
+            assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+
+            let result = state_machine.simulate(&[Toggle]);
+
+            assert_eq!(result.state(), &State::on());
+            assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+        }
+    }
+}
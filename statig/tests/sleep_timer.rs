@@ -0,0 +1,67 @@
+#![cfg(feature = "async")]
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::executor::block_on;
+    use statig::awaitable::TimedStateMachine;
+    use statig::prelude::*;
+    use statig::SleepTimer;
+
+    enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    struct Light;
+
+    #[state_machine(
+        initial = "State::off()",
+        after = "Self::after",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Light {
+        fn after(state: &State) -> Option<(Duration, State)> {
+            match state {
+                State::On {} => Some((Duration::from_secs(5), State::off())),
+                State::Off {} => None,
+            }
+        }
+
+        #[state]
+        async fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        async fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn sleeps_with_the_given_function_on_any_executor() {
+        block_on(async {
+            let mut slept = Vec::new();
+            let state_machine = Light.uninitialized_state_machine().init().await;
+            let timer = SleepTimer(|duration| {
+                slept.push(duration);
+                core::future::ready(())
+            });
+            let mut state_machine = TimedStateMachine::new(state_machine, timer);
+
+            state_machine.handle(&Event::Press).await;
+            assert_eq!(state_machine.state(), &State::on());
+
+            state_machine.timeout().await;
+            assert_eq!(state_machine.state(), &State::off());
+            drop(state_machine);
+            assert_eq!(slept, [Duration::from_secs(5)]);
+        });
+    }
+}
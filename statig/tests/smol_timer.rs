@@ -0,0 +1,59 @@
+#![cfg(feature = "smol")]
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use statig::awaitable::TimedStateMachine;
+    use statig::prelude::*;
+    use statig::SmolTimer;
+
+    enum Event {
+        Press,
+    }
+
+    #[derive(Default)]
+    struct Light;
+
+    #[state_machine(
+        initial = "State::off()",
+        after = "Self::after",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Light {
+        fn after(state: &State) -> Option<(Duration, State)> {
+            match state {
+                State::On {} => Some((Duration::from_millis(10), State::off())),
+                State::Off {} => None,
+            }
+        }
+
+        #[state]
+        async fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+            }
+        }
+
+        #[state]
+        async fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn timed_transition_is_taken_on_smol() {
+        smol::block_on(async {
+            let state_machine = Light.uninitialized_state_machine().init().await;
+            let mut state_machine = TimedStateMachine::new(state_machine, SmolTimer);
+
+            state_machine.handle(&Event::Press).await;
+            assert_eq!(state_machine.state(), &State::on());
+
+            state_machine.timeout().await;
+            assert_eq!(state_machine.state(), &State::off());
+        });
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::SplitChannel;
+    use statig::prelude::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Event {
+        Count(u32),
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        total: u32,
+    }
+
+    #[state_machine(initial = "State::counting()")]
+    impl Counter {
+        #[state]
+        fn counting(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Count(count) => {
+                    self.total += count;
+                    Handled
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_channel_hands_the_event_back() {
+        let mut channel: SplitChannel<Event, 2> = SplitChannel::new();
+        let (mut producer, mut consumer) = channel.split();
+
+        assert_eq!(producer.push(Event::Count(1)), Ok(()));
+        assert_eq!(producer.push(Event::Count(2)), Ok(()));
+        assert!(producer.is_full());
+        assert_eq!(producer.push(Event::Count(3)), Err(Event::Count(3)));
+
+        assert_eq!(consumer.pop(), Some(Event::Count(1)));
+        assert_eq!(producer.push(Event::Count(3)), Ok(()));
+        assert_eq!(consumer.pop(), Some(Event::Count(2)));
+        assert_eq!(consumer.pop(), Some(Event::Count(3)));
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn events_from_another_thread_are_processed() {
+        let mut channel: SplitChannel<Event, 4> = SplitChannel::new();
+        let (mut producer, mut consumer) = channel.split();
+        let mut state_machine = Counter::default().uninitialized_state_machine().init();
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for count in 1..=100 {
+                    let mut event = Event::Count(count);
+                    while let Err(rejected) = producer.push(event) {
+                        event = rejected;
+                        std::thread::yield_now();
+                    }
+                }
+            });
+
+            while state_machine.inner().total < 5050 {
+                consumer.process(&mut state_machine);
+            }
+        });
+
+        assert_eq!(state_machine.inner().total, 5050);
+    }
+}
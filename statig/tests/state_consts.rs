@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Modem;
+
+    #[state_machine(initial = "State::dialing()")]
+    impl Modem {
+        #[state(const(TIMEOUT_MS = 500u32, LABEL = "dialing"))]
+        fn dialing(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::connected()),
+            }
+        }
+
+        #[state(const(TIMEOUT_MS = 30_000, LABEL = "connected"))]
+        fn connected(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::idle()),
+            }
+        }
+
+        #[state(const(LABEL = "idle"))]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::dialing()),
+            }
+        }
+    }
+
+    const DIALING_TIMEOUT_MS: Option<u32> = State::dialing().timeout_ms();
+
+    #[test]
+    fn constants_are_looked_up_per_state() {
+        assert_eq!(DIALING_TIMEOUT_MS, Some(500));
+        assert_eq!(State::connected().timeout_ms(), Some(30_000));
+        assert_eq!(State::idle().timeout_ms(), None);
+    }
+
+    #[test]
+    fn constants_declared_on_every_state_are_always_found() {
+        let mut state_machine = Modem.state_machine();
+        for label in ["dialing", "connected", "idle", "dialing"] {
+            assert_eq!(state_machine.state().label(), Some(label));
+            state_machine.handle(&Event::Next);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use statig::prelude::*;
+
+    enum Event {
+        Sample(u32),
+        Reset,
+    }
+
+    // The handle to the sensor can not be serialized, so the state enum can
+    // only derive `Serialize` for the state that doesn't hold it.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Sensor;
+
+    #[derive(Default)]
+    struct Logger;
+
+    #[state_machine(initial = "State::idle(Sensor)", state(derive(Debug, PartialEq, Eq)))]
+    impl Logger {
+        #[state]
+        fn idle(sensor: &mut Sensor, event: &Event) -> Response<State> {
+            let _ = sensor;
+            match event {
+                Event::Sample(value) => Transition(State::logging(1, *value)),
+                Event::Reset => Handled,
+            }
+        }
+
+        #[state(derive(Serialize, Clone))]
+        fn logging(samples: &mut usize, total: &mut u32, event: &Event) -> Response<State> {
+            match event {
+                Event::Sample(value) => {
+                    *samples += 1;
+                    *total += value;
+                    Handled
+                }
+                Event::Reset => Transition(State::idle(Sensor)),
+            }
+        }
+    }
+
+    #[test]
+    fn storage_of_state_is_derived() {
+        let mut state_machine = Logger.state_machine();
+
+        state_machine.handle(&Event::Sample(3));
+        state_machine.handle(&Event::Sample(4));
+
+        let State::Logging(storage) = state_machine.state() else {
+            panic!("expected the logging state");
+        };
+        assert_eq!(
+            storage,
+            &LoggingStorage {
+                samples: 2,
+                total: 7
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&storage.clone()).unwrap(),
+            r#"{"samples":2,"total":7}"#
+        );
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::idle(Sensor));
+    }
+}
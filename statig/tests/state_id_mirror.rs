@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    use statig::prelude::*;
+    use statig::STATE_ID_UNKNOWN;
+
+    static STATE_ID: AtomicU8 = AtomicU8::new(STATE_ID_UNKNOWN);
+
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::led_on()", state_id_mirror = "STATE_ID")]
+    impl Blinky {
+        #[state(superstate = "blinking")]
+        fn led_on(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::led_off()),
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn led_off(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on()),
+                _ => Super,
+            }
+        }
+
+        #[superstate]
+        fn blinking(event: &Event) -> Response<State> {
+            match event {
+                Event::ButtonPressed => Transition(State::not_blinking()),
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn not_blinking(event: &Event) -> Response<State> {
+            match event {
+                Event::ButtonPressed => Transition(State::led_on()),
+                _ => Super,
+            }
+        }
+    }
+
+    #[test]
+    fn state_id_mirror() {
+        let state_machine = Blinky.uninitialized_state_machine();
+        assert_eq!(STATE_ID.load(Ordering::Acquire), STATE_ID_UNKNOWN);
+
+        let mut state_machine = state_machine.init();
+        assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(STATE_ID.load(Ordering::Acquire), 1);
+
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(STATE_ID.load(Ordering::Acquire), 2);
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(STATE_ID.load(Ordering::Acquire), 2);
+
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(STATE_ID.load(Ordering::Acquire), 0);
+    }
+}
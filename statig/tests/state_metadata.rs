@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+    use statig::{IntoStateMachine, StateInfo};
+
+    enum Event {
+        Next,
+    }
+
+    #[derive(Default)]
+    struct Radio;
+
+    #[state_machine(initial = "State::idle()")]
+    impl Radio {
+        #[state(superstate = "link", meta(owner = "comms", severity = "low"))]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::transmitting()),
+            }
+        }
+
+        #[state(superstate = "link", meta(owner = "comms", severity = "high"))]
+        fn transmitting(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::idle()),
+            }
+        }
+
+        #[cfg(not(test))]
+        #[state(meta(owner = "factory"))]
+        fn calibrating(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::idle()),
+            }
+        }
+
+        #[superstate(meta(owner = "comms"))]
+        fn link(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn hierarchy_lists_states_and_superstates_in_declaration_order() {
+        assert_eq!(
+            Radio::HIERARCHY,
+            [
+                StateInfo {
+                    name: "idle",
+                    superstate: Some("link"),
+                    is_superstate: false,
+                    meta: &[("owner", "comms"), ("severity", "low")],
+                },
+                StateInfo {
+                    name: "transmitting",
+                    superstate: Some("link"),
+                    is_superstate: false,
+                    meta: &[("owner", "comms"), ("severity", "high")],
+                },
+                StateInfo {
+                    name: "link",
+                    superstate: None,
+                    is_superstate: true,
+                    meta: &[("owner", "comms")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn states_can_be_filtered_by_metadata() {
+        let critical: Vec<&str> = Radio::HIERARCHY
+            .iter()
+            .filter(|info| info.meta("severity") == Some("high"))
+            .map(|info| info.name)
+            .collect();
+        assert_eq!(critical, ["transmitting"]);
+    }
+
+    #[test]
+    fn metadata_of_the_current_state_can_be_looked_up() {
+        let mut state_machine = Radio.uninitialized_state_machine().init();
+        state_machine.handle(&Event::Next);
+
+        let info = Radio::HIERARCHY
+            .iter()
+            .find(|info| info.name == "transmitting")
+            .unwrap();
+        assert_eq!(info.meta("severity"), Some("high"));
+        assert_eq!(info.meta("unknown"), None);
+    }
+}
@@ -0,0 +1,30 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Default)]
+    struct Blinky;
+
+    // The build fails if the `State` enum grows beyond 8 bytes.
+    #[state_machine(initial = "State::on(0)", max_state_size = 8)]
+    impl Blinky {
+        #[state]
+        fn on(counter: &mut u32) -> Response<State> {
+            *counter += 1;
+            Transition(State::off())
+        }
+
+        #[state]
+        fn off() -> Response<State> {
+            Transition(State::on(0))
+        }
+    }
+
+    #[test]
+    fn state_size_within_limit() {
+        let mut state_machine = Blinky.uninitialized_state_machine().init();
+        state_machine.step();
+
+        assert!(core::mem::size_of::<State>() <= 8);
+    }
+}
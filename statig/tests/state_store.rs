@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::PersistentStateMachine;
+    use statig::prelude::*;
+    use statig::{MemoryStore, PersistError, StateIdCodec, StateIdCodecError, StateStore};
+
+    enum Event {
+        Toggle,
+        Ignore,
+        Fill,
+    }
+
+    #[derive(Default)]
+    struct Switch;
+
+    #[state_machine(initial = "State::off()", state(derive(Debug, PartialEq, Eq)))]
+    impl Switch {
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::on()),
+                Event::Ignore => Handled,
+                Event::Fill => Transition(State::filled(0)),
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Toggle => Transition(State::off()),
+                _ => Handled,
+            }
+        }
+
+        #[state]
+        fn filled(level: &mut u8) -> Response<State> {
+            *level += 1;
+            Handled
+        }
+    }
+
+    /// Store that mimics a page of flash and counts how often it is written.
+    #[derive(Default)]
+    struct Flash {
+        page: Option<[u8; 2]>,
+        writes: usize,
+    }
+
+    impl StateStore for Flash {
+        type Error = ();
+
+        fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.page = Some(bytes.try_into().map_err(|_| ())?);
+            self.writes += 1;
+            Ok(())
+        }
+
+        fn read<'buf>(
+            &mut self,
+            buffer: &'buf mut [u8],
+        ) -> Result<Option<&'buf [u8]>, Self::Error> {
+            match self.page {
+                Some(page) => {
+                    buffer[..2].copy_from_slice(&page);
+                    Ok(Some(&buffer[..2]))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+
+    #[test]
+    fn restores_persisted_state() {
+        let mut state_machine = PersistentStateMachine::<_, _, _>::restore(
+            Switch,
+            MemoryStore::<2>::new(),
+            StateIdCodec,
+        )
+        .unwrap();
+        assert_eq!(state_machine.state(), &State::off());
+        assert_eq!(state_machine.store().bytes(), None);
+
+        state_machine.handle(&Event::Toggle).unwrap();
+        assert!(state_machine.store().bytes().is_some());
+
+        let store = state_machine.into_store();
+        let state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Switch, store, StateIdCodec).unwrap();
+        assert_eq!(state_machine.state(), &State::on());
+    }
+
+    #[test]
+    fn only_writes_after_transitions() {
+        let mut state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Switch, Flash::default(), StateIdCodec)
+                .unwrap();
+
+        state_machine.handle(&Event::Ignore).unwrap();
+        state_machine.handle(&Event::Ignore).unwrap();
+        assert_eq!(state_machine.store().writes, 0);
+
+        state_machine.handle(&Event::Toggle).unwrap();
+        state_machine.handle(&Event::Ignore).unwrap();
+        state_machine.handle(&Event::Toggle).unwrap();
+        assert_eq!(state_machine.store().writes, 2);
+    }
+
+    #[test]
+    fn state_local_storage_is_not_supported_by_state_id_codec() {
+        let mut state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Switch, Flash::default(), StateIdCodec)
+                .unwrap();
+
+        assert_eq!(
+            state_machine.handle(&Event::Fill),
+            Err(PersistError::Codec(StateIdCodecError::Unsupported))
+        );
+        assert_eq!(state_machine.state(), &State::filled(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn file_store() {
+        use statig::FileStore;
+
+        let path = std::env::temp_dir().join(format!("statig-file-store-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Switch, FileStore::new(&path), StateIdCodec)
+                .unwrap();
+        state_machine.handle(&Event::Toggle).unwrap();
+        drop(state_machine);
+
+        let state_machine =
+            PersistentStateMachine::<_, _, _>::restore(Switch, FileStore::new(&path), StateIdCodec)
+                .unwrap();
+        assert_eq!(state_machine.state(), &State::on());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
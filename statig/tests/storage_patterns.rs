@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Data(u32),
+        Flush,
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct OnData {
+        counter: usize,
+        total: u32,
+        label: &'static str,
+    }
+
+    #[derive(Default)]
+    struct Collector {
+        flushed: Vec<(usize, u32)>,
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Collector {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Data(value) => Transition(State::collecting(OnData {
+                    counter: 1,
+                    total: *value,
+                    label: "collecting",
+                })),
+                Event::Flush => Handled,
+            }
+        }
+
+        #[state(superstate = "receiving")]
+        fn collecting(
+            OnData { counter, total, .. }: &mut OnData,
+            event: &Event,
+        ) -> Response<State> {
+            match event {
+                Event::Data(value) => {
+                    *counter += 1;
+                    *total += value;
+                    Handled
+                }
+                Event::Flush => Super,
+            }
+        }
+
+        #[superstate]
+        fn receiving(&mut self, OnData { counter, total, .. }: &mut OnData) -> Response<State> {
+            self.flushed.push((*counter, *total));
+            Transition(State::idle())
+        }
+    }
+
+    #[test]
+    fn storage_is_destructured() {
+        let mut state_machine = Collector::default().state_machine();
+
+        state_machine.handle(&Event::Data(2));
+        state_machine.handle(&Event::Data(3));
+
+        assert_eq!(
+            state_machine.state(),
+            &State::collecting(OnData {
+                counter: 2,
+                total: 5,
+                label: "collecting"
+            })
+        );
+
+        state_machine.handle(&Event::Flush);
+
+        assert_eq!(state_machine.flushed, [(2, 5)]);
+        assert_eq!(state_machine.state(), &State::idle());
+    }
+
+    mod named {
+        use statig::prelude::*;
+
+        pub struct Reading(pub u32);
+
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct Bound {
+            pub value: u32,
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct HTTPData {
+            pub requests: u32,
+        }
+
+        #[derive(Default)]
+        struct Monitor;
+
+        #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+        impl Monitor {
+            #[state]
+            fn idle(event: &Reading) -> Response<State> {
+                Transition(State::watching(
+                    Bound { value: event.0 },
+                    Bound { value: event.0 },
+                    HTTPData { requests: 0 },
+                ))
+            }
+
+            // Two structs of the same type are told apart by their binding.
+            #[state]
+            fn watching(
+                low @ Bound { value: low_value }: &mut Bound,
+                high @ Bound { value: high_value }: &mut Bound,
+                HTTPData { requests }: &mut HTTPData,
+                event: &Reading,
+            ) -> Response<State> {
+                *requests += 1;
+                *low_value = (*low_value).min(event.0);
+                *high_value = (*high_value).max(event.0);
+                Handled
+            }
+        }
+
+        #[test]
+        fn storage_is_named_after_its_binding() {
+            let mut state_machine = Monitor.state_machine();
+
+            state_machine.handle(&Reading(5));
+            state_machine.handle(&Reading(2));
+            state_machine.handle(&Reading(9));
+
+            match state_machine.state() {
+                State::Watching {
+                    low,
+                    high,
+                    http_data,
+                } => {
+                    assert_eq!(low, &Bound { value: 2 });
+                    assert_eq!(high, &Bound { value: 9 });
+                    assert_eq!(http_data, &HTTPData { requests: 2 });
+                }
+                state => panic!("unexpected state {state:?}"),
+            }
+        }
+    }
+}
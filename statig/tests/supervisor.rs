@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::{Escalation, InitializedStateMachine, RestartStrategy, Supervisor};
+    use statig::prelude::*;
+
+    pub enum Event {
+        Connect,
+        Drop,
+        Panic,
+    }
+
+    #[derive(Default)]
+    struct Link {
+        connects: usize,
+    }
+
+    #[state_machine(initial = "State::connecting()", state(derive(Debug, PartialEq)))]
+    impl Link {
+        #[state(entry_action = "enter_connecting")]
+        fn connecting(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Transition(State::connected()),
+                Event::Drop => Transition(State::failed()),
+                Event::Panic => Handled,
+            }
+        }
+
+        #[action]
+        fn enter_connecting(&mut self) {
+            self.connects += 1;
+        }
+
+        #[state]
+        fn connected(event: &Event) -> Response<State> {
+            match event {
+                Event::Connect => Handled,
+                Event::Drop => Transition(State::failed()),
+                Event::Panic => Transition(State::panicking()),
+            }
+        }
+
+        #[state(entry_action = "enter_panicking")]
+        fn panicking() -> Response<State> {
+            Handled
+        }
+
+        #[action]
+        fn enter_panicking() {
+            panic!("link panicked");
+        }
+
+        #[state]
+        fn failed() -> Response<State> {
+            Handled
+        }
+    }
+
+    fn failed(state: &State) -> bool {
+        matches!(state, State::Failed {})
+    }
+
+    fn links<const N: usize>() -> [InitializedStateMachine<Link>; N] {
+        core::array::from_fn(|_| Link::default().uninitialized_state_machine().init())
+    }
+
+    #[test]
+    fn failed_child_is_restarted_until_it_fails_too_often() {
+        let mut supervisor = Supervisor::new(links::<2>(), failed, 2);
+
+        supervisor.handle_all(&Event::Connect).unwrap();
+        for _ in 0..2 {
+            assert_eq!(supervisor.handle(0, &Event::Drop), Ok(()));
+            assert_eq!(supervisor.child(0).state(), &State::connecting());
+        }
+        assert_eq!(supervisor.child(0).connects, 3);
+        assert_eq!(supervisor.child(1).state(), &State::connected());
+        assert_eq!(supervisor.failures(0), 2);
+
+        assert_eq!(
+            supervisor.handle(0, &Event::Drop),
+            Err(Escalation {
+                child: 0,
+                failures: 3
+            })
+        );
+        assert_eq!(supervisor.child(0).state(), &State::failed());
+
+        supervisor.reset_failures();
+        assert_eq!(supervisor.handle(0, &Event::Drop), Ok(()));
+        assert_eq!(supervisor.child(0).state(), &State::connecting());
+    }
+
+    #[test]
+    fn one_for_all_restarts_every_child() {
+        let mut supervisor =
+            Supervisor::new(links::<3>(), failed, 1).with_strategy(RestartStrategy::OneForAll);
+
+        supervisor.handle_all(&Event::Connect).unwrap();
+        supervisor.handle(1, &Event::Drop).unwrap();
+
+        let children = supervisor.into_children();
+        for child in &children {
+            assert_eq!(child.state(), &State::connecting());
+            assert_eq!(child.connects, 2);
+        }
+    }
+
+    #[test]
+    fn handle_all_reports_the_first_escalation() {
+        let mut supervisor = Supervisor::new(links::<2>(), failed, 0);
+
+        assert_eq!(
+            supervisor.handle_all(&Event::Drop),
+            Err(Escalation {
+                child: 0,
+                failures: 1
+            })
+        );
+        assert_eq!(supervisor.child(1).state(), &State::failed());
+    }
+
+    #[test]
+    fn poisoned_child_is_restarted() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut supervisor = Supervisor::new(links::<1>(), failed, 1);
+        supervisor.handle(0, &Event::Connect).unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| supervisor.handle(0, &Event::Panic)));
+        assert!(result.is_err());
+        assert!(supervisor.child(0).is_poisoned());
+
+        // The child is restarted before it handles the next event.
+        supervisor.handle(0, &Event::Connect).unwrap();
+        assert!(!supervisor.child(0).is_poisoned());
+        assert_eq!(supervisor.failures(0), 1);
+        assert_eq!(supervisor.child(0).state(), &State::connected());
+    }
+}
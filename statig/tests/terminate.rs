@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Start,
+        EmergencyStop,
+    }
+
+    #[derive(Default)]
+    struct Press {
+        log: Vec<&'static str>,
+    }
+
+    impl Press {
+        fn cut_power(&mut self) {
+            self.log.push("cut power");
+        }
+    }
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Press {
+        #[state(superstate = "powered")]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Start => Transition(State::pressing()),
+                _ => Super,
+            }
+        }
+
+        #[state(
+            superstate = "powered",
+            exit_action = "retract",
+            transition_actions("emergency_stop => cut_power")
+        )]
+        fn pressing() -> Response<State> {
+            Super
+        }
+
+        #[superstate(exit_action = "power_down")]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::EmergencyStop => Transition(State::emergency_stop()),
+                _ => Super,
+            }
+        }
+
+        #[state(terminate)]
+        fn emergency_stop() {}
+
+        #[action]
+        fn retract(&mut self) {
+            self.log.push("retract");
+        }
+
+        #[action]
+        fn power_down(&mut self) {
+            self.log.push("power down");
+        }
+    }
+
+    #[test]
+    fn terminate_stops_without_exit_actions() {
+        let mut state_machine = Press::default().state_machine();
+        state_machine.handle(&Event::Start);
+        assert!(!state_machine.is_terminated());
+
+        state_machine.handle(&Event::EmergencyStop);
+        assert!(state_machine.is_terminated());
+        assert_eq!(state_machine.state(), &State::emergency_stop());
+        assert_eq!(state_machine.log, ["cut power"]);
+    }
+
+    #[test]
+    fn terminated_state_machine_ignores_events() {
+        let mut state_machine = Press::default().state_machine();
+        state_machine.handle(&Event::EmergencyStop);
+
+        state_machine.handle(&Event::Start);
+        assert_eq!(state_machine.state(), &State::emergency_stop());
+
+        let press = state_machine.shutdown();
+        assert!(press.log.is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use statig::blocking::TimedStateMachine;
+    use statig::prelude::*;
+    use statig::Timer;
+
+    /// Timer that runs on a clock that is advanced by hand.
+    struct ManualTimer {
+        clock: Rc<Cell<Duration>>,
+        deadline: Option<Duration>,
+        starts: usize,
+    }
+
+    impl Timer for ManualTimer {
+        fn start(&mut self, duration: Duration) {
+            self.deadline = Some(self.clock.get() + duration);
+            self.starts += 1;
+        }
+
+        fn cancel(&mut self) {
+            self.deadline = None;
+        }
+
+        fn poll_expired(&mut self) -> bool {
+            match self.deadline {
+                Some(deadline) if deadline <= self.clock.get() => {
+                    self.deadline = None;
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    enum Event {
+        Press,
+        Poke,
+    }
+
+    #[derive(Default)]
+    struct Light;
+
+    #[state_machine(
+        initial = "State::off()",
+        after = "Self::after",
+        state(derive(Debug, PartialEq, Eq))
+    )]
+    impl Light {
+        fn after(state: &State) -> Option<(Duration, State)> {
+            match state {
+                State::On {} => Some((Duration::from_secs(5), State::off())),
+                State::Off {} => None,
+            }
+        }
+
+        #[state]
+        fn off(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+                Event::Poke => Handled,
+            }
+        }
+
+        #[state]
+        fn on(event: &Event) -> Response<State> {
+            match event {
+                Event::Press => Transition(State::on()),
+                Event::Poke => Handled,
+            }
+        }
+    }
+
+    fn timed_state_machine() -> (TimedStateMachine<Light, ManualTimer>, Rc<Cell<Duration>>) {
+        let clock = Rc::new(Cell::new(Duration::ZERO));
+        let timer = ManualTimer {
+            clock: clock.clone(),
+            deadline: None,
+            starts: 0,
+        };
+        let state_machine = Light.uninitialized_state_machine().init();
+        (TimedStateMachine::new(state_machine, timer), clock)
+    }
+
+    fn advance(clock: &Cell<Duration>, secs: u64) {
+        clock.set(clock.get() + Duration::from_secs(secs));
+    }
+
+    #[test]
+    fn fires_after_duration() {
+        let (mut state_machine, clock) = timed_state_machine();
+        assert!(!state_machine.poll());
+
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.state(), &State::on());
+
+        advance(&clock, 4);
+        assert!(!state_machine.poll());
+        assert_eq!(state_machine.state(), &State::on());
+
+        advance(&clock, 1);
+        assert!(state_machine.poll());
+        assert_eq!(state_machine.state(), &State::off());
+
+        advance(&clock, 5);
+        assert!(!state_machine.poll());
+        assert_eq!(state_machine.timer().deadline, None);
+    }
+
+    #[test]
+    fn restarts_on_transition_only() {
+        let (mut state_machine, clock) = timed_state_machine();
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.timer().starts, 1);
+
+        advance(&clock, 3);
+        state_machine.handle(&Event::Poke);
+        assert_eq!(state_machine.timer().starts, 1);
+
+        state_machine.handle(&Event::Press);
+        assert_eq!(state_machine.timer().starts, 2);
+
+        advance(&clock, 3);
+        assert!(!state_machine.poll());
+
+        advance(&clock, 2);
+        assert!(state_machine.poll());
+        assert_eq!(state_machine.state(), &State::off());
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    #[test]
+    fn count_down_timer() {
+        use embedded_hal::timer::CountDown;
+        use statig::CountDownTimer;
+
+        /// Count down that counts the number of times it is waited on.
+        struct Ticks {
+            remaining: u32,
+        }
+
+        impl CountDown for Ticks {
+            type Time = u32;
+
+            fn start<T>(&mut self, count: T)
+            where
+                T: Into<Self::Time>,
+            {
+                self.remaining = count.into();
+            }
+
+            fn wait(&mut self) -> nb::Result<(), void::Void> {
+                match self.remaining {
+                    0 => Ok(()),
+                    _ => {
+                        self.remaining -= 1;
+                        Err(nb::Error::WouldBlock)
+                    }
+                }
+            }
+        }
+
+        let timer = CountDownTimer::new(Ticks { remaining: 0 }, |duration: Duration| {
+            duration.as_secs() as u32
+        });
+        let state_machine = Light.uninitialized_state_machine().init();
+        let mut state_machine = TimedStateMachine::new(state_machine, timer);
+        assert!(!state_machine.poll());
+
+        state_machine.handle(&Event::Press);
+        for _ in 0..5 {
+            assert!(!state_machine.poll());
+        }
+        assert!(state_machine.poll());
+        assert_eq!(state_machine.state(), &State::off());
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Open,
+        Close,
+    }
+
+    /// Interface shared by a family of protocol state machines.
+    trait Protocol: Sized {
+        type State;
+
+        fn closed(&mut self, event: &Event) -> Response<Self::State>;
+
+        fn open(&mut self, event: &Event) -> Response<Self::State>;
+
+        fn on_open(&mut self);
+    }
+
+    #[derive(Default)]
+    struct Tcp {
+        connections: usize,
+    }
+
+    #[state_machine(initial = "State::closed()", state(derive(Debug, PartialEq, Eq)))]
+    impl Protocol for Tcp {
+        type State = State;
+
+        #[state]
+        fn closed(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Open => Transition(State::open()),
+                Event::Close => Handled,
+            }
+        }
+
+        #[state(entry_action = "on_open")]
+        fn open(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Open => Handled,
+                Event::Close => Transition(State::closed()),
+            }
+        }
+
+        #[action]
+        fn on_open(&mut self) {
+            self.connections += 1;
+        }
+    }
+
+    #[test]
+    fn handlers_can_implement_a_trait() {
+        let mut state_machine = Tcp::default().state_machine();
+        state_machine.handle(&Event::Open);
+        state_machine.handle(&Event::Close);
+        state_machine.handle(&Event::Open);
+
+        assert_eq!(state_machine.state(), &State::open());
+        assert_eq!(state_machine.inner().connections, 2);
+    }
+}
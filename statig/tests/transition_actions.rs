@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Ack,
+        Timeout,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Link {
+        log: Vec<&'static str>,
+    }
+
+    impl Link {
+        fn send_nack(&mut self) {
+            self.log.push("send nack");
+        }
+    }
+
+    #[state_machine(initial = "State::await_ack()", state(derive(Debug, PartialEq, Eq)))]
+    impl Link {
+        #[state(
+            exit_action = "exit_await_ack",
+            transition_actions("error => send_nack")
+        )]
+        fn await_ack(event: &Event) -> Response<State> {
+            match event {
+                Event::Ack => Transition(State::idle()),
+                Event::Timeout => Transition(State::error()),
+                Event::Reset => Handled,
+            }
+        }
+
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Reset => Transition(State::await_ack()),
+                Event::Ack | Event::Timeout => Handled,
+            }
+        }
+
+        #[state(entry_action = "enter_error")]
+        fn error(event: &Event) -> Response<State> {
+            match event {
+                Event::Reset => Transition(State::await_ack()),
+                Event::Ack | Event::Timeout => Handled,
+            }
+        }
+
+        #[action]
+        fn exit_await_ack(&mut self) {
+            self.log.push("exit await ack");
+        }
+
+        #[action]
+        fn enter_error(&mut self) {
+            self.log.push("enter error");
+        }
+    }
+
+    #[test]
+    fn transition_action_runs_between_exit_and_entry() {
+        let mut state_machine = Link::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Timeout);
+        assert_eq!(state_machine.state(), &State::error());
+        assert_eq!(
+            state_machine.inner().log,
+            ["exit await ack", "send nack", "enter error"]
+        );
+    }
+
+    #[test]
+    fn transition_action_only_runs_for_its_target() {
+        let mut state_machine = Link::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Ack);
+        assert_eq!(state_machine.state(), &State::idle());
+        assert_eq!(state_machine.inner().log, ["exit await ack"]);
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::await_ack());
+        assert_eq!(state_machine.inner().log, ["exit await ack"]);
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use statig::blocking::State as _;
+    use statig::prelude::*;
+
+    pub enum Event {
+        A,
+        B,
+        C,
+        D,
+    }
+
+    #[derive(Default)]
+    struct Machine;
+
+    #[state_machine(initial = "State::a()", state(derive(Debug, PartialEq, Eq)))]
+    impl Machine {
+        #[state(superstate = "ab")]
+        fn a(event: &Event) -> Response<State> {
+            match event {
+                Event::A => Transition(State::a()),
+                Event::B => Transition(State::b()),
+                Event::C => Transition(State::c()),
+                Event::D => Transition(State::d()),
+            }
+        }
+
+        #[state(superstate = "ab")]
+        fn b() -> Response<State> {
+            Handled
+        }
+
+        #[superstate(superstate = "abc")]
+        fn ab() -> Response<State> {
+            Handled
+        }
+
+        #[state(superstate = "abc")]
+        fn c() -> Response<State> {
+            Transition(State::b())
+        }
+
+        #[superstate]
+        fn abc() -> Response<State> {
+            Handled
+        }
+
+        #[state]
+        fn d() -> Response<State> {
+            Transition(State::c())
+        }
+    }
+
+    #[test]
+    fn depth_lookup() {
+        assert_eq!(State::a().lookup_depth(), Some(3));
+        assert_eq!(State::c().lookup_depth(), Some(2));
+        assert_eq!(State::d().lookup_depth(), Some(1));
+    }
+
+    #[test]
+    fn transition_path_lookup() {
+        let cases = [
+            (State::a(), State::a(), (1, 1)),
+            (State::a(), State::b(), (1, 1)),
+            (State::a(), State::c(), (2, 1)),
+            (State::c(), State::b(), (1, 2)),
+            (State::a(), State::d(), (3, 1)),
+            (State::d(), State::c(), (1, 2)),
+        ];
+
+        for (source, target, path) in cases {
+            assert_eq!(source.lookup_transition_path(&target), Some(path));
+        }
+    }
+
+    #[test]
+    fn only_referenced_transition_paths_are_looked_up() {
+        assert_eq!(State::b().lookup_transition_path(&State::d()), None);
+        assert_eq!(State::c().lookup_transition_path(&State::d()), None);
+        assert_eq!(State::d().transition_path(&mut State::b()), (1, 3));
+    }
+
+    #[test]
+    fn transitions_follow_the_looked_up_paths() {
+        let mut state_machine = Machine.state_machine();
+
+        state_machine.handle(&Event::A);
+        assert_eq!(state_machine.state(), &State::a());
+
+        state_machine.handle(&Event::D);
+        assert_eq!(state_machine.state(), &State::d());
+
+        state_machine.handle(&Event::C);
+        assert_eq!(state_machine.state(), &State::c());
+
+        state_machine.handle(&Event::B);
+        assert_eq!(state_machine.state(), &State::b());
+    }
+
+    #[test]
+    fn max_transition_depth() {
+        assert_eq!(<Machine as IntoStateMachine>::MAX_TRANSITION_DEPTH, 4);
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Cause {
+        Timeout,
+        Overheated,
+    }
+
+    enum Event {
+        Timeout,
+        Overheat,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Controller {
+        log: Vec<Option<Cause>>,
+    }
+
+    #[state_machine(initial = "State::running()", state(derive(Debug, PartialEq, Eq)))]
+    impl Controller {
+        #[state]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Timeout => Transition(State::error_with(Cause::Timeout)),
+                Event::Overheat => Transition(State::error_with(Cause::Overheated)),
+                Event::Reset => Transition(State::error()),
+            }
+        }
+
+        #[state(payload = "cause: Cause", entry_action = "log_cause")]
+        fn error(cause: &mut Option<Cause>, event: &Event) -> Response<State> {
+            match (event, cause) {
+                (Event::Reset, _) => Transition(State::running()),
+                (_, Some(Cause::Timeout)) => Transition(State::running()),
+                (_, _) => Handled,
+            }
+        }
+
+        #[action]
+        fn log_cause(&mut self, cause: &mut Option<Cause>) {
+            self.log.push(*cause);
+        }
+    }
+
+    #[test]
+    fn payload_is_passed_to_the_entry_action_and_the_handler() {
+        let mut state_machine = Controller::default().state_machine();
+        state_machine.handle(&Event::Overheat);
+        assert!(matches!(
+            state_machine.state(),
+            State::Error {
+                cause: Some(Cause::Overheated)
+            }
+        ));
+
+        state_machine.handle(&Event::Timeout);
+        state_machine.handle(&Event::Reset);
+        state_machine.handle(&Event::Timeout);
+        state_machine.handle(&Event::Overheat);
+        assert_eq!(state_machine.state(), &State::running());
+        assert_eq!(
+            state_machine.inner().log,
+            [Some(Cause::Overheated), Some(Cause::Timeout)]
+        );
+    }
+
+    #[test]
+    fn payload_is_none_without_a_payload_constructor() {
+        let mut state_machine = Controller::default().state_machine();
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::error());
+        assert_eq!(state_machine.inner().log, [None]);
+    }
+
+    mod builder {
+        use statig::prelude::*;
+
+        enum Event {
+            Start,
+            Fail,
+        }
+
+        #[derive(Default)]
+        struct Downloader;
+
+        #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+        impl Downloader {
+            #[state]
+            fn idle(event: &Event) -> Response<State> {
+                match event {
+                    Event::Start => Transition(State::downloading().retries(3).build()),
+                    Event::Fail => Handled,
+                }
+            }
+
+            #[state(builder, payload = "reason: &'static str")]
+            fn downloading(
+                retries: &mut u8,
+                reason: &mut Option<&'static str>,
+                event: &Event,
+            ) -> Response<State> {
+                match (event, reason) {
+                    (Event::Fail, None) => Transition(
+                        State::downloading()
+                            .retries(*retries - 1)
+                            .build_with("failed"),
+                    ),
+                    (_, _) => Handled,
+                }
+            }
+        }
+
+        #[test]
+        fn payload_is_passed_to_the_built_state() {
+            let mut state_machine = Downloader.state_machine();
+            state_machine.handle(&Event::Start);
+            assert_eq!(
+                state_machine.state(),
+                &State::Downloading {
+                    retries: 3,
+                    reason: None,
+                }
+            );
+
+            state_machine.handle(&Event::Fail);
+            state_machine.handle(&Event::Fail);
+            assert_eq!(
+                state_machine.state(),
+                &State::Downloading {
+                    retries: 2,
+                    reason: Some("failed"),
+                }
+            );
+        }
+    }
+}
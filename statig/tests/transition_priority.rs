@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Alarm,
+    }
+
+    #[derive(Default)]
+    struct Door {
+        fire: bool,
+        intruder: bool,
+    }
+
+    #[state_machine(initial = "State::closed()", state(derive(Debug, PartialEq, Eq)))]
+    impl Door {
+        #[state(transitions(
+            "Event::Alarm if shared_storage.intruder => State::locked()",
+            "#[priority = 1] Event::Alarm if shared_storage.fire => State::open()",
+        ))]
+        fn closed(event: &Event) -> Response<State> {
+            match event {
+                Event::Alarm => Handled,
+            }
+        }
+
+        #[state]
+        fn open(event: &Event) -> Response<State> {
+            match event {
+                Event::Alarm => Handled,
+            }
+        }
+
+        #[state]
+        fn locked(event: &Event) -> Response<State> {
+            match event {
+                Event::Alarm => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn transition_with_higher_priority_is_taken() {
+        let door = Door {
+            fire: true,
+            intruder: true,
+        };
+        let mut state_machine = door.uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Alarm);
+        assert_eq!(state_machine.state(), &State::open());
+    }
+
+    #[test]
+    fn transitions_of_the_same_priority_keep_their_order() {
+        let door = Door {
+            intruder: true,
+            ..Door::default()
+        };
+        let mut state_machine = door.uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::Alarm);
+        assert_eq!(state_machine.state(), &State::locked());
+    }
+
+    mod condition {
+        use statig::prelude::*;
+
+        pub enum Event {
+            Tick,
+        }
+
+        #[derive(Default)]
+        pub struct Tank {
+            pressure: u32,
+        }
+
+        const LIMIT: u32 = 10;
+
+        #[state_machine(initial = "State::filling()", state(derive(Debug, PartialEq, Eq)))]
+        impl Tank {
+            #[state(
+                transitions("Event::Tick => State::full()"),
+                when = "self.pressure > LIMIT",
+                transition = "venting",
+                priority = 1
+            )]
+            fn filling(&mut self, event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => {
+                        self.pressure += 1;
+                        Handled
+                    }
+                }
+            }
+
+            #[state]
+            fn full(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Handled,
+                }
+            }
+
+            #[state]
+            fn venting(event: &Event) -> Response<State> {
+                match event {
+                    Event::Tick => Handled,
+                }
+            }
+        }
+
+        #[test]
+        fn condition_with_higher_priority_is_taken() {
+            let tank = Tank { pressure: 11 };
+            let mut state_machine = tank.uninitialized_state_machine().init();
+
+            state_machine.handle(&Event::Tick);
+            assert_eq!(state_machine.state(), &State::venting());
+        }
+
+        #[test]
+        fn condition_with_priority_is_evaluated_before_dispatch() {
+            let tank = Tank { pressure: 10 };
+            let mut state_machine = tank.uninitialized_state_machine().init();
+
+            state_machine.handle(&Event::Tick);
+            assert_eq!(state_machine.state(), &State::full());
+            assert_eq!(state_machine.pressure, 10);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug)]
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+        Reset { hard: bool },
+    }
+
+    #[derive(Default)]
+    struct Blinky {
+        handled: usize,
+        superstate_dispatches: usize,
+    }
+
+    #[state_machine(
+        initial = "State::led_on()",
+        state(derive(Debug, PartialEq, Eq)),
+        before_dispatch = "Self::before_dispatch"
+    )]
+    impl Blinky {
+        #[state(
+            superstate = "blinking",
+            transitions("Event::TimerElapsed => State::led_off()")
+        )]
+        fn led_on(&mut self) -> Response<State> {
+            self.handled += 1;
+            Super
+        }
+
+        #[state(
+            superstate = "blinking",
+            transitions("Event::TimerElapsed => State::led_on()")
+        )]
+        fn led_off(&mut self, event: &Event) -> Response<State> {
+            self.handled += 1;
+            match event {
+                // Takes precedence over the transition declared by `blinking`.
+                Event::ButtonPressed => Handled,
+                _ => Super,
+            }
+        }
+
+        #[superstate(
+            superstate = "powered",
+            transitions("Event::ButtonPressed => State::not_blinking()")
+        )]
+        fn blinking() -> Response<State> {
+            Super
+        }
+
+        #[state(
+            superstate = "powered",
+            transitions("Event::ButtonPressed => State::led_on()")
+        )]
+        fn not_blinking() -> Response<State> {
+            Super
+        }
+
+        #[superstate(transitions("Event::Reset { hard } if *hard => State::not_blinking()"))]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::Reset { .. } => Handled,
+                _ => Super,
+            }
+        }
+    }
+
+    impl Blinky {
+        fn before_dispatch(&mut self, state: StateOrSuperstate<'_, '_, Self>, _: &Event) {
+            if let StateOrSuperstate::Superstate(_) = state {
+                self.superstate_dispatches += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn declared_transitions() {
+        let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(state_machine.state(), &State::led_off());
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(state_machine.state(), &State::led_on());
+
+        // Declared transitions of a state are matched before its handler.
+        assert_eq!(state_machine.handled, 0);
+
+        // Declared transitions of a superstate are matched once the state
+        // defers the event.
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(state_machine.state(), &State::not_blinking());
+        assert_eq!(state_machine.handled, 1);
+
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(state_machine.state(), &State::led_on());
+
+        // Events that don't match a declared transition go to the handlers.
+        state_machine.handle(&Event::Reset { hard: false });
+        assert_eq!(state_machine.state(), &State::led_on());
+        assert_eq!(state_machine.handled, 2);
+
+        state_machine.handle(&Event::Reset { hard: true });
+        assert_eq!(state_machine.state(), &State::not_blinking());
+        assert_eq!(state_machine.handled, 3);
+    }
+
+    #[test]
+    fn handlers_of_substates_take_precedence() {
+        let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        state_machine.handle(&Event::TimerElapsed);
+        assert_eq!(state_machine.state(), &State::led_off());
+
+        // `led_off` handles the event before `blinking` would transition.
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(state_machine.state(), &State::led_off());
+        assert_eq!(state_machine.handled, 1);
+    }
+
+    #[test]
+    fn superstate_transitions_are_flattened_into_the_state() {
+        let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        // The transitions declared by `blinking` and `powered` are matched by
+        // `led_on` itself, so the event is never dispatched to a superstate.
+        state_machine.handle(&Event::ButtonPressed);
+        assert_eq!(state_machine.state(), &State::not_blinking());
+
+        state_machine.handle(&Event::Reset { hard: true });
+        assert_eq!(state_machine.state(), &State::not_blinking());
+        assert_eq!(state_machine.superstate_dispatches, 0);
+
+        // Events that match none of them are still deferred to the superstates.
+        state_machine.handle(&Event::Reset { hard: false });
+        assert_eq!(state_machine.superstate_dispatches, 1);
+    }
+
+    #[test]
+    fn transitions_are_listed() {
+        use statig::TransitionInfo;
+
+        let transition = |source, event, guard, target| TransitionInfo {
+            source: Some(source),
+            event: Some(event),
+            guard,
+            target,
+        };
+
+        assert_eq!(
+            <Blinky as IntoStateMachine>::TRANSITIONS,
+            &[
+                TransitionInfo {
+                    source: None,
+                    event: None,
+                    guard: None,
+                    target: "led_on",
+                },
+                transition("led_on", "TimerElapsed", None, "led_off"),
+                transition("led_off", "TimerElapsed", None, "led_on"),
+                transition("blinking", "ButtonPressed", None, "not_blinking"),
+                transition("not_blinking", "ButtonPressed", None, "led_on"),
+                transition("powered", "Reset", Some("* hard"), "not_blinking"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Command {
+        Start,
+        Stop,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Priority(u8);
+
+    #[derive(Default)]
+    struct Motor {
+        log: Vec<(Command, u8)>,
+    }
+
+    #[state_machine(initial = "State::stopped()", state(derive(Debug, PartialEq, Eq)))]
+    impl Motor {
+        #[state(superstate = "powered")]
+        fn stopped(
+            &mut self,
+            (command, Priority(priority)): &(Command, Priority),
+        ) -> Response<State> {
+            self.log.push((*command, *priority));
+            match command {
+                Command::Start => Transition(State::running()),
+                Command::Stop => Handled,
+            }
+        }
+
+        #[state(superstate = "powered")]
+        fn running(&mut self, (Command::Stop, priority): &(Command, Priority)) -> Response<State> {
+            self.log.push((Command::Stop, priority.0));
+            Transition(State::stopped())
+        }
+
+        #[superstate]
+        fn powered(&mut self, (command, priority): &(Command, Priority)) -> Response<State> {
+            match priority {
+                Priority(0) => Handled,
+                Priority(_) => {
+                    self.log.push((*command, priority.0));
+                    Super
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tuple_event_is_destructured() {
+        let mut state_machine = Motor::default().state_machine();
+
+        state_machine.handle(&(Command::Stop, Priority(1)));
+        state_machine.handle(&(Command::Start, Priority(2)));
+
+        assert_eq!(state_machine.log, [(Command::Stop, 1), (Command::Start, 2)]);
+        assert_eq!(state_machine.state(), &State::running());
+    }
+
+    #[test]
+    fn unmatched_tuple_is_deferred_to_superstate() {
+        let mut state_machine = Motor::default().state_machine();
+
+        state_machine.handle(&(Command::Start, Priority(0)));
+        state_machine.handle(&(Command::Start, Priority(0)));
+        state_machine.handle(&(Command::Start, Priority(3)));
+        state_machine.handle(&(Command::Stop, Priority(4)));
+
+        assert_eq!(
+            state_machine.log,
+            [(Command::Start, 0), (Command::Start, 3), (Command::Stop, 4)]
+        );
+        assert_eq!(state_machine.state(), &State::stopped());
+    }
+}
@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests {
+    /// The same state machine with each of the semantics, logging the states
+    /// that are exited and entered.
+    macro_rules! blinky {
+        ($module:ident, $semantics:literal) => {
+            mod $module {
+                use statig::prelude::*;
+
+                pub enum Event {
+                    TimerElapsed,
+                    Restart,
+                    Reset,
+                }
+
+                #[derive(Default)]
+                pub struct Blinky {
+                    pub log: Vec<&'static str>,
+                }
+
+                #[state_machine(initial = "State::led_on()", semantics = $semantics)]
+                impl Blinky {
+                    #[state(
+                        superstate = "blinking",
+                        entry_action = "enter_led_on",
+                        exit_action = "exit_led_on",
+                        transitions("Event::TimerElapsed => State::led_off()")
+                    )]
+                    fn led_on() -> Response<State> {
+                        Super
+                    }
+
+                    #[state(
+                        superstate = "blinking",
+                        entry_action = "enter_led_off",
+                        exit_action = "exit_led_off",
+                        transitions("Event::TimerElapsed => State::led_on()")
+                    )]
+                    fn led_off() -> Response<State> {
+                        Super
+                    }
+
+                    #[superstate(
+                        superstate = "powered",
+                        entry_action = "enter_blinking",
+                        exit_action = "exit_blinking",
+                        transitions("Event::Restart => State::led_off()")
+                    )]
+                    fn blinking() -> Response<State> {
+                        Super
+                    }
+
+                    #[superstate(entry_action = "enter_powered", exit_action = "exit_powered")]
+                    fn powered(event: &Event) -> Response<State> {
+                        match event {
+                            Event::Reset => Transition(State::led_on()),
+                            Event::TimerElapsed | Event::Restart => Handled,
+                        }
+                    }
+
+                    #[action]
+                    fn enter_led_on(&mut self) {
+                        self.log.push("enter led_on");
+                    }
+
+                    #[action]
+                    fn exit_led_on(&mut self) {
+                        self.log.push("exit led_on");
+                    }
+
+                    #[action]
+                    fn enter_led_off(&mut self) {
+                        self.log.push("enter led_off");
+                    }
+
+                    #[action]
+                    fn exit_led_off(&mut self) {
+                        self.log.push("exit led_off");
+                    }
+
+                    #[action]
+                    fn enter_blinking(&mut self) {
+                        self.log.push("enter blinking");
+                    }
+
+                    #[action]
+                    fn exit_blinking(&mut self) {
+                        self.log.push("exit blinking");
+                    }
+
+                    #[action]
+                    fn enter_powered(&mut self) {
+                        self.log.push("enter powered");
+                    }
+
+                    #[action]
+                    fn exit_powered(&mut self) {
+                        self.log.push("exit powered");
+                    }
+                }
+
+                /// Handle the event in `led_on` and return the states that
+                /// were exited and entered.
+                pub fn log(event: Event) -> Vec<&'static str> {
+                    let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+                    state_machine.handle(&Event::Restart);
+                    state_machine.handle(&Event::TimerElapsed);
+                    unsafe { state_machine.inner_mut() }.log.clear();
+                    state_machine.handle(&event);
+                    state_machine.log.clone()
+                }
+            }
+        };
+    }
+
+    blinky!(pragmatic, "pragmatic");
+    blinky!(uml, "uml");
+    blinky!(uml_local, "uml_local");
+
+    #[test]
+    fn transitions_between_states_are_the_same() {
+        let log = ["exit led_on", "enter led_off"];
+        assert_eq!(pragmatic::log(pragmatic::Event::TimerElapsed), log);
+        assert_eq!(uml::log(uml::Event::TimerElapsed), log);
+        assert_eq!(uml_local::log(uml_local::Event::TimerElapsed), log);
+    }
+
+    #[test]
+    fn self_transitions_of_superstates() {
+        assert_eq!(
+            pragmatic::log(pragmatic::Event::Reset),
+            ["exit led_on", "enter led_on"]
+        );
+        assert_eq!(
+            uml::log(uml::Event::Reset),
+            [
+                "exit led_on",
+                "exit blinking",
+                "exit powered",
+                "enter powered",
+                "enter blinking",
+                "enter led_on"
+            ]
+        );
+        assert_eq!(
+            uml_local::log(uml_local::Event::Reset),
+            [
+                "exit led_on",
+                "exit blinking",
+                "enter blinking",
+                "enter led_on"
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_transitions_of_superstates() {
+        assert_eq!(
+            pragmatic::log(pragmatic::Event::Restart),
+            ["exit led_on", "enter led_off"]
+        );
+        assert_eq!(
+            uml::log(uml::Event::Restart),
+            [
+                "exit led_on",
+                "exit blinking",
+                "enter blinking",
+                "enter led_off"
+            ]
+        );
+        assert_eq!(
+            uml_local::log(uml_local::Event::Restart),
+            ["exit led_on", "enter led_off"]
+        );
+    }
+
+    #[test]
+    fn initialization_is_the_same() {
+        use statig::prelude::*;
+
+        let log = ["enter powered", "enter blinking", "enter led_on"];
+        let state_machine = pragmatic::Blinky::default()
+            .uninitialized_state_machine()
+            .init();
+        assert_eq!(state_machine.log, log);
+        let state_machine = uml::Blinky::default().uninitialized_state_machine().init();
+        assert_eq!(state_machine.log, log);
+    }
+
+    #[cfg(feature = "async")]
+    mod awaitable {
+        use statig::prelude::*;
+
+        pub enum Event {
+            Reset,
+        }
+
+        #[derive(Default)]
+        pub struct Blinky {
+            pub log: Vec<&'static str>,
+        }
+
+        #[state_machine(initial = "State::led_on()", semantics = "uml")]
+        impl Blinky {
+            #[state(superstate = "blinking", exit_action = "exit_led_on")]
+            async fn led_on() -> Response<State> {
+                Super
+            }
+
+            #[superstate(exit_action = "exit_blinking")]
+            async fn blinking(event: &Event) -> Response<State> {
+                match event {
+                    Event::Reset => Transition(State::led_on()),
+                }
+            }
+
+            #[action]
+            async fn exit_led_on(&mut self) {
+                self.log.push("exit led_on");
+            }
+
+            #[action]
+            async fn exit_blinking(&mut self) {
+                self.log.push("exit blinking");
+            }
+        }
+
+        #[test]
+        fn self_transitions_of_superstates() {
+            futures::executor::block_on(async {
+                let mut state_machine =
+                    Blinky::default().uninitialized_state_machine().init().await;
+                state_machine.handle(&Event::Reset).await;
+                assert_eq!(state_machine.log, ["exit led_on", "exit blinking"]);
+            });
+        }
+    }
+}
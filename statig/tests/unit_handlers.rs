@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Tick,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        ticks: usize,
+        resets: usize,
+    }
+
+    #[state_machine(initial = "State::counting()", state(derive(Debug, PartialEq, Eq)))]
+    impl Counter {
+        #[state(superstate = "active", transitions("Event::Reset => State::idle()"))]
+        fn counting(&mut self, event: &Event) {
+            if let Event::Tick = event {
+                self.ticks += 1;
+            }
+        }
+
+        #[state(superstate = "active")]
+        fn idle() -> Response<State> {
+            Super
+        }
+
+        #[superstate]
+        #[allow(clippy::unused_unit)]
+        fn active(&mut self, event: &Event) -> () {
+            if let Event::Reset = event {
+                self.resets += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn handlers_without_return_type_handle_every_event() {
+        let mut state_machine = Counter::default().state_machine();
+        state_machine.handle(&Event::Tick);
+        state_machine.handle(&Event::Tick);
+        assert_eq!(state_machine.state(), &State::counting());
+        assert_eq!(state_machine.inner().ticks, 2);
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::idle());
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.inner().resets, 1);
+    }
+}
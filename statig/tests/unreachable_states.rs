@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Next,
+        Reset,
+    }
+
+    #[derive(Default)]
+    struct Cycle;
+
+    // Every state is either the initial state or the target of a transition,
+    // so denying unreachable states doesn't fail the build.
+    #[state_machine(
+        initial = "State::first()",
+        state(derive(Debug, PartialEq, Eq)),
+        unreachable_states = "deny"
+    )]
+    impl Cycle {
+        #[state(superstate = "running")]
+        fn first(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Transition(State::second()),
+                Event::Reset => Super,
+            }
+        }
+
+        #[state(superstate = "running", transitions("Event::Next => State::stopped()"))]
+        fn second(event: &Event) -> Response<State> {
+            match event {
+                Event::Next | Event::Reset => Super,
+            }
+        }
+
+        #[superstate(transitions("Event::Reset => State::first()"))]
+        fn running(event: &Event) -> Response<State> {
+            match event {
+                Event::Next | Event::Reset => Handled,
+            }
+        }
+
+        #[state]
+        fn stopped(event: &Event) -> Response<State> {
+            match event {
+                Event::Next => Handled,
+                Event::Reset => Transition(State::first()),
+            }
+        }
+    }
+
+    #[test]
+    fn all_states_are_reachable() {
+        let mut state_machine = Cycle.state_machine();
+
+        state_machine.handle(&Event::Next);
+        state_machine.handle(&Event::Next);
+        assert_eq!(state_machine.state(), &State::stopped());
+
+        state_machine.handle(&Event::Reset);
+        assert_eq!(state_machine.state(), &State::first());
+    }
+}
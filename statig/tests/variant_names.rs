@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use statig::prelude::*;
+
+    enum Event {
+        Send,
+        Ack,
+    }
+
+    #[derive(Default)]
+    struct Link;
+
+    #[state_machine(initial = "State::idle()", state(derive(Debug, PartialEq, Eq)))]
+    impl Link {
+        #[state]
+        fn idle(event: &Event) -> Response<State> {
+            match event {
+                Event::Send => Transition(State::wait_ack(1)),
+                Event::Ack => Handled,
+            }
+        }
+
+        #[state(name = "WaitingForAck", superstate = "tx")]
+        fn wait_ack(retries: &mut u8, event: &Event) -> Response<State> {
+            match event {
+                Event::Send => {
+                    *retries += 1;
+                    Handled
+                }
+                Event::Ack => Super,
+            }
+        }
+
+        #[superstate(name = "Transmitting")]
+        fn tx(event: &Event) -> Response<State> {
+            match event {
+                Event::Send | Event::Ack => Transition(State::idle()),
+            }
+        }
+    }
+
+    #[test]
+    fn variants_have_custom_names() {
+        let mut state_machine = Link.state_machine();
+
+        state_machine.handle(&Event::Send);
+        state_machine.handle(&Event::Send);
+        assert_eq!(state_machine.state(), &State::WaitingForAck { retries: 2 });
+
+        state_machine.handle(&Event::Ack);
+        assert_eq!(state_machine.state(), &State::Idle {});
+    }
+}
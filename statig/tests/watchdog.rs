@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::time::Duration;
+    use std::rc::Rc;
+
+    use statig::blocking::WatchdogStateMachine;
+    use statig::prelude::*;
+
+    pub enum Event {
+        Work(u64),
+        Toggle,
+    }
+
+    #[derive(Default)]
+    struct Machine {
+        /// The time of the fake clock, in milliseconds.
+        now: Rc<Cell<u64>>,
+    }
+
+    #[state_machine(initial = "State::fast()", state(derive(Clone, Debug, PartialEq)))]
+    impl Machine {
+        #[state]
+        fn fast(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Work(millis) => {
+                    self.now.set(self.now.get() + millis);
+                    Handled
+                }
+                Event::Toggle => Transition(State::slow()),
+            }
+        }
+
+        #[state]
+        fn slow(&mut self, event: &Event) -> Response<State> {
+            match event {
+                Event::Work(millis) => {
+                    self.now.set(self.now.get() + 10 * millis);
+                    Handled
+                }
+                Event::Toggle => Transition(State::fast()),
+            }
+        }
+    }
+
+    #[test]
+    fn overruns_are_reported_with_the_state_that_handled_the_event() {
+        let now = Rc::new(Cell::new(0));
+        let clock = {
+            let now = now.clone();
+            move || Duration::from_millis(now.get())
+        };
+        let mut overruns = Vec::new();
+        let state_machine = Machine { now }.uninitialized_state_machine().init();
+        let mut state_machine = WatchdogStateMachine::new(
+            state_machine,
+            clock,
+            Duration::from_millis(20),
+            |state: &State, elapsed| overruns.push((state.clone(), elapsed)),
+        );
+
+        state_machine.handle(&Event::Work(5));
+        state_machine.handle(&Event::Work(20));
+        state_machine.handle(&Event::Work(21));
+        assert_eq!(state_machine.overruns(), 1);
+
+        state_machine.handle(&Event::Toggle);
+        state_machine.handle(&Event::Work(1));
+        state_machine.handle(&Event::Work(3));
+        assert_eq!(state_machine.overruns(), 2);
+
+        state_machine.set_budget(Duration::from_millis(50));
+        state_machine.handle(&Event::Work(3));
+        assert_eq!(state_machine.budget(), Duration::from_millis(50));
+        assert_eq!(state_machine.overruns(), 2);
+        assert_eq!(state_machine.state(), &State::slow());
+
+        drop(state_machine);
+        assert_eq!(
+            overruns,
+            [
+                (State::fast(), Duration::from_millis(21)),
+                (State::slow(), Duration::from_millis(30)),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn std_clock_measures_real_time() {
+        let state_machine = Machine::default().uninitialized_state_machine().init();
+        let mut state_machine = WatchdogStateMachine::new(
+            state_machine,
+            statig::StdClock::new(),
+            Duration::from_secs(60),
+            |_: &State, _| panic!("handling an event took a minute"),
+        );
+
+        state_machine.handle(&Event::Work(1));
+        assert_eq!(state_machine.overruns(), 0);
+    }
+}
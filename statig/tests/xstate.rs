@@ -0,0 +1,121 @@
+#![cfg(feature = "json")]
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use statig::prelude::*;
+
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+        Reset { hard: bool },
+    }
+
+    #[derive(Default)]
+    struct Blinky;
+
+    #[state_machine(initial = "State::led_on()")]
+    impl Blinky {
+        #[state(
+            superstate = "blinking",
+            transitions("Event::TimerElapsed => State::led_off()")
+        )]
+        fn led_on() -> Response<State> {
+            Super
+        }
+
+        #[state(
+            superstate = "blinking",
+            transitions("Event::TimerElapsed => State::led_on()")
+        )]
+        fn led_off() -> Response<State> {
+            Super
+        }
+
+        #[superstate(
+            superstate = "powered",
+            transitions("Event::ButtonPressed => State::not_blinking()")
+        )]
+        fn blinking() -> Response<State> {
+            Super
+        }
+
+        #[state(
+            superstate = "powered",
+            transitions(
+                "Event::ButtonPressed => State::led_on()",
+                "_ => State::not_blinking()"
+            )
+        )]
+        fn not_blinking() -> Response<State> {
+            Super
+        }
+
+        #[superstate(transitions(
+            "Event::Reset { hard } if *hard => State::not_blinking()",
+            "Event::Reset { .. } => State::led_on()"
+        ))]
+        fn powered(event: &Event) -> Response<State> {
+            match event {
+                Event::Reset { .. } | Event::TimerElapsed | Event::ButtonPressed => Handled,
+            }
+        }
+    }
+
+    #[test]
+    fn hierarchy_and_transitions_are_exported() {
+        assert_eq!(
+            statig::to_xstate::<Blinky>("blinky"),
+            json!({
+                "id": "blinky",
+                "initial": "powered",
+                "states": {
+                    "powered": {
+                        "id": "powered",
+                        "initial": "blinking",
+                        "states": {
+                            "blinking": {
+                                "id": "blinking",
+                                "initial": "led_on",
+                                "states": {
+                                    "led_on": {
+                                        "id": "led_on",
+                                        "on": { "TimerElapsed": [{ "target": "#led_off" }] },
+                                    },
+                                    "led_off": {
+                                        "id": "led_off",
+                                        "on": { "TimerElapsed": [{ "target": "#led_on" }] },
+                                    },
+                                },
+                                "on": { "ButtonPressed": [{ "target": "#not_blinking" }] },
+                            },
+                            "not_blinking": {
+                                "id": "not_blinking",
+                                "on": {
+                                    "ButtonPressed": [{ "target": "#led_on" }],
+                                    "*": [{ "target": "#not_blinking" }],
+                                },
+                            },
+                        },
+                        "on": {
+                            "Reset": [
+                                { "target": "#not_blinking", "guard": "* hard" },
+                                { "target": "#led_on" },
+                            ],
+                        },
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn state_machine_runs() {
+        let mut state_machine = Blinky.uninitialized_state_machine().init();
+        state_machine.handle(&Event::TimerElapsed);
+        state_machine.handle(&Event::ButtonPressed);
+        // The wildcard of the state comes before the transitions of its superstate.
+        state_machine.handle(&Event::Reset { hard: false });
+        assert!(matches!(state_machine.state(), State::NotBlinking {}));
+    }
+}
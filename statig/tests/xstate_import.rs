@@ -0,0 +1,122 @@
+#![cfg(feature = "json")]
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    // The skeleton that is generated from `machine()`, with its guards
+    // implemented. Not every event is used.
+    #[allow(dead_code)]
+    mod player {
+        include!("xstate_import/player.rs");
+
+        impl PlayerGuards for Player {
+            fn is_buffering(&self) -> bool {
+                false
+            }
+
+            fn can_seek(&self) -> bool {
+                true
+            }
+        }
+    }
+
+    fn machine() -> Value {
+        json!({
+            "id": "player",
+            "initial": "stopped",
+            "states": {
+                "stopped": {
+                    "description": "Nothing is playing.",
+                    "on": { "PLAY": "playing" },
+                },
+                "playing": {
+                    "initial": "normal",
+                    "on": {
+                        "STOP": "stopped",
+                        "media.*": ".normal",
+                    },
+                    "states": {
+                        "normal": {
+                            "on": {
+                                "FAST_FORWARD": { "target": "fastForward", "guard": "canSeek" },
+                                "media.next": { "actions": "next" },
+                                "media.previous": { "actions": "previous" },
+                            },
+                        },
+                        "fastForward": {
+                            "id": "fast",
+                            "on": {
+                                "PLAY": "#player.playing.normal",
+                                "*": [{ "target": "#fast", "cond": "isBuffering" }, "normal"],
+                            },
+                        },
+                    },
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn skeleton_is_generated() {
+        assert_eq!(
+            statig::from_xstate(&machine()).unwrap(),
+            include_str!("xstate_import/player.rs")
+        );
+    }
+
+    #[test]
+    fn skeleton_runs() {
+        use player::{Event, Player, State};
+        use statig::prelude::*;
+
+        let mut state_machine = Player.state_machine();
+        assert!(matches!(state_machine.state(), State::Stopped {}));
+
+        // A transition to a compound state enters its initial state.
+        state_machine.handle(&Event::Play);
+        assert!(matches!(state_machine.state(), State::Normal {}));
+
+        state_machine.handle(&Event::MediaNext);
+        assert!(matches!(state_machine.state(), State::Normal {}));
+
+        // The guards are called on the shared storage.
+        state_machine.handle(&Event::FastForward);
+        assert!(matches!(state_machine.state(), State::FastForward {}));
+
+        state_machine.handle(&Event::MediaNext);
+        assert!(matches!(state_machine.state(), State::Normal {}));
+
+        state_machine.handle(&Event::Stop);
+        assert!(matches!(state_machine.state(), State::Stopped {}));
+    }
+
+    #[test]
+    fn unsupported_machines_are_rejected() {
+        let parallel = json!({
+            "initial": "on",
+            "states": { "on": { "type": "parallel" } },
+        });
+        assert!(statig::from_xstate(&parallel).is_err());
+
+        let unknown_target = json!({
+            "initial": "on",
+            "states": { "on": { "on": { "TOGGLE": "off" } } },
+        });
+        assert_eq!(
+            statig::from_xstate(&unknown_target)
+                .unwrap_err()
+                .to_string(),
+            "invalid XState machine: there is no state `off`"
+        );
+
+        let no_initial = json!({
+            "initial": "on",
+            "states": { "on": { "states": { "dim": {}, "bright": {} } } },
+        });
+        assert_eq!(
+            statig::from_xstate(&no_initial).unwrap_err().to_string(),
+            "invalid XState machine: the state `on` has no initial state"
+        );
+    }
+}
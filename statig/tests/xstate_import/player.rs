@@ -0,0 +1,63 @@
+// Generated from the XState machine `player`.
+
+use statig::prelude::*;
+
+#[derive(Debug)]
+pub enum Event {
+    Stop,
+    Play,
+    FastForward,
+    MediaNext,
+    MediaPrevious,
+}
+
+#[derive(Default)]
+pub struct Player;
+
+#[state_machine(initial = "State::stopped()")]
+impl Player {
+    #[superstate(transitions(
+        "Event::Stop => State::stopped()",
+        "Event::MediaNext | Event::MediaPrevious => State::normal()"
+    ))]
+    fn playing(event: &Event) -> Response<State> {
+        let _ = event;
+        Super
+    }
+
+    #[state(
+        superstate = "playing",
+        transitions(
+            "Event::Play => State::normal()",
+            "_ if all(is_buffering) => State::fast_forward()",
+            "_ => State::normal()"
+        )
+    )]
+    fn fast_forward(event: &Event) -> Response<State> {
+        let _ = event;
+        Super
+    }
+
+    #[state(
+        superstate = "playing",
+        transitions("Event::FastForward if all(can_seek) => State::fast_forward()")
+    )]
+    fn normal(event: &Event) -> Response<State> {
+        let _ = event;
+        Super
+    }
+
+    /// Nothing is playing.
+    #[state(transitions("Event::Play => State::normal()"))]
+    fn stopped(event: &Event) -> Response<State> {
+        let _ = event;
+        Super
+    }
+}
+
+/// The guards of the transitions of `Player`.
+pub trait PlayerGuards {
+    fn is_buffering(&self) -> bool;
+
+    fn can_seek(&self) -> bool;
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "count-allocations")]
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use statig::prelude::*;
+
+    /// Global allocator that counts the allocations made by the current thread,
+    /// so tests running in parallel don't affect each other.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Run the closure and return the number of heap allocations it made.
+    fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+        let before = ALLOCATIONS.with(Cell::get);
+        let output = f();
+        let after = ALLOCATIONS.with(Cell::get);
+        (output, after - before)
+    }
+
+    enum Event {
+        TimerElapsed,
+        ButtonPressed,
+    }
+
+    #[derive(Default)]
+    struct Blinky {
+        toggles: usize,
+        transitions: usize,
+        dispatches: usize,
+    }
+
+    #[state_machine(
+        initial = "State::led_on(0)",
+        before_dispatch = "Self::before_dispatch",
+        after_transition = "Self::after_transition"
+    )]
+    impl Blinky {
+        #[state(superstate = "blinking", entry_action = "enter_led_on")]
+        fn led_on(counter: &mut usize, event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => {
+                    *counter += 1;
+                    Transition(State::led_off())
+                }
+                _ => Super,
+            }
+        }
+
+        #[state(superstate = "blinking")]
+        fn led_off(event: &Event) -> Response<State> {
+            match event {
+                Event::TimerElapsed => Transition(State::led_on(0)),
+                _ => Super,
+            }
+        }
+
+        #[superstate(exit_action = "exit_blinking")]
+        fn blinking(event: &Event) -> Response<State> {
+            match event {
+                Event::ButtonPressed => Transition(State::not_blinking()),
+                _ => Super,
+            }
+        }
+
+        #[state]
+        fn not_blinking(event: &Event) -> Response<State> {
+            match event {
+                Event::ButtonPressed => Transition(State::led_on(0)),
+                _ => Super,
+            }
+        }
+
+        #[action]
+        fn enter_led_on(&mut self) {
+            self.toggles += 1;
+        }
+
+        #[action]
+        fn exit_blinking(&mut self) {
+            self.toggles = 0;
+        }
+    }
+
+    impl Blinky {
+        fn before_dispatch(&mut self, _: StateOrSuperstate<'_, '_, Self>, _: &Event) {
+            self.dispatches += 1;
+        }
+
+        fn after_transition(&mut self, _: &State, _: &State) {
+            self.transitions += 1;
+        }
+    }
+
+    #[test]
+    fn init_does_not_allocate() {
+        let (state_machine, allocations) =
+            count_allocations(|| Blinky::default().uninitialized_state_machine().init());
+
+        assert_eq!(allocations, 0);
+        assert_eq!(state_machine.toggles, 1);
+    }
+
+    #[test]
+    fn handle_does_not_allocate() {
+        let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        let (_, allocations) = count_allocations(|| {
+            for _ in 0..100 {
+                state_machine.handle(&Event::TimerElapsed);
+            }
+        });
+
+        assert_eq!(allocations, 0);
+        assert_eq!(state_machine.dispatches, 100);
+    }
+
+    #[test]
+    fn transition_does_not_allocate() {
+        let mut state_machine = Blinky::default().uninitialized_state_machine().init();
+
+        let (_, allocations) = count_allocations(|| {
+            for _ in 0..100 {
+                state_machine.handle(&Event::ButtonPressed);
+            }
+        });
+
+        assert_eq!(allocations, 0);
+        assert_eq!(state_machine.transitions, 100);
+    }
+
+    #[test]
+    fn lazy_state_machine_does_not_allocate() {
+        let (_, allocations) = count_allocations(|| {
+            let mut state_machine = Blinky::default().state_machine();
+            state_machine.handle(&Event::TimerElapsed);
+            state_machine.handle(&Event::ButtonPressed);
+        });
+
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn allocations_are_counted() {
+        let (_, allocations) = count_allocations(|| std::hint::black_box(Box::new(1)));
+
+        assert_eq!(allocations, 1);
+    }
+}